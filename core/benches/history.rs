@@ -0,0 +1,57 @@
+//! The throughput of the value history, in values stored or decimated per second.
+//!
+//! Run with `cargo bench -p serialplotter-core --bench history`, the plot decimates every channel
+//! once per frame.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serialplotter_core::{decimate, DataValue, ValueHistory};
+
+/// Number of lines every benchmark stores, four channels each.
+const LINES: usize = 100_000;
+
+/// Lines of four channels, one millisecond apart.
+fn lines(lines: usize) -> Vec<DataValue> {
+    let names = ["temperature", "pressure", "voltage", "flag"];
+    (0..lines)
+        .flat_map(|line| {
+            let timestamp = 1e9 + line as f64 * 1e-3;
+            names
+                .iter()
+                .enumerate()
+                .map(move |(channel, name)| DataValue {
+                    name: name.to_string(),
+                    value: (line as f64 * 0.01 + channel as f64).sin(),
+                    timestamp,
+                    line: line as u64,
+                })
+        })
+        .collect()
+}
+
+fn history(c: &mut Criterion) {
+    let mut group = c.benchmark_group("history");
+    let values = lines(LINES);
+    group.throughput(Throughput::Elements(values.len() as u64));
+    group.bench_function("push", |b| {
+        b.iter(|| {
+            let mut history = ValueHistory::with_capacity(LINES);
+            for value in values.iter().cloned() {
+                history.push(value);
+            }
+            black_box(history)
+        })
+    });
+
+    let mut history = ValueHistory::with_capacity(LINES);
+    values.iter().cloned().for_each(|value| history.push(value));
+    let samples = history
+        .samples("temperature")
+        .expect("the channel received values");
+    group.throughput(Throughput::Elements(samples.len() as u64));
+    // Two points per pixel of a wide plot
+    group.bench_function("decimate", |b| b.iter(|| decimate(samples, 4000)));
+    group.finish();
+}
+
+criterion_group!(benches, history);
+criterion_main!(benches);
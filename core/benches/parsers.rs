@@ -0,0 +1,120 @@
+//! The throughput of the parsers, in bytes of input per second.
+//!
+//! Run with `cargo bench -p serialplotter-core`, a serial port at 1 MB/s is far beyond what
+//! the parsers have to keep up with.
+
+use std::io::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serialplotter_core::{
+    BinaryFormat, DataFormat, NumberType, ParserSettings, ParsingResult, ValueParser,
+};
+
+/// Number of lines or frames every benchmark parses.
+const LINES: usize = 10_000;
+
+/// Lines like `temperature:21.5,pressure:1013.2,voltage:3.3,flag:1`.
+fn csv(lines: usize) -> Vec<u8> {
+    let mut input = Vec::new();
+    for line in 0..lines {
+        let phase = line as f64 * 0.01;
+        writeln!(
+            input,
+            "temperature:{:.2},pressure:{:.1},voltage:{:.3},flag:{}",
+            21.5 + phase.sin() * 3.0,
+            1013.2 + phase.cos() * 10.0,
+            3.3 + (phase * 7.0).sin() * 0.1,
+            line / 500 % 2
+        )
+        .expect("writing to a Vec does not fail");
+    }
+    input
+}
+
+/// The same values as [`csv`] in the other text formats.
+fn text(format: DataFormat, lines: usize) -> Vec<u8> {
+    let csv = String::from_utf8(csv(lines)).expect("the input is ASCII");
+    let mut input = Vec::new();
+    for line in csv.lines() {
+        let mut values = line.split(',').map(|value| value.split_once(':').unwrap());
+        match format {
+            DataFormat::Json => {
+                let fields: Vec<_> = values
+                    .map(|(name, value)| format!("\"{}\":{}", name, value))
+                    .collect();
+                writeln!(input, "{{{}}}", fields.join(","))
+            }
+            DataFormat::Teleplot => {
+                values.try_for_each(|(name, value)| writeln!(input, ">{}:{}", name, value))
+            }
+            DataFormat::Arduino => {
+                let fields: Vec<_> = values
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                writeln!(input, "{}", fields.join(" "))
+            }
+            _ => writeln!(input, "{}", line),
+        }
+        .expect("writing to a Vec does not fail");
+    }
+    input
+}
+
+/// Frames of four little endian `f32`.
+fn binary(frames: usize) -> Vec<u8> {
+    let mut input = Vec::new();
+    for frame in 0..frames {
+        for channel in 0..4 {
+            input.extend_from_slice(&((frame * channel) as f32).to_le_bytes());
+        }
+    }
+    input
+}
+
+fn parse(parser: &mut dyn ValueParser, input: &[u8]) -> usize {
+    let mut values = 0;
+    for byte in input {
+        if let ParsingResult::Ok(line) = parser.parse(*byte) {
+            values += line.len();
+            black_box(line);
+        }
+    }
+    values
+}
+
+fn parsers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parsers");
+    let formats = [
+        DataFormat::Csv,
+        DataFormat::Arduino,
+        DataFormat::Json,
+        DataFormat::Teleplot,
+        DataFormat::Binary,
+    ];
+    for format in formats {
+        let mut settings = ParserSettings {
+            format,
+            ..ParserSettings::default()
+        };
+        let input = match format {
+            DataFormat::Binary => {
+                settings.binary = BinaryFormat {
+                    channels: 4,
+                    number_type: NumberType::F32,
+                    little_endian: true,
+                };
+                binary(LINES)
+            }
+            format => text(format, LINES),
+        };
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(format!("{:?}", format), |b| {
+            let mut parser = settings.create_parser();
+            b.iter(|| parse(parser.as_mut(), &input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parsers);
+criterion_main!(benches);
@@ -0,0 +1,144 @@
+use super::{parsing_state_machine::ParsingResult, ValueParser};
+
+/// What a byte of text with ANSI escape sequences turned out to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scanned {
+    /// A byte of the text itself
+    Text(u8),
+    /// Part of an escape sequence that is not complete yet
+    Pending,
+    /// A complete control sequence like `ESC[1;31m`, its parameters and the final byte
+    Csi { params: String, command: u8 },
+    /// A complete escape sequence of another kind, e.g. an operating system command
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum State {
+    #[default]
+    Text,
+    Escape,
+    Csi,
+    /// An operating system command, ended by BEL or `ESC\`
+    Osc,
+    OscEscape,
+}
+
+/// Separates the escape sequences of terminal output, like the colors of a firmware log, from
+/// its text, one byte at a time.
+#[derive(Debug, Clone, Default)]
+pub struct EscapeScanner {
+    state: State,
+    params: Vec<u8>,
+}
+
+impl EscapeScanner {
+    pub fn scan(&mut self, byte: u8) -> Scanned {
+        const ESC: u8 = 0x1B;
+        const BEL: u8 = 0x07;
+        match (self.state, byte) {
+            (State::Text, ESC) => {
+                self.state = State::Escape;
+                Scanned::Pending
+            }
+            (State::Text, byte) => Scanned::Text(byte),
+            (State::Escape, b'[') => {
+                self.state = State::Csi;
+                self.params.clear();
+                Scanned::Pending
+            }
+            (State::Escape, b']') => {
+                self.state = State::Osc;
+                Scanned::Pending
+            }
+            // Sequences of two bytes like `ESC7`, or a second escape
+            (State::Escape, ESC) => Scanned::Pending,
+            (State::Escape, _) => {
+                self.state = State::Text;
+                Scanned::Other
+            }
+            // Parameter and intermediate bytes
+            (State::Csi, 0x20..=0x3F) => {
+                self.params.push(byte);
+                Scanned::Pending
+            }
+            (State::Csi, 0x40..=0x7E) => {
+                self.state = State::Text;
+                Scanned::Csi {
+                    params: String::from_utf8_lossy(&self.params).into_owned(),
+                    command: byte,
+                }
+            }
+            // A broken sequence, the byte ending it is not text either
+            (State::Csi, _) => {
+                self.state = State::Text;
+                Scanned::Other
+            }
+            (State::Osc, BEL) => {
+                self.state = State::Text;
+                Scanned::Other
+            }
+            (State::Osc, ESC) => {
+                self.state = State::OscEscape;
+                Scanned::Pending
+            }
+            (State::Osc, _) => Scanned::Pending,
+            (State::OscEscape, b'\\') => {
+                self.state = State::Text;
+                Scanned::Other
+            }
+            (State::OscEscape, _) => {
+                self.state = State::Osc;
+                Scanned::Pending
+            }
+        }
+    }
+}
+
+/// Hands only the text to the parser of a text format, so colored output of a device is parsed
+/// like plain lines and no byte of an escape sequence ends up in a value.
+pub struct AnsiFilter {
+    parser: Box<dyn ValueParser>,
+    scanner: EscapeScanner,
+}
+
+impl AnsiFilter {
+    pub fn new(parser: Box<dyn ValueParser>) -> Self {
+        Self {
+            parser,
+            scanner: EscapeScanner::default(),
+        }
+    }
+}
+
+impl ValueParser for AnsiFilter {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        match self.scanner.scan(byte) {
+            Scanned::Text(byte) => self.parser.parse(byte),
+            _ => ParsingResult::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parsing_state_machine::Parser, DataValue};
+
+    #[test]
+    fn should_parse_colored_lines_like_plain_ones() {
+        let mut parser = AnsiFilter::new(Box::new(Parser::new()));
+        let line = b"\x1b]0;title\x07\x1b[1;32mx:1\x1b[0m,\x1b[31my:2\x1b[m\r\n";
+        let values: Vec<DataValue> = line
+            .iter()
+            .filter_map(|byte| match parser.parse(*byte) {
+                ParsingResult::Ok(values) => Some(values),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let parsed: Vec<_> = values.iter().map(|x| (x.name.as_str(), x.value)).collect();
+        assert_eq!(parsed, [("x", 1.0), ("y", 2.0)]);
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+
+use crossbeam::channel::{Receiver, Sender, TrySendError};
+
+use super::{DataValue, ParseError};
+
+/// What a source does with its values while the channel to the ui is full.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Waits for the ui, the reader falls behind the device and its buffer may overflow
+    #[default]
+    Block,
+    /// Discards the oldest queued values to make room, the plot stays current
+    DropOldest,
+    /// Discards the new values, the plot shows a gap
+    DropNewest,
+    /// Keeps the values of every second read once the channel is half full
+    Decimate,
+}
+
+impl OverflowPolicy {
+    pub const ALL: [OverflowPolicy; 4] = [
+        OverflowPolicy::Block,
+        OverflowPolicy::DropOldest,
+        OverflowPolicy::DropNewest,
+        OverflowPolicy::Decimate,
+    ];
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL
+            .get(usize::from(value))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            OverflowPolicy::Block => "block",
+            OverflowPolicy::DropOldest => "drop oldest",
+            OverflowPolicy::DropNewest => "drop newest",
+            OverflowPolicy::Decimate => "decimate",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The overflow policy of the data channel and the values it discarded, shared by the ui and the source.
+#[derive(Debug, Default)]
+pub struct Backpressure {
+    policy: AtomicU8,
+    dropped: AtomicU64,
+    /// Alternates while decimating, so the values of every second read are kept
+    skip_next: AtomicBool,
+}
+
+impl Backpressure {
+    pub fn new(policy: OverflowPolicy) -> Self {
+        let backpressure = Self::default();
+        backpressure.set_policy(policy);
+        backpressure
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        OverflowPolicy::from_u8(self.policy.load(Ordering::Relaxed))
+    }
+
+    pub fn set_policy(&self, policy: OverflowPolicy) {
+        self.policy.store(policy as u8, Ordering::Relaxed);
+    }
+
+    /// The number of values discarded since the last reset.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn reset_dropped(&self) {
+        self.dropped.store(0, Ordering::Relaxed);
+    }
+
+    fn drop_values(&self, values: &[DataValue]) {
+        self.dropped
+            .fetch_add(values.len() as u64, Ordering::Relaxed);
+    }
+
+    /// Hands the values of a read to the ui over `data` at once, `queued` receives from the same
+    /// channel to drop the oldest values.
+    ///
+    /// `can_block` is false where the ui runs on the thread of the source, blocking then drops the new values.
+    pub fn send(
+        &self,
+        data: &Sender<Vec<DataValue>>,
+        queued: &Receiver<Vec<DataValue>>,
+        values: Vec<DataValue>,
+        can_block: bool,
+    ) -> Result<(), ParseError> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let policy = match self.policy() {
+            OverflowPolicy::Block if !can_block => OverflowPolicy::DropNewest,
+            policy => policy,
+        };
+        if policy == OverflowPolicy::Block {
+            return Ok(data.send(values)?);
+        }
+        let half_full = data
+            .capacity()
+            .is_some_and(|capacity| data.len() * 2 >= capacity);
+        if policy == OverflowPolicy::Decimate
+            && half_full
+            && self.skip_next.fetch_xor(true, Ordering::Relaxed)
+        {
+            self.drop_values(&values);
+            return Ok(());
+        }
+
+        let values = match data.try_send(values) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(_)) => return Err(ParseError::ChannelClosed),
+            Err(TrySendError::Full(values)) => values,
+        };
+        if policy != OverflowPolicy::DropOldest {
+            self.drop_values(&values);
+            return Ok(());
+        }
+        if let Ok(oldest) = queued.try_recv() {
+            self.drop_values(&oldest);
+        }
+        match data.try_send(values) {
+            Ok(()) => {}
+            Err(TrySendError::Disconnected(_)) => return Err(ParseError::ChannelClosed),
+            // Another source filled the room, the new values are lost as well
+            Err(TrySendError::Full(values)) => self.drop_values(&values),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(value: f64) -> DataValue {
+        DataValue {
+            name: "X".to_string(),
+            value,
+            timestamp: 0.0,
+            line: 0,
+        }
+    }
+
+    fn send_all(policy: OverflowPolicy, capacity: usize, count: usize) -> (Vec<f64>, u64) {
+        let backpressure = Backpressure::new(policy);
+        let (sender, receiver) = crossbeam::channel::bounded(capacity);
+        for index in 0..count {
+            backpressure
+                .send(&sender, &receiver, vec![value(index as f64)], true)
+                .unwrap();
+        }
+        let values = receiver
+            .try_iter()
+            .flatten()
+            .map(|value| value.value)
+            .collect();
+        (values, backpressure.dropped())
+    }
+
+    #[test]
+    fn should_apply_the_overflow_policy_to_a_full_channel() {
+        assert_eq!(
+            send_all(OverflowPolicy::DropOldest, 2, 4),
+            (vec![2.0, 3.0], 2)
+        );
+        assert_eq!(
+            send_all(OverflowPolicy::DropNewest, 2, 4),
+            (vec![0.0, 1.0], 2)
+        );
+        assert_eq!(
+            send_all(OverflowPolicy::Decimate, 4, 6),
+            (vec![0.0, 1.0, 2.0, 4.0], 2)
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use super::{parsing_state_machine::ParsingResult, DataValue, ValueParser};
+
+/// The encoding of a single number in a binary frame.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    F64,
+}
+
+impl NumberType {
+    pub const ALL: [NumberType; 8] = [
+        NumberType::U8,
+        NumberType::I8,
+        NumberType::U16,
+        NumberType::I16,
+        NumberType::U32,
+        NumberType::I32,
+        NumberType::F32,
+        NumberType::F64,
+    ];
+
+    pub fn size(self) -> usize {
+        match self {
+            NumberType::U8 | NumberType::I8 => 1,
+            NumberType::U16 | NumberType::I16 => 2,
+            NumberType::U32 | NumberType::I32 | NumberType::F32 => 4,
+            NumberType::F64 => 8,
+        }
+    }
+
+    /// The number stored in `bytes`, which hold exactly [`Self::size`] bytes.
+    pub fn decode(self, bytes: &[u8], little_endian: bool) -> f64 {
+        macro_rules! decode {
+            ($type:ty) => {{
+                let bytes = bytes.try_into().expect("slice has the size of the number");
+                if little_endian {
+                    <$type>::from_le_bytes(bytes) as f64
+                } else {
+                    <$type>::from_be_bytes(bytes) as f64
+                }
+            }};
+        }
+
+        match self {
+            NumberType::U8 => decode!(u8),
+            NumberType::I8 => decode!(i8),
+            NumberType::U16 => decode!(u16),
+            NumberType::I16 => decode!(i16),
+            NumberType::U32 => decode!(u32),
+            NumberType::I32 => decode!(i32),
+            NumberType::F32 => decode!(f32),
+            NumberType::F64 => decode!(f64),
+        }
+    }
+}
+
+/// Describes frames consisting of `channels` numbers of the same type without any separators.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BinaryFormat {
+    pub channels: usize,
+    pub number_type: NumberType,
+    pub little_endian: bool,
+}
+
+impl Default for BinaryFormat {
+    fn default() -> Self {
+        Self {
+            channels: 1,
+            number_type: NumberType::F32,
+            little_endian: true,
+        }
+    }
+}
+
+impl BinaryFormat {
+    pub fn frame_size(&self) -> usize {
+        self.channels.max(1) * self.number_type.size()
+    }
+}
+
+#[derive(Debug)]
+pub struct BinaryParser {
+    format: BinaryFormat,
+    frame: Vec<u8>,
+}
+
+impl BinaryParser {
+    pub fn new(format: BinaryFormat) -> Self {
+        Self {
+            frame: Vec::with_capacity(format.frame_size()),
+            format,
+        }
+    }
+}
+
+impl ValueParser for BinaryParser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        self.frame.push(byte);
+        if self.frame.len() < self.format.frame_size() {
+            return ParsingResult::Pending;
+        }
+
+        let size = self.format.number_type.size();
+        let values = self
+            .frame
+            .chunks_exact(size)
+            .enumerate()
+            .map(|(index, bytes)| DataValue {
+                name: index.to_string(),
+                value: self
+                    .format
+                    .number_type
+                    .decode(bytes, self.format.little_endian),
+                timestamp: 0.0,
+                line: 0,
+            })
+            .collect();
+        self.frame.clear();
+        ParsingResult::Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_frames() {
+        let mut parser = BinaryParser::new(BinaryFormat {
+            channels: 2,
+            number_type: NumberType::I16,
+            little_endian: false,
+        });
+
+        let results: Vec<_> = [0x01, 0x00, 0xff, 0xfe]
+            .into_iter()
+            .map(|byte| parser.parse(byte))
+            .collect();
+
+        assert_eq!(results[..3], vec![ParsingResult::Pending; 3]);
+        assert_eq!(
+            results[3],
+            ParsingResult::Ok(vec![
+                DataValue {
+                    name: "0".to_string(),
+                    value: 256.0,
+                    timestamp: 0.0,
+                    line: 0,
+                },
+                DataValue {
+                    name: "1".to_string(),
+                    value: -2.0,
+                    timestamp: 0.0,
+                    line: 0,
+                }
+            ])
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use super::{DataValue, NumberType};
+
+/// The COB-ID of the first transmit PDO of node 0, the PDOs 2 to 4 follow in steps of 0x100.
+const TPDO1: u32 = 0x180;
+
+/// An entry of the object dictionary of a node that is mapped into a PDO.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct MappedObject {
+    /// The name of the channel, `node:index.subindex` if it is empty
+    pub name: String,
+    pub index: u16,
+    pub subindex: u8,
+    pub number_type: NumberType,
+    /// Multiplies the raw value, e.g. to turn encoder increments into degrees
+    pub scale: f64,
+}
+
+impl Default for MappedObject {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            // The position actual value of CiA 402 drives
+            index: 0x6064,
+            subindex: 0,
+            number_type: NumberType::I32,
+            scale: 1.0,
+        }
+    }
+}
+
+impl MappedObject {
+    pub fn channel_name(&self, node_id: u8) -> String {
+        if self.name.trim().is_empty() {
+            format!("{}:{:04x}.{:02x}", node_id, self.index, self.subindex)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// The objects a node sends in one of its transmit PDOs, in the order of the mapping.
+///
+/// The COB-ID follows the predefined connection set, PDOs moved to other ids are not supported.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PdoMapping {
+    pub node_id: u8,
+    /// The transmit PDO from 1 to 4
+    pub pdo: u8,
+    pub objects: Vec<MappedObject>,
+}
+
+impl Default for PdoMapping {
+    fn default() -> Self {
+        Self {
+            node_id: 1,
+            pdo: 1,
+            objects: vec![MappedObject::default()],
+        }
+    }
+}
+
+impl PdoMapping {
+    pub fn cob_id(&self) -> u32 {
+        TPDO1 + 0x100 * (u32::from(self.pdo.clamp(1, 4)) - 1) + u32::from(self.node_id & 0x7f)
+    }
+
+    /// The values of the mapped objects, objects reaching past the end of `data` are left out.
+    pub fn decode(&self, data: &[u8]) -> Vec<DataValue> {
+        let mut offset = 0;
+        self.objects
+            .iter()
+            .map_while(|object| {
+                let size = object.number_type.size();
+                let bytes = data.get(offset..offset + size)?;
+                offset += size;
+                Some(DataValue {
+                    name: object.channel_name(self.node_id),
+                    // CANopen sends all numbers in little endian
+                    value: object.number_type.decode(bytes, true) * object.scale,
+                    timestamp: 0.0,
+                    line: 0,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_the_mapped_objects_of_a_pdo() {
+        let mapping = PdoMapping {
+            node_id: 5,
+            pdo: 2,
+            objects: vec![
+                MappedObject {
+                    name: "position".to_string(),
+                    scale: 0.5,
+                    ..Default::default()
+                },
+                MappedObject {
+                    index: 0x6041,
+                    number_type: NumberType::U16,
+                    ..Default::default()
+                },
+                MappedObject::default(),
+            ],
+        };
+        assert_eq!(mapping.cob_id(), 0x285);
+
+        let values = mapping.decode(&[0x10, 0x00, 0x00, 0x00, 0x37, 0x02]);
+        assert_eq!(
+            values,
+            vec![
+                DataValue {
+                    name: "position".to_string(),
+                    value: 8.0,
+                    timestamp: 0.0,
+                    line: 0,
+                },
+                DataValue {
+                    name: "5:6041.00".to_string(),
+                    value: 0x0237 as f64,
+                    timestamp: 0.0,
+                    line: 0,
+                },
+            ]
+        );
+    }
+}
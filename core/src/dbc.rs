@@ -0,0 +1,205 @@
+use super::DataValue;
+
+/// The flag of the message ids in a DBC file marking extended 29 bit ids.
+const EXTENDED_ID: u32 = 0x8000_0000;
+/// Holds the signals of a DBC file that belong to no message, it is never sent.
+const INDEPENDENT_SIGNALS: &str = "VECTOR__INDEPENDENT_SIG_MSG";
+
+/// A signal of a CAN message as described in a DBC file, e.g.
+/// `SG_ EngineSpeed : 0|16@1+ (0.125,0) [0|8031.875] "rpm" Vector__XXX`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct DbcSignal {
+    pub name: String,
+    /// The lowest bit for little endian signals, the most significant one for big endian signals
+    pub start_bit: u16,
+    pub length: u16,
+    /// `@1` (Intel) in the DBC file, `@0` is big endian (Motorola)
+    pub little_endian: bool,
+    pub signed: bool,
+    pub factor: f64,
+    pub offset: f64,
+    pub unit: String,
+}
+
+impl DbcSignal {
+    /// The scaled value of the signal, if `data` is long enough to hold it.
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        let length = u32::from(self.length);
+        if length == 0 || length > 64 {
+            return None;
+        }
+        let start = u32::from(self.start_bit);
+        // The bit of the signal closest to the start of the frame, counted from its first bit
+        let first = if self.little_endian {
+            start
+        } else {
+            (start / 8) * 8 + (7 - start % 8)
+        };
+        if first + length > (data.len().min(8) * 8) as u32 {
+            return None;
+        }
+
+        let mut bytes = [0u8; 8];
+        bytes[..data.len().min(8)].copy_from_slice(&data[..data.len().min(8)]);
+        let mask = u64::MAX >> (64 - length);
+        let raw = if self.little_endian {
+            (u64::from_le_bytes(bytes) >> first) & mask
+        } else {
+            (u64::from_be_bytes(bytes) >> (64 - first - length)) & mask
+        };
+        let raw = if !self.signed {
+            raw as f64
+        } else if length < 64 && (raw >> (length - 1)) & 1 == 1 {
+            (raw as i64 - (1i64 << length)) as f64
+        } else {
+            raw as i64 as f64
+        };
+        Some(raw * self.factor + self.offset)
+    }
+}
+
+/// A CAN message as described in a DBC file, e.g. `BO_ 291 EngineData: 8 Vector__XXX`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct DbcMessage {
+    pub id: u32,
+    /// Whether the id has 29 instead of 11 bits
+    pub extended: bool,
+    pub name: String,
+    pub signals: Vec<DbcSignal>,
+}
+
+impl DbcMessage {
+    /// The values of all signals within `data`, named after the signals.
+    pub fn decode(&self, data: &[u8]) -> Vec<DataValue> {
+        self.signals
+            .iter()
+            .filter_map(|signal| {
+                Some(DataValue {
+                    name: signal.name.clone(),
+                    value: signal.decode(data)?,
+                    timestamp: 0.0,
+                    line: 0,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads the messages and their signals from the text of a DBC file, everything else is skipped.
+///
+/// Multiplexed signals are left out, as their meaning depends on the value of the multiplexor.
+pub fn parse_dbc(text: &str) -> Result<Vec<DbcMessage>, String> {
+    let mut messages: Vec<DbcMessage> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let error = |what: &str| format!("line {}: invalid {}: {}", number + 1, what, line.trim());
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("BO_ ") {
+            let mut fields = rest.split_whitespace();
+            let id: u32 = fields
+                .next()
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| error("message"))?;
+            let name = fields
+                .next()
+                .map(|name| name.trim_end_matches(':'))
+                .ok_or_else(|| error("message"))?;
+            messages.push(DbcMessage {
+                id: id & !EXTENDED_ID,
+                extended: id & EXTENDED_ID != 0,
+                name: name.to_string(),
+                signals: Vec::new(),
+            });
+        } else if let Some(rest) = line.strip_prefix("SG_ ") {
+            let message = messages.last_mut().ok_or_else(|| error("signal"))?;
+            let signal = parse_signal(rest).ok_or_else(|| error("signal"))?;
+            if let Some(signal) = signal {
+                message.signals.push(signal);
+            }
+        }
+    }
+    messages.retain(|message| message.name != INDEPENDENT_SIGNALS && !message.signals.is_empty());
+    Ok(messages)
+}
+
+/// Parses the part of a signal line after `SG_`, `Some(None)` for multiplexed signals.
+fn parse_signal(line: &str) -> Option<Option<DbcSignal>> {
+    let (names, layout) = line.split_once(':')?;
+    let mut names = names.split_whitespace();
+    let name = names.next()?;
+    // `M` marks the multiplexor itself, `m0` a signal only present for multiplexor value 0
+    if names.next().is_some_and(|mux| mux.starts_with('m')) {
+        return Some(None);
+    }
+
+    let mut fields = layout.split_whitespace();
+    let (start_bit, rest) = fields.next()?.split_once('|')?;
+    let (length, format) = rest.split_once('@')?;
+    let (little_endian, signed) = match format {
+        "1+" => (true, false),
+        "1-" => (true, true),
+        "0+" => (false, false),
+        "0-" => (false, true),
+        _ => return None,
+    };
+    let scaling = fields.next()?.strip_prefix('(')?.strip_suffix(')')?;
+    let (factor, offset) = scaling.split_once(',')?;
+    let unit = layout.split('"').nth(1).unwrap_or_default();
+    Some(Some(DbcSignal {
+        name: name.to_string(),
+        start_bit: start_bit.parse().ok()?,
+        length: length.parse().ok()?,
+        little_endian,
+        signed,
+        factor: factor.parse().ok()?,
+        offset: offset.parse().ok()?,
+        unit: unit.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DBC: &str = r#"VERSION ""
+
+BU_: ECU
+
+BO_ 291 EngineData: 8 ECU
+ SG_ EngineSpeed : 0|16@1+ (0.125,0) [0|8031.875] "rpm" Vector__XXX
+ SG_ CoolantTemp : 16|8@1- (1,-40) [-40|215] "degC" Vector__XXX
+
+BO_ 2147484672 Motor: 8 ECU
+ SG_ Mode M : 0|8@1+ (1,0) [0|255] "" Vector__XXX
+ SG_ Current m1 : 8|16@1+ (1,0) [0|0] "A" Vector__XXX
+ SG_ Position : 7|16@0+ (1,0) [0|65535] "" Vector__XXX
+
+CM_ SG_ 291 EngineSpeed "The speed of the crankshaft";
+"#;
+
+    #[test]
+    fn should_parse_messages_and_signals() {
+        let messages = parse_dbc(DBC).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!((messages[0].id, messages[0].extended), (291, false));
+        assert_eq!((messages[1].id, messages[1].extended), (1024, true));
+        let names: Vec<_> = messages[1].signals.iter().map(|x| &x.name).collect();
+        assert_eq!(names, ["Mode", "Position"]);
+        assert_eq!(messages[0].signals[0].unit, "rpm");
+        assert!(parse_dbc("SG_ Orphan : 0|8@1+ (1,0) [0|0] \"\" X").is_err());
+    }
+
+    #[test]
+    fn should_decode_signals_in_both_byte_orders() {
+        let messages = parse_dbc(DBC).unwrap();
+        let engine = &messages[0];
+        let values = engine.decode(&[0x10, 0x27, 0xff]);
+        assert_eq!(values[0].value, 1250.0);
+        assert_eq!(values[1].value, -41.0);
+        // The frame is too short for the speed
+        assert_eq!(engine.decode(&[0x10]).len(), 0);
+
+        let position = &messages[1].signals[1];
+        assert_eq!(position.decode(&[0x27, 0x10]), Some(10000.0));
+    }
+}
@@ -0,0 +1,140 @@
+use serde_json::Value;
+
+use super::{
+    parsing_state_machine::{ParseFailure, ParsingResult},
+    DataValue, ParseError, ValueParser,
+};
+
+/// Parses lines containing a json object like `{"temperature": 21.5, "motor": {"rpm": 1200}}`.
+///
+/// Every number or boolean is a channel, nested fields are named by their path (`motor.rpm`).
+#[derive(Debug, Default)]
+pub struct JsonParser {
+    line: Vec<u8>,
+}
+
+impl ValueParser for JsonParser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        if byte != b'\n' {
+            self.line.push(byte);
+            return ParsingResult::Pending;
+        }
+
+        let line = std::mem::take(&mut self.line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return ParsingResult::Ok(Vec::new());
+        }
+
+        match serde_json::from_slice::<Value>(&line) {
+            Ok(value @ (Value::Object(_) | Value::Array(_))) => {
+                let mut values = Vec::new();
+                flatten(&value, &mut String::new(), &mut values);
+                ParsingResult::Ok(values)
+            }
+            Ok(_) | Err(_) => {
+                let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                ParsingResult::Err(ParseFailure {
+                    error: ParseError::InvalidFormat,
+                    channel: String::new(),
+                    value: line.clone(),
+                    line,
+                })
+            }
+        }
+    }
+}
+
+fn flatten(value: &Value, path: &mut String, values: &mut Vec<DataValue>) {
+    let push_child = |path: &mut String, key: &str| {
+        let length = path.len();
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+        length
+    };
+
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let length = push_child(path, key);
+                flatten(field, path, values);
+                path.truncate(length);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let length = push_child(path, &index.to_string());
+                flatten(item, path, values);
+                path.truncate(length);
+            }
+        }
+        Value::Number(number) => {
+            if let Some(value) = number.as_f64() {
+                values.push(DataValue {
+                    name: path.clone(),
+                    value,
+                    timestamp: 0.0,
+                    line: 0,
+                });
+            }
+        }
+        Value::Bool(flag) => values.push(DataValue {
+            name: path.clone(),
+            value: if *flag { 1.0 } else { 0.0 },
+            timestamp: 0.0,
+            line: 0,
+        }),
+        Value::Null | Value::String(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(parser: &mut JsonParser, line: &str) -> ParsingResult {
+        for byte in line.bytes() {
+            assert_eq!(parser.parse(byte), ParsingResult::Pending);
+        }
+        parser.parse(b'\n')
+    }
+
+    #[test]
+    fn should_flatten_nested_objects() {
+        let mut parser = JsonParser::default();
+
+        let result = parse_line(
+            &mut parser,
+            r#"{"a": 1.5, "b": {"c": 2, "d": true}, "e": [3], "f": "text"}"#,
+        );
+
+        let names_and_values = match result {
+            ParsingResult::Ok(values) => values
+                .into_iter()
+                .map(|x| (x.name, x.value))
+                .collect::<Vec<_>>(),
+            other => panic!("unexpected result {:?}", other),
+        };
+        assert_eq!(
+            names_and_values,
+            vec![
+                ("a".to_string(), 1.5),
+                ("b.c".to_string(), 2.0),
+                ("b.d".to_string(), 1.0),
+                ("e.0".to_string(), 3.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_json() {
+        let mut parser = JsonParser::default();
+
+        let result = parse_line(&mut parser, "{\"a\": 1.5\r");
+
+        assert!(
+            matches!(result, ParsingResult::Err(ParseFailure { line, .. }) if line == "{\"a\": 1.5")
+        );
+    }
+}
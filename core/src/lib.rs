@@ -0,0 +1,784 @@
+//! The parsers of serialplotter, turning the bytes received from a device into [`DataValue`]s,
+//! and the plumbing that carries them from a [`DataSource`] into the [`ValueHistory`] and the
+//! [`Sinks`].
+//!
+//! The crate has no user interface, so the parsers and the history can be reused in other tools
+//! and tested and benchmarked on their own. A parser is handed one byte at a time by [`ValueParser::parse`]:
+//!
+//! ```
+//! use serialplotter_core::{ParserSettings, ParsingResult};
+//!
+//! let mut parser = ParserSettings::default().create_parser();
+//! let results: Vec<ParsingResult> = b"x:1,y:2\n".iter().map(|byte| parser.parse(*byte)).collect();
+//! let ParsingResult::Ok(values) = results.last().unwrap() else {
+//!     panic!("the line is complete");
+//! };
+//! assert_eq!(values.len(), 2);
+//! ```
+
+use std::collections::BTreeMap;
+
+use crossbeam::channel::SendError;
+
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub struct DataValue {
+    pub name: String,
+    pub value: f64,
+    /// Seconds since the unix epoch at which the value was received.
+    /// Unless the device sends timestamps the parser leaves this at zero, the source stamps it before handing the value on.
+    pub timestamp: f64,
+    /// The number of the line the value arrived in, the values of a line share it.
+    /// The parser leaves this at zero, the source numbers the lines with [`SourceSenders::next_line`].
+    #[serde(skip)]
+    pub line: u64,
+}
+
+/// The current time in seconds since the unix epoch, as used for [`DataValue::timestamp`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_timestamp() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// The current time in seconds since the unix epoch, as used for [`DataValue::timestamp`].
+#[cfg(target_arch = "wasm32")]
+pub fn unix_timestamp() -> f64 {
+    js_sys::Date::now() / 1000.0
+}
+
+pub use ansi::{AnsiFilter, EscapeScanner, Scanned};
+pub use backpressure::{Backpressure, OverflowPolicy};
+pub use binary_parser::{BinaryFormat, BinaryParser, NumberType};
+pub use canopen::{MappedObject, PdoMapping};
+pub use dbc::{parse_dbc, DbcMessage};
+pub use json_parser::JsonParser;
+pub use line_check::LineCheck;
+pub use nmea_parser::NmeaParser;
+pub use parsing_state_machine::{ParseFailure, Parser, ParsingResult};
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+pub use plugin_parser::WasmPlugin;
+pub use plugin_parser::{ParserPlugin, PluginParser, PluginStep, PLUGIN_ABI_VERSION};
+pub use sample_buffer::{Encoding, Precision, SampleBuffer};
+pub use sinks::{DataSink, SinkError, Sinks, FLUSH_INTERVAL};
+pub use slcan_parser::{CanSettings, SlcanParser, BITRATES};
+pub use source::{process_chunk, Commands, DataSource, SourceEvent, SourceSenders};
+pub use teleplot_parser::TeleplotParser;
+pub use value_history::{
+    alias, decimate, decimate_with, interpolate, HistoryLimits, Sample, ValueHistory,
+};
+
+/// Turns the bytes received from a source into values, one byte at a time.
+pub trait ValueParser: Send {
+    fn parse(&mut self, byte: u8) -> ParsingResult;
+}
+
+impl ValueParser for Parser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        Parser::parse(self, byte)
+    }
+}
+
+/// The formats a source can send its values in.
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum DataFormat {
+    /// Comma separated values with optional `name:` prefixes, one line per set of values
+    Csv,
+    /// One json object per line, every numeric field is a channel
+    Json,
+    /// Fixed size frames of binary numbers
+    Binary,
+    /// The formats the plotter of the Arduino IDE accepts: values separated by commas, spaces or tabs
+    Arduino,
+    /// The line protocol of Teleplot, `>name:value` with optional timestamps
+    Teleplot,
+    /// CAN frames received by a SLCAN adapter, decoded as CANopen PDOs or with a DBC file
+    Slcan,
+    /// The GGA, RMC and VTG sentences of GPS modules
+    Nmea,
+    /// A WebAssembly module loaded at runtime, for protocols the plotter does not know
+    Plugin,
+}
+
+impl DataFormat {
+    /// Whether the format sends text lines, whose integrity can be checked with a [`LineCheck`].
+    pub fn has_lines(self) -> bool {
+        matches!(
+            self,
+            DataFormat::Csv | DataFormat::Json | DataFormat::Arduino | DataFormat::Teleplot
+        )
+    }
+}
+
+/// The bytes that end the lines of the Csv format and separate their fields.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct Delimiters {
+    /// Ends a line, a `\r` is ignored so `\n` ends `\r\n` lines as well
+    pub line_end: u8,
+    /// Separates the values of a line
+    pub field: u8,
+    /// Separates the name of a value from the value
+    pub key_value: u8,
+    /// Separates the integer part of a value from its fraction, a comma for European locales
+    pub decimal: u8,
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self {
+            line_end: b'\n',
+            field: b',',
+            key_value: b':',
+            decimal: b'.',
+        }
+    }
+}
+
+impl Delimiters {
+    pub const LINE_ENDS: [(u8, &'static str); 2] = [(b'\n', "LF / CRLF"), (b';', "semicolon")];
+    pub const FIELDS: [(u8, &'static str); 4] = [
+        (b',', "comma"),
+        (b'\t', "tab"),
+        (b' ', "space"),
+        (b';', "semicolon"),
+    ];
+    pub const KEY_VALUES: [(u8, &'static str); 2] = [(b':', "colon"), (b'=', "equals sign")];
+    pub const DECIMALS: [(u8, &'static str); 2] = [(b'.', "point"), (b',', "comma")];
+}
+
+impl std::fmt::Display for Delimiters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let escaped = |byte: u8| std::ascii::escape_default(byte).to_string();
+        write!(
+            f,
+            "lines ending with '{}', fields separated by '{}', names by '{}', decimal '{}'",
+            escaped(self.line_end),
+            escaped(self.field),
+            escaped(self.key_value),
+            escaped(self.decimal)
+        )
+    }
+}
+
+/// Selects and configures the parser used for new connections.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ParserSettings {
+    pub format: DataFormat,
+    pub binary: BinaryFormat,
+    pub can: CanSettings,
+    /// The checksum every line ends with, by the format it is checked for
+    pub line_checks: BTreeMap<DataFormat, LineCheck>,
+    pub delimiters: Delimiters,
+    /// The path of the WebAssembly module of [`DataFormat::Plugin`]
+    pub plugin: String,
+}
+
+impl Default for ParserSettings {
+    fn default() -> Self {
+        Self {
+            format: DataFormat::Csv,
+            binary: BinaryFormat::default(),
+            can: CanSettings::default(),
+            line_checks: BTreeMap::new(),
+            delimiters: Delimiters::default(),
+            plugin: String::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParserSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            DataFormat::Binary => write!(
+                f,
+                "Binary ({} × {:?}, {} endian)",
+                self.binary.channels,
+                self.binary.number_type,
+                if self.binary.little_endian {
+                    "little"
+                } else {
+                    "big"
+                }
+            ),
+            DataFormat::Slcan => write!(
+                f,
+                "Slcan ({} PDOs, {} DBC messages)",
+                self.can.pdos.len(),
+                self.can.messages.len()
+            ),
+            DataFormat::Plugin => write!(f, "Plugin {}", self.plugin),
+            DataFormat::Csv if self.delimiters != Delimiters::default() => {
+                write!(f, "Csv ({})", self.delimiters)
+            }
+            format => write!(f, "{:?}", format),
+        }?;
+        match self.line_check() {
+            LineCheck::None => Ok(()),
+            check => write!(f, ", {} checked", check),
+        }
+    }
+}
+
+impl ParserSettings {
+    /// The checksum the lines of the selected format end with.
+    pub fn line_check(&self) -> LineCheck {
+        match self.format.has_lines() {
+            true => self
+                .line_checks
+                .get(&self.format)
+                .copied()
+                .unwrap_or_default(),
+            false => LineCheck::None,
+        }
+    }
+
+    /// The byte the lines of the selected format end with.
+    fn line_end(&self) -> u8 {
+        match self.format {
+            DataFormat::Csv => self.delimiters.line_end,
+            _ => b'\n',
+        }
+    }
+
+    pub fn create_parser(&self) -> Box<dyn ValueParser> {
+        let parser: Box<dyn ValueParser> = match self.format {
+            DataFormat::Csv => Box::new(Parser::with_delimiters(self.delimiters)),
+            DataFormat::Json => Box::new(JsonParser::default()),
+            DataFormat::Binary => Box::new(BinaryParser::new(self.binary.clone())),
+            DataFormat::Arduino => Box::new(Parser::arduino()),
+            DataFormat::Teleplot => Box::new(TeleplotParser::default()),
+            DataFormat::Slcan => Box::new(SlcanParser::new(self.can.clone())),
+            DataFormat::Nmea => Box::new(NmeaParser::default()),
+            DataFormat::Plugin => plugin_parser::load(&self.plugin),
+        };
+        let parser: Box<dyn ValueParser> = match self.line_check() {
+            LineCheck::None => parser,
+            check => Box::new(line_check::CheckedLines::new(
+                parser,
+                check,
+                self.line_end(),
+            )),
+        };
+        // Escape sequences are never part of the values of a text format, e.g. colored log lines
+        match self.format.has_lines() {
+            true => Box::new(AnsiFilter::new(parser)),
+            false => parser,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseError {
+    ChannelClosed,
+    InvalidFormat,
+}
+
+impl<T> From<SendError<T>> for ParseError {
+    fn from(_value: SendError<T>) -> Self {
+        Self::ChannelClosed
+    }
+}
+
+pub mod parsing_state_machine {
+    use std::{borrow::Cow, mem};
+
+    use super::{DataValue, Delimiters, ParseError};
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ParsingResult {
+        Ok(Vec<DataValue>),
+        Pending,
+        Err(ParseFailure),
+    }
+
+    impl From<Result<Vec<DataValue>, ParseFailure>> for ParsingResult {
+        fn from(other: Result<Vec<DataValue>, ParseFailure>) -> Self {
+            match other {
+                Ok(values) => ParsingResult::Ok(values),
+                Err(failure) => ParsingResult::Err(failure),
+            }
+        }
+    }
+
+    /// Describes a line that was discarded because one of its values could not be parsed.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ParseFailure {
+        pub error: ParseError,
+        /// The channel the invalid value would have been stored in.
+        pub channel: String,
+        /// The text that could not be parsed as a value.
+        pub value: String,
+        /// The complete line as it was received, without the line ending.
+        pub line: String,
+    }
+
+    /// The parser of lines like `x:1,y:2`, the buffers are kept between lines, so only the names
+    /// of the values and the list handed out per line are allocated.
+    #[derive(Debug, Clone)]
+    pub struct Parser {
+        /// Spaces and tabs separate values like commas, as in the plotter of the Arduino IDE
+        arduino: bool,
+        delimiters: Delimiters,
+        name: Option<String>,
+        value: Vec<u8>,
+        line: Vec<u8>,
+        failure: Option<ParseFailure>,
+        completed_values: Vec<DataValue>,
+    }
+
+    impl Default for Parser {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Parser {
+        pub fn new() -> Self {
+            Self {
+                arduino: false,
+                delimiters: Delimiters::default(),
+                name: None,
+                value: Vec::with_capacity(32),
+                line: Vec::with_capacity(256),
+                failure: None,
+                completed_values: Vec::new(),
+            }
+        }
+
+        pub fn with_delimiters(delimiters: Delimiters) -> Self {
+            Self {
+                delimiters,
+                ..Self::new()
+            }
+        }
+
+        pub fn arduino() -> Self {
+            Self {
+                arduino: true,
+                ..Self::new()
+            }
+        }
+
+        pub fn parse(&mut self, byte: u8) -> ParsingResult {
+            let Delimiters {
+                line_end,
+                field,
+                key_value,
+                ..
+            } = self.delimiters;
+            if byte != line_end && byte != b'\r' && byte != b'\n' {
+                self.line.push(byte);
+            }
+
+            match byte {
+                x if x == line_end => ParsingResult::from(self.finish()),
+                // Part of `\r\n` line endings, or left over between lines ending otherwise
+                b'\r' | b'\n' => ParsingResult::Pending,
+                b',' | b' ' | b'\t' if self.arduino => {
+                    // Runs of separators like `, ` or the space in `label: value` separate nothing
+                    if !self.value.is_empty() {
+                        self.complete_value();
+                    }
+                    ParsingResult::Pending
+                }
+                x if x == field => {
+                    self.complete_value();
+                    ParsingResult::Pending
+                }
+                x if x == key_value => {
+                    self.name = Some(text(&self.value).into_owned());
+                    self.value.clear();
+
+                    ParsingResult::Pending
+                }
+                b' ' | b'\t' => ParsingResult::Pending, // Whitespace is ignored
+                x => {
+                    self.value.push(x);
+
+                    ParsingResult::Pending
+                }
+            }
+        }
+
+        fn finish(&mut self) -> Result<Vec<DataValue>, ParseFailure> {
+            if self.line.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            if !(self.arduino && self.value.is_empty()) {
+                self.complete_value();
+            }
+            let result = match self.failure.take() {
+                // The next line most likely has as many values
+                None => {
+                    let capacity = self.completed_values.len();
+                    Ok(mem::replace(
+                        &mut self.completed_values,
+                        Vec::with_capacity(capacity),
+                    ))
+                }
+                Some(mut failure) => {
+                    failure.line = String::from_utf8_lossy(&self.line).into_owned();
+                    Err(failure)
+                }
+            };
+            self.reset();
+            result
+        }
+
+        fn complete_value(&mut self) {
+            let name = match self.name.take() {
+                None => self.completed_values.len().to_string(),
+                Some(name) => name,
+            };
+            let value = text(&self.value);
+            match parse_number(&value, self.delimiters.decimal) {
+                Some(value) => self.completed_values.push(DataValue {
+                    name,
+                    value,
+                    timestamp: 0.0,
+                    line: 0,
+                }),
+                None => {
+                    // Only the first invalid value of a line is reported, the whole line is discarded anyway.
+                    if self.failure.is_none() {
+                        self.failure = Some(ParseFailure {
+                            error: ParseError::InvalidFormat,
+                            channel: name,
+                            value: value.into_owned(),
+                            line: String::new(),
+                        });
+                    }
+                }
+            }
+            self.value.clear();
+        }
+
+        fn reset(&mut self) {
+            self.name = None;
+            self.value.clear();
+            self.line.clear();
+            self.failure = None;
+            self.completed_values.clear();
+        }
+    }
+
+    /// The received bytes as text, every byte is a character of its own, as in Latin-1.
+    ///
+    /// Only text outside of ASCII is copied, numbers never are.
+    fn text(bytes: &[u8]) -> Cow<'_, str> {
+        if bytes.is_ascii() {
+            Cow::Borrowed(std::str::from_utf8(bytes).expect("ASCII is valid UTF-8"))
+        } else {
+            Cow::Owned(bytes.iter().map(|&byte| char::from(byte)).collect())
+        }
+    }
+
+    /// Parses a decimal number with the `decimal` separator, or an integer like `0x1A3F` or `0b1010`
+    /// as devices print register contents.
+    ///
+    /// Scientific notation, `nan` and `inf` are understood by the parsing of Rust.
+    fn parse_number(text: &str, decimal: u8) -> Option<f64> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let radix = match digits.get(..2) {
+            Some("0x" | "0X") => 16,
+            Some("0b" | "0B") => 2,
+            _ => {
+                return match decimal {
+                    b'.' => text.parse().ok(),
+                    decimal => parse_with_decimal(text, decimal),
+                };
+            }
+        };
+        let magnitude = u64::from_str_radix(&digits[2..], radix).ok()? as f64;
+        Some(if negative { -magnitude } else { magnitude })
+    }
+
+    /// Parses a number with another decimal separator than `.`, short numbers without allocating.
+    fn parse_with_decimal(text: &str, decimal: u8) -> Option<f64> {
+        let mut buffer = [0; 64];
+        let Some(copy) = buffer.get_mut(..text.len()) else {
+            return text.replace(char::from(decimal), ".").parse().ok();
+        };
+        for (copy, byte) in copy.iter_mut().zip(text.bytes()) {
+            *copy = if byte == decimal { b'.' } else { byte };
+        }
+        std::str::from_utf8(copy).ok()?.parse().ok()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn simple_test() {
+            parser_test(
+                "X:0,Y:0",
+                vec![
+                    DataValue {
+                        name: "X".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                    DataValue {
+                        name: "Y".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                ],
+            )
+        }
+
+        #[test]
+        fn should_parse_data_without_names() {
+            parser_test(
+                "0,0",
+                vec![
+                    DataValue {
+                        name: "0".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                    DataValue {
+                        name: "1".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                ],
+            )
+        }
+
+        #[test]
+        fn multi_line_test() {
+            let data = b"0,0\n1,1";
+            let mut parser = Parser::new();
+
+            for byte in &data[..3] {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+
+            assert_eq!(
+                parser.parse(data[3]),
+                ParsingResult::Ok(vec![
+                    DataValue {
+                        name: "0".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                    DataValue {
+                        name: "1".to_string(),
+                        value: 0.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                ],)
+            );
+
+            for byte in &data[4..] {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+
+            assert_eq!(
+                parser.finish(),
+                Result::Ok(vec![
+                    DataValue {
+                        name: "0".to_string(),
+                        value: 1.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                    DataValue {
+                        name: "1".to_string(),
+                        value: 1.0,
+                        timestamp: 0.0,
+                        line: 0,
+                    },
+                ],)
+            )
+        }
+
+        #[test]
+        fn should_report_invalid_value_with_line() {
+            let mut parser = Parser::new();
+            for byte in b"X:1,Y:1.2.3" {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+
+            assert_eq!(
+                parser.parse(b'\n'),
+                ParsingResult::Err(ParseFailure {
+                    error: ParseError::InvalidFormat,
+                    channel: "Y".to_string(),
+                    value: "1.2.3".to_string(),
+                    line: "X:1,Y:1.2.3".to_string(),
+                })
+            );
+
+            // The failure must not leak into the next line
+            for byte in b"X:2" {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+            assert_eq!(
+                parser.parse(b'\n'),
+                ParsingResult::Ok(vec![DataValue {
+                    name: "X".to_string(),
+                    value: 2.0,
+                    timestamp: 0.0,
+                    line: 0,
+                }])
+            );
+        }
+
+        #[test]
+        fn should_ignore_empty_lines() {
+            let mut parser = Parser::new();
+            assert_eq!(parser.parse(b'\n'), ParsingResult::Ok(vec![]));
+        }
+
+        #[test]
+        fn should_ignore_carriage_returns() {
+            let mut parser = Parser::new();
+            for byte in b"X:1\r" {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+
+            assert_eq!(
+                parser.parse(b'\n'),
+                ParsingResult::Ok(vec![DataValue {
+                    name: "X".to_string(),
+                    value: 1.0,
+                    timestamp: 0.0,
+                    line: 0,
+                }])
+            );
+        }
+
+        #[test]
+        fn should_parse_arduino_plotter_lines() {
+            let value = |name: &str, value| DataValue {
+                name: name.to_string(),
+                value,
+                timestamp: 0.0,
+                line: 0,
+            };
+            let lines: [(&[u8], _); 3] = [
+                (
+                    b"1 2\t3\r\n",
+                    vec![value("0", 1.0), value("1", 2.0), value("2", 3.0)],
+                ),
+                (b"1, 2 \r\n", vec![value("0", 1.0), value("1", 2.0)]),
+                (
+                    b"temp: 25.5\thum:40\r\n",
+                    vec![value("temp", 25.5), value("hum", 40.0)],
+                ),
+            ];
+
+            let mut parser = Parser::arduino();
+            for (line, expected) in lines {
+                let (last, rest) = line.split_last().unwrap();
+                for byte in rest {
+                    assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+                }
+                assert_eq!(parser.parse(*last), ParsingResult::Ok(expected));
+            }
+        }
+
+        #[test]
+        fn should_split_at_configured_delimiters() {
+            let mut parser = Parser::with_delimiters(Delimiters {
+                line_end: b';',
+                field: b'\t',
+                key_value: b'=',
+                ..Delimiters::default()
+            });
+            let mut results = b"\r\na=1\tb=2;c=3;"
+                .iter()
+                .map(|byte| parser.parse(*byte))
+                .filter(|result| *result != ParsingResult::Pending);
+            let value = |name: &str, value| DataValue {
+                name: name.to_string(),
+                value,
+                timestamp: 0.0,
+                line: 0,
+            };
+            assert_eq!(
+                results.next(),
+                Some(ParsingResult::Ok(vec![value("a", 1.0), value("b", 2.0)]))
+            );
+            assert_eq!(
+                results.next(),
+                Some(ParsingResult::Ok(vec![value("c", 3.0)]))
+            );
+        }
+
+        #[test]
+        fn should_parse_decimal_commas_and_special_numbers() {
+            let mut parser = Parser::with_delimiters(Delimiters {
+                field: b';',
+                decimal: b',',
+                ..Delimiters::default()
+            });
+            for byte in b"a:2,5;b:-1,5e3;c:nan;d:-inf" {
+                assert_eq!(parser.parse(*byte), ParsingResult::Pending);
+            }
+            let ParsingResult::Ok(values) = parser.parse(b'\n') else {
+                panic!("the line was not parsed");
+            };
+            let values: Vec<f64> = values.iter().map(|x| x.value).collect();
+            assert_eq!(values[..2], [2.5, -1500.0]);
+            assert!(values[2].is_nan());
+            assert_eq!(values[3], f64::NEG_INFINITY);
+        }
+
+        #[test]
+        fn should_parse_hexadecimal_and_binary_integers() {
+            assert_eq!(parse_number("0x1A3F", b'.'), Some(6719.0));
+            assert_eq!(parse_number("0B1010", b'.'), Some(10.0));
+            assert_eq!(parse_number("-0x10", b'.'), Some(-16.0));
+            assert_eq!(parse_number("1023", b'.'), Some(1023.0));
+            assert_eq!(parse_number("0x", b'.'), None);
+            assert_eq!(parse_number("0b102", b'.'), None);
+        }
+
+        fn parser_test(data: &str, expected_values: Vec<DataValue>) {
+            let mut parser = Parser::new();
+            for byte in data.bytes() {
+                assert_eq!(parser.parse(byte), ParsingResult::Pending);
+            }
+
+            assert_eq!(parser.finish(), Result::Ok(expected_values))
+        }
+    }
+}
+
+mod ansi;
+mod backpressure;
+mod binary_parser;
+mod canopen;
+mod dbc;
+mod json_parser;
+mod line_check;
+mod nmea_parser;
+mod plugin_parser;
+mod sample_buffer;
+mod sinks;
+mod slcan_parser;
+mod source;
+mod teleplot_parser;
+mod value_history;
@@ -0,0 +1,187 @@
+use std::fmt::Display;
+
+use super::{
+    parsing_state_machine::{ParseFailure, ParsingResult},
+    ParseError, ValueParser,
+};
+
+/// How the integrity of every line is checked before its values are parsed.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCheck {
+    #[default]
+    None,
+    /// The line ends with `*XX`, the hexadecimal XOR of all bytes before the `*`, like NMEA
+    Xor,
+    /// The line ends with `*XXXX`, the hexadecimal CRC-16/CCITT-FALSE of all bytes before the `*`
+    Crc16,
+}
+
+impl LineCheck {
+    pub const ALL: [LineCheck; 3] = [LineCheck::None, LineCheck::Xor, LineCheck::Crc16];
+
+    /// The expected suffix of `content` in hexadecimal.
+    fn checksum(self, content: &[u8]) -> String {
+        match self {
+            LineCheck::None => String::new(),
+            LineCheck::Xor => format!("{:02X}", content.iter().fold(0u8, |xor, x| xor ^ x)),
+            LineCheck::Crc16 => format!("{:04X}", crc16(content)),
+        }
+    }
+}
+
+impl Display for LineCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            LineCheck::None => "none",
+            LineCheck::Xor => "XOR",
+            LineCheck::Crc16 => "CRC16",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// CRC-16/CCITT-FALSE, the polynomial 0x1021 starting at 0xFFFF.
+fn crc16(content: &[u8]) -> u16 {
+    content.iter().fold(0xFFFF, |crc, byte| {
+        let mut crc = crc ^ (u16::from(*byte) << 8);
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+/// Checks every line before handing it without its checksum to the parser of the protocol.
+///
+/// Lines with a missing or wrong checksum are discarded and reported as parse errors, instead of
+/// plotting the values of a corrupted line.
+pub struct CheckedLines {
+    parser: Box<dyn ValueParser>,
+    check: LineCheck,
+    /// The byte the lines end with, as the inner parser expects it
+    line_end: u8,
+    line: Vec<u8>,
+}
+
+impl CheckedLines {
+    pub fn new(parser: Box<dyn ValueParser>, check: LineCheck, line_end: u8) -> Self {
+        Self {
+            parser,
+            check,
+            line_end,
+            line: Vec::new(),
+        }
+    }
+
+    fn check_line(&self, line: &[u8]) -> Result<usize, ParseFailure> {
+        let text = String::from_utf8_lossy(line);
+        let failure = |value: String| ParseFailure {
+            error: ParseError::InvalidFormat,
+            channel: String::new(),
+            value,
+            line: text.to_string(),
+        };
+        let Some(star) = line.iter().rposition(|x| *x == b'*') else {
+            return Err(failure(format!("no {} checksum", self.check)));
+        };
+        let expected = self.check.checksum(&line[..star]);
+        let received = String::from_utf8_lossy(&line[star + 1..]);
+        if !received.trim().eq_ignore_ascii_case(&expected) {
+            return Err(failure(format!(
+                "{} checksum {} instead of {}",
+                self.check, received, expected
+            )));
+        }
+        Ok(star)
+    }
+}
+
+impl ValueParser for CheckedLines {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        if byte != self.line_end {
+            self.line.push(byte);
+            return ParsingResult::Pending;
+        }
+
+        let mut line = std::mem::take(&mut self.line);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        // The line ending of the device is left over when lines end with another byte
+        let leftover = line
+            .iter()
+            .take_while(|x| matches!(x, b'\r' | b'\n'))
+            .count();
+        line.drain(..leftover);
+        if line.is_empty() {
+            return ParsingResult::Ok(Vec::new());
+        }
+        let content = match self.check_line(&line) {
+            Ok(star) => &line[..star],
+            Err(failure) => return ParsingResult::Err(failure),
+        };
+
+        // The whole line is parsed even after a failure, so the parser starts the next one clean
+        let mut values = Vec::new();
+        let mut failure = None;
+        for byte in content.iter().chain([&self.line_end]) {
+            match self.parser.parse(*byte) {
+                ParsingResult::Pending => {}
+                ParsingResult::Ok(parsed) => values.extend(parsed),
+                ParsingResult::Err(err) => failure = failure.or(Some(err)),
+            }
+        }
+        match failure {
+            Some(failure) => ParsingResult::Err(failure),
+            None => ParsingResult::Ok(values),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing_state_machine::Parser;
+
+    fn parse_line(parser: &mut CheckedLines, line: &str) -> ParsingResult {
+        for byte in line.bytes() {
+            assert_eq!(parser.parse(byte), ParsingResult::Pending);
+        }
+        parser.parse(b'\n')
+    }
+
+    fn values(result: ParsingResult) -> Vec<(String, f64)> {
+        match result {
+            ParsingResult::Ok(values) => values.into_iter().map(|x| (x.name, x.value)).collect(),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_discard_lines_with_a_wrong_checksum() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+
+        let mut parser = CheckedLines::new(Box::new(Parser::new()), LineCheck::Xor, b'\n');
+        let result = parse_line(&mut parser, "a:1,b:2*2C\r");
+        assert_eq!(
+            values(result),
+            [("a".to_string(), 1.0), ("b".to_string(), 2.0)]
+        );
+        let ParsingResult::Err(failure) = parse_line(&mut parser, "a:1,b:3*2C") else {
+            panic!("the corrupted line was accepted");
+        };
+        assert_eq!(failure.value, "XOR checksum 2C instead of 2D");
+        assert!(matches!(
+            parse_line(&mut parser, "a:1,b:2"),
+            ParsingResult::Err(_)
+        ));
+
+        let mut parser = CheckedLines::new(Box::new(Parser::new()), LineCheck::Crc16, b'\n');
+        let line = format!("a:1*{:04x}", crc16(b"a:1"));
+        assert_eq!(values(parse_line(&mut parser, &line)).len(), 1);
+    }
+}
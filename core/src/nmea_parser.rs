@@ -0,0 +1,157 @@
+use super::{
+    parsing_state_machine::{ParseFailure, ParsingResult},
+    DataValue, ParseError, ValueParser,
+};
+
+/// Parses the GGA, RMC and VTG sentences of NMEA 0183, as sent by most GPS modules.
+///
+/// Positions are in decimal degrees, negative to the south and west. Fields a receiver leaves
+/// empty without a fix are skipped, as are other sentences. Sentences with a checksum that does
+/// not match their content are reported.
+#[derive(Debug, Default)]
+pub struct NmeaParser {
+    line: Vec<u8>,
+}
+
+impl ValueParser for NmeaParser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        if byte != b'\n' {
+            self.line.push(byte);
+            return ParsingResult::Pending;
+        }
+
+        let line = std::mem::take(&mut self.line);
+        let line = String::from_utf8_lossy(&line);
+        ParsingResult::from(parse_sentence(line.trim()))
+    }
+}
+
+fn parse_sentence(line: &str) -> Result<Vec<DataValue>, ParseFailure> {
+    let Some(sentence) = line.strip_prefix('$') else {
+        return Ok(Vec::new());
+    };
+    let failure = |channel: &str, value: String| ParseFailure {
+        error: ParseError::InvalidFormat,
+        channel: channel.to_string(),
+        value,
+        line: line.to_string(),
+    };
+    let address = sentence.split(',').next().unwrap_or_default();
+    // The checksum is optional before NMEA 0183 version 2.3
+    let body = match sentence.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = body.bytes().fold(0u8, |checksum, byte| checksum ^ byte);
+            if u8::from_str_radix(checksum.trim(), 16) != Ok(expected) {
+                return Err(failure(
+                    address,
+                    format!("checksum {} instead of {:02X}", checksum, expected),
+                ));
+            }
+            body
+        }
+        None => sentence,
+    };
+
+    let fields: Vec<&str> = body.split(',').collect();
+    let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+    let mut values = Vec::new();
+    let mut push = |name: &str, value: Option<f64>| {
+        values.extend(value.map(|value| DataValue {
+            name: name.to_string(),
+            value,
+            // Stamped with the time of reception by the source
+            timestamp: 0.0,
+            line: 0,
+        }));
+    };
+    let number = |index: usize| -> Result<Option<f64>, ParseFailure> {
+        match field(index) {
+            "" => Ok(None),
+            field => field
+                .parse()
+                .map(Some)
+                .map_err(|_| failure(address, field.to_string())),
+        }
+    };
+    let coordinate = |index: usize| -> Result<Option<f64>, ParseFailure> {
+        let Some(value) = number(index)? else {
+            return Ok(None);
+        };
+        // Degrees and minutes, e.g. 4807.038 for 48° 7.038'
+        let degrees = (value / 100.0).trunc();
+        let degrees = degrees + (value - degrees * 100.0) / 60.0;
+        Ok(Some(match field(index + 1) {
+            "S" | "W" => -degrees,
+            _ => degrees,
+        }))
+    };
+
+    // The first two letters name the talker, e.g. GP for GPS or GN for several systems
+    match address.get(2..) {
+        Some("GGA") => {
+            push("latitude", coordinate(2)?);
+            push("longitude", coordinate(4)?);
+            push("fix_quality", number(6)?);
+            push("satellites", number(7)?);
+            push("hdop", number(8)?);
+            push("altitude", number(9)?);
+        }
+        // Without a valid fix the receiver repeats its last position
+        Some("RMC") if field(2) == "A" => {
+            push("latitude", coordinate(3)?);
+            push("longitude", coordinate(5)?);
+            push("speed_knots", number(7)?);
+            push("course", number(8)?);
+        }
+        Some("VTG") => {
+            push("course", number(1)?);
+            push("speed_knots", number(5)?);
+            push("speed_kmh", number(7)?);
+        }
+        _ => {}
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(line: &str) -> Vec<(String, f64)> {
+        parse_sentence(line)
+            .unwrap()
+            .into_iter()
+            .map(|value| (value.name, (value.value * 1e6).round() / 1e6))
+            .collect()
+    }
+
+    #[test]
+    fn should_parse_sentences_and_validate_their_checksum() {
+        let gga = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+        assert_eq!(
+            values(gga),
+            [
+                ("latitude", 48.1173),
+                ("longitude", 11.516667),
+                ("fix_quality", 1.0),
+                ("satellites", 8.0),
+                ("hdop", 0.9),
+                ("altitude", 545.4),
+            ]
+            .map(|(name, value)| (name.to_string(), value))
+        );
+
+        let rmc = "$GPRMC,123519,A,4807.038,S,01131.000,W,022.4,084.4,230394,003.1,W";
+        assert_eq!(values(rmc)[0], ("latitude".to_string(), -48.1173));
+        assert_eq!(values(rmc)[2], ("speed_knots".to_string(), 22.4));
+        assert!(values("$GPRMC,123519,V,,,,,,,230394,,*33").is_empty());
+
+        let vtg = "$GNVTG,054.7,T,034.4,M,005.5,N,010.2,K*56";
+        assert_eq!(values(vtg)[2], ("speed_kmh".to_string(), 10.2));
+
+        let corrupted = "$GPGGA,123519,4807.038,N,01131.000,E,1,09,0.9,545.4,M,46.9,M,,*47";
+        let failure = parse_sentence(corrupted).unwrap_err();
+        assert_eq!(failure.channel, "GPGGA");
+        assert_eq!(failure.value, "checksum 47 instead of 46");
+    }
+}
@@ -0,0 +1,345 @@
+//! Parsers loaded at runtime, for protocols the plotter does not know.
+//!
+//! A plugin is a WebAssembly module that exports:
+//!
+//! - `plugin_abi_version() -> i32`, returning [`PLUGIN_ABI_VERSION`]
+//! - `parse(byte: i32) -> i32`, which is handed every received byte and returns `-1` while the
+//!   values are incomplete, `-2` for invalid input or the number of values the byte completed
+//! - `value(index: i32) -> f64` and the optional `timestamp(index: i32) -> f64` of a completed
+//!   value, a timestamp of 0 stands for the time the byte was received
+//! - `name(index: i32) -> i32` and `name_len(index: i32) -> i32`, the address and length of
+//!   the UTF-8 name of a completed value in the exported `memory`
+//!
+//! The module can not import any functions.
+
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+use wasmi::{
+    core::F64, Engine, Instance, Linker, Memory, Module, Store, TypedFunc, WasmParams, WasmResults,
+};
+
+use super::{DataValue, ParseError, ParseFailure, ParsingResult, ValueParser};
+
+/// The version of the interface between the plotter and its parser plugins.
+pub const PLUGIN_ABI_VERSION: i32 = 1;
+
+/// The most bytes of invalid input kept to describe it.
+#[cfg_attr(
+    not(all(feature = "plugins", not(target_arch = "wasm32"))),
+    allow(dead_code)
+)]
+const MAX_LINE: usize = 256;
+
+/// What a plugin made of a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginStep {
+    Pending,
+    Invalid,
+    /// The byte completed this many values
+    Completed(usize),
+}
+
+/// The interface of a parser loaded at runtime.
+///
+/// It only changes with [`PLUGIN_ABI_VERSION`], so plugins keep working with later versions of
+/// the plotter. The errors are failures of the plugin itself, e.g. a trap of a WebAssembly module.
+pub trait ParserPlugin: Send {
+    /// Hands the next received byte to the plugin.
+    fn parse(&mut self, byte: u8) -> Result<PluginStep, String>;
+
+    /// A value completed by the last byte, `index` is below the count of [`PluginStep::Completed`].
+    fn value(&mut self, index: usize) -> Result<DataValue, String>;
+}
+
+/// Parses with a [`ParserPlugin`], keeping the bytes since the last completed values to
+/// describe invalid input.
+#[cfg_attr(
+    not(all(feature = "plugins", not(target_arch = "wasm32"))),
+    allow(dead_code)
+)]
+pub struct PluginParser<P> {
+    plugin: P,
+    line: Vec<u8>,
+}
+
+#[cfg_attr(
+    not(all(feature = "plugins", not(target_arch = "wasm32"))),
+    allow(dead_code)
+)]
+impl<P: ParserPlugin> PluginParser<P> {
+    pub fn new(plugin: P) -> Self {
+        Self {
+            plugin,
+            line: Vec::new(),
+        }
+    }
+}
+
+impl<P: ParserPlugin> ValueParser for PluginParser<P> {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        if self.line.len() < MAX_LINE {
+            self.line.push(byte);
+        }
+        let values = match self.plugin.parse(byte) {
+            Ok(PluginStep::Pending) => return ParsingResult::Pending,
+            Ok(PluginStep::Invalid) => Err(String::new()),
+            Ok(PluginStep::Completed(count)) => {
+                (0..count).map(|index| self.plugin.value(index)).collect()
+            }
+            Err(err) => Err(err),
+        };
+        let line = String::from_utf8_lossy(&self.line).trim().to_string();
+        self.line.clear();
+        match values {
+            Ok(values) => ParsingResult::Ok(values),
+            Err(value) => ParsingResult::Err(ParseFailure {
+                error: ParseError::InvalidFormat,
+                channel: String::new(),
+                value,
+                line,
+            }),
+        }
+    }
+}
+
+/// Stands in for a plugin that could not be loaded, it reports why once.
+struct Unavailable {
+    error: Option<String>,
+}
+
+impl ValueParser for Unavailable {
+    fn parse(&mut self, _byte: u8) -> ParsingResult {
+        match self.error.take() {
+            Some(error) => ParsingResult::Err(ParseFailure {
+                error: ParseError::InvalidFormat,
+                channel: String::new(),
+                value: error,
+                line: String::new(),
+            }),
+            None => ParsingResult::Pending,
+        }
+    }
+}
+
+/// Loads the plugin at `path` for a new connection.
+pub fn load(path: &str) -> Box<dyn ValueParser> {
+    #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+    let error = match WasmPlugin::load(path) {
+        Ok(plugin) => return Box::new(PluginParser::new(plugin)),
+        Err(err) => format!("parser plugin {}: {}", path, err),
+    };
+    #[cfg(not(all(feature = "plugins", not(target_arch = "wasm32"))))]
+    let error = format!(
+        "parser plugin {}: built without the `plugins` feature",
+        path
+    );
+    tracing::warn!("{}", error);
+    Box::new(Unavailable { error: Some(error) })
+}
+
+/// A plugin compiled to WebAssembly, run by an interpreter.
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+pub struct WasmPlugin {
+    store: Store<()>,
+    memory: Memory,
+    parse: TypedFunc<i32, i32>,
+    /// wasmi passes floats as their bits, as [`F64`]
+    value: TypedFunc<i32, F64>,
+    timestamp: Option<TypedFunc<i32, F64>>,
+    name: TypedFunc<i32, i32>,
+    name_len: TypedFunc<i32, i32>,
+}
+
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+impl WasmPlugin {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+        let engine = Engine::default();
+        let module = Module::new(&engine, &bytes[..]).map_err(|err| err.to_string())?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .map_err(|err| err.to_string())?
+            .start(&mut store)
+            .map_err(|err| err.to_string())?;
+
+        let version = instance
+            .get_typed_func::<(), i32>(&store, "plugin_abi_version")
+            .map_err(|err| err.to_string())
+            .and_then(|function| function.call(&mut store, ()).map_err(|err| err.to_string()))
+            .map_err(|err| format!("plugin_abi_version: {}", err))?;
+        if version != PLUGIN_ABI_VERSION {
+            return Err(format!(
+                "the plugin implements version {} of the interface instead of {}",
+                version, PLUGIN_ABI_VERSION
+            ));
+        }
+        fn export<P: WasmParams, R: WasmResults>(
+            instance: &Instance,
+            store: &Store<()>,
+            name: &str,
+        ) -> Result<TypedFunc<P, R>, String> {
+            instance
+                .get_typed_func(store, name)
+                .map_err(|err| format!("{}: {}", name, err))
+        }
+        Ok(Self {
+            parse: export(&instance, &store, "parse")?,
+            value: export(&instance, &store, "value")?,
+            timestamp: export(&instance, &store, "timestamp").ok(),
+            name: export(&instance, &store, "name")?,
+            name_len: export(&instance, &store, "name_len")?,
+            memory: instance
+                .get_memory(&store, "memory")
+                .ok_or("the plugin exports no memory")?,
+            store,
+        })
+    }
+}
+
+#[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+impl ParserPlugin for WasmPlugin {
+    fn parse(&mut self, byte: u8) -> Result<PluginStep, String> {
+        let result = self
+            .parse
+            .call(&mut self.store, byte.into())
+            .map_err(|err| err.to_string())?;
+        Ok(match result {
+            -1 => PluginStep::Pending,
+            -2 => PluginStep::Invalid,
+            count => PluginStep::Completed(count.max(0) as usize),
+        })
+    }
+
+    fn value(&mut self, index: usize) -> Result<DataValue, String> {
+        let index = index as i32;
+        let call = |function: &TypedFunc<i32, i32>, store: &mut Store<()>| {
+            function
+                .call(store, index)
+                .map(|result| result as u32 as usize)
+                .map_err(|err| err.to_string())
+        };
+        let address = call(&self.name, &mut self.store)?;
+        let len = call(&self.name_len, &mut self.store)?;
+        let name = self
+            .memory
+            .data(&self.store)
+            .get(address..address + len)
+            .ok_or("the name is outside of the memory")?;
+        let name = std::str::from_utf8(name)
+            .map_err(|err| err.to_string())?
+            .to_string();
+        let timestamp = match &self.timestamp {
+            Some(timestamp) => timestamp
+                .call(&mut self.store, index)
+                .map(f64::from)
+                .map_err(|err| err.to_string())?,
+            None => 0.0,
+        };
+        Ok(DataValue {
+            name,
+            value: self
+                .value
+                .call(&mut self.store, index)
+                .map(f64::from)
+                .map_err(|err| err.to_string())?,
+            timestamp,
+            line: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Completes a value `x` with every digit, anything but digits and line ends is invalid.
+    struct Digits;
+
+    impl ParserPlugin for Digits {
+        fn parse(&mut self, byte: u8) -> Result<PluginStep, String> {
+            Ok(match byte {
+                b'0'..=b'9' => PluginStep::Completed(1),
+                b'\n' => PluginStep::Pending,
+                b'!' => return Err("trap".to_string()),
+                _ => PluginStep::Invalid,
+            })
+        }
+
+        fn value(&mut self, _index: usize) -> Result<DataValue, String> {
+            Ok(DataValue {
+                name: "x".to_string(),
+                value: 1.0,
+                timestamp: 0.0,
+                line: 0,
+            })
+        }
+    }
+
+    #[test]
+    fn should_describe_the_input_a_plugin_rejected() {
+        let mut parser = PluginParser::new(Digits);
+        let value = DataValue {
+            name: "x".to_string(),
+            value: 1.0,
+            timestamp: 0.0,
+            line: 0,
+        };
+        assert_eq!(parser.parse(b'7'), ParsingResult::Ok(vec![value]));
+        assert_eq!(parser.parse(b'\n'), ParsingResult::Pending);
+        let ParsingResult::Err(failure) = parser.parse(b'a') else {
+            panic!("the plugin rejected the byte");
+        };
+        assert_eq!(failure.line, "a");
+        let ParsingResult::Err(failure) = parser.parse(b'!') else {
+            panic!("the plugin failed");
+        };
+        assert_eq!(failure.value, "trap");
+    }
+
+    /// A plugin completing a value `x` of 2.5 with every byte, assembled by hand.
+    #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+    const MODULE: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic and version
+        // types: () -> i32, (i32) -> i32, (i32) -> f64
+        0x01, 0x0f, 0x03, 0x60, 0x00, 0x01, 0x7f, 0x60, 0x01, 0x7f, 0x01, 0x7f, 0x60, 0x01, 0x7f,
+        0x01, 0x7c, //
+        // functions: plugin_abi_version, parse, value, name, name_len
+        0x03, 0x06, 0x05, 0x00, 0x01, 0x02, 0x01, 0x01, //
+        // a memory of one page
+        0x05, 0x03, 0x01, 0x00, 0x01, //
+        // exports
+        0x07, 0x41, 0x06, //
+        0x12, b'p', b'l', b'u', b'g', b'i', b'n', b'_', b'a', b'b', b'i', b'_', b'v', b'e', b'r',
+        b's', b'i', b'o', b'n', 0x00, 0x00, //
+        0x05, b'p', b'a', b'r', b's', b'e', 0x00, 0x01, //
+        0x05, b'v', b'a', b'l', b'u', b'e', 0x00, 0x02, //
+        0x04, b'n', b'a', b'm', b'e', 0x00, 0x03, //
+        0x08, b'n', b'a', b'm', b'e', b'_', b'l', b'e', b'n', 0x00, 0x04, //
+        0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02, 0x00, //
+        // code: 1, 1, 2.5, 0 and 1
+        0x0a, 0x21, 0x05, //
+        0x04, 0x00, 0x41, 0x01, 0x0b, //
+        0x04, 0x00, 0x41, 0x01, 0x0b, //
+        0x0b, 0x00, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x40, 0x0b, //
+        0x04, 0x00, 0x41, 0x00, 0x0b, //
+        0x04, 0x00, 0x41, 0x01, 0x0b, //
+        // data: the name `x` at address 0
+        0x0b, 0x07, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x01, b'x',
+    ];
+
+    #[cfg(all(feature = "plugins", not(target_arch = "wasm32")))]
+    #[test]
+    fn should_parse_with_a_webassembly_plugin() {
+        let path = std::env::temp_dir().join("serialplotter_test_plugin.wasm");
+        std::fs::write(&path, MODULE).unwrap();
+        let mut parser = load(path.to_str().unwrap());
+
+        let value = DataValue {
+            name: "x".to_string(),
+            value: 2.5,
+            timestamp: 0.0,
+            line: 0,
+        };
+        assert_eq!(parser.parse(b'1'), ParsingResult::Ok(vec![value]));
+    }
+}
@@ -0,0 +1,397 @@
+use std::{collections::VecDeque, mem::size_of, ops::Range};
+
+use super::value_history::Sample;
+
+/// How precisely the history stores its samples.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precision {
+    #[default]
+    Double,
+    /// Half the memory, the values keep about 7 digits and the times stay below a millisecond
+    /// while a channel keeps up to 4 hours of samples
+    Single,
+}
+
+/// Seconds the oldest sample of a buffer in single precision may be after its start, before
+/// the times are made relative to that sample again.
+///
+/// The times lose precision as they grow, so in a rolling capture they are kept small.
+const REBASE_AFTER: f32 = 60.0;
+
+impl Precision {
+    /// The bytes a stored sample takes.
+    pub fn sample_size(self) -> usize {
+        match self {
+            Precision::Double => size_of::<Sample>(),
+            Precision::Single => size_of::<SingleSample>(),
+        }
+    }
+}
+
+/// A sample in single precision, its time relative to the start of its buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SingleSample {
+    time: f32,
+    value: f32,
+}
+
+/// How a buffer stores its samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Plain(Precision),
+    /// Stores a stretch of equal values once, for channels that rarely change like status flags,
+    /// the time of every sample is kept as it was received
+    RunLength,
+}
+
+impl From<Precision> for Encoding {
+    fn from(precision: Precision) -> Self {
+        Encoding::Plain(precision)
+    }
+}
+
+/// Consecutive samples of the same value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Run {
+    /// The index of the first sample counted from the first sample ever stored in the buffer
+    start: usize,
+    count: usize,
+    value: f64,
+}
+
+/// The samples of a channel in the order they were stored, in any [`Encoding`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleBuffer {
+    Double(VecDeque<Sample>),
+    Single {
+        /// The time the times of the samples are relative to
+        start: f64,
+        samples: VecDeque<SingleSample>,
+    },
+    RunLength {
+        /// The number of samples removed from the front, the [`Run::start`] of the first sample
+        removed: usize,
+        /// The time of every sample
+        times: VecDeque<f64>,
+        runs: VecDeque<Run>,
+    },
+}
+
+impl Default for SampleBuffer {
+    fn default() -> Self {
+        Self::new(Encoding::Plain(Precision::Double))
+    }
+}
+
+impl FromIterator<Sample> for SampleBuffer {
+    fn from_iter<T: IntoIterator<Item = Sample>>(iter: T) -> Self {
+        Self::Double(iter.into_iter().collect())
+    }
+}
+
+impl SampleBuffer {
+    pub fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Plain(Precision::Double) => Self::Double(VecDeque::new()),
+            Encoding::Plain(Precision::Single) => Self::Single {
+                start: 0.0,
+                samples: VecDeque::new(),
+            },
+            Encoding::RunLength => Self::RunLength {
+                removed: 0,
+                times: VecDeque::new(),
+                runs: VecDeque::new(),
+            },
+        }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        match self {
+            Self::Double(_) => Encoding::Plain(Precision::Double),
+            Self::Single { .. } => Encoding::Plain(Precision::Single),
+            Self::RunLength { .. } => Encoding::RunLength,
+        }
+    }
+
+    /// A copy of the samples stored in `encoding`.
+    pub fn with_encoding(&self, encoding: Encoding) -> Self {
+        let mut buffer = Self::new(encoding);
+        self.iter().for_each(|sample| buffer.push_back(sample));
+        buffer
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Double(samples) => samples.len(),
+            Self::Single { samples, .. } => samples.len(),
+            Self::RunLength { times, .. } => times.len(),
+        }
+    }
+
+    /// The bytes the stored samples take.
+    pub fn memory_usage(&self) -> usize {
+        match self {
+            Self::Double(samples) => samples.len() * Precision::Double.sample_size(),
+            Self::Single { samples, .. } => samples.len() * Precision::Single.sample_size(),
+            Self::RunLength { times, runs, .. } => {
+                times.len() * size_of::<f64>() + runs.len() * size_of::<Run>()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The sample at `index`, panics if it is out of bounds like indexing a [`VecDeque`].
+    pub fn sample(&self, index: usize) -> Sample {
+        match self {
+            Self::Double(samples) => samples[index],
+            Self::Single { start, samples } => {
+                let sample = samples[index];
+                Sample {
+                    time: start + f64::from(sample.time),
+                    value: f64::from(sample.value),
+                }
+            }
+            Self::RunLength {
+                removed,
+                times,
+                runs,
+            } => {
+                let time = times[index];
+                let index = removed + index;
+                let run = &runs[runs.partition_point(|run| run.start + run.count <= index)];
+                Sample {
+                    time,
+                    value: run.value,
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<Sample> {
+        (index < self.len()).then(|| self.sample(index))
+    }
+
+    pub fn front(&self) -> Option<Sample> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<Sample> {
+        self.len().checked_sub(1).map(|index| self.sample(index))
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Sample> + ExactSizeIterator + '_ {
+        self.range(0..self.len())
+    }
+
+    pub fn range(
+        &self,
+        range: Range<usize>,
+    ) -> impl DoubleEndedIterator<Item = Sample> + ExactSizeIterator + '_ {
+        range.map(|index| self.sample(index))
+    }
+
+    /// The index of the first sample for which `pred` is false, the samples have to be partitioned by `pred`.
+    pub fn partition_point(&self, pred: impl Fn(usize, Sample) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if pred(middle, self.sample(middle)) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    pub fn push_back(&mut self, sample: Sample) {
+        match self {
+            Self::Double(samples) => samples.push_back(sample),
+            Self::Single { start, samples } => {
+                if samples.is_empty() {
+                    *start = sample.time;
+                }
+                samples.push_back(SingleSample {
+                    time: (sample.time - *start) as f32,
+                    value: sample.value as f32,
+                });
+            }
+            Self::RunLength {
+                removed,
+                times,
+                runs,
+            } => {
+                times.push_back(sample.time);
+                match runs.back_mut() {
+                    // Compares the bits, so a run of NaN continues as well
+                    Some(run) if run.value.to_bits() == sample.value.to_bits() => run.count += 1,
+                    _ => runs.push_back(Run {
+                        start: *removed + times.len() - 1,
+                        count: 1,
+                        value: sample.value,
+                    }),
+                }
+            }
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<Sample> {
+        let sample = self.front()?;
+        match self {
+            Self::Double(samples) => samples.pop_front(),
+            Self::Single { start, samples } => {
+                samples.pop_front();
+                let front = samples.front().map(|front| front.time);
+                if let Some(shift) = front.filter(|time| *time >= REBASE_AFTER) {
+                    let shift = f64::from(shift);
+                    for sample in samples.iter_mut() {
+                        sample.time = (f64::from(sample.time) - shift) as f32;
+                    }
+                    *start += shift;
+                }
+                Some(sample)
+            }
+            Self::RunLength {
+                removed,
+                times,
+                runs,
+            } => {
+                *removed += 1;
+                times.pop_front();
+                let run = runs.front_mut()?;
+                if run.count == 1 {
+                    runs.pop_front();
+                } else {
+                    run.start += 1;
+                    run.count -= 1;
+                }
+                Some(sample)
+            }
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<Sample> {
+        let sample = self.back()?;
+        match self {
+            Self::Double(samples) => samples.pop_back(),
+            Self::Single { samples, .. } => samples.pop_back().map(|_| sample),
+            Self::RunLength { times, runs, .. } => {
+                times.pop_back();
+                let run = runs.back_mut()?;
+                if run.count == 1 {
+                    runs.pop_back();
+                } else {
+                    run.count -= 1;
+                }
+                Some(sample)
+            }
+        }
+    }
+
+    /// Adds the samples of `other` and orders all samples by their time.
+    pub fn merge(&mut self, other: &SampleBuffer) {
+        let mut samples: Vec<Sample> = self.iter().chain(other.iter()).collect();
+        samples.sort_by(|a, b| a.time.total_cmp(&b.time));
+        *self = Self::new(self.encoding());
+        samples
+            .into_iter()
+            .for_each(|sample| self.push_back(sample));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_store_single_precision_relative_to_the_first_sample() {
+        let mut buffer = SampleBuffer::new(Precision::Single.into());
+        buffer.push_back(Sample {
+            time: 1_700_000_000.0,
+            value: 1.5,
+        });
+        buffer.push_back(Sample {
+            time: 1_700_000_000.25,
+            value: -2.0,
+        });
+
+        assert_eq!(
+            Precision::Single.sample_size() * 2,
+            Precision::Double.sample_size()
+        );
+        assert_eq!(
+            buffer.with_encoding(Precision::Double.into()),
+            SampleBuffer::Double(VecDeque::from([
+                Sample {
+                    time: 1_700_000_000.0,
+                    value: 1.5,
+                },
+                Sample {
+                    time: 1_700_000_000.25,
+                    value: -2.0,
+                },
+            ]))
+        );
+        assert_eq!(buffer.pop_front().map(|sample| sample.value), Some(1.5));
+        assert_eq!(
+            buffer.back().map(|sample| sample.time),
+            Some(1_700_000_000.25)
+        );
+    }
+
+    #[test]
+    fn should_keep_the_precision_of_the_times_in_a_rolling_capture() {
+        let mut buffer = SampleBuffer::new(Precision::Single.into());
+        let time = |index: usize| 1_700_000_000.000_3 + index as f64 * 0.1;
+        // Five hours at 10 samples per second, keeping the last minute
+        for index in 0..180_000 {
+            buffer.push_back(Sample {
+                time: time(index),
+                value: 0.0,
+            });
+            if buffer.len() > 600 {
+                buffer.pop_front();
+            }
+        }
+
+        let first = 180_000 - buffer.len();
+        for (index, sample) in buffer.iter().enumerate() {
+            assert!((sample.time - time(first + index)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn should_store_equal_values_as_one_run() {
+        let mut buffer = SampleBuffer::new(Encoding::RunLength);
+        // Unevenly spaced, the times are kept as received
+        for (time, value) in [(0.0, 1.0), (0.1, 1.0), (2.5, 1.0), (3.0, 1.0), (4.0, 0.0)] {
+            buffer.push_back(Sample { time, value });
+        }
+
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(
+            buffer.memory_usage(),
+            5 * size_of::<f64>() + 2 * size_of::<Run>()
+        );
+        assert_eq!(
+            buffer.sample(2),
+            Sample {
+                time: 2.5,
+                value: 1.0
+            }
+        );
+        assert_eq!(buffer.pop_front().map(|sample| sample.time), Some(0.0));
+        assert_eq!(buffer.pop_back().map(|sample| sample.value), Some(0.0));
+        assert_eq!(
+            buffer.iter().map(|sample| sample.time).collect::<Vec<_>>(),
+            [0.1, 2.5, 3.0]
+        );
+        assert_eq!(
+            buffer.memory_usage(),
+            3 * size_of::<f64>() + size_of::<Run>()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::{fmt::Display, io};
+
+use tracing::error;
+
+use super::DataValue;
+
+/// Seconds between two flushes of the sinks while values arrive, a flush after every read would
+/// cost a write to the disk for every few lines.
+pub const FLUSH_INTERVAL: f64 = 0.25;
+
+#[derive(Debug)]
+pub enum SinkError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl Display for SinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SinkError::Io(err) => write!(f, "{}", err),
+            SinkError::Format(err) => write!(f, "failed to encode value: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for SinkError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SinkError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+/// A destination the received values are recorded to or forwarded to, the counterpart of a
+/// [`DataSource`](crate::DataSource).
+pub trait DataSink: Send {
+    /// Describes the destination, e.g. the path of the file.
+    fn name(&self) -> &str;
+
+    /// Hands a value to the sink, which may buffer it.
+    fn write(&mut self, value: &DataValue) -> Result<(), SinkError>;
+
+    /// Passes on the buffered values, called every [`FLUSH_INTERVAL`] seconds.
+    fn flush(&mut self) -> Result<(), SinkError>;
+}
+
+/// The sinks enabled for a session.
+///
+/// A sink that fails is disabled and its error reported, so one broken destination does not
+/// stop the others.
+#[derive(Default)]
+pub struct Sinks {
+    sinks: Vec<Box<dyn DataSink>>,
+    errors: Vec<String>,
+    /// The time of the last flush by [`Sinks::flush_due`]
+    flushed_at: f64,
+}
+
+impl Sinks {
+    pub fn add(&mut self, sink: Box<dyn DataSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Disables the sink with this name.
+    pub fn remove(&mut self, name: &str) {
+        self.sinks.retain(|sink| sink.name() != name);
+    }
+
+    /// Writes out what the sinks buffered and disables all of them.
+    pub fn clear(&mut self) {
+        self.flush();
+        self.sinks.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    pub fn write(&mut self, value: &DataValue) {
+        self.retain(|sink| sink.write(value));
+    }
+
+    pub fn flush(&mut self) {
+        self.retain(|sink| sink.flush());
+    }
+
+    /// Flushes the sinks unless that happened less than [`FLUSH_INTERVAL`] seconds before `now`.
+    pub fn flush_due(&mut self, now: f64) {
+        if now - self.flushed_at >= FLUSH_INTERVAL {
+            self.flushed_at = now;
+            self.flush();
+        }
+    }
+
+    /// The errors of the sinks that were disabled since the last call.
+    pub fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn retain(&mut self, mut f: impl FnMut(&mut dyn DataSink) -> Result<(), SinkError>) {
+        let errors = &mut self.errors;
+        self.sinks.retain_mut(|sink| match f(sink.as_mut()) {
+            Ok(()) => true,
+            Err(err) => {
+                let message = format!("{}: {}", sink.name(), err);
+                error!("Disabled sink {}", message);
+                errors.push(message);
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingSink;
+
+    /// Counts its flushes.
+    #[derive(Clone, Default)]
+    struct FlushCounter(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl DataSink for FlushCounter {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn write(&mut self, _value: &DataValue) -> Result<(), SinkError> {
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), SinkError> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    impl DataSink for FailingSink {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn write(&mut self, _value: &DataValue) -> Result<(), SinkError> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe).into())
+        }
+
+        fn flush(&mut self) -> Result<(), SinkError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_disable_failing_sinks() {
+        let mut sinks = Sinks::default();
+        sinks.add(Box::new(FailingSink));
+        let value = DataValue {
+            name: "X".to_string(),
+            value: 1.5,
+            timestamp: 2.0,
+            line: 0,
+        };
+
+        sinks.write(&value);
+
+        assert!(sinks.is_empty());
+        assert_eq!(sinks.take_errors().len(), 1);
+        assert!(sinks.take_errors().is_empty());
+    }
+
+    #[test]
+    fn should_flush_at_most_once_per_interval() {
+        let mut sinks = Sinks::default();
+        let counter = FlushCounter::default();
+        sinks.add(Box::new(counter.clone()));
+
+        for now in [100.0, 100.1, 100.2, 100.25, 100.3, 101.0] {
+            sinks.flush_due(now);
+        }
+
+        assert_eq!(counter.0.load(std::sync::atomic::Ordering::Relaxed), 3);
+    }
+}
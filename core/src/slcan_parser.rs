@@ -0,0 +1,206 @@
+use super::{
+    canopen::PdoMapping,
+    dbc::DbcMessage,
+    parsing_state_machine::{ParseFailure, ParsingResult},
+    DataValue, ParseError, ValueParser,
+};
+
+/// A frame received from the CAN bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanFrame {
+    pub id: u32,
+    /// Whether the id has 29 instead of 11 bits
+    pub extended: bool,
+    pub data: Vec<u8>,
+}
+
+impl CanFrame {
+    /// Parses a data frame of the SLCAN protocol, e.g. `t1852E803` or `T1234567820102`.
+    ///
+    /// The optional timestamp of the adapter is ignored, it wraps around every minute.
+    fn parse_slcan(line: &str) -> Option<Self> {
+        let extended = match line.as_bytes().first()? {
+            b't' => false,
+            b'T' => true,
+            _ => return None,
+        };
+        let rest = &line[1..];
+        let id_digits = if extended { 8 } else { 3 };
+        let id = u32::from_str_radix(rest.get(..id_digits)?, 16).ok()?;
+        let length: usize = rest.get(id_digits..id_digits + 1)?.parse().ok()?;
+        if length > 8 {
+            return None;
+        }
+        let data = rest.get(id_digits + 1..id_digits + 1 + 2 * length)?;
+        let data = (0..length)
+            .map(|byte| u8::from_str_radix(data.get(2 * byte..2 * byte + 2)?, 16).ok())
+            .collect::<Option<_>>()?;
+        Some(Self { id, extended, data })
+    }
+}
+
+/// The bit rates in kbit/s a SLCAN adapter is opened with, by the digit of their `S` command.
+pub const BITRATES: [u32; 9] = [10, 20, 50, 100, 125, 250, 500, 800, 1000];
+
+/// How the adapter is opened and how the frames received from the CAN bus are turned into channels.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CanSettings {
+    /// The bit rate of the bus in kbit/s, one of [`BITRATES`]
+    pub bitrate: u32,
+    /// The CANopen PDOs decoded into channels
+    pub pdos: Vec<PdoMapping>,
+    /// The messages imported from a DBC file, their signals are decoded into channels
+    pub messages: Vec<DbcMessage>,
+}
+
+impl Default for CanSettings {
+    fn default() -> Self {
+        Self {
+            bitrate: 500,
+            pdos: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+}
+
+impl CanSettings {
+    /// Closes the adapter in case it is still open, sets the bit rate and opens it again.
+    pub fn open_commands(&self) -> String {
+        let bitrate = BITRATES
+            .iter()
+            .position(|bitrate| *bitrate == self.bitrate)
+            .unwrap_or(6);
+        format!("C\rS{}\rO\r", bitrate)
+    }
+
+    /// The values of the PDOs and DBC messages sent in `frame`, other frames carry none.
+    pub fn decode(&self, frame: &CanFrame) -> Vec<DataValue> {
+        let messages = self
+            .messages
+            .iter()
+            .filter(|message| message.id == frame.id && message.extended == frame.extended)
+            .flat_map(|message| message.decode(&frame.data));
+        self.pdos
+            .iter()
+            // CANopen only uses 11 bit ids
+            .filter(|pdo| !frame.extended && pdo.cob_id() == frame.id)
+            .flat_map(|pdo| pdo.decode(&frame.data))
+            .chain(messages)
+            .collect()
+    }
+}
+
+/// Parses the frames a SLCAN (Lawicel) adapter receives from the CAN bus, one per line.
+///
+/// Remote frames and the replies to commands carry no values and are skipped.
+#[derive(Debug, Default)]
+pub struct SlcanParser {
+    line: Vec<u8>,
+    settings: CanSettings,
+}
+
+impl SlcanParser {
+    pub fn new(settings: CanSettings) -> Self {
+        Self {
+            line: Vec::new(),
+            settings,
+        }
+    }
+
+    fn parse_line(&self, line: &str) -> Result<Vec<DataValue>, ParseFailure> {
+        if !line.starts_with(['t', 'T']) {
+            return Ok(Vec::new());
+        }
+        let frame = CanFrame::parse_slcan(line).ok_or_else(|| ParseFailure {
+            error: ParseError::InvalidFormat,
+            channel: String::new(),
+            value: line.to_string(),
+            line: line.to_string(),
+        })?;
+        Ok(self.settings.decode(&frame))
+    }
+}
+
+impl ValueParser for SlcanParser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        // Adapters end frames with a carriage return, some add a line feed
+        if byte != b'\r' && byte != b'\n' {
+            self.line.push(byte);
+            return ParsingResult::Pending;
+        }
+
+        let line = std::mem::take(&mut self.line);
+        let line = String::from_utf8_lossy(&line);
+        ParsingResult::from(self.parse_line(line.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canopen::MappedObject;
+
+    #[test]
+    fn should_parse_slcan_frames() {
+        assert_eq!(
+            CanFrame::parse_slcan("t1852E803"),
+            Some(CanFrame {
+                id: 0x185,
+                extended: false,
+                data: vec![0xe8, 0x03],
+            })
+        );
+        assert_eq!(
+            CanFrame::parse_slcan("T1234567810102"),
+            Some(CanFrame {
+                id: 0x12345678,
+                extended: true,
+                data: vec![0x01],
+            })
+        );
+        assert_eq!(CanFrame::parse_slcan("t1852E8"), None);
+        assert_eq!(CanFrame::parse_slcan("t1859"), None);
+
+        let settings = CanSettings {
+            bitrate: 125,
+            ..Default::default()
+        };
+        assert_eq!(settings.open_commands(), "C\rS4\rO\r");
+    }
+
+    #[test]
+    fn should_decode_the_pdos_of_received_frames() {
+        let mut parser = SlcanParser::new(CanSettings {
+            pdos: vec![PdoMapping {
+                node_id: 5,
+                pdo: 1,
+                objects: vec![MappedObject {
+                    name: "speed".to_string(),
+                    number_type: crate::NumberType::I16,
+                    ..Default::default()
+                }],
+            }],
+            ..Default::default()
+        });
+
+        let mut parse = |line: &str| {
+            line.bytes()
+                .chain([b'\r'])
+                .map(|byte| parser.parse(byte))
+                .last()
+        };
+        assert_eq!(
+            parse("t1852E803"),
+            Some(ParsingResult::Ok(vec![DataValue {
+                name: "speed".to_string(),
+                value: 1000.0,
+                timestamp: 0.0,
+                line: 0,
+            }]))
+        );
+        assert_eq!(parse("t1862E803"), Some(ParsingResult::Ok(Vec::new())));
+        assert_eq!(parse("z"), Some(ParsingResult::Ok(Vec::new())));
+        assert!(matches!(parse("t18"), Some(ParsingResult::Err(_))));
+    }
+}
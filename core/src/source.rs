@@ -0,0 +1,195 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Mutex, PoisonError};
+
+use crossbeam::channel::{Receiver, Sender};
+use tracing::warn;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::{unix_timestamp, Sinks};
+use super::{Backpressure, DataValue, ParseError, ParseFailure, ParsingResult, ValueParser};
+
+/// The channels a source uses to hand its results over to the ui.
+#[derive(Clone)]
+pub struct SourceSenders {
+    /// The values of every read at once, which keeps the cost per value small at high rates
+    pub data: Sender<Vec<DataValue>>,
+    /// The receiving end of `data`, to drop the oldest values when the ui falls behind
+    pub queued: Receiver<Vec<DataValue>>,
+    pub backpressure: Arc<Backpressure>,
+    pub raw: Sender<Vec<u8>>,
+    pub parse_errors: Sender<ParseFailure>,
+    /// What happens to the connection itself, e.g. a sent break or a failed read
+    pub events: Sender<SourceEvent>,
+    /// The number of the next line, shared by all sources so their lines never share a number
+    pub lines: Arc<AtomicU64>,
+    /// Receive the values on the reading thread, before they are queued for the ui
+    #[cfg(not(target_arch = "wasm32"))]
+    pub sinks: Arc<Mutex<Sinks>>,
+}
+
+impl SourceSenders {
+    /// The number for the values of a new line, see [`DataValue::line`].
+    pub fn next_line(&self) -> u64 {
+        self.lines.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Reports an event of the connection, it is dropped while the ui is behind on the events.
+    pub fn report(&self, event: SourceEvent) {
+        let _ = self.events.try_send(event);
+    }
+
+    /// Hands the values to the sinks, which are flushed every [`FLUSH_INTERVAL`](crate::FLUSH_INTERVAL) seconds.
+    ///
+    /// The ui flushes the values left over when the source turns idle or stops. The lock is
+    /// released before the values are sent, as the ui may wait for it while the channel to the
+    /// ui is full.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn record(&self, values: &[DataValue]) {
+        let mut sinks = self.sinks.lock().unwrap_or_else(PoisonError::into_inner);
+        for value in values {
+            sinks.write(value);
+        }
+        sinks.flush_due(unix_timestamp());
+    }
+
+    /// Hands the values of a read over to the ui, the [`OverflowPolicy`] decides what happens
+    /// while the ui is behind.
+    ///
+    /// On the web the ui runs on the same thread as the source and can not catch up while
+    /// the source waits, so the values are dropped instead of blocking.
+    pub fn send_values(&self, values: Vec<DataValue>) -> Result<(), ParseError> {
+        let can_block = cfg!(not(target_arch = "wasm32"));
+        self.backpressure
+            .send(&self.data, &self.queued, values, can_block)
+    }
+}
+
+/// What a source reports about its connection, next to the values it receives.
+///
+/// The values have a channel of their own, as the [`OverflowPolicy`] applies to them only.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceEvent {
+    /// A change of the connection, e.g. a sent break
+    Condition(String),
+    /// A failure the source continues after, e.g. a failed write
+    Error(String),
+    /// The failure the source stopped after, e.g. because the device was unplugged
+    Disconnected(String),
+}
+
+/// Requests to a running source, handled by the thread reading it.
+pub enum Commands {
+    Stop,
+    SendMessage(String),
+    /// Sets the Data Terminal Ready line of a serial port
+    SetDtr(bool),
+    /// Sets the Request To Send line of a serial port
+    SetRts(bool),
+    /// Releases DTR briefly, which reboots most Arduino-compatible boards
+    ResetBoard,
+    /// Holds the transmit line low for longer than a byte, e.g. the break of LIN or to enter a bootloader
+    SendBreak,
+}
+
+/// A running connection that feeds the values it receives into its [`SourceSenders`].
+pub trait DataSource {
+    /// Describes the connection, e.g. the name of the port.
+    fn name(&self) -> &str;
+
+    /// Whether the source still receives data, it stops on its own if the connection is lost.
+    fn is_running(&self) -> bool;
+
+    /// Asks the source to close its connection.
+    fn stop(&mut self);
+
+    /// Hands a command to the source, returns whether the source accepted it.
+    fn command(&mut self, _command: Commands) -> bool {
+        false
+    }
+}
+
+/// Parses a chunk of bytes received at `received_at` and hands the results over to the ui.
+pub fn process_chunk(
+    parser: &mut dyn ValueParser,
+    chunk: &[u8],
+    received_at: f64,
+    senders: &SourceSenders,
+) -> Result<(), ParseError> {
+    if !chunk.is_empty() {
+        // The raw monitor is only a diagnostic aid, so it may lose chunks instead of stalling the reader.
+        let _ = senders.raw.try_send(chunk.to_vec());
+    }
+    let mut received = Vec::new();
+    for byte in chunk {
+        match parser.parse(*byte) {
+            ParsingResult::Pending => {}
+            ParsingResult::Err(failure) => {
+                warn!("error parsing value {:?}", failure);
+                let _ = senders.parse_errors.try_send(failure);
+            }
+            ParsingResult::Ok(mut values) => {
+                // The lines of a chunk share the time it was received at, their number tells
+                // them apart
+                let line = senders.next_line();
+                for value in values.iter_mut() {
+                    value.line = line;
+                    if value.timestamp == 0.0 {
+                        value.timestamp = received_at;
+                    }
+                }
+                received.append(&mut values);
+            }
+        }
+    }
+    if received.is_empty() {
+        return Ok(());
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    senders.record(&received);
+    senders.send_values(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OverflowPolicy, ParserSettings};
+
+    #[test]
+    fn should_send_the_values_of_a_chunk_at_once() {
+        let (data, queued) = crossbeam::channel::unbounded();
+        let senders = SourceSenders {
+            data,
+            queued: queued.clone(),
+            backpressure: Arc::new(Backpressure::new(OverflowPolicy::Block)),
+            raw: crossbeam::channel::unbounded().0,
+            parse_errors: crossbeam::channel::unbounded().0,
+            events: crossbeam::channel::unbounded().0,
+            lines: Default::default(),
+            sinks: Default::default(),
+        };
+        let mut parser = ParserSettings::default().create_parser();
+
+        process_chunk(parser.as_mut(), b"a:1,b:2\na:3,b:4\na:", 10.0, &senders).unwrap();
+        process_chunk(parser.as_mut(), b"", 11.0, &senders).unwrap();
+
+        let reads: Vec<Vec<DataValue>> = queued.try_iter().collect();
+        assert_eq!(reads.len(), 1);
+        let values: Vec<_> = reads[0]
+            .iter()
+            .map(|x| (x.name.as_str(), x.value, x.line, x.timestamp))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("a", 1.0, 0, 10.0),
+                ("b", 2.0, 0, 10.0),
+                ("a", 3.0, 1, 10.0),
+                ("b", 4.0, 1, 10.0)
+            ]
+        );
+    }
+}
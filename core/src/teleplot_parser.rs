@@ -0,0 +1,158 @@
+use super::{
+    parsing_state_machine::{ParseFailure, ParsingResult},
+    unix_timestamp, DataValue, ParseError, ValueParser,
+};
+
+/// Device timestamps below this many seconds count from the start of the device, not the unix epoch.
+const EPOCH_THRESHOLD: f64 = 1e9;
+
+/// Parses the line protocol of Teleplot, e.g. `>temp:25.4` or `>temp:1627551892437:25.4§°C|np`.
+///
+/// Timestamps are milliseconds, either since the unix epoch or since the start of the device.
+/// Lines without the leading `>` are log output of the firmware and skipped.
+#[derive(Debug, Default)]
+pub struct TeleplotParser {
+    line: Vec<u8>,
+    /// Seconds from the clock of the device to the unix epoch, fixed by its first timestamp
+    clock_offset: Option<f64>,
+}
+
+impl ValueParser for TeleplotParser {
+    fn parse(&mut self, byte: u8) -> ParsingResult {
+        if byte != b'\n' {
+            self.line.push(byte);
+            return ParsingResult::Pending;
+        }
+
+        let line = std::mem::take(&mut self.line);
+        let line = String::from_utf8_lossy(&line);
+        ParsingResult::from(self.parse_line(line.trim_end()))
+    }
+}
+
+impl TeleplotParser {
+    fn parse_line(&mut self, line: &str) -> Result<Vec<DataValue>, ParseFailure> {
+        let Some(rest) = line.strip_prefix('>') else {
+            return Ok(Vec::new());
+        };
+        let (body, flags) = rest.split_once('|').unwrap_or((rest, ""));
+        // Text values, xy plots and values marked as "no plot" have no place in the plot
+        if flags
+            .split(',')
+            .any(|flag| matches!(flag.trim(), "t" | "xy" | "np"))
+        {
+            return Ok(Vec::new());
+        }
+
+        let failure = |channel: &str, value: &str| ParseFailure {
+            error: ParseError::InvalidFormat,
+            channel: channel.to_string(),
+            value: value.to_string(),
+            line: line.to_string(),
+        };
+        let (name, points) = body.split_once(':').ok_or_else(|| failure("", body))?;
+        // The unit is only shown by Teleplot
+        let points = points.split_once('§').map_or(points, |(points, _)| points);
+
+        let mut values = Vec::new();
+        for point in points.split(';').map(str::trim).filter(|x| !x.is_empty()) {
+            let (timestamp, value) = match point.rsplit_once(':') {
+                Some((timestamp, value)) => (Some(timestamp), value),
+                None => (None, point),
+            };
+            let value = value.trim().parse().map_err(|_| failure(name, point))?;
+            let timestamp = match timestamp {
+                Some(timestamp) => {
+                    let milliseconds: f64 =
+                        timestamp.trim().parse().map_err(|_| failure(name, point))?;
+                    self.unix_time(milliseconds / 1000.0)
+                }
+                // Stamped with the time of reception by the source
+                None => 0.0,
+            };
+            values.push(DataValue {
+                name: name.to_string(),
+                value,
+                timestamp,
+                line: 0,
+            });
+        }
+        Ok(values)
+    }
+
+    fn unix_time(&mut self, device_time: f64) -> f64 {
+        if device_time >= EPOCH_THRESHOLD {
+            return device_time;
+        }
+        let offset = *self
+            .clock_offset
+            .get_or_insert_with(|| unix_timestamp() - device_time);
+        device_time + offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(parser: &mut TeleplotParser, line: &str) -> Vec<DataValue> {
+        for byte in line.bytes() {
+            assert_eq!(parser.parse(byte), ParsingResult::Pending);
+        }
+        match parser.parse(b'\n') {
+            ParsingResult::Ok(values) => values,
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_parse_values_with_units_and_skip_other_lines() {
+        let mut parser = TeleplotParser::default();
+
+        let values = parse_line(&mut parser, ">temp:25.4§°C\r");
+        assert_eq!(
+            values,
+            vec![DataValue {
+                name: "temp".to_string(),
+                value: 25.4,
+                timestamp: 0.0,
+                line: 0,
+            }]
+        );
+
+        assert!(parse_line(&mut parser, "booting...").is_empty());
+        assert!(parse_line(&mut parser, ">state:idle|t").is_empty());
+        assert!(parse_line(&mut parser, ">hidden:1|np").is_empty());
+    }
+
+    #[test]
+    fn should_keep_the_spacing_of_device_timestamps() {
+        let mut parser = TeleplotParser::default();
+
+        let values = parse_line(&mut parser, ">x:1000:1;1500:2");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1].timestamp - values[0].timestamp, 0.5);
+        assert!(values[0].timestamp > EPOCH_THRESHOLD);
+
+        let values = parse_line(&mut parser, ">x:1627551892437:3");
+        assert_eq!(values[0].timestamp, 1627551892.437);
+    }
+
+    #[test]
+    fn should_report_invalid_values() {
+        let mut parser = TeleplotParser::default();
+        for byte in b">x:abc" {
+            parser.parse(*byte);
+        }
+
+        assert_eq!(
+            parser.parse(b'\n'),
+            ParsingResult::Err(ParseFailure {
+                error: ParseError::InvalidFormat,
+                channel: "x".to_string(),
+                value: "abc".to_string(),
+                line: ">x:abc".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,908 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    time::Duration,
+};
+
+use crossbeam::channel::{Receiver, TryRecvError};
+
+use super::{
+    sample_buffer::{Encoding, Precision, SampleBuffer},
+    unix_timestamp, DataValue,
+};
+
+/// The number of latest samples the sample rate of a channel is measured over.
+const RATE_SAMPLES: usize = 100;
+
+/// A single value of a channel together with the time it was received.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Seconds since the unix epoch
+    pub time: f64,
+    pub value: f64,
+}
+
+/// Limits on the samples kept beyond the number of displayed values.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct HistoryLimits {
+    /// Samples kept for these channels instead of the displayed values
+    pub channel_capacities: BTreeMap<String, usize>,
+    /// Megabytes all channels together may use, the oldest samples are evicted first
+    pub memory_budget: Option<usize>,
+    pub precision: Precision,
+    /// Channels storing a stretch of equal values only once, regardless of the precision
+    pub compressed_channels: BTreeSet<String>,
+}
+
+impl HistoryLimits {
+    pub fn capacity_of(&self, name: &str, default: usize) -> usize {
+        self.channel_capacities
+            .get(name)
+            .copied()
+            .unwrap_or(default)
+    }
+
+    pub fn encoding_of(&self, name: &str) -> Encoding {
+        if self.compressed_channels.contains(name) {
+            Encoding::RunLength
+        } else {
+            Encoding::Plain(self.precision)
+        }
+    }
+}
+
+/// The samples received of every channel, within the capacity and the memory budget.
+///
+/// It only stores and looks up the samples, how they are plotted is up to the user interface.
+#[derive(Clone)]
+pub struct ValueHistory {
+    channels: HashMap<String, Channel>,
+    cap: usize,
+    limits: HistoryLimits,
+    /// Friendly names for the channels, by the name the source sends
+    aliases: BTreeMap<String, String>,
+    /// Seconds added to the time of the samples of a channel, by the name the samples are stored under
+    time_offsets: BTreeMap<String, f64>,
+    /// The number of samples in all buffers
+    sample_count: usize,
+    /// The line the last value was received in and the frame it was stored in
+    line: Option<(u64, u64)>,
+    /// The frame the values of the next line are stored in
+    next_frame: u64,
+}
+
+/// The stored samples of a channel and the frames they were received in.
+#[derive(Clone)]
+struct Channel {
+    samples: SampleBuffer,
+    frames: Frames,
+}
+
+impl Channel {
+    fn new(encoding: Encoding) -> Self {
+        Self {
+            samples: SampleBuffer::new(encoding),
+            frames: Frames::default(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn push_back(&mut self, sample: Sample, frame: u64) {
+        self.samples.push_back(sample);
+        self.frames.push_back(frame);
+    }
+
+    fn pop_front(&mut self) -> Option<Sample> {
+        self.frames.pop_front();
+        self.samples.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<Sample> {
+        self.frames.pop_back();
+        self.samples.pop_back()
+    }
+
+    /// Adds the samples of `other` and orders all samples by their time, then by their frame.
+    fn merge(&mut self, other: &Channel) {
+        let samples = |channel: &Channel| {
+            let frames = (0..channel.len()).filter_map(|index| channel.frames.get(index));
+            channel.samples.iter().zip(frames).collect::<Vec<_>>()
+        };
+        let mut merged = samples(self);
+        merged.extend(samples(other));
+        merged.sort_by(|a, b| a.0.time.total_cmp(&b.0.time).then(a.1.cmp(&b.1)));
+        *self = Self::new(self.samples.encoding());
+        for (sample, frame) in merged {
+            self.push_back(sample, frame);
+        }
+    }
+}
+
+/// The frame of each sample of a channel, stored as runs of consecutive frames as most channels
+/// receive a value in every line.
+#[derive(Clone, Default)]
+struct Frames {
+    /// The number of frames removed from the front
+    removed: usize,
+    len: usize,
+    /// The index of the first sample of each run, counted like `removed`, and its frame
+    runs: VecDeque<(usize, u64)>,
+}
+
+impl Frames {
+    fn get(&self, index: usize) -> Option<u64> {
+        if index >= self.len {
+            return None;
+        }
+        let index = self.removed + index;
+        let run = self.runs.partition_point(|(start, _)| *start <= index);
+        let (start, first) = self.runs[run - 1];
+        Some(first + (index - start) as u64)
+    }
+
+    fn push_back(&mut self, frame: u64) {
+        let index = self.removed + self.len;
+        self.len += 1;
+        match self.runs.back() {
+            Some(&(start, first)) if first + (index - start) as u64 == frame => {}
+            _ => self.runs.push_back((index, frame)),
+        }
+    }
+
+    fn pop_front(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        self.removed += 1;
+        self.len -= 1;
+        if self.len == 0
+            || self
+                .runs
+                .get(1)
+                .is_some_and(|(start, _)| *start <= self.removed)
+        {
+            self.runs.pop_front();
+        }
+    }
+
+    fn pop_back(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+        self.len -= 1;
+        if self.len == 0 {
+            self.runs.clear();
+        } else if self
+            .runs
+            .back()
+            .is_some_and(|(start, _)| *start == self.removed + self.len)
+        {
+            self.runs.pop_back();
+        }
+    }
+}
+
+impl ValueHistory {
+    /// Stores the values of the next read of a source, returns whether there was one.
+    pub fn try_receive(&mut self, rx: &mut Receiver<Vec<DataValue>>) -> bool {
+        match rx.try_recv() {
+            Err(TryRecvError::Disconnected) => false,
+            Err(TryRecvError::Empty) => false, // Great we are faster at consuming than producing (Blocking is not available as this thread must render the ui)
+            Ok(values) => {
+                for value in values {
+                    self.push(value);
+                }
+                true
+            }
+        }
+    }
+
+    /// Stores a value of a channel, the values of a line are stored in the same frame.
+    pub fn push(
+        &mut self,
+        DataValue {
+            name,
+            value,
+            timestamp,
+            line,
+        }: DataValue,
+    ) {
+        let frame = match self.line {
+            Some((last, frame)) if last == line => frame,
+            _ => {
+                let frame = self.next_frame;
+                self.next_frame += 1;
+                self.line = Some((line, frame));
+                frame
+            }
+        };
+        let name = match alias(&self.aliases, &name) {
+            alias if alias != name => alias.to_string(),
+            _ => name,
+        };
+        self.store_value(
+            Sample {
+                time: timestamp,
+                value,
+            },
+            Cow::Owned(name),
+            frame,
+        );
+    }
+
+    /// The time of the newest sample of all channels.
+    pub fn newest(&self) -> f64 {
+        self.channels()
+            .filter_map(|(_, buffer)| buffer.back())
+            .fold(f64::NEG_INFINITY, |newest, sample| newest.max(sample.time))
+    }
+
+    /// The samples per second a channel received recently, measured over its latest samples.
+    pub fn sample_rate(&self, name: &str) -> Option<f64> {
+        let buffer = self.samples(name)?;
+        let mut latest = buffer.iter().rev().take(RATE_SAMPLES);
+        let newest = latest.next()?.time;
+        let (count, oldest) = latest.fold((0, newest), |(count, _), x| (count + 1, x.time));
+        (newest > oldest).then(|| count as f64 / (newest - oldest))
+    }
+
+    /// Whether a channel received samples and all of them were 0 or 1.
+    pub fn is_binary(&self, name: &str) -> bool {
+        self.samples(name).is_some_and(|buffer| {
+            !buffer.is_empty() && buffer.iter().all(|x| x.value == 0.0 || x.value == 1.0)
+        })
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ValueHistory {
+            channels: HashMap::new(),
+            cap: capacity,
+            limits: HistoryLimits::default(),
+            aliases: BTreeMap::new(),
+            time_offsets: BTreeMap::new(),
+            sample_count: 0,
+            line: None,
+            next_frame: 0,
+        }
+    }
+
+    /// The samples kept of a channel without a capacity of its own.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        if self.cap != capacity {
+            self.cap = capacity;
+            self.trim();
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: &HistoryLimits) {
+        if self.limits == *limits {
+            return;
+        }
+        for (name, channel) in self.channels.iter_mut() {
+            let encoding = limits.encoding_of(name);
+            if channel.samples.encoding() != encoding {
+                channel.samples = channel.samples.with_encoding(encoding);
+            }
+        }
+        self.limits = limits.clone();
+        self.trim();
+    }
+
+    /// Stores the values of a channel under its alias from now on, the samples already received are moved over.
+    pub fn set_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        if self.aliases == *aliases {
+            return;
+        }
+
+        let previous = std::mem::replace(&mut self.aliases, aliases.clone());
+        for name in previous.keys().chain(aliases.keys()) {
+            let from = alias(&previous, name);
+            let to = alias(aliases, name);
+            if from == to {
+                continue;
+            }
+            if let Some(samples) = self.channels.remove(from) {
+                let encoding = self.limits.encoding_of(to);
+                self.channels
+                    .entry(to.to_string())
+                    .or_insert_with(|| Channel::new(encoding))
+                    .merge(&samples);
+            }
+        }
+        self.trim();
+    }
+
+    pub fn set_time_offsets(&mut self, offsets: &BTreeMap<String, f64>) {
+        if self.time_offsets != *offsets {
+            self.time_offsets = offsets.clone();
+        }
+    }
+
+    /// The mean of the latest `count` values of a channel, `None` before it received any.
+    pub fn latest_mean(&self, name: &str, count: usize) -> Option<f64> {
+        let buffer = self.samples(name)?;
+        let latest = buffer.iter().rev().take(count.max(1));
+        let (sum, count) = latest.fold((0.0, 0), |(sum, count), x| (sum + x.value, count + 1));
+        (count > 0).then(|| sum / count as f64)
+    }
+
+    /// Seconds by which the samples of the channel are shifted in the plot and the exports.
+    pub fn time_offset(&self, name: &str) -> f64 {
+        self.time_offsets.get(name).copied().unwrap_or_default()
+    }
+
+    /// A copy of the history without the samples received after `until`.
+    pub fn snapshot(&self, until: f64) -> Self {
+        let mut snapshot = self.clone();
+        for channel in snapshot.channels.values_mut() {
+            while channel
+                .samples
+                .back()
+                .is_some_and(|sample| sample.time > until)
+            {
+                channel.pop_back();
+                snapshot.sample_count -= 1;
+            }
+        }
+        snapshot
+    }
+
+    /// The times of the first and the last sample of all channels, `None` without samples.
+    pub fn time_range(&self) -> Option<[f64; 2]> {
+        let first = self.channels().filter_map(|(_, buffer)| buffer.front());
+        let first = first.map(|sample| sample.time).min_by(f64::total_cmp)?;
+        Some([first, self.newest()])
+    }
+
+    /// The time of the first sample of any channel after `time`.
+    pub fn next_sample(&self, time: f64) -> Option<f64> {
+        self.channels()
+            .filter_map(|(_, buffer)| buffer.get(buffer.partition_point(|_, x| x.time <= time)))
+            .map(|sample| sample.time)
+            .min_by(f64::total_cmp)
+    }
+
+    /// The time of the last sample of any channel before `time`.
+    pub fn previous_sample(&self, time: f64) -> Option<f64> {
+        self.channels()
+            .filter_map(|(_, buffer)| {
+                let index = buffer.partition_point(|_, x| x.time < time);
+                buffer.get(index.checked_sub(1)?)
+            })
+            .map(|sample| sample.time)
+            .max_by(f64::total_cmp)
+    }
+
+    /// Drops the samples received after `time`, channels without samples left disappear.
+    pub fn discard_after(&mut self, time: f64) {
+        for channel in self.channels.values_mut() {
+            while channel
+                .samples
+                .back()
+                .is_some_and(|sample| sample.time > time)
+            {
+                channel.pop_back();
+                self.sample_count -= 1;
+            }
+        }
+        self.channels.retain(|_, channel| channel.len() > 0);
+    }
+
+    /// Drops the samples of all channels, e.g. to start a new measurement with the port kept open.
+    pub fn clear(&mut self) {
+        self.channels.clear();
+        self.sample_count = 0;
+        self.line = None;
+        self.next_frame = 0;
+    }
+
+    /// Drops the samples of a single channel, it reappears with its next sample.
+    pub fn clear_channel(&mut self, name: &str) {
+        if let Some(channel) = self.channels.remove(name) {
+            self.sample_count -= channel.len();
+        }
+    }
+
+    /// Adds samples of the channels that were stored before, e.g. by an autosave.
+    ///
+    /// Which line a sample was received in is not stored, so the samples at the same index of
+    /// every channel are taken as one line, after the lines received so far.
+    pub fn restore(&mut self, channels: impl IntoIterator<Item = (String, Vec<Sample>)>) {
+        let first = self.next_frame;
+        for (name, samples) in channels {
+            for (frame, sample) in (first..).zip(samples) {
+                self.next_frame = self.next_frame.max(frame + 1);
+                self.store_value(sample, Cow::Borrowed(&name), frame);
+            }
+        }
+        self.line = None;
+    }
+
+    /// A history of the `[time, value]` samples of an imported file, which keeps all of them.
+    ///
+    /// The times of exported files already include the time offsets, so none are applied.
+    pub fn imported(channels: impl IntoIterator<Item = (String, Vec<[f64; 2]>)>) -> Self {
+        let channels: Vec<_> = channels.into_iter().collect();
+        let longest = channels.iter().map(|(_, samples)| samples.len()).max();
+        let mut history = Self::with_capacity(longest.unwrap_or_default() + 1);
+        history.restore(channels.into_iter().map(|(name, samples)| {
+            let samples = samples
+                .into_iter()
+                .map(|[time, value]| Sample { time, value });
+            (name, samples.collect())
+        }));
+        history
+    }
+
+    /// An estimate of the memory used by the stored samples in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.channels
+            .values()
+            .map(|channel| channel.samples.memory_usage())
+            .sum()
+    }
+
+    /// The number of samples of all channels together.
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Drops the samples exceeding the capacity of their channel or the memory budget.
+    fn trim(&mut self) {
+        for (name, channel) in self.channels.iter_mut() {
+            let capacity = self.limits.capacity_of(name, self.cap);
+            while channel.len() >= capacity {
+                channel.pop_front();
+                self.sample_count -= 1;
+            }
+        }
+        self.enforce_memory_budget();
+    }
+
+    fn enforce_memory_budget(&mut self) {
+        let Some(budget) = self.limits.memory_budget else {
+            return;
+        };
+        while self.memory_usage() > budget * 1_000_000 {
+            let oldest = self
+                .channels
+                .values_mut()
+                .filter_map(|channel| Some((channel.samples.front()?.time, channel)))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, channel)| channel);
+            match oldest {
+                Some(channel) => {
+                    channel.pop_front();
+                    self.sample_count -= 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Integrates pending values until the channel is empty or `time_budget` is used up.
+    ///
+    /// The values of a read are stored together, the budget is checked between the reads.
+    pub fn update(
+        &mut self,
+        receiver: &mut Receiver<Vec<DataValue>>,
+        displayed_values: usize,
+        time_budget: Option<Duration>,
+    ) {
+        self.set_capacity(displayed_values);
+        // `Instant` is not available on the web, the wall clock is precise enough for the budget
+        let start = unix_timestamp();
+        let mut count = 0usize;
+        // The value that waited longest for the ui
+        let mut oldest = f64::INFINITY;
+        while let Ok(values) = receiver.try_recv() {
+            count += values.len();
+            for value in values {
+                oldest = oldest.min(value.timestamp);
+                self.push(value);
+            }
+            if let Some(budget) = time_budget {
+                if unix_timestamp() - start >= budget.as_secs_f64() {
+                    break;
+                }
+            }
+        }
+
+        // The measurements belong to the latest line
+        let frame = self.next_frame.saturating_sub(1);
+        let now = unix_timestamp();
+        self.store_value(
+            Sample {
+                time: now,
+                value: count as f64,
+            },
+            Cow::Borrowed("fetch_count"),
+            frame,
+        );
+
+        self.store_value(
+            Sample {
+                time: now,
+                value: receiver.len() as f64,
+            },
+            Cow::Borrowed("pending_messages"),
+            frame,
+        );
+
+        // From reading the bytes to storing their values, the device and its driver add their own
+        // delay before. Values with the timestamps of a device include the offset of its clock.
+        if count > 0 {
+            self.store_value(
+                Sample {
+                    time: now,
+                    value: (now - oldest) * 1000.0,
+                },
+                Cow::Borrowed("latency_ms"),
+                frame,
+            );
+        }
+    }
+
+    /// The names of all channels that received at least one value.
+    pub fn channel_names(&self) -> impl Iterator<Item = &str> {
+        self.channels.keys().map(String::as_str)
+    }
+
+    /// Every channel with its stored samples.
+    pub fn channels(&self) -> impl Iterator<Item = (&String, &SampleBuffer)> {
+        self.channels
+            .iter()
+            .map(|(name, channel)| (name, &channel.samples))
+    }
+
+    /// The stored samples of a channel, oldest first.
+    pub fn samples(&self, name: &str) -> Option<&SampleBuffer> {
+        self.channels.get(name).map(|channel| &channel.samples)
+    }
+
+    /// The frame the sample of a channel at `index` was stored in, the values of a line share it.
+    ///
+    /// Series computed from the samples, e.g. resampled ones, have indices of their own, for them
+    /// the frame of the first stored sample at `time` or after it is taken.
+    pub fn frame(&self, name: &str, index: usize, time: f64) -> Option<u64> {
+        let channel = self.channels.get(name)?;
+        let index = match channel.samples.get(index) {
+            Some(sample) if sample.time == time => index,
+            _ => channel
+                .samples
+                .partition_point(|_, sample| sample.time < time)
+                .min(channel.len().checked_sub(1)?),
+        };
+        channel.frames.get(index)
+    }
+
+    /// The oldest frame any channel still has a sample of.
+    pub fn first_frame(&self) -> Option<u64> {
+        self.channels
+            .values()
+            .filter_map(|channel| channel.frames.get(0))
+            .min()
+    }
+
+    fn store_value(&mut self, value: Sample, key: Cow<'_, str>, frame: u64) {
+        let capacity = self.limits.capacity_of(&key, self.cap);
+        let encoding = self.limits.encoding_of(&key);
+        // The buffers grow on demand, so the memory usage follows the stored samples
+        let channel = self
+            .channels
+            .entry(key.into_owned())
+            .or_insert_with(|| Channel::new(encoding));
+
+        channel.push_back(value, frame);
+        self.sample_count += 1;
+        if channel.len() >= capacity {
+            channel.pop_front();
+            self.sample_count -= 1;
+        }
+        if self.limits.memory_budget.is_some() {
+            self.enforce_memory_budget();
+        }
+    }
+}
+
+/// The name the values of a channel are stored under, empty aliases are ignored.
+pub fn alias<'a>(aliases: &'a BTreeMap<String, String>, name: &'a str) -> &'a str {
+    match aliases.get(name) {
+        Some(alias) if !alias.is_empty() => alias,
+        _ => name,
+    }
+}
+
+/// The value of the series at `x`, interpolated between the samples next to it.
+///
+/// `x_of` maps a sample and its index to its ascending x coordinate, there is no value outside of the samples.
+pub fn interpolate(
+    buffer: &SampleBuffer,
+    x: f64,
+    x_of: impl Fn(usize, &Sample) -> f64,
+) -> Option<f64> {
+    let index = buffer.partition_point(|index, sample| x_of(index, &sample) < x);
+    let after = buffer.get(index)?;
+    let after_x = x_of(index, &after);
+    if after_x == x {
+        return Some(after.value);
+    }
+    let before = buffer.sample(index.checked_sub(1)?);
+    let before_x = x_of(index - 1, &before);
+    let fraction = (x - before_x) / (after_x - before_x);
+    Some(before.value + (after.value - before.value) * fraction)
+}
+
+/// Reduces a series to at most `max_points` points plotted over the sample index.
+///
+/// Each bucket keeps its minimum and maximum in the order they occurred,
+/// so peaks stay visible no matter how much the series is compressed.
+pub fn decimate(samples: &SampleBuffer, max_points: usize) -> Vec<[f64; 2]> {
+    decimate_with(samples, max_points, |index, _| index as f64)
+}
+
+/// Like [`decimate`], with the x coordinate of a sample given by `x` from its index and the sample.
+pub fn decimate_with(
+    samples: &SampleBuffer,
+    max_points: usize,
+    x: impl Fn(usize, &Sample) -> f64,
+) -> Vec<[f64; 2]> {
+    if samples.len() <= max_points {
+        return samples
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| [x(index, &sample), sample.value])
+            .collect();
+    }
+
+    let bucket_size = samples.len().div_ceil((max_points / 2).max(1));
+    let mut points = Vec::with_capacity(max_points);
+    let mut index = 0;
+    while index < samples.len() {
+        let end = (index + bucket_size).min(samples.len());
+        let mut min = (index, samples.sample(index).value);
+        let mut max = min;
+        for (offset, sample) in samples.range(index..end).enumerate() {
+            if sample.value < min.1 {
+                min = (index + offset, sample.value);
+            }
+            if sample.value > max.1 {
+                max = (index + offset, sample.value);
+            }
+        }
+
+        let (first, second) = if min.0 <= max.0 {
+            (min, max)
+        } else {
+            (max, min)
+        };
+        points.push([x(first.0, &samples.sample(first.0)), first.1]);
+        if second.0 != first.0 {
+            points.push([x(second.0, &samples.sample(second.0)), second.1]);
+        }
+        index = end;
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(values: &[f64]) -> SampleBuffer {
+        values
+            .iter()
+            .map(|&value| Sample { time: 0.0, value })
+            .collect()
+    }
+
+    fn store(history: &mut ValueHistory, name: &'static str, time: f64) {
+        history.store_value(
+            Sample { time, value: 0.0 },
+            Cow::Borrowed(name),
+            time as u64,
+        );
+    }
+
+    #[test]
+    fn should_measure_the_sample_rate_of_a_channel() {
+        let mut history = ValueHistory::with_capacity(1000);
+        for index in 0..=200 {
+            store(&mut history, "fast", 10.0 + index as f64 * 0.01);
+        }
+        store(&mut history, "once", 10.0);
+        assert!((history.sample_rate("fast").unwrap() - 100.0).abs() < 1e-6);
+        assert_eq!(history.sample_rate("once"), None);
+        assert_eq!(history.sample_rate("unknown"), None);
+    }
+
+    #[test]
+    fn should_apply_channel_capacity() {
+        let mut history = ValueHistory::with_capacity(10);
+        let mut limits = HistoryLimits::default();
+        limits.channel_capacities.insert("small".to_string(), 3);
+        history.set_limits(&limits);
+
+        for time in 0..20 {
+            store(&mut history, "small", time as f64);
+            store(&mut history, "large", time as f64);
+        }
+
+        assert_eq!(history.samples("small").unwrap().len(), 2);
+        assert_eq!(history.samples("large").unwrap().len(), 9);
+        assert_eq!(history.sample_count, 11);
+    }
+
+    #[test]
+    fn should_evict_oldest_samples_across_channels() {
+        let mut history = ValueHistory::with_capacity(usize::MAX);
+        for time in 0..100 {
+            store(&mut history, "early", time as f64);
+        }
+        for time in 100..200 {
+            store(&mut history, "late", time as f64);
+        }
+        // Allow a single megabyte, then fill it up with the late channel
+        history.set_limits(&HistoryLimits {
+            memory_budget: Some(1),
+            ..Default::default()
+        });
+        let max_samples = 1_000_000 / size_of::<Sample>();
+        for time in 200..(200 + max_samples) {
+            store(&mut history, "late", time as f64);
+        }
+
+        assert_eq!(history.sample_count, max_samples);
+        assert!(history.samples("early").unwrap().is_empty());
+        assert_eq!(
+            history.samples("late").unwrap().front().unwrap().time,
+            200.0
+        );
+    }
+
+    #[test]
+    fn should_interpolate_between_the_neighbouring_samples() {
+        let buffer: SampleBuffer = [(10.0, 1.0), (11.0, 3.0), (13.0, 7.0)]
+            .into_iter()
+            .map(|(time, value)| Sample { time, value })
+            .collect();
+        let time = |_: usize, sample: &Sample| sample.time;
+        assert_eq!(interpolate(&buffer, 10.5, time), Some(2.0));
+        assert_eq!(interpolate(&buffer, 13.0, time), Some(7.0));
+        assert_eq!(interpolate(&buffer, 9.0, time), None);
+        assert_eq!(
+            interpolate(&buffer, 1.5, |index, _| index as f64),
+            Some(5.0)
+        );
+    }
+
+    #[test]
+    fn should_move_samples_to_alias() {
+        let mut history = ValueHistory::with_capacity(10);
+        store(&mut history, "a0", 0.0);
+
+        let mut aliases = BTreeMap::new();
+        aliases.insert("a0".to_string(), "temperature".to_string());
+        history.set_aliases(&aliases);
+        let (sender, mut receiver) = crossbeam::channel::unbounded();
+        sender
+            .send(vec![DataValue {
+                name: "a0".to_string(),
+                value: 1.0,
+                timestamp: 1.0,
+                line: 0,
+            }])
+            .unwrap();
+        history.try_receive(&mut receiver);
+
+        assert!(history.samples("a0").is_none());
+        assert_eq!(history.samples("temperature").unwrap().len(), 2);
+
+        history.set_aliases(&BTreeMap::new());
+
+        assert!(history.samples("temperature").is_none());
+        assert_eq!(history.samples("a0").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn should_snapshot_until_time() {
+        let mut history = ValueHistory::with_capacity(10);
+        for time in 0..5 {
+            store(&mut history, "a", time as f64);
+        }
+
+        let snapshot = history.snapshot(2.0);
+
+        assert_eq!(snapshot.samples("a").unwrap().len(), 3);
+        assert_eq!(snapshot.sample_count, 3);
+        assert_eq!(history.samples("a").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn should_clear_channels() {
+        let mut history = ValueHistory::with_capacity(10);
+        for time in 0..3 {
+            store(&mut history, "a", time as f64);
+            store(&mut history, "b", time as f64);
+        }
+
+        history.clear_channel("a");
+        assert!(history.samples("a").is_none());
+        assert_eq!(history.sample_count, 3);
+
+        history.clear();
+        assert_eq!(history.channel_names().count(), 0);
+        assert_eq!(history.sample_count, 0);
+    }
+
+    #[test]
+    fn should_keep_short_series() {
+        let points = decimate(&series(&[1.0, 2.0, 3.0]), 10);
+
+        assert_eq!(points, vec![[0.0, 1.0], [1.0, 2.0], [2.0, 3.0]]);
+    }
+
+    #[test]
+    fn should_align_values_of_the_same_line() {
+        let (sender, mut receiver) = crossbeam::channel::unbounded();
+        // All lines arrived in one read, the second line only contains `a`, the third only `b`
+        for (name, value, line) in [("a", 1.0, 7), ("b", 1.0, 7), ("a", 2.0, 8), ("b", 3.0, 9)] {
+            let value = DataValue {
+                name: name.to_string(),
+                value,
+                timestamp: 5.0,
+                line,
+            };
+            sender.send(vec![value]).unwrap();
+        }
+        let mut history = ValueHistory::with_capacity(100);
+        while history.try_receive(&mut receiver) {}
+
+        let frames = |history: &ValueHistory, name| {
+            let buffer = history.samples(name).unwrap();
+            let first = history.first_frame().unwrap();
+            decimate_with(buffer, 100, |index, sample| {
+                (history.frame(name, index, sample.time).unwrap() - first) as f64
+            })
+        };
+        assert_eq!(frames(&history, "a"), vec![[0.0, 1.0], [1.0, 2.0]]);
+        assert_eq!(frames(&history, "b"), vec![[0.0, 1.0], [2.0, 3.0]]);
+
+        history.set_capacity(2);
+        assert_eq!(history.first_frame(), Some(1));
+        assert_eq!(frames(&history, "b"), vec![[1.0, 3.0]]);
+    }
+
+    #[test]
+    fn should_place_points_at_their_shifted_time() {
+        let samples: SampleBuffer = [(10.0, 1.0), (10.5, 2.0)]
+            .into_iter()
+            .map(|(time, value)| Sample { time, value })
+            .collect();
+
+        let points = decimate_with(&samples, 10, |_, sample| sample.time + 0.25 - 10.5);
+
+        assert_eq!(points, vec![[-0.25, 1.0], [0.25, 2.0]]);
+    }
+
+    #[test]
+    fn should_keep_peaks_when_decimating() {
+        let mut values = vec![0.0; 100];
+        values[42] = 10.0;
+        values[77] = -10.0;
+
+        let points = decimate(&series(&values), 10);
+
+        assert!(points.len() <= 10);
+        assert!(points.contains(&[42.0, 10.0]));
+        assert!(points.contains(&[77.0, -10.0]));
+    }
+}
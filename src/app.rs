@@ -1,16 +1,100 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
 use egui::{InnerResponse, Ui};
 
 use crossbeam::channel::{Receiver, Sender};
-use serialport::available_ports;
 use tracing::info;
 
-use crate::value_parsing::Commands;
+use crate::calibration::Calibrations;
+use crate::cli::{Args, DEFAULT_BAUD_RATE};
+use crate::csv_format::CsvFormat;
+use crate::dsp::DisplayFilters;
+#[cfg(target_arch = "wasm32")]
+use crate::value_parsing::WebSerialSource;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::value_parsing::{BusPirateSource, DemoSource, SerialSource, ValueParser};
 use crate::{
     frame_history::{self, FrameHistory},
-    value_parsing::{DataValue, SerialSource},
+    value_parsing::{
+        parsing_state_machine::ParsingResult, Backpressure, Commands, DataFormat, DataSource,
+        DataValue, Delimiters, LineCheck, NumberType, OverflowPolicy, ParseFailure, ParserSettings,
+        SourceEvent, SourceSenders, BITRATES,
+    },
 };
+use alarms::Alarms;
+use alerts::Alerts;
+#[cfg(not(target_arch = "wasm32"))]
+use audio_input::AudioInput;
+use baselines::Baselines;
+use burst::BurstMode;
+#[cfg(not(target_arch = "wasm32"))]
+use bus_bridge::BusBridge;
+use calibration_wizard::CalibrationWizard;
+use can_decoding::CanDecoding;
+use channel_aliases::ChannelAliases;
+use cursors::Cursors;
+#[cfg(not(target_arch = "wasm32"))]
+use data_files::DataFiles;
+#[cfg(not(target_arch = "wasm32"))]
+use data_logger::DataLogger;
+#[cfg(not(target_arch = "wasm32"))]
+use demo_signals::DemoSignals;
+use derived::DerivedSeries;
+use event_log::{EventKind, EventLog};
+#[cfg(not(target_arch = "wasm32"))]
+use export_channels::ExportChannels;
+use gamepad_mapping::GamepadMapping;
 use gilrs::Gilrs;
+use histogram::Histogram;
+use latency::LatencyMeasurement;
+use legend::SeriesLegend;
+use logic_lanes::LogicLanes;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use metrics_endpoint::MetricsEndpoint;
+#[cfg(not(target_arch = "wasm32"))]
+use opcua_client::OpcUaClient;
+use overview::Overview;
+use parse_errors::ParseErrors;
+#[cfg(not(target_arch = "wasm32"))]
+use plot_export::PlotExport;
+#[cfg(not(target_arch = "wasm32"))]
+use port_selection::PortSelection;
+use raw_monitor::RawMonitor;
+#[cfg(not(target_arch = "wasm32"))]
+use recovery::Recovery;
+#[cfg(not(target_arch = "wasm32"))]
+use replay::Replay;
+use run_summary::{RunAction, RunSummaries};
+use scripting::{ScriptAction, Scripting};
+use series_styles::SeriesStyles;
+use session::{SessionAction, SessionMenu};
+use settings_check::SettingsCheck;
+use shortcuts::{Action, Shortcuts};
+use stopwatch::Stopwatch;
+use time_alignment::TimeAlignment;
+#[cfg(not(target_arch = "wasm32"))]
+use tray::{TrayCommand, TrayMode};
+use update_cadence::UpdateCadence;
 use value_history::*;
+#[cfg(not(target_arch = "wasm32"))]
+use websocket::WebSocketLink;
+pub use widget::SerialPlotWidget;
+
+/// The views that can be shown in the panel at the bottom of the window.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum BottomTab {
+    Log,
+    RawMonitor,
+    Events,
+    Alerts,
+    Histogram,
+}
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -18,53 +102,257 @@ use value_history::*;
 pub struct TemplateApp {
     // this how you opt-out of serialization of a member
     displayed_values: usize,
-    max_fetch_count: usize,
 
     serial_port_name: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    port_selection: PortSelection,
     baud_rate: u32,
+    parser_settings: ParserSettings,
+    can_decoding: CanDecoding,
 
-    #[serde(skip)]
     show_log: bool,
 
+    bottom_tab: BottomTab,
+
+    raw_monitor: RawMonitor,
+
+    event_log: EventLog,
+
     #[serde(skip)]
-    value_history: ValueHistory,
+    raw_receiver: Receiver<Vec<u8>>,
 
     #[serde(skip)]
-    receiver: Receiver<DataValue>,
+    raw_sender: Sender<Vec<u8>>,
 
     #[serde(skip)]
-    sender: Sender<DataValue>,
+    parse_errors: ParseErrors,
+
+    latency: LatencyMeasurement,
 
+    alarms: Alarms,
+
+    alerts: Alerts,
+
+    histogram: Histogram,
+
+    /// The plot as it was when it was paused or an alarm froze it, together with the reason
     #[serde(skip)]
-    open_port: Option<(String, u32)>,
+    frozen: Option<(String, ValueHistory)>,
+    /// An imported recording played back instead of the live values
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    replay: Option<Replay>,
+
+    update_cadence: UpdateCadence,
 
+    session_menu: SessionMenu,
+    /// What was adjusted of the loaded settings
     #[serde(skip)]
-    fps_history: frame_history::FrameHistory,
+    settings_check: SettingsCheck,
+
+    shortcuts: Shortcuts,
+
+    run_summaries: RunSummaries,
+    stopwatch: Stopwatch,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    plot_export: PlotExport,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    export_channels: ExportChannels,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    opcua_client: OpcUaClient,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    websocket: WebSocketLink,
+
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics_endpoint: MetricsEndpoint,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    audio_input: AudioInput,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    bus_bridge: BusBridge,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    demo_signals: DemoSignals,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    recovery: Recovery,
+
+    #[serde(skip)]
+    parse_error_channel: (Sender<ParseFailure>, Receiver<ParseFailure>),
+    #[serde(skip)]
+    source_events: (Sender<SourceEvent>, Receiver<SourceEvent>),
+
+    history_limits: HistoryLimits,
+    channel_aliases: ChannelAliases,
+    csv_format: CsvFormat,
+    #[cfg(not(target_arch = "wasm32"))]
+    data_logger: DataLogger,
+    #[cfg(not(target_arch = "wasm32"))]
+    data_files: DataFiles,
+    #[cfg(not(target_arch = "wasm32"))]
+    tray_mode: TrayMode,
+    /// The sinks of the session, they are handed to every source that is opened
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    sinks: std::sync::Arc<std::sync::Mutex<crate::sinks::Sinks>>,
+    calibrations: Calibrations,
+    calibration_wizard: CalibrationWizard,
+    display_filters: DisplayFilters,
+    baselines: Baselines,
+    series_styles: SeriesStyles,
+    logic_lanes: LogicLanes,
+
+    scripting: Scripting,
+    derived_series: DerivedSeries,
+    y_range: Option<YRange>,
+    x_axis: XAxis,
+    /// Seconds shown with `XAxis::Window`
+    time_window: f64,
+    overview: Overview,
+    cursors: Cursors,
+    legend: SeriesLegend,
+    /// Draw dense traces through the gpu, only with the wgpu renderer
+    #[cfg(feature = "gpu_plot")]
+    gpu_rendering: bool,
+    burst: BurstMode,
+    time_alignment: TimeAlignment,
+    gamepad_mapping: GamepadMapping,
+
+    #[serde(skip)]
+    value_history: ValueHistory,
+    #[serde(skip)]
+    plot_settings: PlotSettings,
+
     #[serde(skip)]
-    command: (Sender<Commands>, Receiver<Commands>),
+    receiver: Receiver<Vec<DataValue>>,
 
     #[serde(skip)]
-    gilrs: Gilrs,
+    sender: Sender<Vec<DataValue>>,
+
+    /// What the source does with values while the ui falls behind
+    overflow_policy: OverflowPolicy,
+    #[serde(skip)]
+    backpressure: Arc<Backpressure>,
+    /// The number of the next line of any source, see `SourceSenders::next_line`
+    #[serde(skip)]
+    lines: Arc<AtomicU64>,
+
+    #[serde(skip)]
+    source: Option<Box<dyn DataSource>>,
+
+    #[serde(skip)]
+    fps_history: frame_history::FrameHistory,
+    #[serde(skip)]
+    gilrs: Option<Gilrs>,
 }
 
 impl Default for TemplateApp {
     fn default() -> Self {
-        let gilrs = Gilrs::new().unwrap();
-        let (tx, rx) = crossbeam::channel::bounded(10000);
-        let (command_tx, command_rx) = crossbeam::channel::bounded(10);
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            // Without a backend for the platform no gamepads are found, which is fine
+            Err(gilrs::Error::NotImplemented(gilrs)) => Some(gilrs),
+            Err(err) => {
+                tracing::warn!("Gamepads are not available: {}", err);
+                None
+            }
+        };
+        // Every message holds the values of a read, most reads hold a few lines
+        let (tx, rx) = crossbeam::channel::bounded(1000);
+        let (raw_tx, raw_rx) = crossbeam::channel::bounded(1000);
         Self {
             // Example stuff:
             displayed_values: 1000,
-            max_fetch_count: 100,
             serial_port_name: None,
-            baud_rate: 9600,
+            #[cfg(not(target_arch = "wasm32"))]
+            port_selection: PortSelection::default(),
+            baud_rate: DEFAULT_BAUD_RATE,
+            parser_settings: ParserSettings::default(),
+            can_decoding: CanDecoding::default(),
+            history_limits: HistoryLimits::default(),
+            channel_aliases: ChannelAliases::default(),
+            csv_format: CsvFormat::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            data_logger: DataLogger::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            data_files: DataFiles::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            tray_mode: TrayMode::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            sinks: Default::default(),
+            calibrations: Calibrations::default(),
+            calibration_wizard: CalibrationWizard::default(),
+            display_filters: DisplayFilters::default(),
+            baselines: Baselines::default(),
+            series_styles: SeriesStyles::default(),
+            logic_lanes: LogicLanes::default(),
+            scripting: Scripting::default(),
+            derived_series: DerivedSeries::default(),
+            y_range: None,
+            x_axis: XAxis::Samples,
+            time_window: 10.0,
+            overview: Overview::default(),
+            cursors: Cursors::default(),
+            legend: SeriesLegend::default(),
+            #[cfg(feature = "gpu_plot")]
+            gpu_rendering: false,
+            burst: BurstMode::default(),
+            time_alignment: TimeAlignment::default(),
+            gamepad_mapping: GamepadMapping::default(),
             value_history: ValueHistory::with_capacity(1000),
+            plot_settings: PlotSettings::default(),
             receiver: rx,
+            overflow_policy: OverflowPolicy::default(),
+            backpressure: Arc::new(Backpressure::new(OverflowPolicy::default())),
+            lines: Default::default(),
             sender: tx,
-            open_port: None,
+            source: None,
             show_log: true,
+            bottom_tab: BottomTab::Log,
+            raw_monitor: RawMonitor::default(),
+            event_log: EventLog::default(),
+            raw_receiver: raw_rx,
+            raw_sender: raw_tx,
+            parse_errors: ParseErrors::default(),
+            latency: LatencyMeasurement::default(),
+            alarms: Alarms::default(),
+            alerts: Alerts::default(),
+            histogram: Histogram::default(),
+            frozen: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            replay: None,
+            update_cadence: UpdateCadence::default(),
+            session_menu: SessionMenu::default(),
+            settings_check: SettingsCheck::default(),
+            shortcuts: Shortcuts::default(),
+            run_summaries: RunSummaries::default(),
+            stopwatch: Stopwatch::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            plot_export: PlotExport::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            export_channels: ExportChannels::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            opcua_client: OpcUaClient::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            websocket: WebSocketLink::default(),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics_endpoint: MetricsEndpoint::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            audio_input: AudioInput::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            bus_bridge: BusBridge::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            demo_signals: DemoSignals::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            recovery: Recovery::default(),
+            parse_error_channel: crossbeam::channel::bounded(1000),
+            source_events: crossbeam::channel::bounded(100),
             fps_history: FrameHistory::default(),
-            command: (command_tx, command_rx),
             gilrs,
         }
     }
@@ -78,11 +366,37 @@ impl TemplateApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        #[cfg(feature = "gpu_plot")]
+        if let Some(render_state) = &cc.wgpu_render_state {
+            gpu_plot::init(render_state);
         }
 
-        Default::default()
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+        app.check_settings();
+        #[cfg(not(target_arch = "wasm32"))]
+        app.recovery.check();
+        app
+    }
+
+    /// Adjusts the loaded settings that would otherwise fail later, e.g. a baud rate of 0.
+    fn check_settings(&mut self) {
+        let check = &mut self.settings_check;
+        check.baud_rate(&mut self.baud_rate, DEFAULT_BAUD_RATE);
+        check.displayed_values(&mut self.displayed_values);
+        check.time_window(&mut self.time_window, 10.0);
+        check.history_limits(&mut self.history_limits);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let ports: Vec<String> = serialport::available_ports()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|port| port.port_name)
+                .collect();
+            check.serial_port(self.serial_port_name.as_deref(), &ports);
+        }
     }
 }
 
@@ -93,8 +407,13 @@ impl eframe::App for TemplateApp {
     }
 
     fn on_close_event(&mut self) -> bool {
-        let Self { command, .. } = self;
-        let _ = command.0.send(Commands::Stop);
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.tray_mode.on_close() {
+            return false;
+        }
+        self.stop_sources();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.recovery.exit();
         true
     }
 
@@ -103,36 +422,116 @@ impl eframe::App for TemplateApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let Self {
             serial_port_name,
+            #[cfg(not(target_arch = "wasm32"))]
+            port_selection,
             baud_rate,
+            parser_settings,
+            can_decoding,
+            history_limits,
+            channel_aliases,
+            csv_format,
+            #[cfg(not(target_arch = "wasm32"))]
+            data_logger,
+            #[cfg(not(target_arch = "wasm32"))]
+            data_files,
+            #[cfg(not(target_arch = "wasm32"))]
+            tray_mode,
+            #[cfg(not(target_arch = "wasm32"))]
+            sinks,
+            calibrations,
+            calibration_wizard,
+            display_filters,
+            baselines,
+            series_styles,
+            logic_lanes,
+            scripting,
+            derived_series,
+            y_range,
+            x_axis,
+            time_window,
+            overview,
+            cursors,
+            legend,
+            #[cfg(feature = "gpu_plot")]
+            gpu_rendering,
+            burst,
+            time_alignment,
+            gamepad_mapping,
             value_history,
-            receiver,
+            plot_settings,
             sender,
-            open_port,
-            max_fetch_count,
+            receiver,
+            overflow_policy,
+            backpressure,
+            lines,
+            source,
             displayed_values,
             show_log,
+            bottom_tab,
+            raw_monitor,
+            event_log,
+            raw_receiver,
+            parse_errors,
+            parse_error_channel,
+            source_events,
+            latency,
+            alarms,
+            alerts,
+            histogram,
+            frozen,
+            #[cfg(not(target_arch = "wasm32"))]
+            replay,
+            update_cadence,
+            session_menu,
+            settings_check,
+            shortcuts,
+            run_summaries,
+            stopwatch,
+            #[cfg(not(target_arch = "wasm32"))]
+            plot_export,
+            #[cfg(not(target_arch = "wasm32"))]
+            export_channels,
+            #[cfg(not(target_arch = "wasm32"))]
+            opcua_client,
+            #[cfg(not(target_arch = "wasm32"))]
+            websocket,
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics_endpoint,
+            #[cfg(not(target_arch = "wasm32"))]
+            audio_input,
+            #[cfg(not(target_arch = "wasm32"))]
+            bus_bridge,
+            #[cfg(not(target_arch = "wasm32"))]
+            demo_signals,
+            #[cfg(not(target_arch = "wasm32"))]
+            recovery,
             fps_history,
-            command,
             gilrs,
             ..
         } = self;
 
-        let mut update_display = false;
-
         // Examine new events
-        while let Some(gilrs::Event { id, event, time }) = gilrs.next_event() {
+        while let Some(gilrs::Event { id, event, time }) =
+            gilrs.as_mut().and_then(Gilrs::next_event)
+        {
+            gamepad_mapping.handle(&event);
             match event {
                 gilrs::EventType::ButtonPressed(_, _)
                 | gilrs::EventType::ButtonReleased(_, _)
                 | gilrs::EventType::ButtonRepeated(_, _) => {
                     info!("{:?} New event from {}: {:?}", time, id, event);
-                    update_display = true;
                 }
                 _ => {}
             }
         }
 
-        fps_history.on_new_frame(ctx.input(|x| x.time), None);
+        let now = ctx.input(|x| x.time);
+        fps_history.on_new_frame(now, _frame.info().cpu_usage);
+        for message in gamepad_mapping.take_messages(now) {
+            if let Some(source) = source {
+                source.command(Commands::SendMessage(message));
+            }
+        }
 
         #[cfg(feature = "profiling")]
         {
@@ -141,15 +540,225 @@ impl eframe::App for TemplateApp {
             puffin_egui::profiler_window(ctx);
         }
 
-        if update_display {
-            value_history.update(receiver, *displayed_values, *max_fetch_count);
+        value_history.set_limits(history_limits);
+        value_history.set_aliases(&channel_aliases.aliases);
+        value_history.set_time_offsets(&time_alignment.offsets);
+        plot_settings.set_time_window(*time_window);
+        plot_settings.set_display_filters(&display_filters.filters);
+        plot_settings.set_baselines(&baselines.baselines);
+        plot_settings.set_calibrations(calibrations);
+        plot_settings.set_series_styles(&series_styles.styles);
+        plot_settings.set_logic_channels(&logic_lanes.channels);
+        plot_settings.set_derived(&derived_series.series);
+        plot_settings.set_resampling(
+            time_alignment.reference.as_deref(),
+            time_alignment.resampling,
+        );
+        #[cfg(feature = "gpu_plot")]
+        plot_settings.set_gpu_rendering(*gpu_rendering && _frame.wgpu_render_state().is_some());
+        backpressure.set_policy(*overflow_policy);
+        // Ingestion runs every frame on its own cadence, a frame is repainted even without input
+        if update_cadence.ingest_due(now) {
+            let frame_time = fps_history.mean_frame_time().into();
+            let budget = update_cadence.fetch_budget(frame_time, receiver.len());
+            let start = crate::value_parsing::unix_timestamp();
+            if burst.enabled {
+                burst.update(receiver, budget);
+            } else {
+                value_history.update(receiver, *displayed_values, budget);
+            }
+            update_cadence.ingested(crate::value_parsing::unix_timestamp() - start);
+        }
+        // The source reports why it stopped with a `SourceEvent::Disconnected`
+        if source.as_ref().is_some_and(|source| !source.is_running()) {
+            // A vanished serial port is opened again once it is back, e.g. after an upload
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(port) = source
+                .as_deref()
+                .map(DataSource::name)
+                .filter(|name| serial_port_name.as_deref() == Some(*name))
+            {
+                port_selection.lost(port);
+            }
+            *source = None;
         }
+        run_summaries.update(
+            source.as_deref(),
+            value_history,
+            plot_settings,
+            backpressure.dropped(),
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        opcua_client.update();
+        #[cfg(not(target_arch = "wasm32"))]
+        websocket.update();
+        #[cfg(not(target_arch = "wasm32"))]
+        audio_input.update();
+        #[cfg(not(target_arch = "wasm32"))]
+        recovery.update(value_history);
+        raw_monitor.update(raw_receiver);
+        parse_errors.update(&parse_error_channel.1);
+        for event in source_events.1.try_iter() {
+            match event {
+                SourceEvent::Condition(message) => {
+                    event_log.record(EventKind::PortCondition, message)
+                }
+                SourceEvent::Error(message) => event_log.record(EventKind::SourceError, message),
+                SourceEvent::Disconnected(message) => {
+                    event_log.record(EventKind::ConnectionLost, message)
+                }
+            }
+        }
+        latency.update(value_history);
+        for fired in alarms.update(value_history) {
+            let description = fired.alarm.condition.to_string();
+            event_log.record(EventKind::Alarm, description.clone());
+            run_summaries.alarm();
+            if fired.alarm.freeze && frozen.is_none() {
+                let reason = format!("alarm {}", description);
+                *frozen = Some((reason, value_history.snapshot(fired.time)));
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if fired.alarm.capture {
+                let displayed = frozen
+                    .as_ref()
+                    .map_or(&*value_history, |(_, frozen)| frozen);
+                let source = source
+                    .as_ref()
+                    .map_or("not connected", |source| source.name());
+                alarms.capture(&fired.trigger(), displayed, source, export_channels);
+            }
+        }
+        #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+        for fired in alerts.update(value_history) {
+            #[cfg(not(target_arch = "wasm32"))]
+            if fired.rule.capture {
+                let displayed = frozen
+                    .as_ref()
+                    .map_or(&*value_history, |(_, frozen)| frozen);
+                let source = source
+                    .as_ref()
+                    .map_or("not connected", |source| source.name());
+                let trigger = alarms::Trigger {
+                    kind: "alert",
+                    time: fired.time,
+                    channel: &fired.rule.channel,
+                    description: fired.rule.to_string(),
+                };
+                alarms.capture(&trigger, displayed, source, export_channels);
+            }
+        }
+        let time = crate::value_parsing::unix_timestamp();
+        // The values a script emits at once form a line, numbered with the first of them
+        let mut line = None;
+        for action in scripting.update(value_history, raw_monitor.received(), time) {
+            match action {
+                ScriptAction::Emit { name, value } => {
+                    let line = *line.get_or_insert_with(|| lines.fetch_add(1, Ordering::Relaxed));
+                    let value = DataValue {
+                        name,
+                        value,
+                        timestamp: time,
+                        line,
+                    };
+                    // Err: the ui is behind, the value is lost like one of a source
+                    let _ = sender.try_send(vec![value]);
+                }
+                ScriptAction::Send(text) => match source {
+                    Some(source) => {
+                        source.command(Commands::SendMessage(text));
+                    }
+                    None => event_log.record(
+                        EventKind::SourceError,
+                        format!("script: not connected, can not send {:?}", text),
+                    ),
+                },
+                ScriptAction::Alert(message) => alerts.raise(time, message),
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // The sources flush while values arrive, this writes out the last ones of an idle source
+            sinks
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .flush_due(crate::value_parsing::unix_timestamp());
+            data_logger.update(sinks);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        for command in tray_mode.update(ctx, _frame) {
+            if command == TrayCommand::StopLogging {
+                data_logger.stop(sinks);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        alarms.write_snippets(
+            value_history,
+            crate::value_parsing::unix_timestamp(),
+            csv_format,
+            calibrations,
+            export_channels,
+        );
 
         // Examples of how to create different panels and windows.
         // Pick whichever suits you.
         // Tip: a good default choice is to just keep the `CentralPanel`.
         // For inspiration and more examples, go to https://emilk.github.io/egui
 
+        let mut session_action = None;
+        // No live source is opened while a recording is replayed
+        #[cfg(not(target_arch = "wasm32"))]
+        let replaying = replay.is_some();
+        #[cfg(target_arch = "wasm32")]
+        let replaying = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut open_requested =
+            source.is_none() && !replaying && port_selection.reopen_due(serial_port_name);
+        #[cfg(target_arch = "wasm32")]
+        let mut open_requested = false;
+
+        for action in shortcuts.update(ctx) {
+            match action {
+                Action::TogglePause => {
+                    *frozen = match frozen.take() {
+                        Some(_) => None,
+                        None => Some(("paused".to_string(), value_history.clone())),
+                    };
+                }
+                Action::Clear => value_history.clear(),
+                Action::Screenshot => {
+                    // The screenshot shows the plot as it is shown, frozen or live
+                    #[cfg(not(target_arch = "wasm32"))]
+                    plot_export.screenshot(
+                        frozen
+                            .as_ref()
+                            .map_or(&*value_history, |(_, frozen)| frozen),
+                        source
+                            .as_ref()
+                            .map_or("not connected", |source| source.name()),
+                        export_channels,
+                    );
+                }
+                Action::TogglePort => match source.take() {
+                    Some(mut open) => {
+                        open.stop();
+                        event_log.record(EventKind::Disconnected, open.name());
+                    }
+                    None => {
+                        open_requested = !replaying
+                            && (cfg!(target_arch = "wasm32") || serial_port_name.is_some());
+                    }
+                },
+                Action::ToggleStopwatch => stopwatch.toggle(event_log),
+                Action::StopwatchLap => stopwatch.lap(event_log),
+                Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown => {
+                    if let Some(fraction) = action.pan() {
+                        overview.viewport.pan(fraction);
+                    }
+                }
+            }
+        }
+
         #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             #[cfg(feature = "profiling")]
@@ -158,7 +767,16 @@ impl eframe::App for TemplateApp {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    session_action = session_menu.menu_ui(ui);
+                    ui.separator();
+                    if ui.button("Import / export…").clicked() {
+                        data_files.open();
+                        ui.close_menu();
+                    }
+                    ui.separator();
                     if ui.button("Quit").clicked() {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        tray_mode.quit();
                         _frame.close();
                     }
                 });
@@ -176,36 +794,262 @@ impl eframe::App for TemplateApp {
 
             *displayed_values = (scaled_value * 1000.0).round().clamp(100.0, 100000.0) as usize;
 
-            let mut scaled_value = (*max_fetch_count).clamp(1, 100000) as f64 / 1000.0;
-            ui.add(egui::Slider::new(&mut scaled_value, 0.0..=100.0).text("fetch count"));
+            let waiting = receiver.len();
+            if waiting > 0 {
+                let color = if receiver
+                    .capacity()
+                    .is_some_and(|capacity| waiting * 2 >= capacity)
+                {
+                    ui.visuals().warn_fg_color
+                } else {
+                    ui.visuals().weak_text_color()
+                };
+                ui.colored_label(
+                    color,
+                    format!(
+                        "{} reads waiting, ingesting for {:.1} ms per frame",
+                        waiting,
+                        update_cadence.budget() * 1000.0
+                    ),
+                )
+                .on_hover_text(
+                    "Received but not plotted yet, the time per frame grows until they shrink",
+                );
+            }
+            ui.horizontal(|ui| {
+                ui.label("when full");
+                egui::ComboBox::from_id_source("overflow_policy")
+                    .selected_text(overflow_policy.to_string())
+                    .show_ui(ui, |ui| {
+                        for policy in OverflowPolicy::ALL {
+                            ui.selectable_value(overflow_policy, policy, policy.to_string());
+                        }
+                    });
+            })
+            .response
+            .on_hover_text("What the source does with new values while the ui falls behind");
+            let dropped = backpressure.dropped();
+            if dropped > 0 {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} values dropped", dropped),
+                    );
+                    if ui.button("reset").clicked() {
+                        backpressure.reset_dropped();
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("x axis");
+                ui.radio_value(x_axis, XAxis::Samples, "samples");
+                ui.radio_value(x_axis, XAxis::Time, "time");
+                ui.radio_value(x_axis, XAxis::Frames, "frames").on_hover_text(
+                    "The line the values arrived in, keeps the channels of sparse lines aligned",
+                );
+                ui.radio_value(x_axis, XAxis::Window, "last seconds")
+                    .on_hover_text("The same span of time for every channel, whatever its sample rate");
+            });
+            if *x_axis == XAxis::Window {
+                ui.add(
+                    egui::Slider::new(time_window, 0.1..=3600.0)
+                        .logarithmic(true)
+                        .suffix(" s")
+                        .text("time window"),
+                );
+            }
+            ui.checkbox(&mut cursors.enabled, "Cursors")
+                .on_hover_text("Two cursors to drag over the plot, measuring the distance and the values between them");
+            ui.checkbox(&mut overview.enabled, "Overview")
+                .on_hover_text(
+                    "A strip with the whole history below the plot, drag in it to move the plot",
+                );
+            #[cfg(feature = "gpu_plot")]
+            ui.add_enabled(
+                _frame.wgpu_render_state().is_some(),
+                egui::Checkbox::new(gpu_rendering, "GPU rendering"),
+            )
+            .on_hover_text("Draw every sample through the gpu instead of a decimated line")
+            .on_disabled_hover_text("Requires the wgpu renderer");
+            YRange::ui(y_range, ui);
+            burst.settings_ui(ui);
+
+            ui.horizontal(|ui| {
+                memory_ui(value_history, ui);
+                if ui
+                    .button("Clear")
+                    .on_hover_text("Drop the samples of all channels, the port stays open. Right click a channel in the history limits to clear only that one")
+                    .clicked()
+                {
+                    value_history.clear();
+                }
+            });
+            let clear = ui
+                .collapsing("History limits", |ui| {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    recovery.ui(ui);
+                    limits_ui(history_limits, ui, value_history)
+                })
+                .body_returned
+                .flatten();
+            if let Some(channel) = clear {
+                value_history.clear_channel(&channel);
+            }
+
+            ui.checkbox(show_log, "Show log panel");
+
+            ui.collapsing("Update rate", |ui| update_cadence.ui(ui));
+
+            ui.collapsing("CSV export", |ui| csv_format.ui(ui));
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.collapsing("Data logger", |ui| {
+                data_logger.ui(ui, sinks, csv_format, export_channels, &channel_aliases.aliases)
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.collapsing("Background", |ui| tray_mode.ui(ui));
+
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            ui.collapsing("Metrics endpoint", |ui| metrics_endpoint.ui(ui, sinks));
+
+            ui.collapsing("Run summary", |ui| run_summaries.ui(ui));
+
+            ui.collapsing("Stopwatch", |ui| stopwatch.ui(ui, event_log));
+
+            parse_errors.badge(ui);
+
+            if ui.button("Latency measurement").clicked() {
+                latency.open();
+            }
+
+            if ui.button("Alarms").clicked() {
+                alarms.open();
+            }
+
+            if ui.button("Alert rules").clicked() {
+                alerts.open();
+            }
+
+            if ui.button("Time alignment").clicked() {
+                time_alignment.open();
+            }
+
+            if ui.button("Gamepad mapping").clicked() {
+                gamepad_mapping.open();
+            }
+
+            if ui.button("Channel aliases").clicked() {
+                channel_aliases.open();
+            }
+
+            if ui.button("Calibration").clicked() {
+                calibrations.open();
+            }
+
+            if ui.button("Calibration wizard").clicked() {
+                calibration_wizard.open();
+            }
+
+            if ui.button("Display filters").clicked() {
+                display_filters.open();
+            }
+
+            if ui.button("Baselines").clicked() {
+                baselines.open();
+            }
+
+            if ui.button("Series styles").clicked() {
+                series_styles.open();
+            }
+
+            if ui.button("Logic lanes").clicked() {
+                logic_lanes.open();
+            }
+
+            if ui.button("Script").clicked() {
+                scripting.open();
+            }
+
+            if ui.button("Keyboard shortcuts").clicked() {
+                shortcuts.open();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Save plot image").clicked() {
+                plot_export.open();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("OPC UA").clicked() {
+                opcua_client.open();
+            }
 
-            *max_fetch_count = (scaled_value * 1000.0).round().clamp(10f64, 100000f64) as usize;
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("WebSocket").clicked() {
+                websocket.open();
+            }
 
-            ui.checkbox(show_log, "Show tracing log");
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Audio input").clicked() {
+                audio_input.open();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("I2C/SPI bridge").clicked() {
+                bus_bridge.open();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button("Demo signals").clicked() {
+                demo_signals.open();
+            }
 
             ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                 ui.label("Serialport configuration");
-                create_serial_port_selection(ui, serial_port_name);
+                if replaying {
+                    ui.weak("Close the replay to connect");
+                }
+                ui.set_enabled(!replaying);
+                #[cfg(not(target_arch = "wasm32"))]
+                port_selection.ui(ui, serial_port_name);
                 create_baud_rate_selection(ui, baud_rate);
 
-                match (&open_port, serial_port_name) {
-                    (None, Some(serial_port_name)) => {
-                        if ui.button("open").clicked() {
-                            *open_port = open_serial_port(
-                                serial_port_name.clone(),
-                                baud_rate,
-                                sender,
-                                command.1.clone(),
-                            );
+                let previous_settings = parser_settings.clone();
+                create_format_selection(ui, parser_settings);
+                if parser_settings.format == DataFormat::Csv {
+                    ui.collapsing("Protocol", |ui| {
+                        create_protocol_settings(ui, parser_settings, &raw_monitor.recent(1024))
+                    });
+                }
+                if parser_settings.format == DataFormat::Slcan && ui.button("CAN decoding").clicked()
+                {
+                    can_decoding.open();
+                }
+                if *parser_settings != previous_settings {
+                    event_log.record(EventKind::ParserChanged, parser_settings.to_string());
+                }
+
+                match source {
+                    None => {
+                        // The browser asks for the port when connecting
+                        let can_open = cfg!(target_arch = "wasm32") || serial_port_name.is_some();
+                        if can_open {
+                            open_requested = ui.button("open").clicked();
                         }
                     }
-                    (Some(_), _) => {
+                    Some(open) => {
+                        ui.label(open.name());
+                        ui.label(format!("{:.0} bytes/s", raw_monitor.byte_rate()));
+                        #[cfg(not(target_arch = "wasm32"))]
+                        port_selection.signals_ui(ui, open.as_mut());
                         if ui.button("close").clicked() {
-                            close_serial_port(command);
-                            *open_port = None
+                            open.stop();
+                            event_log.record(EventKind::Disconnected, open.name());
+                            *source = None
                         }
                     }
-                    (None, None) => {}
                 }
             });
 
@@ -229,21 +1073,171 @@ impl eframe::App for TemplateApp {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // The central panel the region left after adding TopPanel's and SidePanel's
-            value_history.render_plot(ui);
+            if burst.enabled {
+                burst.render_plot(ui, *y_range);
+            } else {
+                #[cfg(not(target_arch = "wasm32"))]
+                if replay.as_mut().is_some_and(|replay| replay.ui(ui)) {
+                    *replay = None;
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                let replayed = replay.as_ref().map(Replay::shown);
+                #[cfg(target_arch = "wasm32")]
+                let replayed = None;
+                let mut resume = false;
+                let displayed = match (replayed, frozen.as_ref()) {
+                    (Some(replayed), _) => replayed,
+                    (None, Some((reason, frozen))) => {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("Frozen: {}", reason),
+                            );
+                            resume = ui.button("Resume").clicked();
+                        });
+                        frozen
+                    }
+                    (None, None) => &*value_history,
+                };
+                let flashing = alerts.active_channels();
+                if !flashing.is_empty() {
+                    // Keeps the alerting channels blinking while no new samples arrive
+                    ui.ctx().request_repaint_after(Duration::from_millis(250));
+                }
+                let mut channels: Vec<&str> = displayed.channel_names().collect();
+                channels.sort_unstable();
+                let response = plot_settings.render_plot(
+                    displayed,
+                    ui,
+                    *y_range,
+                    *x_axis,
+                    &flashing,
+                    &mut overview.viewport,
+                    cursors,
+                    legend,
+                );
+                stopwatch.paint(ui, response.rect);
+                if let Some(shown) = overview.viewport.shown {
+                    logic_lanes.paint(ui, displayed, plot_settings, *x_axis, response.rect, shown);
+                }
+                response.context_menu(|ui| derived_series.menu_ui(ui, &channels));
+                if overview.enabled {
+                    let max_points = ui.available_width().max(2.0) as usize;
+                    overview.ui(
+                        ui,
+                        plot_settings.overview_series(displayed, *x_axis, max_points),
+                    );
+                }
+                if resume {
+                    *frozen = None;
+                }
+            }
 
             egui::warn_if_debug_build(ui);
         });
 
+        parse_errors.window(ctx);
+        settings_check.window(ctx);
+        latency.window(ctx, value_history);
+        alarms.window(ctx, value_history);
+        #[cfg(not(target_arch = "wasm32"))]
+        if port_selection.failure_window(ctx, serial_port_name) {
+            open_requested = true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(port) = port_selection.arrivals_ui(ctx, source.is_some()) {
+            *serial_port_name = Some(port);
+            open_requested = true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(snapshot) = recovery.window(ctx) {
+            snapshot.restore(value_history);
+        }
+        match run_summaries.window(ctx) {
+            Some(RunAction::Discard(start)) => value_history.discard_after(start),
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(RunAction::Export) => {
+                run_summaries.export(value_history, csv_format, calibrations, export_channels)
+            }
+            None => {}
+        }
+        let mut channels: Vec<&str> = value_history.channel_names().collect();
+        channels.sort_unstable();
+        alerts.window(ctx, &channels);
+        channel_aliases.window(ctx, &channels);
+        time_alignment.window(ctx, &channels);
+        gamepad_mapping.window(ctx);
+        calibrations.window(ctx, &channels);
+        calibration_wizard.window(ctx, &channels, value_history, calibrations);
+        display_filters.window(ctx, &channels);
+        baselines.window(ctx, &channels, value_history);
+        series_styles.window(ctx, &channels);
+        logic_lanes.window(ctx, value_history);
+        scripting.window(ctx);
+        can_decoding.window(ctx, &mut parser_settings.can);
+        #[cfg(not(target_arch = "wasm32"))]
+        plot_export.window(
+            ctx,
+            value_history,
+            source
+                .as_ref()
+                .map_or("not connected", |source| source.name()),
+            export_channels,
+        );
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((path, imported)) =
+            data_files.window(ctx, value_history, calibrations, export_channels)
+        {
+            *replay = Replay::new(
+                format!("imported {}", path),
+                ValueHistory::imported(imported),
+            );
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        export_channels.window(ctx, &channels);
+        shortcuts.window(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        let opcua_requested = opcua_client.window(ctx, event_log);
+        #[cfg(not(target_arch = "wasm32"))]
+        let websocket_requested = websocket.window(ctx, event_log, sinks);
+        #[cfg(not(target_arch = "wasm32"))]
+        let audio_requested = audio_input.window(ctx, event_log);
+        #[cfg(not(target_arch = "wasm32"))]
+        let bridge_requested =
+            bus_bridge.window(ctx, serial_port_name.as_deref(), source.is_some());
+        #[cfg(not(target_arch = "wasm32"))]
+        let demo_requested = demo_signals.window(ctx, source.is_some());
+        session_action = session_menu.window(ctx).or(session_action);
+
         if *show_log {
             #[cfg(feature = "profiling")]
             puffin::profile_scope!("Display Log");
 
             egui::TopBottomPanel::bottom("log").show(ctx, |ui| {
-                let widget = tracing_egui::Widget {
-                    filter: true,
-                    ..Default::default()
-                };
-                ui.add(widget);
+                ui.horizontal(|ui| {
+                    ui.selectable_value(bottom_tab, BottomTab::Log, "Tracing log");
+                    ui.selectable_value(bottom_tab, BottomTab::RawMonitor, "Raw monitor");
+                    ui.selectable_value(bottom_tab, BottomTab::Events, "Events");
+                    ui.selectable_value(bottom_tab, BottomTab::Alerts, "Alerts");
+                    ui.selectable_value(bottom_tab, BottomTab::Histogram, "Histogram");
+                });
+                ui.separator();
+
+                match bottom_tab {
+                    BottomTab::Log => {
+                        let widget = tracing_egui::Widget {
+                            filter: true,
+                            ..Default::default()
+                        };
+                        ui.add(widget);
+                    }
+                    BottomTab::RawMonitor => raw_monitor.ui(ui),
+                    BottomTab::Events => event_log.ui(ui, csv_format),
+                    BottomTab::Alerts => alerts.ui(ui),
+                    BottomTab::Histogram => {
+                        histogram.ui(ui, value_history, plot_settings, &channels)
+                    }
+                }
             });
         }
 
@@ -256,54 +1250,235 @@ impl eframe::App for TemplateApp {
             });
         }
 
-        ctx.request_repaint();
-        //ctx.request_repaint_after(Duration::from_secs_f64(0.05));
+        update_cadence.request_repaint(ctx);
+
+        if open_requested && !replaying {
+            self.connect();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if opcua_requested && !replaying {
+            let senders = self.source_senders();
+            self.opcua_client.connect(senders, &mut self.event_log);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if websocket_requested && !replaying {
+            let senders = self.source_senders();
+            let parser = self.parser_settings.create_parser();
+            self.websocket.connect(parser, senders, &mut self.event_log);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if audio_requested && !replaying {
+            let senders = self.source_senders();
+            self.audio_input.connect(senders, &mut self.event_log);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if bridge_requested {
+            self.poll_bus_bridge();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if demo_requested && !replaying {
+            self.start_demo_signals();
+        }
+        if let Some(action) = session_action {
+            self.apply_session_action(action);
+        }
     }
 }
 
-fn close_serial_port(command: &mut (Sender<Commands>, Receiver<Commands>)) {
-    let _ = command.0.send(Commands::Stop); // Err: channel is already disconnected, so there is nothing to close.
+impl TemplateApp {
+    /// Applies the command line arguments on top of the persisted settings.
+    pub fn apply_args(&mut self, args: &Args) {
+        if let Some(port) = &args.port {
+            self.serial_port_name = Some(port.clone());
+        }
+        if let Some(baud_rate) = args.baud {
+            self.baud_rate = baud_rate;
+        }
+        if let Some(format) = args.format {
+            self.parser_settings.format = format;
+        }
+        if let Some(plugin) = &args.plugin {
+            self.parser_settings.plugin = plugin.clone();
+        }
+        if args.connect {
+            self.connect();
+        }
+    }
+
+    /// The channels a new source hands its values and conditions over with.
+    fn source_senders(&self) -> SourceSenders {
+        SourceSenders {
+            data: self.sender.clone(),
+            queued: self.receiver.clone(),
+            backpressure: self.backpressure.clone(),
+            raw: self.raw_sender.clone(),
+            parse_errors: self.parse_error_channel.0.clone(),
+            events: self.source_events.0.clone(),
+            lines: self.lines.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            sinks: self.sinks.clone(),
+        }
+    }
+
+    /// Opens the selected serial port with the configured baud rate and parser.
+    fn connect(&mut self) {
+        let senders = self.source_senders();
+        let parser = self.parser_settings.create_parser();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(serial_port_name) = self.serial_port_name.clone() {
+            let opened = open_serial_port(
+                &self.port_selection,
+                &serial_port_name,
+                &self.baud_rate,
+                parser,
+                self.parser_settings.format.has_lines(),
+                senders,
+            );
+            self.source = match opened {
+                Ok(source) => Some(Box::new(source) as Box<dyn DataSource>),
+                Err(err) => {
+                    tracing::error!("{}", err);
+                    // Reopening a lost port is retried until it succeeds
+                    if !self.port_selection.waiting() {
+                        self.event_log.record(EventKind::SourceError, err.clone());
+                        self.port_selection.failed(err);
+                    }
+                    None
+                }
+            };
+            if let Some(source) = &mut self.source {
+                self.port_selection.opened(&serial_port_name);
+                self.port_selection.apply_signals(source.as_mut());
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.source = Some(Box::new(WebSerialSource::start(
+                self.baud_rate,
+                parser,
+                senders,
+            )));
+        }
+
+        if let Some(source) = &mut self.source {
+            if self.parser_settings.format == DataFormat::Slcan {
+                source.command(Commands::SendMessage(
+                    self.parser_settings.can.open_commands(),
+                ));
+            }
+            let message = format!(
+                "{} at {} baud, {}",
+                source.name(),
+                self.baud_rate,
+                self.parser_settings
+            );
+            self.event_log.record(EventKind::Connected, message);
+        }
+    }
+
+    /// Polls the sensor registers with the Bus Pirate on the selected serial port.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll_bus_bridge(&mut self) {
+        let Some(serial_port_name) = self.serial_port_name.clone() else {
+            return;
+        };
+        let settings = self.bus_bridge.settings.clone();
+        let channels: Vec<String> = settings
+            .registers
+            .iter()
+            .map(|register| register.channel_name())
+            .collect();
+        match BusPirateSource::open(&serial_port_name, settings, self.source_senders()) {
+            Ok(source) => {
+                let message = format!("{}: {}", source.name(), channels.join(", "));
+                self.event_log.record(EventKind::Connected, message);
+                self.source = Some(Box::new(source));
+            }
+            Err(err) => tracing::error!("Failed to open port: {}", err),
+        }
+    }
+
+    /// Generates the demo signals in place of the serial port.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_demo_signals(&mut self) {
+        let settings = self.demo_signals.settings.clone();
+        let channels: Vec<&str> = settings.signals.iter().map(|x| x.name.as_str()).collect();
+        let message = format!("Demo signals: {}", channels.join(", "));
+        self.event_log.record(EventKind::Connected, message);
+        self.source = Some(Box::new(DemoSource::start(settings, self.source_senders())));
+    }
+
+    fn apply_session_action(&mut self, action: SessionAction) {
+        match action {
+            SessionAction::Save(name) => match session::save(&name, self) {
+                Ok(path) => info!("Saved session {} to {}", name, path.display()),
+                Err(err) => tracing::error!("Failed to save session {}: {}", name, err),
+            },
+            SessionAction::Load(name) => match session::load::<TemplateApp>(&name) {
+                Ok(session) => self.load_session(name, session),
+                Err(err) => tracing::error!("Failed to load session {}: {}", name, err),
+            },
+        }
+    }
+
+    /// Replaces the settings with the ones of `session`, after stopping everything that still
+    /// delivers or writes values, the loaded session starts disconnected.
+    fn load_session(&mut self, name: String, session: TemplateApp) {
+        if let Some(source) = &self.source {
+            self.event_log
+                .record(EventKind::Disconnected, source.name());
+        }
+        self.stop_sources();
+        // Closes the log file and the endpoints, the session starts out without sinks
+        #[cfg(not(target_arch = "wasm32"))]
+        self.sinks
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clear();
+        // The log covers the whole run of the application, not a single session, and the
+        // gamepads stay connected
+        let event_log = std::mem::take(&mut self.event_log);
+        let gilrs = self.gilrs.take();
+        *self = session;
+        self.event_log = event_log;
+        self.gilrs = gilrs;
+        self.check_settings();
+        self.event_log.record(EventKind::SessionLoaded, name);
+    }
+
+    /// Stops the source and the clients of the other protocols.
+    fn stop_sources(&mut self) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.opcua_client.stop();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.websocket.stop();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.audio_input.stop();
+    }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn open_serial_port(
-    serial_port_name: String,
+    port_selection: &PortSelection,
+    serial_port_name: &str,
     baud_rate: &u32,
-    sender: &mut Sender<DataValue>,
-    command: Receiver<Commands>,
-) -> Option<(String, u32)> {
-    let port = match serialport::new(
-        std::borrow::Cow::Owned(serial_port_name.clone()),
-        *baud_rate,
-    )
-    .open()
-    {
-        Ok(port) => Some(port),
-        Err(err) => {
-            tracing::error!("Failed to open port: {}", err);
-            None
-        }
-    };
-
-    port.map(|x| SerialSource::start(x, sender.clone(), command))
-        .map(|_| (serial_port_name.clone(), *baud_rate))
-}
-
-fn create_serial_port_selection(
-    ui: &mut Ui,
-    serial_port_name: &mut Option<String>,
-) -> InnerResponse<Option<()>> {
-    let ports = available_ports().unwrap_or(Vec::new());
-    egui::ComboBox::from_label("Serial port")
-        .selected_text(format!("{:?}", serial_port_name))
-        .show_ui(ui, |ui| {
-            for port in ports {
-                ui.selectable_value(
-                    serial_port_name,
-                    Some(port.port_name.clone()),
-                    port.port_name,
-                );
-            }
-        })
+    parser: Box<dyn ValueParser>,
+    text: bool,
+    senders: SourceSenders,
+) -> Result<SerialSource, String> {
+    let port = port_selection.open(serial_port_name, *baud_rate)?;
+    Ok(SerialSource::start(
+        port,
+        parser,
+        port_selection.read_timing(),
+        text,
+        senders,
+    ))
 }
 
 fn create_baud_rate_selection(ui: &mut Ui, baud_rate: &mut u32) -> InnerResponse<Option<()>> {
@@ -316,4 +1491,296 @@ fn create_baud_rate_selection(ui: &mut Ui, baud_rate: &mut u32) -> InnerResponse
         })
 }
 
-mod value_history;
+/// The delimiters of the Csv format, with the last received lines parsed by them.
+fn create_protocol_settings(ui: &mut Ui, settings: &mut ParserSettings, raw: &[u8]) {
+    let delimiters = &mut settings.delimiters;
+    delimiter_selection(
+        ui,
+        "Line end",
+        &mut delimiters.line_end,
+        &Delimiters::LINE_ENDS,
+    );
+    delimiter_selection(
+        ui,
+        "Field separator",
+        &mut delimiters.field,
+        &Delimiters::FIELDS,
+    );
+    delimiter_selection(
+        ui,
+        "Name separator",
+        &mut delimiters.key_value,
+        &Delimiters::KEY_VALUES,
+    );
+    delimiter_selection(
+        ui,
+        "Decimal separator",
+        &mut delimiters.decimal,
+        &Delimiters::DECIMALS,
+    );
+    if delimiters.decimal == delimiters.field {
+        // Devices printing decimal commas separate their fields with semicolons
+        delimiters.field = b';';
+    }
+
+    ui.label("Preview of the last received lines");
+    let mut parser = settings.create_parser();
+    let lines: Vec<ParsingResult> = raw
+        .iter()
+        .map(|byte| parser.parse(*byte))
+        .filter(|result| {
+            !matches!(result, ParsingResult::Pending) && *result != ParsingResult::Ok(Vec::new())
+        })
+        // The first line is likely cut off at the start of the buffer
+        .skip(1)
+        .collect();
+    if lines.is_empty() {
+        ui.weak("Nothing received yet");
+    }
+    for line in lines.iter().rev().take(3).rev() {
+        match line {
+            ParsingResult::Ok(values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| format!("{} = {}", value.name, value.value))
+                    .collect();
+                ui.monospace(values.join(", "));
+            }
+            ParsingResult::Err(failure) => {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{}: invalid value {:?}", failure.channel, failure.value),
+                );
+            }
+            ParsingResult::Pending => {}
+        }
+    }
+}
+
+/// Selects one of the `presets` or any other ASCII character as a delimiter.
+fn delimiter_selection(ui: &mut Ui, label: &str, delimiter: &mut u8, presets: &[(u8, &str)]) {
+    ui.horizontal(|ui| {
+        let preset = presets.iter().find(|(byte, _)| byte == delimiter);
+        egui::ComboBox::from_id_source(label)
+            .selected_text(preset.map_or("custom", |(_, name)| name))
+            .show_ui(ui, |ui| {
+                for (byte, name) in presets {
+                    ui.selectable_value(delimiter, *byte, *name);
+                }
+            });
+        // Typing a character replaces the delimiter, invisible ones are shown escaped
+        let mut custom = std::ascii::escape_default(*delimiter).to_string();
+        let response = ui.add(egui::TextEdit::singleline(&mut custom).desired_width(24.0));
+        if response.changed() {
+            if let Some(byte) = custom.bytes().last().filter(u8::is_ascii) {
+                *delimiter = byte;
+            }
+        }
+        ui.label(label);
+    });
+}
+
+fn create_format_selection(ui: &mut Ui, settings: &mut ParserSettings) {
+    egui::ComboBox::from_label("Format")
+        .selected_text(format!("{:?}", settings.format))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut settings.format, DataFormat::Csv, "Csv");
+            ui.selectable_value(&mut settings.format, DataFormat::Json, "Json");
+            ui.selectable_value(&mut settings.format, DataFormat::Binary, "Binary");
+            ui.selectable_value(
+                &mut settings.format,
+                DataFormat::Arduino,
+                "Arduino-compatible",
+            )
+            .on_hover_text(
+                "Values separated by commas, spaces or tabs, like the plotter of the Arduino IDE",
+            );
+            ui.selectable_value(&mut settings.format, DataFormat::Teleplot, "Teleplot")
+                .on_hover_text("Lines like `>temp:25.4`, other lines are skipped");
+            ui.selectable_value(&mut settings.format, DataFormat::Slcan, "SLCAN (CAN)")
+                .on_hover_text(
+                    "CAN frames of a SLCAN adapter like `t1852E803`, decoded with a DBC file or as CANopen PDOs",
+                );
+            ui.selectable_value(&mut settings.format, DataFormat::Nmea, "NMEA 0183 (GPS)")
+                .on_hover_text("The GGA, RMC and VTG sentences of GPS modules, e.g. speed_knots and altitude");
+            ui.selectable_value(&mut settings.format, DataFormat::Plugin, "Plugin")
+                .on_hover_text("A parser compiled to WebAssembly, for protocols of your own");
+        })
+        .response
+        .on_hover_text("Takes effect when the port is opened");
+
+    if settings.format.has_lines() {
+        let mut check = settings.line_check();
+        egui::ComboBox::from_label("Checksum")
+            .selected_text(check.to_string())
+            .show_ui(ui, |ui| {
+                for option in LineCheck::ALL {
+                    ui.selectable_value(&mut check, option, option.to_string());
+                }
+            })
+            .response
+            .on_hover_text("Lines end with `*` and the checksum of the bytes before it, lines with a wrong checksum are discarded");
+        if check != settings.line_check() {
+            settings.line_checks.insert(settings.format, check);
+        }
+    }
+
+    if settings.format == DataFormat::Plugin {
+        ui.horizontal(|ui| {
+            ui.label("Module");
+            ui.add(egui::TextEdit::singleline(&mut settings.plugin).hint_text("parser.wasm"))
+                .on_hover_text(
+                    "The path of the WebAssembly module, it is loaded when the port is opened",
+                );
+        });
+    }
+
+    if settings.format == DataFormat::Binary {
+        let binary = &mut settings.binary;
+        ui.add(
+            egui::DragValue::new(&mut binary.channels)
+                .clamp_range(1..=64)
+                .prefix("channels: "),
+        );
+        egui::ComboBox::from_label("Number type")
+            .selected_text(format!("{:?}", binary.number_type))
+            .show_ui(ui, |ui| {
+                for number_type in NumberType::ALL {
+                    ui.selectable_value(
+                        &mut binary.number_type,
+                        number_type,
+                        format!("{:?}", number_type),
+                    );
+                }
+            });
+        ui.checkbox(&mut binary.little_endian, "little endian");
+    }
+
+    if settings.format == DataFormat::Slcan {
+        egui::ComboBox::from_label("CAN bit rate")
+            .selected_text(format!("{} kbit/s", settings.can.bitrate))
+            .show_ui(ui, |ui| {
+                for bitrate in BITRATES {
+                    ui.selectable_value(
+                        &mut settings.can.bitrate,
+                        bitrate,
+                        format!("{} kbit/s", bitrate),
+                    );
+                }
+            });
+    }
+}
+
+mod alarms;
+mod alerts;
+#[cfg(not(target_arch = "wasm32"))]
+mod audio_input;
+mod baselines;
+mod burst;
+#[cfg(not(target_arch = "wasm32"))]
+mod bus_bridge;
+mod calibration_wizard;
+mod can_decoding;
+mod channel_aliases;
+mod condition;
+mod cursors;
+#[cfg(not(target_arch = "wasm32"))]
+mod data_files;
+#[cfg(not(target_arch = "wasm32"))]
+mod data_logger;
+#[cfg(not(target_arch = "wasm32"))]
+mod demo_signals;
+mod derived;
+mod event_log;
+#[cfg(not(target_arch = "wasm32"))]
+mod export_channels;
+mod gamepad_mapping;
+#[cfg(feature = "gpu_plot")]
+mod gpu_plot;
+mod histogram;
+mod latency;
+mod legend;
+mod logic_lanes;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+mod metrics_endpoint;
+#[cfg(not(target_arch = "wasm32"))]
+mod opcua_client;
+mod overview;
+mod parse_errors;
+#[cfg(not(target_arch = "wasm32"))]
+mod plot_export;
+#[cfg(not(target_arch = "wasm32"))]
+mod port_selection;
+mod raw_monitor;
+#[cfg(not(target_arch = "wasm32"))]
+mod recovery;
+#[cfg(not(target_arch = "wasm32"))]
+mod replay;
+mod run_summary;
+mod scripting;
+mod series_styles;
+mod session;
+mod settings_check;
+mod shortcuts;
+mod stopwatch;
+mod time_alignment;
+#[cfg(not(target_arch = "wasm32"))]
+mod tray;
+mod update_cadence;
+pub(crate) mod value_history;
+#[cfg(not(target_arch = "wasm32"))]
+mod websocket;
+mod widget;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    struct RunningSource(Arc<AtomicBool>);
+
+    impl DataSource for RunningSource {
+        fn name(&self) -> &str {
+            "COM1"
+        }
+
+        fn is_running(&self) -> bool {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        fn stop(&mut self) {
+            self.0.store(false, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn should_stop_the_source_and_the_sinks_when_loading_a_session() {
+        let running = Arc::new(AtomicBool::new(true));
+        let mut app = TemplateApp {
+            source: Some(Box::new(RunningSource(running.clone()))),
+            ..TemplateApp::default()
+        };
+        let sinks = app.sinks.clone();
+        let record = crate::sinks::RecordSink::new(
+            "log.csv".to_string(),
+            Box::new(std::io::sink()),
+            crate::cli::OutputFormat::Csv,
+            Default::default(),
+            Default::default(),
+        );
+        sinks.lock().unwrap().add(Box::new(record.unwrap()));
+
+        app.load_session("bench".to_string(), TemplateApp::default());
+
+        assert!(
+            !running.load(Ordering::Relaxed),
+            "the source should be stopped"
+        );
+        assert!(app.source.is_none());
+        assert!(
+            sinks.lock().unwrap().is_empty(),
+            "the sinks should be closed"
+        );
+    }
+}
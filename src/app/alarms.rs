@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use egui::Ui;
+
+use super::{
+    condition::{condition_ui, crossings, Condition, Edge},
+    value_history::{Sample, ValueHistory},
+};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Alarm {
+    pub condition: Condition,
+    /// Keep showing the plot as it was when the alarm fired
+    pub freeze: bool,
+    /// Save an image of the plot and the samples around the alarm
+    pub capture: bool,
+}
+
+impl Default for Alarm {
+    fn default() -> Self {
+        Self {
+            condition: Condition {
+                channel: String::new(),
+                threshold: 0.0,
+                edge: Edge::Rising,
+            },
+            freeze: true,
+            capture: false,
+        }
+    }
+}
+
+/// An alarm whose condition was met by a new sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiredAlarm {
+    /// The time of the sample that met the condition
+    pub time: f64,
+    pub alarm: Alarm,
+}
+
+/// Samples around an alarm that are saved once the time after the alarm has passed.
+#[cfg(not(target_arch = "wasm32"))]
+struct PendingSnippet {
+    time: f64,
+    path: std::path::PathBuf,
+}
+
+/// Watches the channels for threshold crossings and preserves what happened around them.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Alarms {
+    alarms: Vec<Alarm>,
+    /// Seconds of samples before and after the alarm in a captured data snippet
+    snippet_before: f64,
+    snippet_after: f64,
+    capture_directory: String,
+
+    show: bool,
+    /// The last processed sample of every channel involved, used to detect edges.
+    #[serde(skip)]
+    previous: HashMap<String, Sample>,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pending_snippets: Vec<PendingSnippet>,
+}
+
+impl Default for Alarms {
+    fn default() -> Self {
+        Self {
+            alarms: Vec::new(),
+            snippet_before: 5.0,
+            snippet_after: 5.0,
+            capture_directory: "captures".to_string(),
+            show: false,
+            previous: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_snippets: Vec::new(),
+        }
+    }
+}
+
+impl Alarms {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Checks the samples received since the last call against all alarms.
+    pub fn update(&mut self, history: &ValueHistory) -> Vec<FiredAlarm> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!("alarms");
+
+        let mut fired = Vec::new();
+        for alarm in &self.alarms {
+            for time in crossings(history, &alarm.condition, &mut self.previous) {
+                fired.push(FiredAlarm {
+                    time,
+                    alarm: alarm.clone(),
+                });
+            }
+        }
+        fired
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, history: &ValueHistory) {
+        let mut show = self.show;
+        egui::Window::new("Alarms")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, history));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, history: &ValueHistory) {
+        let mut channels: Vec<&str> = history.channel_names().collect();
+        channels.sort_unstable();
+
+        let mut remove = None;
+        for (index, alarm) in self.alarms.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                condition_ui(
+                    ui,
+                    &format!("alarm{}", index),
+                    &mut alarm.condition,
+                    &channels,
+                );
+                ui.checkbox(&mut alarm.freeze, "freeze plot");
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.checkbox(&mut alarm.capture, "save capture");
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            self.alarms.remove(index);
+        }
+        if ui.button("add alarm").clicked() {
+            let mut alarm = Alarm::default();
+            alarm.condition.channel = channels.first().copied().unwrap_or_default().to_string();
+            self.alarms.push(alarm);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            ui.heading("Captures");
+            ui.horizontal(|ui| {
+                ui.label("Directory");
+                ui.text_edit_singleline(&mut self.capture_directory);
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.snippet_before)
+                        .clamp_range(0.0..=600.0)
+                        .suffix(" s before"),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.snippet_after)
+                        .clamp_range(0.0..=600.0)
+                        .suffix(" s after"),
+                );
+            })
+            .response
+            .on_hover_text(
+                "The samples around the alarm or alert saved next to the image of the plot",
+            );
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::export_channels::ExportChannels;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{calibration::Calibrations, csv_format::CsvFormat};
+
+/// What a capture is saved for, an alarm or an alert.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Trigger<'a> {
+    /// Starts the file names, e.g. `alarm`
+    pub kind: &'a str,
+    pub time: f64,
+    pub channel: &'a str,
+    /// The condition that was met, shown in the caption of the image
+    pub description: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FiredAlarm {
+    pub fn trigger(&self) -> Trigger<'_> {
+        Trigger {
+            kind: "alarm",
+            time: self.time,
+            channel: &self.alarm.condition.channel,
+            description: self.alarm.condition.to_string(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Alarms {
+    /// Saves an image of `displayed` right away and the data snippet once the time after the
+    /// trigger has passed.
+    pub fn capture(
+        &mut self,
+        trigger: &Trigger<'_>,
+        displayed: &ValueHistory,
+        source: &str,
+        channels: &ExportChannels,
+    ) {
+        use super::plot_export::{export, ImageFormat, ImageStyle};
+
+        let directory = std::path::Path::new(&self.capture_directory);
+        if let Err(err) = std::fs::create_dir_all(directory) {
+            tracing::error!("Failed to create the capture directory: {}", err);
+            return;
+        }
+        // Channel names may contain characters that are not allowed in file names
+        let channel: String = trigger
+            .channel
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let name = format!(
+            "{}_{}_{}",
+            trigger.kind,
+            super::event_log::file_stamp(trigger.time),
+            channel
+        );
+
+        let image = directory.join(format!("{}.png", name));
+        let caption = format!(
+            "{}, {}, {}",
+            source,
+            trigger.description,
+            super::event_log::format_utc(trigger.time)
+        );
+        match export(
+            &channels.selected(displayed),
+            &caption,
+            ImageFormat::Png,
+            ImageStyle::Color,
+            &image,
+            (1280, 720),
+        ) {
+            Ok(()) => tracing::info!("Saved {} image to {}", trigger.kind, image.display()),
+            Err(err) => tracing::error!("Failed to save {} image: {}", trigger.kind, err),
+        }
+
+        self.pending_snippets.push(PendingSnippet {
+            time: trigger.time,
+            path: directory.join(format!("{}.csv", name)),
+        });
+    }
+
+    /// Writes the data snippets whose time after the trigger has passed.
+    pub fn write_snippets(
+        &mut self,
+        history: &ValueHistory,
+        now: f64,
+        format: &CsvFormat,
+        calibrations: &Calibrations,
+        channels: &ExportChannels,
+    ) {
+        let (before, after) = (self.snippet_before, self.snippet_after);
+        self.pending_snippets.retain(|snippet| {
+            if now < snippet.time + after {
+                return true;
+            }
+            let range = (snippet.time - before)..=(snippet.time + after);
+            match write_snippet(
+                history,
+                range,
+                &snippet.path,
+                format,
+                calibrations,
+                channels,
+            ) {
+                Ok(()) => tracing::info!("Saved capture data to {}", snippet.path.display()),
+                Err(err) => tracing::error!("Failed to save capture data: {}", err),
+            }
+            false
+        });
+    }
+}
+
+/// Writes all samples within `range` as `timestamp,channel,value,time_offset,unit` rows ordered
+/// by time.
+///
+/// The timestamps include the time offset of their channel, which is recorded next to them.
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn write_snippet(
+    history: &ValueHistory,
+    range: std::ops::RangeInclusive<f64>,
+    path: &std::path::Path,
+    format: &CsvFormat,
+    calibrations: &Calibrations,
+    channels: &ExportChannels,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut rows: Vec<(&str, Sample)> = history
+        .channel_names()
+        .filter(|name| channels.includes(name))
+        .filter_map(|name| Some((name, history.samples(name)?)))
+        .flat_map(|(name, samples)| {
+            let offset = history.time_offset(name);
+            samples.iter().map(move |sample| {
+                let time = sample.time + offset;
+                (name, Sample { time, ..sample })
+            })
+        })
+        .filter(|(_, sample)| range.contains(&sample.time))
+        .collect();
+    rows.sort_by(|a, b| a.1.time.total_cmp(&b.1.time));
+
+    let mut output = std::io::BufWriter::new(std::fs::File::create(path)?);
+    format.write_row(
+        &mut output,
+        &["timestamp", "channel", "value", "time_offset", "unit"],
+    )?;
+    for (name, sample) in rows {
+        format.write_row(
+            &mut output,
+            &[
+                &format.timestamp(sample.time),
+                name,
+                &format.number(calibrations.export_value(name, sample.value)),
+                &format.number(history.time_offset(name)),
+                calibrations.export_unit(name).unwrap_or_default(),
+            ],
+        )?;
+    }
+    output.flush()
+}
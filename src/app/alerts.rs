@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use egui::Ui;
+
+use super::{
+    event_log::format_utc,
+    value_history::{Sample, ValueHistory},
+};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::Below => value < threshold,
+        }
+    }
+}
+
+/// A channel staying above or below a threshold for some time, e.g. `temp > 80 for 2 s`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Rule {
+    pub channel: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Seconds the comparison has to hold before the alert fires
+    pub duration: f64,
+    pub sound: bool,
+    /// Save an image of the plot and the samples around the alert
+    pub capture: bool,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            comparison: Comparison::Above,
+            threshold: 0.0,
+            duration: 0.0,
+            sound: false,
+            capture: false,
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self.comparison {
+            Comparison::Above => ">",
+            Comparison::Below => "<",
+        };
+        write!(
+            f,
+            "{} {} {} for {} s",
+            self.channel, operator, self.threshold, self.duration
+        )
+    }
+}
+
+/// The progress of a rule through the samples of its channel.
+#[derive(Debug, Default, Clone, Copy)]
+struct RuleState {
+    /// The time of the last processed sample
+    last: Option<f64>,
+    /// The time since which the comparison holds
+    since: Option<f64>,
+    /// Whether the alert fired and the comparison still holds
+    active: bool,
+}
+
+impl RuleState {
+    /// Returns whether the alert fires with this sample.
+    fn step(&mut self, rule: &Rule, sample: Sample) -> bool {
+        self.last = Some(sample.time);
+        if !rule.comparison.holds(sample.value, rule.threshold) {
+            self.since = None;
+            self.active = false;
+            return false;
+        }
+
+        let since = *self.since.get_or_insert(sample.time);
+        if !self.active && sample.time - since >= rule.duration {
+            self.active = true;
+            return true;
+        }
+        false
+    }
+}
+
+/// A rule whose comparison held for its duration with a new sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiredAlert {
+    /// The time of the sample the alert fired with
+    pub time: f64,
+    pub rule: Rule,
+}
+
+/// A fired alert as listed in the alerts panel.
+pub struct Alert {
+    pub time: f64,
+    pub message: String,
+}
+
+/// Watches the channels for values outside their limits and makes sure they are noticed.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Alerts {
+    rules: Vec<Rule>,
+
+    show: bool,
+    /// The state of every rule, by its description so edited rules start over
+    #[serde(skip)]
+    states: HashMap<String, RuleState>,
+    #[serde(skip)]
+    alerts: Vec<Alert>,
+}
+
+impl Alerts {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Checks the samples received since the last call against all rules.
+    pub fn update(&mut self, history: &ValueHistory) -> Vec<FiredAlert> {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!("alerts");
+
+        let mut fired = Vec::new();
+        for rule in &self.rules {
+            let Some(samples) = history.samples(&rule.channel) else {
+                continue;
+            };
+            let state = self.states.entry(rule.to_string()).or_default();
+            // A new rule starts with the latest sample instead of the whole history
+            let new_samples = match state.last {
+                Some(last) => samples.iter().rev().take_while(|x| x.time > last).count(),
+                None => samples.len().min(1),
+            };
+            for sample in samples.iter().skip(samples.len() - new_samples) {
+                if state.step(rule, sample) {
+                    let message = rule.to_string();
+                    tracing::warn!("Alert: {}", message);
+                    if rule.sound {
+                        beep();
+                    }
+                    self.alerts.push(Alert {
+                        time: sample.time,
+                        message,
+                    });
+                    fired.push(FiredAlert {
+                        time: sample.time,
+                        rule: rule.clone(),
+                    });
+                }
+            }
+        }
+        fired
+    }
+
+    /// Lists an alert that was not fired by a rule, e.g. by a script.
+    pub fn raise(&mut self, time: f64, message: String) {
+        tracing::warn!("Alert: {}", message);
+        self.alerts.push(Alert { time, message });
+    }
+
+    /// The channels of the alerts whose comparison still holds.
+    pub fn active_channels(&self) -> Vec<&str> {
+        self.rules
+            .iter()
+            .filter(|rule| {
+                let state = self.states.get(&rule.to_string());
+                state.is_some_and(|state| state.active)
+            })
+            .map(|rule| rule.channel.as_str())
+            .collect()
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Alert rules")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.rules_ui(ui, channels));
+        self.show = show;
+    }
+
+    fn rules_ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        let mut remove = None;
+        for (index, rule) in self.rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source(("alert_channel", index))
+                    .selected_text(&rule.channel)
+                    .show_ui(ui, |ui| {
+                        for channel in channels {
+                            ui.selectable_value(&mut rule.channel, channel.to_string(), *channel);
+                        }
+                    });
+                egui::ComboBox::from_id_source(("alert_comparison", index))
+                    .selected_text(match rule.comparison {
+                        Comparison::Above => ">",
+                        Comparison::Below => "<",
+                    })
+                    .width(40.0)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut rule.comparison, Comparison::Above, ">");
+                        ui.selectable_value(&mut rule.comparison, Comparison::Below, "<");
+                    });
+                ui.add(egui::DragValue::new(&mut rule.threshold));
+                ui.add(
+                    egui::DragValue::new(&mut rule.duration)
+                        .clamp_range(0.0..=3600.0)
+                        .speed(0.1)
+                        .prefix("for ")
+                        .suffix(" s"),
+                );
+                ui.checkbox(&mut rule.sound, "sound");
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.checkbox(&mut rule.capture, "save capture")
+                    .on_hover_text("Saved to the capture directory of the alarms");
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            self.rules.remove(index);
+        }
+        if ui.button("add rule").clicked() {
+            self.rules.push(Rule {
+                channel: channels.first().copied().unwrap_or_default().to_string(),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// The list of fired alerts, shown in the bottom panel.
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if ui.button("Clear").clicked() {
+            self.alerts.clear();
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                egui::Grid::new("alerts").striped(true).show(ui, |ui| {
+                    for alert in &self.alerts {
+                        ui.label(format_utc(alert.time));
+                        ui.colored_label(ui.visuals().warn_fg_color, &alert.message);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+}
+
+/// Rings the terminal bell, the native build has no audio output of its own.
+#[cfg(not(target_arch = "wasm32"))]
+fn beep() {
+    use std::io::Write;
+
+    let mut stderr = std::io::stderr();
+    let _ = stderr.write_all(b"\x07").and_then(|_| stderr.flush());
+}
+
+/// Plays a short tone through the Web Audio API.
+#[cfg(target_arch = "wasm32")]
+fn beep() {
+    let play = || -> Result<(), wasm_bindgen::JsValue> {
+        let context = web_sys::AudioContext::new()?;
+        let oscillator = context.create_oscillator()?;
+        oscillator.frequency().set_value(880.0);
+        oscillator.connect_with_audio_node(&context.destination())?;
+        oscillator.start()?;
+        oscillator.stop_with_when(context.current_time() + 0.2)?;
+        Ok(())
+    };
+    if let Err(err) = play() {
+        tracing::warn!("Failed to play the alert sound: {:?}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fire_once_the_comparison_held_for_the_duration() {
+        let rule = Rule {
+            channel: "temp".to_string(),
+            comparison: Comparison::Above,
+            threshold: 80.0,
+            duration: 2.0,
+            sound: false,
+            capture: false,
+        };
+        let mut state = RuleState::default();
+        let mut step = |time: f64, value: f64| state.step(&rule, Sample { time, value });
+
+        assert!(!step(0.0, 81.0));
+        assert!(!step(1.0, 85.0));
+        assert!(step(2.0, 90.0));
+        assert!(!step(3.0, 90.0), "an active alert should not fire again");
+        assert!(!step(4.0, 70.0));
+        assert!(!step(5.0, 81.0));
+        assert!(
+            !step(6.0, 79.0),
+            "dropping below should restart the duration"
+        );
+        assert!(!step(7.0, 81.0));
+        assert!(step(9.0, 81.0));
+    }
+
+    #[test]
+    fn should_return_the_fired_rules_with_their_time() {
+        let rule = Rule {
+            channel: "temp".to_string(),
+            threshold: 80.0,
+            duration: 1.0,
+            capture: true,
+            ..Default::default()
+        };
+        let mut alerts = Alerts {
+            rules: vec![rule.clone()],
+            ..Default::default()
+        };
+        let mut history = ValueHistory::with_capacity(100);
+        history.push(crate::value_parsing::DataValue {
+            name: "temp".to_string(),
+            value: 85.0,
+            timestamp: 1.0,
+            line: 0,
+        });
+        assert!(alerts.update(&history).is_empty());
+
+        for timestamp in [1.5, 2.0, 2.5] {
+            history.push(crate::value_parsing::DataValue {
+                name: "temp".to_string(),
+                value: 90.0,
+                timestamp,
+                line: 0,
+            });
+        }
+        assert_eq!(
+            alerts.update(&history),
+            vec![FiredAlert { time: 2.0, rule }]
+        );
+        assert_eq!(alerts.alerts.len(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use egui::Ui;
+
+use super::event_log::{EventKind, EventLog};
+#[cfg(feature = "audio")]
+use crate::value_parsing::AudioSource;
+use crate::value_parsing::{AudioSettings, DataSource, SourceSenders};
+
+/// Records an audio input next to the serial port, e.g. a microphone or a sensor on the line-in.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct AudioInput {
+    settings: AudioSettings,
+
+    show: bool,
+    #[serde(skip)]
+    source: Option<Box<dyn DataSource>>,
+    /// The input devices found when the window was opened
+    #[serde(skip)]
+    devices: Vec<String>,
+}
+
+impl AudioInput {
+    pub fn open(&mut self) {
+        self.show = true;
+        self.refresh_devices();
+    }
+
+    #[cfg(feature = "audio")]
+    fn refresh_devices(&mut self) {
+        self.devices = crate::value_parsing::input_devices();
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn refresh_devices(&mut self) {}
+
+    /// Returns whether recording was requested.
+    pub fn window(&mut self, ctx: &egui::Context, event_log: &mut EventLog) -> bool {
+        let mut show = self.show;
+        let mut connect = false;
+        egui::Window::new("Audio input")
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| connect = self.ui(ui, event_log));
+        self.show = show;
+        connect
+    }
+
+    /// Starts recording, the samples are received like those of the serial port.
+    pub fn connect(&mut self, senders: SourceSenders, event_log: &mut EventLog) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+        self.source = self.start(senders);
+        if let Some(source) = &self.source {
+            let message = format!("{}: {}", source.name(), self.settings.channel);
+            event_log.record(EventKind::Connected, message);
+        }
+    }
+
+    #[cfg(feature = "audio")]
+    fn start(&self, senders: SourceSenders) -> Option<Box<dyn DataSource>> {
+        Some(Box::new(AudioSource::start(self.settings.clone(), senders)))
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn start(&self, _senders: SourceSenders) -> Option<Box<dyn DataSource>> {
+        None
+    }
+
+    /// Forgets the source once it stopped on its own, it reports why.
+    pub fn update(&mut self) {
+        if self
+            .source
+            .as_ref()
+            .is_some_and(|source| !source.is_running())
+        {
+            self.source = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, event_log: &mut EventLog) -> bool {
+        let recording = self.source.is_some();
+        ui.add_enabled_ui(!recording, |ui| {
+            egui::Grid::new("audio_settings").show(ui, |ui| {
+                ui.label("Device");
+                ui.horizontal(|ui| {
+                    let selected = match self.settings.device.as_str() {
+                        "" => "default input",
+                        device => device,
+                    };
+                    egui::ComboBox::from_id_source("audio_device")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.settings.device,
+                                String::new(),
+                                "default input",
+                            );
+                            for device in &self.devices {
+                                ui.selectable_value(
+                                    &mut self.settings.device,
+                                    device.clone(),
+                                    device,
+                                );
+                            }
+                        });
+                    if ui.button("🔄").on_hover_text("search devices").clicked() {
+                        self.refresh_devices();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Channel");
+                ui.text_edit_singleline(&mut self.settings.channel)
+                    .on_hover_text("Inputs with several channels are numbered, e.g. audio 1");
+                ui.end_row();
+
+                ui.label("Average");
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.downsample)
+                        .clamp_range(1..=4800)
+                        .suffix(" samples"),
+                )
+                .on_hover_text("Samples averaged into one value, at 48 kHz 8 samples plot 6000 values per second");
+                ui.end_row();
+            });
+        });
+
+        ui.separator();
+        match &mut self.source {
+            Some(source) => {
+                let stop = ui
+                    .horizontal(|ui| {
+                        ui.label(format!("Recording {}", source.name()));
+                        ui.button("stop").clicked()
+                    })
+                    .inner;
+                if stop {
+                    source.stop();
+                    event_log.record(EventKind::Disconnected, source.name());
+                    self.source = None;
+                }
+                false
+            }
+            None if cfg!(feature = "audio") => ui
+                .add_enabled(
+                    !self.settings.channel.trim().is_empty(),
+                    egui::Button::new("record"),
+                )
+                .clicked(),
+            None => {
+                ui.label("Built without the `audio` feature");
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use egui::Ui;
+
+use super::value_history::ValueHistory;
+
+/// The values subtracted from the plotted values of the channels, e.g. to tare a load cell or to
+/// zero a sensor. The stored samples and the exports keep the values as they were received.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Baselines {
+    /// The baseline of each channel, by the name shown in the legend
+    pub baselines: BTreeMap<String, f64>,
+    /// The latest values averaged into a new baseline, one takes the current value
+    average: usize,
+
+    show: bool,
+}
+
+impl Default for Baselines {
+    fn default() -> Self {
+        Self {
+            baselines: BTreeMap::new(),
+            average: 1,
+            show: false,
+        }
+    }
+}
+
+impl Baselines {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Takes the mean of the latest values of `channel` as its baseline.
+    fn set(&mut self, channel: &str, history: &ValueHistory) {
+        if let Some(baseline) = history.latest_mean(channel, self.average) {
+            self.baselines.insert(channel.to_string(), baseline);
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str], history: &ValueHistory) {
+        let mut show = self.show;
+        egui::Window::new("Baselines")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels, history));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str], history: &ValueHistory) {
+        ui.horizontal(|ui| {
+            ui.label("Average of the latest");
+            ui.add(
+                egui::DragValue::new(&mut self.average)
+                    .clamp_range(1..=100_000)
+                    .suffix(" values"),
+            );
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Set all").clicked() {
+                for channel in channels {
+                    self.set(channel, history);
+                }
+            }
+            if ui
+                .add_enabled(!self.baselines.is_empty(), egui::Button::new("Reset all"))
+                .clicked()
+            {
+                self.baselines.clear();
+            }
+        });
+        egui::Grid::new("baselines").striped(true).show(ui, |ui| {
+            for channel in channels {
+                ui.label(*channel);
+                match self.baselines.get_mut(*channel) {
+                    Some(baseline) => {
+                        ui.add(egui::DragValue::new(baseline).speed(0.01))
+                            .on_hover_text("Subtracted from the plotted values");
+                    }
+                    None => {
+                        ui.weak("none");
+                    }
+                }
+                if ui.button("set").clicked() {
+                    self.set(channel, history);
+                }
+                if ui
+                    .add_enabled(
+                        self.baselines.contains_key(*channel),
+                        egui::Button::new("reset"),
+                    )
+                    .clicked()
+                {
+                    self.baselines.remove(*channel);
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_parsing::DataValue;
+
+    #[test]
+    fn should_average_the_latest_values_into_the_baseline() {
+        let mut history = ValueHistory::with_capacity(100);
+        for (timestamp, value) in [(1.0, 10.0), (2.0, 2.0), (3.0, 4.0)] {
+            history.push(DataValue {
+                name: "load".to_string(),
+                value,
+                timestamp,
+                line: 0,
+            });
+        }
+        let mut baselines = Baselines {
+            average: 2,
+            ..Baselines::default()
+        };
+
+        baselines.set("load", &history);
+        baselines.set("missing", &history);
+
+        assert_eq!(baselines.baselines, [("load".to_string(), 3.0)].into());
+    }
+}
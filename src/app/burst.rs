@@ -0,0 +1,223 @@
+use std::{collections::VecDeque, time::Duration};
+
+use crossbeam::channel::Receiver;
+use egui::{
+    plot::{Legend, Line, Plot, PlotPoints},
+    Color32, Ui,
+};
+
+use super::value_history::YRange;
+use crate::value_parsing::{unix_timestamp, DataValue};
+
+/// The values of a line arrive within this time, a frame older than that is complete.
+const FRAME_TIMEOUT: f64 = 0.05;
+
+/// Plots devices that send a whole waveform per line, e.g. 256 ADC samples.
+///
+/// Every line is a frame that replaces the previous trace instead of being appended to a time series.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BurstMode {
+    pub enabled: bool,
+    /// The number of earlier frames drawn fading behind the newest one
+    persistence: usize,
+    /// The number of frames averaged to pull a repetitive signal out of the noise, 1 disables averaging
+    average: usize,
+
+    /// The completed frames, newest last
+    #[serde(skip)]
+    frames: VecDeque<Vec<f64>>,
+    #[serde(skip)]
+    pending: Vec<DataValue>,
+}
+
+impl Default for BurstMode {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            persistence: 0,
+            average: 1,
+            frames: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl BurstMode {
+    /// Integrates pending values until the channel is empty or `time_budget` is used up.
+    pub fn update(&mut self, receiver: &Receiver<Vec<DataValue>>, time_budget: Option<Duration>) {
+        let start = unix_timestamp();
+        while let Ok(values) = receiver.try_recv() {
+            for value in values {
+                self.push(value);
+            }
+            if time_budget.is_some_and(|budget| unix_timestamp() - start >= budget.as_secs_f64()) {
+                break;
+            }
+        }
+        self.close_stale_frame(unix_timestamp());
+    }
+
+    fn push(&mut self, value: DataValue) {
+        // Lines received in the same chunk share their timestamp, the source numbers them
+        if self
+            .pending
+            .first()
+            .is_some_and(|first| first.line != value.line)
+        {
+            self.close_frame();
+        }
+        self.pending.push(value);
+    }
+
+    fn close_stale_frame(&mut self, now: f64) {
+        if self
+            .pending
+            .first()
+            .is_some_and(|first| now - first.timestamp > FRAME_TIMEOUT)
+        {
+            self.close_frame();
+        }
+    }
+
+    fn close_frame(&mut self) {
+        let frame = self.pending.drain(..).map(|value| value.value).collect();
+        self.frames.push_back(frame);
+        while self.frames.len() > (self.persistence + 1).max(self.average) {
+            self.frames.pop_front();
+        }
+    }
+
+    /// The mean of the latest frames and the number of frames in it.
+    ///
+    /// Only frames as long as the newest one are averaged, a frame of another length restarts the average.
+    fn averaged(&self) -> Option<(Vec<f64>, usize)> {
+        if self.average <= 1 {
+            return None;
+        }
+        let newest = self.frames.back()?;
+        let frames: Vec<&Vec<f64>> = self
+            .frames
+            .iter()
+            .rev()
+            .take(self.average)
+            .take_while(|frame| frame.len() == newest.len())
+            .collect();
+        let count = frames.len();
+        let mean = (0..newest.len())
+            .map(|index| frames.iter().map(|frame| frame[index]).sum::<f64>() / count as f64)
+            .collect();
+        Some((mean, count))
+    }
+
+    pub fn settings_ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Burst mode")
+            .on_hover_text("Plot every line as a waveform over the index of its values");
+        ui.add_enabled(
+            self.enabled,
+            egui::Slider::new(&mut self.persistence, 0..=32).text("persistence"),
+        )
+        .on_hover_text("Earlier frames drawn fading behind the newest one");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut self.average, 1..=256).text("average"))
+                    .on_hover_text(
+                        "Number of frames averaged, to pull small repetitive signals out of noise",
+                    );
+                if let Some((_, count)) = self.averaged() {
+                    ui.label(format!("{}/{}", count, self.average));
+                    if ui.button("Restart").clicked() {
+                        self.frames.clear();
+                    }
+                }
+            });
+        });
+    }
+
+    pub fn render_plot(&self, ui: &mut Ui, y_range: Option<YRange>) {
+        let mut plot = Plot::new("burst_plot")
+            .view_aspect(2.0)
+            .auto_bounds_x()
+            .legend(Legend::default());
+        plot = match y_range {
+            Some(range) => plot.include_y(range.min).include_y(range.max),
+            None => plot.auto_bounds_y(),
+        };
+
+        let newest_color = ui.visuals().strong_text_color();
+        let averaged = self.averaged();
+        let count = self.frames.len().min(self.persistence + 1);
+        plot.show(ui, |plot_ui| {
+            for (age, frame) in self.frames.iter().rev().take(count).enumerate().rev() {
+                let line = Line::new(PlotPoints::from_ys_f64(frame));
+                // The average takes the place of the newest frame
+                let line = if age == 0 && averaged.is_none() {
+                    line.name(format!("frame ({} values)", frame.len()))
+                        .color(newest_color)
+                } else {
+                    let fade = 1.0 - age as f32 / count as f32;
+                    line.color(Color32::GRAY.gamma_multiply(fade * 0.6))
+                };
+                plot_ui.line(line);
+            }
+            if let Some((mean, averaged)) = &averaged {
+                plot_ui.line(
+                    Line::new(PlotPoints::from_ys_f64(mean))
+                        .name(format!("average of {} frames", averaged))
+                        .color(newest_color),
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(name: &str, value: f64, timestamp: f64, line: u64) -> DataValue {
+        DataValue {
+            name: name.to_string(),
+            value,
+            timestamp,
+            line,
+        }
+    }
+
+    #[test]
+    fn should_split_frames_at_new_lines() {
+        let mut burst = BurstMode {
+            persistence: 2,
+            ..Default::default()
+        };
+        // The first two lines arrived in the same chunk, the second one lacks channel "1"
+        for value in [
+            value("0", 1.0, 1.0, 0),
+            value("1", 2.0, 1.0, 0),
+            value("0", 3.0, 1.0, 1),
+            value("0", 4.0, 1.0, 2),
+            value("1", 5.0, 1.0, 2),
+            value("0", 6.0, 2.0, 3),
+        ] {
+            burst.push(value);
+        }
+        burst.close_stale_frame(2.0);
+        assert_eq!(burst.frames, [vec![1.0, 2.0], vec![3.0], vec![4.0, 5.0]]);
+
+        burst.close_stale_frame(2.1);
+        assert_eq!(burst.frames, [vec![3.0], vec![4.0, 5.0], vec![6.0]]);
+    }
+
+    #[test]
+    fn should_average_the_latest_frames_of_the_same_length() {
+        let mut burst = BurstMode {
+            average: 2,
+            ..Default::default()
+        };
+        burst.frames = VecDeque::from([vec![9.0], vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_eq!(burst.averaged(), Some((vec![2.0, 3.0], 2)));
+
+        burst.frames = VecDeque::from([vec![1.0, 2.0], vec![5.0]]);
+        assert_eq!(burst.averaged(), Some((vec![5.0], 1)));
+    }
+}
@@ -0,0 +1,135 @@
+use egui::Ui;
+
+use crate::value_parsing::{Bus, BusPirateSettings, NumberType, SensorRegister};
+
+/// Polls the registers of I2C or SPI sensors with a Bus Pirate on the selected port, so a
+/// sensor can be evaluated without a microcontroller.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct BusBridge {
+    pub settings: BusPirateSettings,
+
+    show: bool,
+}
+
+impl BusBridge {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Returns whether polling was requested.
+    ///
+    /// Polling uses the selected port, so it can only start while the port is closed.
+    pub fn window(&mut self, ctx: &egui::Context, port: Option<&str>, connected: bool) -> bool {
+        let mut show = self.show;
+        let mut start = false;
+        egui::Window::new("I2C/SPI bridge")
+            .open(&mut show)
+            .resizable(true)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!connected, |ui| self.ui(ui));
+                ui.separator();
+                match port {
+                    _ if connected => {
+                        ui.label("Close the port to change the registers");
+                    }
+                    Some(port) => {
+                        let can_start = !self.settings.registers.is_empty();
+                        start = ui
+                            .add_enabled(can_start, egui::Button::new("start polling"))
+                            .on_hover_text(format!("with the Bus Pirate on {}", port))
+                            .clicked();
+                    }
+                    None => {
+                        ui.label("Select the port of the Bus Pirate");
+                    }
+                }
+            });
+        self.show = show;
+        start
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        let settings = &mut self.settings;
+        egui::Grid::new("bus_bridge_settings").show(ui, |ui| {
+            ui.label("Bus");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut settings.bus, Bus::I2c, "I2C");
+                ui.selectable_value(&mut settings.bus, Bus::Spi, "SPI");
+            });
+            ui.end_row();
+
+            ui.label("Interval");
+            ui.add(
+                egui::DragValue::new(&mut settings.interval)
+                    .clamp_range(1.0..=60_000.0)
+                    .suffix(" ms"),
+            );
+            ui.end_row();
+
+            if settings.bus == Bus::Spi {
+                ui.label("Read flag");
+                ui.add(
+                    egui::DragValue::new(&mut settings.spi_read_flag).hexadecimal(2, false, true),
+                )
+                .on_hover_text("Combined with the register of every read");
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        let i2c = settings.bus == Bus::I2c;
+        let mut remove = None;
+        egui::Grid::new("bus_bridge_registers")
+            .striped(true)
+            .show(ui, |ui| {
+                if i2c {
+                    ui.strong("Device");
+                }
+                ui.strong("Register");
+                ui.strong("Type");
+                ui.strong("Little endian");
+                ui.strong("Scale");
+                ui.strong("Channel");
+                ui.end_row();
+                for (index, register) in settings.registers.iter_mut().enumerate() {
+                    if i2c {
+                        ui.add(
+                            egui::DragValue::new(&mut register.device)
+                                .clamp_range(0..=0x7f)
+                                .hexadecimal(2, false, true),
+                        );
+                    }
+                    ui.add(
+                        egui::DragValue::new(&mut register.register).hexadecimal(2, false, true),
+                    );
+                    egui::ComboBox::from_id_source(("bus_register_type", index))
+                        .selected_text(format!("{:?}", register.number_type))
+                        .show_ui(ui, |ui| {
+                            for number_type in NumberType::ALL {
+                                ui.selectable_value(
+                                    &mut register.number_type,
+                                    number_type,
+                                    format!("{:?}", number_type),
+                                );
+                            }
+                        });
+                    ui.checkbox(&mut register.little_endian, "");
+                    ui.add(egui::DragValue::new(&mut register.scale).speed(0.001));
+                    let hint = register.channel_name();
+                    ui.add(egui::TextEdit::singleline(&mut register.name).hint_text(hint));
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            settings.registers.remove(index);
+        }
+        if ui.button("add register").clicked() {
+            settings.registers.push(SensorRegister::default());
+        }
+    }
+}
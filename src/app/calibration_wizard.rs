@@ -0,0 +1,220 @@
+use egui::Ui;
+
+use super::{event_log::format_utc, value_history::ValueHistory};
+use crate::{
+    calibration::{CalibrationRecord, Calibrations, Pipeline, Step},
+    value_parsing::unix_timestamp,
+};
+
+/// A point of the calibration: the value the channel should read and what it read instead.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+struct ReferencePoint {
+    reference: f64,
+    /// The raw reading, averaged over the latest values when it was captured
+    reading: Option<f64>,
+}
+
+/// The gain and offset converting the readings of both points to their reference values, `None`
+/// if both points read the same.
+fn two_point([first, second]: [[f64; 2]; 2]) -> Option<(f64, f64)> {
+    let [reading_1, reference_1] = first;
+    let [reading_2, reference_2] = second;
+    let gain = (reference_2 - reference_1) / (reading_2 - reading_1);
+    let offset = reference_1 - gain * reading_1;
+    (gain.is_finite() && offset.is_finite()).then_some((gain, offset))
+}
+
+/// Guides through a two-point calibration: the channel is brought to two known reference values,
+/// e.g. the freezing and boiling point of water, and its readings at both give the gain and
+/// offset of its pipeline.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CalibrationWizard {
+    channel: String,
+    /// The latest values averaged into a reading
+    average: usize,
+    points: [ReferencePoint; 2],
+    notes: String,
+
+    show: bool,
+}
+
+impl Default for CalibrationWizard {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            average: 100,
+            points: [
+                ReferencePoint {
+                    reference: 0.0,
+                    reading: None,
+                },
+                ReferencePoint {
+                    reference: 1.0,
+                    reading: None,
+                },
+            ],
+            notes: String::new(),
+            show: false,
+        }
+    }
+}
+
+impl CalibrationWizard {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        channels: &[&str],
+        history: &ValueHistory,
+        calibrations: &mut Calibrations,
+    ) {
+        let mut show = self.show;
+        egui::Window::new("Calibration wizard")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels, history, calibrations));
+        self.show = show;
+    }
+
+    fn measured(&self) -> Option<[[f64; 2]; 2]> {
+        let [first, second] = self.points;
+        Some([
+            [first.reading?, first.reference],
+            [second.reading?, second.reference],
+        ])
+    }
+
+    fn ui(
+        &mut self,
+        ui: &mut Ui,
+        channels: &[&str],
+        history: &ValueHistory,
+        calibrations: &mut Calibrations,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("1. Channel");
+            let previous = self.channel.clone();
+            egui::ComboBox::from_id_source("calibration_wizard_channel")
+                .selected_text(&self.channel)
+                .show_ui(ui, |ui| {
+                    for channel in channels {
+                        ui.selectable_value(&mut self.channel, channel.to_string(), *channel);
+                    }
+                });
+            if self.channel != previous {
+                self.points
+                    .iter_mut()
+                    .for_each(|point| point.reading = None);
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.average)
+                    .clamp_range(1..=100_000)
+                    .prefix("average of ")
+                    .suffix(" values"),
+            );
+        });
+
+        for (index, point) in self.points.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}. Reference", index + 2));
+                ui.add(egui::DragValue::new(&mut point.reference).speed(0.1));
+                if ui
+                    .add_enabled(!self.channel.is_empty(), egui::Button::new("Capture"))
+                    .on_hover_text("Takes the reading once the channel settled at the reference")
+                    .clicked()
+                {
+                    point.reading = history.latest_mean(&self.channel, self.average);
+                }
+                match point.reading {
+                    Some(reading) => ui.label(format!("read {:.6}", reading)),
+                    None => ui.weak("not captured"),
+                };
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("4. Notes");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.notes)
+                    .hint_text("e.g. reference thermometer, ice bath"),
+            );
+        });
+
+        let measured = self.measured();
+        let result = measured.and_then(two_point);
+        match (measured, result) {
+            (Some(_), Some((gain, offset))) => {
+                ui.label(format!("value × {:.6} + {:.6}", gain, offset));
+            }
+            (Some(_), None) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    "Both points read the same, capture them at different references",
+                );
+            }
+            (None, _) => {
+                ui.weak("Capture both points to compute the gain and offset");
+            }
+        }
+        if ui
+            .add_enabled(result.is_some(), egui::Button::new("Apply"))
+            .on_hover_text("Replaces the pipeline of the channel in the calibration table")
+            .clicked()
+        {
+            if let (Some(points), Some((gain, offset))) = (measured, result) {
+                calibrations.pipelines.insert(
+                    self.channel.clone(),
+                    Pipeline {
+                        steps: vec![Step::Scale(gain), Step::Offset(offset)],
+                    },
+                );
+                calibrations.records.insert(
+                    self.channel.clone(),
+                    CalibrationRecord {
+                        date: unix_timestamp(),
+                        notes: std::mem::take(&mut self.notes),
+                        points,
+                    },
+                );
+                self.points
+                    .iter_mut()
+                    .for_each(|point| point.reading = None);
+            }
+        }
+
+        if !calibrations.records.is_empty() {
+            ui.separator();
+            egui::Grid::new("calibration_records")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (channel, record) in &calibrations.records {
+                        ui.label(channel);
+                        ui.label(format_utc(record.date));
+                        ui.label(&record.notes);
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_gain_and_offset_from_two_points() {
+        // An ADC reading 410 in ice water and 3686 in boiling water
+        let (gain, offset) = two_point([[410.0, 0.0], [3686.0, 100.0]]).unwrap();
+        let pipeline = Pipeline {
+            steps: vec![Step::Scale(gain), Step::Offset(offset)],
+        };
+
+        assert!((pipeline.apply(2048.0) - 50.0).abs() < 1e-9);
+        assert_eq!(two_point([[5.0, 0.0], [5.0, 100.0]]), None);
+    }
+}
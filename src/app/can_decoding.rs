@@ -0,0 +1,180 @@
+use egui::Ui;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::value_parsing::parse_dbc;
+use crate::value_parsing::{CanSettings, DbcMessage, MappedObject, NumberType, PdoMapping};
+
+/// Edits how the frames on the CAN bus become channels, from a DBC file or as CANopen PDOs.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct CanDecoding {
+    /// The DBC file imported last
+    dbc_path: String,
+
+    show: bool,
+    /// The result of the last import
+    #[serde(skip)]
+    import_status: Option<Result<String, String>>,
+}
+
+impl CanDecoding {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, settings: &mut CanSettings) {
+        let mut show = self.show;
+        egui::Window::new("CAN decoding")
+            .open(&mut show)
+            .resizable(true)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.label("The decoding takes effect when the port is opened");
+                ui.collapsing("DBC file", |ui| self.dbc_ui(ui, &mut settings.messages));
+                ui.collapsing("CANopen PDOs", |ui| ui_pdos(ui, &mut settings.pdos));
+            });
+        self.show = show;
+    }
+
+    fn dbc_ui(&mut self, ui: &mut Ui, messages: &mut Vec<DbcMessage>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.dbc_path);
+            if ui.button("Import").clicked() {
+                self.import_status = Some(self.import(messages));
+            }
+        });
+        match &self.import_status {
+            Some(Ok(status)) => {
+                ui.label(status);
+            }
+            Some(Err(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            None => {}
+        }
+
+        for message in messages.iter() {
+            let id = if message.extended {
+                format!("0x{:08X}", message.id)
+            } else {
+                format!("0x{:03X}", message.id)
+            };
+            ui.collapsing(format!("{} {}", id, message.name), |ui| {
+                for signal in &message.signals {
+                    ui.label(format!("{} [{}]", signal.name, signal.unit));
+                }
+            });
+        }
+        if !messages.is_empty() && ui.button("Remove the messages").clicked() {
+            messages.clear();
+            self.import_status = None;
+        }
+    }
+
+    /// Replaces `messages` with those of the DBC file at `dbc_path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn import(&self, messages: &mut Vec<DbcMessage>) -> Result<String, String> {
+        let text = std::fs::read_to_string(self.dbc_path.trim())
+            .map_err(|err| format!("Failed to read {}: {}", self.dbc_path, err))?;
+        *messages = parse_dbc(&text)?;
+        let signals: usize = messages.iter().map(|message| message.signals.len()).sum();
+        Ok(format!(
+            "Imported {} messages with {} signals",
+            messages.len(),
+            signals
+        ))
+    }
+}
+
+fn ui_pdos(ui: &mut Ui, pdos: &mut Vec<PdoMapping>) {
+    let mut remove = None;
+    for (index, pdo) in pdos.iter_mut().enumerate() {
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut pdo.node_id)
+                    .clamp_range(1..=127)
+                    .prefix("node "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut pdo.pdo)
+                    .clamp_range(1..=4)
+                    .prefix("TPDO "),
+            );
+            ui.label(format!("COB-ID 0x{:03X}", pdo.cob_id()));
+            if ui.button("🗑").on_hover_text("remove the PDO").clicked() {
+                remove = Some(index);
+            }
+        });
+        ui_objects(ui, index, pdo);
+    }
+    if let Some(index) = remove {
+        pdos.remove(index);
+    }
+
+    ui.separator();
+    if ui.button("add PDO").clicked() {
+        pdos.push(PdoMapping::default());
+    }
+}
+
+fn ui_objects(ui: &mut Ui, pdo_index: usize, pdo: &mut PdoMapping) {
+    let node_id = pdo.node_id;
+    let mut remove = None;
+    egui::Grid::new(("pdo_objects", pdo_index))
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Index");
+            ui.strong("Sub");
+            ui.strong("Type");
+            ui.strong("Scale");
+            ui.strong("Channel");
+            ui.end_row();
+            for (index, object) in pdo.objects.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut object.index).hexadecimal(4, false, true));
+                ui.add(egui::DragValue::new(&mut object.subindex).hexadecimal(2, false, true));
+                egui::ComboBox::from_id_source(("pdo_object_type", pdo_index, index))
+                    .selected_text(format!("{:?}", object.number_type))
+                    .show_ui(ui, |ui| {
+                        for number_type in NumberType::ALL {
+                            ui.selectable_value(
+                                &mut object.number_type,
+                                number_type,
+                                format!("{:?}", number_type),
+                            );
+                        }
+                    });
+                ui.add(egui::DragValue::new(&mut object.scale).speed(0.01));
+                let placeholder = object.channel_name(node_id);
+                ui.add(egui::TextEdit::singleline(&mut object.name).hint_text(placeholder));
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = remove {
+        pdo.objects.remove(index);
+    }
+
+    let mapped: usize = pdo
+        .objects
+        .iter()
+        .map(|object| object.number_type.size())
+        .sum();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(mapped < 8, egui::Button::new("add object"))
+            .clicked()
+        {
+            pdo.objects.push(MappedObject::default());
+        }
+        if mapped > 8 {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("{} bytes mapped, a PDO carries at most 8", mapped),
+            );
+        }
+    });
+}
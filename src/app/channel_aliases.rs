@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use egui::Ui;
+
+/// Friendly labels for terse channel names like `a0`, used in the legend and the exports.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct ChannelAliases {
+    /// The alias of each channel, by the name the source sends
+    pub aliases: BTreeMap<String, String>,
+
+    show: bool,
+    #[serde(skip)]
+    new_channel: String,
+}
+
+impl ChannelAliases {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// `channels` are offered as names to rename.
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Channel aliases")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        let mut remove = None;
+        egui::Grid::new("channel_aliases")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Channel");
+                ui.strong("Alias");
+                ui.end_row();
+                for (channel, alias) in self.aliases.iter_mut() {
+                    ui.label(channel);
+                    ui.text_edit_singleline(alias);
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(channel.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(channel) = remove {
+            self.aliases.remove(&channel);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("new_alias_channel")
+                .selected_text(&self.new_channel)
+                .show_ui(ui, |ui| {
+                    let aliased = |channel: &&str| {
+                        self.aliases.contains_key(*channel)
+                            || self.aliases.values().any(|alias| alias == channel)
+                    };
+                    for channel in channels.iter().filter(|x| !aliased(x)) {
+                        ui.selectable_value(&mut self.new_channel, channel.to_string(), *channel);
+                    }
+                });
+            ui.text_edit_singleline(&mut self.new_channel)
+                .on_hover_text("The name the device sends");
+            if ui
+                .add_enabled(!self.new_channel.is_empty(), egui::Button::new("add"))
+                .clicked()
+            {
+                let channel = std::mem::take(&mut self.new_channel);
+                self.aliases.insert(channel.clone(), channel);
+            }
+        });
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use egui::Ui;
+
+use super::value_history::{Sample, ValueHistory};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+}
+
+impl Edge {
+    /// Whether the step from `previous` to `current` crosses `threshold` in this direction.
+    fn crossed(self, previous: f64, current: f64, threshold: f64) -> bool {
+        match self {
+            Edge::Rising => previous < threshold && current >= threshold,
+            Edge::Falling => previous > threshold && current <= threshold,
+        }
+    }
+}
+
+/// A channel crossing a threshold.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub channel: String,
+    pub threshold: f64,
+    pub edge: Edge,
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.edge {
+            Edge::Rising => write!(f, "{} rises above {}", self.channel, self.threshold),
+            Edge::Falling => write!(f, "{} falls below {}", self.channel, self.threshold),
+        }
+    }
+}
+
+pub fn condition_ui(ui: &mut Ui, id: &str, condition: &mut Condition, channels: &[&str]) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(&condition.channel)
+            .show_ui(ui, |ui| {
+                for channel in channels {
+                    ui.selectable_value(&mut condition.channel, channel.to_string(), *channel);
+                }
+            });
+        egui::ComboBox::from_id_source((id, "edge"))
+            .selected_text(format!("{:?}", condition.edge))
+            .width(70.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut condition.edge, Edge::Rising, "Rising");
+                ui.selectable_value(&mut condition.edge, Edge::Falling, "Falling");
+            });
+        ui.add(egui::DragValue::new(&mut condition.threshold).prefix("threshold: "));
+    });
+}
+
+/// Returns the times at which the samples received since the last call cross the condition.
+pub fn crossings(
+    history: &ValueHistory,
+    condition: &Condition,
+    previous: &mut HashMap<String, Sample>,
+) -> Vec<f64> {
+    let Some(samples) = history.samples(&condition.channel) else {
+        return Vec::new();
+    };
+
+    let key = format!(
+        "{}\u{0}{:?}\u{0}{}",
+        condition.channel, condition.edge, condition.threshold
+    );
+    let last = previous.get(&key).copied();
+    let new_samples = match last {
+        Some(last) => {
+            samples.len()
+                - samples
+                    .iter()
+                    .rev()
+                    .take_while(|x| x.time > last.time)
+                    .count()
+        }
+        None => samples.len().saturating_sub(1),
+    };
+
+    let mut result = Vec::new();
+    let mut before = last.or_else(|| samples.get(new_samples));
+    for sample in samples.iter().skip(new_samples) {
+        if let Some(before) = before {
+            if condition
+                .edge
+                .crossed(before.value, sample.value, condition.threshold)
+            {
+                result.push(sample.time);
+            }
+        }
+        before = Some(sample);
+    }
+    if let Some(before) = before {
+        previous.insert(key, before);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_edges() {
+        assert!(Edge::Rising.crossed(0.0, 1.0, 0.5));
+        assert!(Edge::Rising.crossed(0.0, 0.5, 0.5));
+        assert!(!Edge::Rising.crossed(1.0, 0.0, 0.5));
+        assert!(Edge::Falling.crossed(1.0, 0.0, 0.5));
+        assert!(!Edge::Falling.crossed(0.0, 1.0, 0.5));
+    }
+}
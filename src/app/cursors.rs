@@ -0,0 +1,128 @@
+use egui::{
+    plot::{LineStyle, PlotPoint, PlotUi, VLine},
+    Color32, Ui,
+};
+
+use super::value_history::XAxis;
+
+/// Pixels from a cursor within which pressing the pointer grabs it.
+const GRAB_DISTANCE: f32 = 8.0;
+
+/// Two vertical cursors on the plot measuring the distance between them, like the cursors of a scope.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Cursors {
+    pub enabled: bool,
+
+    /// The x coordinates of both cursors, placed into the visible range when they are first shown
+    #[serde(skip)]
+    positions: Option<[f64; 2]>,
+    /// The cursor following the pointer
+    #[serde(skip)]
+    grabbed: Option<usize>,
+}
+
+impl Cursors {
+    /// The x coordinates of the cursors while they are shown.
+    pub fn positions(&self) -> Option<[f64; 2]> {
+        self.positions.filter(|_| self.enabled)
+    }
+
+    /// While a cursor is dragged the plot must not be dragged along.
+    pub fn grabbed(&self) -> bool {
+        self.grabbed.is_some()
+    }
+
+    /// Draws the cursors and moves the one grabbed with the pointer.
+    pub fn update(&mut self, plot_ui: &mut PlotUi) {
+        if !self.enabled {
+            self.grabbed = None;
+            return;
+        }
+        let bounds = plot_ui.plot_bounds();
+        let [min_x, _] = bounds.min();
+        let positions = self.positions.get_or_insert_with(|| {
+            let third = bounds.width() / 3.0;
+            [min_x + third, min_x + 2.0 * third]
+        });
+
+        let (pressed, down) = plot_ui
+            .ctx()
+            .input(|x| (x.pointer.primary_pressed(), x.pointer.primary_down()));
+        let pointer = plot_ui.pointer_coordinate();
+        if !down {
+            self.grabbed = None;
+        }
+        if let (true, true, Some(pointer)) = (pressed, plot_ui.plot_hovered(), pointer) {
+            let screen_x = |x: f64| plot_ui.screen_from_plot(PlotPoint::new(x, pointer.y)).x;
+            let pointer_x = screen_x(pointer.x);
+            self.grabbed = (0..positions.len())
+                .map(|index| (index, (screen_x(positions[index]) - pointer_x).abs()))
+                .filter(|(_, distance)| *distance <= GRAB_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(index, _)| index);
+        }
+        if let (Some(index), Some(pointer)) = (self.grabbed, pointer) {
+            positions[index] = pointer.x;
+        }
+
+        let color = plot_ui.ctx().style().visuals.strong_text_color();
+        for x in *positions {
+            plot_ui.vline(VLine::new(x).color(color).style(LineStyle::dashed_loose()));
+        }
+    }
+
+    /// The positions of the cursors, their distance and the values of each series at both.
+    ///
+    /// `values` holds the name of every series with its values at the two cursors.
+    pub fn readout_ui(&self, ui: &mut Ui, x_axis: XAxis, values: &[(String, [Option<f64>; 2])]) {
+        let Some([x1, x2]) = self.positions() else {
+            return;
+        };
+        let delta = x2 - x1;
+        let format =
+            |value: Option<f64>| value.map_or("-".to_string(), |value| format!("{:.6}", value));
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::Grid::new("cursor_readout")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong("cursor 1");
+                    ui.strong("cursor 2");
+                    ui.strong("Δ");
+                    ui.end_row();
+
+                    ui.label(match x_axis {
+                        XAxis::Samples => "x (sample)",
+                        XAxis::Time | XAxis::Window => "x (s)",
+                        XAxis::Frames => "x (frame)",
+                    });
+                    ui.label(format!("{:.6}", x1));
+                    ui.label(format!("{:.6}", x2));
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{:.6}", delta));
+                        if delta != 0.0 {
+                            let unit = match x_axis {
+                                XAxis::Samples => "/sample",
+                                XAxis::Time | XAxis::Window => "Hz",
+                                XAxis::Frames => "/frame",
+                            };
+                            ui.colored_label(
+                                Color32::GRAY,
+                                format!("1/Δx = {:.6} {}", 1.0 / delta.abs(), unit),
+                            );
+                        }
+                    });
+                    ui.end_row();
+
+                    for (name, [y1, y2]) in values {
+                        ui.label(name);
+                        ui.label(format(*y1));
+                        ui.label(format(*y2));
+                        ui.label(format(y1.zip(*y2).map(|(y1, y2)| y2 - y1)));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
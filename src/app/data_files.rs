@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use super::{export_channels::ExportChannels, value_history::ValueHistory};
+use crate::{calibration::Calibrations, export::Channels};
+
+/// Imports csv or parquet captures to view them offline, and exports the samples as parquet for
+/// captures too long for csv.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DataFiles {
+    import_path: String,
+    export_path: String,
+
+    show: bool,
+    /// The result of the last import or export
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for DataFiles {
+    fn default() -> Self {
+        Self {
+            import_path: "capture.parquet".to_string(),
+            export_path: "capture.parquet".to_string(),
+            show: false,
+            status: None,
+        }
+    }
+}
+
+impl DataFiles {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Returns the name and channels of an imported file.
+    #[cfg_attr(not(feature = "parquet"), allow(unused_variables))]
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        history: &ValueHistory,
+        calibrations: &Calibrations,
+        channels: &mut ExportChannels,
+    ) -> Option<(String, Channels)> {
+        let mut show = self.show;
+        let mut imported = None;
+        egui::Window::new("Import / export")
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.import_path)
+                        .on_hover_text(
+                            "A csv file of the data logger or an export, or a parquet file",
+                        );
+                    if ui.button("Import").clicked() {
+                        let path = Path::new(&self.import_path);
+                        match crate::export::read(path) {
+                            Ok(channels) => {
+                                let samples: usize = channels.values().map(Vec::len).sum();
+                                self.status = Some(Ok(format!(
+                                    "Imported {} samples of {} channels",
+                                    samples,
+                                    channels.len()
+                                )));
+                                imported = Some((self.import_path.clone(), channels));
+                            }
+                            Err(err) => {
+                                self.status = Some(Err(format!("Failed to import: {}", err)));
+                            }
+                        }
+                    }
+                });
+                ui.label("Imported files are shown frozen, resuming returns to the live values");
+                ui.separator();
+
+                #[cfg(feature = "parquet")]
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.export_path);
+                    channels.button(ui);
+                    if ui.button("Export parquet").clicked() {
+                        let path = Path::new(&self.export_path);
+                        let samples = samples_of(history, calibrations, channels);
+                        self.status = Some(match crate::export::parquet::write(path, &samples) {
+                            Ok(()) => Ok(format!("Exported to {}", self.export_path)),
+                            Err(err) => Err(format!("Failed to export: {}", err)),
+                        });
+                    }
+                });
+                #[cfg(not(feature = "parquet"))]
+                ui.label("Exporting parquet files requires the parquet feature");
+
+                match &self.status {
+                    Some(Ok(status)) => {
+                        ui.label(status);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        self.show = show;
+        imported
+    }
+}
+
+/// The calibrated samples of the exported channels at the time shifted by their offset, like the
+/// csv exports.
+#[cfg(feature = "parquet")]
+fn samples_of(
+    history: &ValueHistory,
+    calibrations: &Calibrations,
+    channels: &ExportChannels,
+) -> Channels {
+    history
+        .channel_names()
+        .filter(|name| channels.includes(name))
+        .filter_map(|name| {
+            let offset = history.time_offset(name);
+            let samples = history.samples(name)?.iter().map(|sample| {
+                let value = calibrations.export_value(name, sample.value);
+                [sample.time + offset, value]
+            });
+            Some((name.to_string(), samples.collect()))
+        })
+        .collect()
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::BufWriter,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use egui::Ui;
+
+use super::{event_log::file_stamp, export_channels::ExportChannels};
+use crate::{
+    calibration::Calibrations,
+    cli::OutputFormat,
+    csv_format::CsvFormat,
+    sinks::{DataSink, RecordSink, SinkError, Sinks},
+    value_parsing::{alias, DataValue},
+};
+
+const SINK_NAME: &str = "data logger";
+
+/// When the data logger starts a new file.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Once the file reached the maximum size
+    Size,
+    /// With the first value of every hour
+    Hourly,
+}
+
+/// Streams every received value to files while plotting continues.
+///
+/// The values are written by the thread reading the source, so a slow ui loses none of them.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DataLogger {
+    directory: String,
+    format: OutputFormat,
+    rotation: Rotation,
+    /// Megabytes after which a new file is started, with [`Rotation::Size`]
+    max_size: u64,
+
+    #[serde(skip)]
+    enabled: bool,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for DataLogger {
+    fn default() -> Self {
+        Self {
+            directory: "recordings".to_string(),
+            format: OutputFormat::Csv,
+            rotation: Rotation::Size,
+            max_size: 100,
+            enabled: false,
+            error: None,
+        }
+    }
+}
+
+impl DataLogger {
+    /// Picks up the errors of the logger since the last frame, it disables itself on errors.
+    pub fn update(&mut self, sinks: &Mutex<Sinks>) {
+        let errors = sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take_errors();
+        if !errors.is_empty() {
+            self.enabled = false;
+            self.error = Some(errors.join("\n"));
+        }
+    }
+
+    /// Closes the current file, e.g. from the menu of the tray icon.
+    pub fn stop(&mut self, sinks: &Mutex<Sinks>) {
+        if self.enabled {
+            self.enabled = false;
+            sinks
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .remove(SINK_NAME);
+        }
+    }
+
+    /// `csv_format` and the selected `channels` apply from the time the logger is enabled.
+    ///
+    /// The `aliases` name the values like the plot, so the selected channels match them.
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        sinks: &Mutex<Sinks>,
+        csv_format: &CsvFormat,
+        channels: &mut ExportChannels,
+        aliases: &BTreeMap<String, String>,
+    ) {
+        ui.add_enabled_ui(!self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Directory");
+                ui.text_edit_singleline(&mut self.directory);
+            });
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.format, OutputFormat::Csv, "CSV");
+                ui.radio_value(&mut self.format, OutputFormat::Jsonl, "JSONL");
+            });
+            ui.horizontal(|ui| {
+                ui.label("New file");
+                ui.radio_value(&mut self.rotation, Rotation::Size, "after");
+                ui.add_enabled(
+                    self.rotation == Rotation::Size,
+                    egui::DragValue::new(&mut self.max_size)
+                        .clamp_range(1..=100_000)
+                        .suffix(" MB"),
+                );
+                ui.radio_value(&mut self.rotation, Rotation::Hourly, "hourly");
+            });
+            channels.button(ui);
+        });
+
+        if ui.checkbox(&mut self.enabled, "Log to file").changed() {
+            let mut sinks = sinks.lock().unwrap_or_else(PoisonError::into_inner);
+            sinks.remove(SINK_NAME);
+            if self.enabled {
+                self.error = None;
+                sinks.add(Box::new(RotatingFileSink {
+                    directory: PathBuf::from(&self.directory),
+                    format: self.format,
+                    csv_format: *csv_format,
+                    rotation: self.rotation,
+                    max_bytes: self.max_size * 1_000_000,
+                    excluded: channels.excluded().clone(),
+                    aliases: aliases.clone(),
+                    current: None,
+                    hour: 0,
+                }));
+            }
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+/// Records to a file that is replaced by a new one according to its [`Rotation`].
+struct RotatingFileSink {
+    directory: PathBuf,
+    format: OutputFormat,
+    csv_format: CsvFormat,
+    rotation: Rotation,
+    max_bytes: u64,
+    /// The channels left out, by their alias
+    excluded: BTreeSet<String>,
+    aliases: BTreeMap<String, String>,
+    /// The file currently written to, opened with the first value
+    current: Option<(PathBuf, RecordSink)>,
+    /// The hour since the unix epoch in which the current file was started
+    hour: i64,
+}
+
+impl RotatingFileSink {
+    fn open(&mut self, time: f64) -> Result<(PathBuf, RecordSink), SinkError> {
+        std::fs::create_dir_all(&self.directory)?;
+        let extension = match self.format {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+        };
+        let stamp = file_stamp(time);
+        let mut path = self
+            .directory
+            .join(format!("values_{}.{}", stamp, extension));
+        // Small files may be rotated more than once per second
+        let mut index = 1;
+        while path.exists() {
+            path = self
+                .directory
+                .join(format!("values_{}_{}.{}", stamp, index, extension));
+            index += 1;
+        }
+
+        tracing::info!("Logging values to {}", path.display());
+        let sink = RecordSink::new(
+            path.display().to_string(),
+            Box::new(BufWriter::new(File::create(&path)?)),
+            self.format,
+            self.csv_format,
+            Calibrations::default(),
+        )?;
+        self.hour = hour(time);
+        Ok((path, sink))
+    }
+}
+
+impl DataSink for RotatingFileSink {
+    fn name(&self) -> &str {
+        SINK_NAME
+    }
+
+    fn write(&mut self, value: &DataValue) -> Result<(), SinkError> {
+        if self.excluded.contains(alias(&self.aliases, &value.name)) {
+            return Ok(());
+        }
+        if self.rotation == Rotation::Hourly && hour(value.timestamp) != self.hour {
+            if let Some((_, mut previous)) = self.current.take() {
+                previous.flush()?;
+            }
+        }
+        let current = match self.current.take() {
+            Some(current) => current,
+            None => self.open(value.timestamp)?,
+        };
+        let (_, sink) = self.current.insert(current);
+        sink.write(value)
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        let Some((path, sink)) = &mut self.current else {
+            return Ok(());
+        };
+        sink.flush()?;
+        if self.rotation == Rotation::Size && std::fs::metadata(path)?.len() >= self.max_bytes {
+            self.current = None;
+        }
+        Ok(())
+    }
+}
+
+fn hour(time: f64) -> i64 {
+    (time / 3600.0).floor() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_start_a_new_file_every_hour() {
+        let directory = std::env::temp_dir().join("serialplotter_rotation_test");
+        let _ = std::fs::remove_dir_all(&directory);
+        let mut sink = RotatingFileSink {
+            directory: directory.clone(),
+            format: OutputFormat::Csv,
+            csv_format: CsvFormat::default(),
+            rotation: Rotation::Hourly,
+            max_bytes: 0,
+            excluded: BTreeSet::new(),
+            aliases: BTreeMap::new(),
+            current: None,
+            hour: 0,
+        };
+
+        for timestamp in [3599.0, 3600.0, 3601.0] {
+            let value = DataValue {
+                name: "X".to_string(),
+                value: 1.0,
+                timestamp,
+                line: 0,
+            };
+            sink.write(&value).unwrap();
+        }
+        sink.flush().unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort_unstable();
+        assert_eq!(
+            files,
+            [
+                "values_1970-01-01_00-59-59.csv",
+                "values_1970-01-01_01-00-00.csv"
+            ]
+        );
+        let second = std::fs::read_to_string(directory.join(&files[1])).unwrap();
+        assert_eq!(second.lines().count(), 3);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
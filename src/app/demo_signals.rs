@@ -0,0 +1,103 @@
+use egui::Ui;
+
+use crate::value_parsing::{DemoSettings, DemoSignal, Waveform};
+
+/// Generates sine, square, triangle and noise signals instead of reading a device, to try the
+/// plotter without hardware.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct DemoSignals {
+    pub settings: DemoSettings,
+
+    show: bool,
+}
+
+impl DemoSignals {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Returns whether generating was requested.
+    ///
+    /// The signals replace the serial port, so they can only start while it is closed.
+    pub fn window(&mut self, ctx: &egui::Context, connected: bool) -> bool {
+        let mut show = self.show;
+        let mut start = false;
+        egui::Window::new("Demo signals")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!connected, |ui| self.ui(ui));
+                ui.separator();
+                if connected {
+                    ui.label("Close the port to change the signals");
+                } else {
+                    let can_start = !self.settings.signals.is_empty();
+                    start = ui
+                        .add_enabled(can_start, egui::Button::new("start"))
+                        .clicked();
+                }
+            });
+        self.show = show;
+        start
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        let signals = &mut self.settings.signals;
+        let mut remove = None;
+        egui::Grid::new("demo_signals")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Channel");
+                ui.strong("Waveform");
+                ui.strong("Frequency");
+                ui.strong("Amplitude");
+                ui.strong("Offset");
+                ui.strong("Rate");
+                ui.end_row();
+                for (index, signal) in signals.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut signal.name);
+                    egui::ComboBox::from_id_source(("demo_waveform", index))
+                        .selected_text(format!("{:?}", signal.waveform))
+                        .show_ui(ui, |ui| {
+                            for waveform in Waveform::ALL {
+                                ui.selectable_value(
+                                    &mut signal.waveform,
+                                    waveform,
+                                    format!("{:?}", waveform),
+                                );
+                            }
+                        });
+                    ui.add_enabled(
+                        signal.waveform != Waveform::Noise,
+                        egui::DragValue::new(&mut signal.frequency)
+                            .clamp_range(0.0..=1000.0)
+                            .speed(0.01)
+                            .suffix(" Hz"),
+                    );
+                    ui.add(egui::DragValue::new(&mut signal.amplitude).speed(0.01));
+                    ui.add(egui::DragValue::new(&mut signal.offset).speed(0.01));
+                    ui.add(
+                        egui::DragValue::new(&mut signal.rate)
+                            .clamp_range(0.1..=10_000.0)
+                            .suffix(" /s"),
+                    )
+                    .on_hover_text("Samples per second");
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            signals.remove(index);
+        }
+        if ui.button("add signal").clicked() {
+            let name = format!("signal {}", signals.len() + 1);
+            signals.push(DemoSignal {
+                name,
+                ..DemoSignal::default()
+            });
+        }
+    }
+}
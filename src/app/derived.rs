@@ -0,0 +1,84 @@
+use std::collections::BTreeSet;
+
+use egui::Ui;
+
+use super::value_history::{Sample, SampleBuffer};
+use crate::dsp;
+
+/// A series computed from the samples of a channel.
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum Derivation {
+    /// The rate of change per second
+    Derivative,
+    /// The cumulative area under the values over seconds
+    Integral,
+}
+
+impl Derivation {
+    pub const ALL: [Derivation; 2] = [Derivation::Derivative, Derivation::Integral];
+
+    /// The name of the series derived from `channel` in the legend.
+    pub fn label(self, channel: &str) -> String {
+        match self {
+            Derivation::Derivative => format!("d/dt {}", channel),
+            Derivation::Integral => format!("∫ {} dt", channel),
+        }
+    }
+
+    /// The derived samples, with the time of the samples they were computed at.
+    pub fn apply(self, buffer: &SampleBuffer) -> SampleBuffer {
+        let points = buffer.iter().map(|sample| [sample.time, sample.value]);
+        let derived = match self {
+            Derivation::Derivative => dsp::derivative(points),
+            Derivation::Integral => dsp::integral(points),
+        };
+        derived
+            .into_iter()
+            .map(|[time, value]| Sample { time, value })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Derivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Derivation::Derivative => write!(f, "Derivative"),
+            Derivation::Integral => write!(f, "Integral"),
+        }
+    }
+}
+
+/// The derived series shown in the plot, chosen from the context menu of the plot.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct DerivedSeries {
+    /// The channel each series is derived from and how
+    pub series: BTreeSet<(String, Derivation)>,
+}
+
+impl DerivedSeries {
+    /// A submenu per channel to toggle its derived series.
+    pub fn menu_ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        if channels.is_empty() {
+            ui.label("No channels yet");
+        }
+        for channel in channels {
+            ui.menu_button(*channel, |ui| {
+                for derivation in Derivation::ALL {
+                    let key = (channel.to_string(), derivation);
+                    let mut shown = self.series.contains(&key);
+                    if ui.checkbox(&mut shown, derivation.to_string()).changed() {
+                        if shown {
+                            self.series.insert(key);
+                        } else {
+                            self.series.remove(&key);
+                        }
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::{fmt::Display, io};
+
+use egui::Ui;
+
+use crate::csv_format::CsvFormat;
+use crate::value_parsing::unix_timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Connected,
+    Disconnected,
+    /// The source stopped on its own, e.g. because the device was unplugged
+    ConnectionLost,
+    /// Reported by the source, e.g. a sent break
+    PortCondition,
+    /// A failure the source continued after, e.g. a failed write
+    SourceError,
+    ParserChanged,
+    SessionLoaded,
+    Alarm,
+    /// Placed by the user to note something happening outside of the data
+    Marker,
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            EventKind::Connected => "connected",
+            EventKind::Disconnected => "disconnected",
+            EventKind::ConnectionLost => "connection lost",
+            EventKind::PortCondition => "port",
+            EventKind::SourceError => "source error",
+            EventKind::ParserChanged => "parser changed",
+            EventKind::SessionLoaded => "session loaded",
+            EventKind::Alarm => "alarm",
+            EventKind::Marker => "marker",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Seconds since the unix epoch
+    pub time: f64,
+    pub kind: EventKind,
+    pub message: String,
+}
+
+/// Timestamped record of what happened during a session, next to the data itself.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct EventLog {
+    export_path: String,
+
+    #[serde(skip)]
+    events: Vec<Event>,
+    #[serde(skip)]
+    marker_note: String,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self {
+            export_path: "events.csv".to_string(),
+            events: Vec::new(),
+            marker_note: String::new(),
+        }
+    }
+}
+
+impl EventLog {
+    pub fn record(&mut self, kind: EventKind, message: impl Into<String>) {
+        let event = Event {
+            time: unix_timestamp(),
+            kind,
+            message: message.into(),
+        };
+        tracing::info!("Event {}: {}", event.kind, event.message);
+        self.events.push(event);
+    }
+
+    pub fn write_csv(&self, output: &mut dyn io::Write, format: &CsvFormat) -> io::Result<()> {
+        format.write_row(output, &["timestamp", "event", "message"])?;
+        for event in &self.events {
+            format.write_row(
+                output,
+                &[
+                    &format.timestamp(event.time),
+                    &event.kind.to_string(),
+                    &event.message,
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// `csv_format` is used when exporting the events.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn ui(&mut self, ui: &mut Ui, csv_format: &CsvFormat) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.marker_note)
+                .on_hover_text("Note stored with the marker");
+            if ui.button("Place marker").clicked() {
+                let note = std::mem::take(&mut self.marker_note);
+                self.record(EventKind::Marker, note);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.separator();
+                ui.text_edit_singleline(&mut self.export_path);
+                if ui.button("Export CSV").clicked() {
+                    match self.export(&self.export_path, csv_format) {
+                        Ok(()) => tracing::info!("Exported events to {}", self.export_path),
+                        Err(err) => tracing::error!("Failed to export events: {}", err),
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                egui::Grid::new("event_log").striped(true).show(ui, |ui| {
+                    for event in &self.events {
+                        ui.label(format_utc(event.time));
+                        ui.label(event.kind.to_string());
+                        ui.label(&event.message);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export(&self, path: &str, format: &CsvFormat) -> io::Result<()> {
+        let mut output = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_csv(&mut output, format)?;
+        io::Write::flush(&mut output)
+    }
+}
+
+/// Formats seconds since the unix epoch as UTC date and time, e.g. `2023-05-14 09:30:00 UTC`.
+pub fn format_utc(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    let (days, time) = (seconds / 86400, seconds % 86400);
+
+    // Converts days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// A time in a form usable in file names, e.g. `2023-05-14_09-30-00`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn file_stamp(time: f64) -> String {
+    format_utc(time)
+        .trim_end_matches(" UTC")
+        .replace(' ', "_")
+        .replace(':', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_events_as_csv() {
+        let mut log = EventLog::default();
+        log.events.push(Event {
+            time: 1.5,
+            kind: EventKind::Marker,
+            message: "valve open, 2 bar".to_string(),
+        });
+
+        let mut output = Vec::new();
+        log.write_csv(&mut output, &CsvFormat::default()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "timestamp,event,message\n1.500000,marker,\"valve open, 2 bar\"\n"
+        );
+    }
+
+    #[test]
+    fn should_format_epoch() {
+        assert_eq!(format_utc(0.0), "1970-01-01 00:00:00 UTC");
+    }
+
+    #[test]
+    fn should_format_leap_day() {
+        assert_eq!(format_utc(1_709_210_096.7), "2024-02-29 12:34:56 UTC");
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use egui::Ui;
+
+use super::value_history::ValueHistory;
+
+/// The channels the exports contain, so diagnostics like `fetch_count` stay out of reports.
+///
+/// Channels are exported unless they were deselected, so new channels appear in the exports.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct ExportChannels {
+    /// The deselected channels, by the name the samples are stored under
+    excluded: BTreeSet<String>,
+    /// Selections to switch between by their name, stored as their deselected channels
+    saved: BTreeMap<String, BTreeSet<String>>,
+
+    show: bool,
+    /// The name the current selection is saved under
+    #[serde(skip)]
+    name: String,
+}
+
+impl ExportChannels {
+    pub fn includes(&self, channel: &str) -> bool {
+        !self.excluded.contains(channel)
+    }
+
+    pub fn excluded(&self) -> &BTreeSet<String> {
+        &self.excluded
+    }
+
+    /// `history` without the deselected channels.
+    pub fn selected<'a>(&self, history: &'a ValueHistory) -> Cow<'a, ValueHistory> {
+        if !history.channel_names().any(|name| !self.includes(name)) {
+            return Cow::Borrowed(history);
+        }
+        let mut selected = history.clone();
+        for channel in &self.excluded {
+            selected.clear_channel(channel);
+        }
+        Cow::Owned(selected)
+    }
+
+    /// Opens the picker, shown in the settings of every export.
+    pub fn button(&mut self, ui: &mut Ui) {
+        let text = match self.excluded.len() {
+            0 => "Channels: all".to_string(),
+            excluded => format!("Channels: {} left out", excluded),
+        };
+        if ui.button(text).clicked() {
+            self.show = true;
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Export channels")
+            .open(&mut show)
+            .resizable(true)
+            .vscroll(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        ui.label("Images, captures and the data logger contain the selected channels");
+        ui.horizontal(|ui| {
+            if ui.button("all").clicked() {
+                self.excluded.clear();
+            }
+            if ui.button("none").clicked() {
+                self.excluded
+                    .extend(channels.iter().map(|channel| channel.to_string()));
+            }
+        });
+        for channel in channels {
+            let mut included = self.includes(channel);
+            if ui.checkbox(&mut included, *channel).changed() {
+                match included {
+                    true => self.excluded.remove(*channel),
+                    false => self.excluded.insert(channel.to_string()),
+                };
+            }
+        }
+
+        ui.separator();
+        let mut remove = None;
+        for (name, excluded) in &self.saved {
+            ui.horizontal(|ui| {
+                if ui.button(name).on_hover_text("apply").clicked() {
+                    self.excluded = excluded.clone();
+                    self.name = name.clone();
+                }
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    remove = Some(name.clone());
+                }
+            });
+        }
+        if let Some(name) = remove {
+            self.saved.remove(&name);
+        }
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.name);
+            let name = self.name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new("save selection"))
+                .clicked()
+            {
+                self.saved.insert(name.to_string(), self.excluded.clone());
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_parsing::DataValue;
+
+    #[test]
+    fn should_leave_out_the_deselected_channels() {
+        let (sender, mut receiver) = crossbeam::channel::unbounded();
+        for name in ["temperature", "pressure"] {
+            let value = DataValue {
+                name: name.to_string(),
+                value: 1.0,
+                timestamp: 1.0,
+                line: 0,
+            };
+            sender.send(vec![value]).unwrap();
+        }
+        let mut history = ValueHistory::with_capacity(100);
+        history.update(&mut receiver, 100, None);
+
+        let mut channels = ExportChannels::default();
+        assert!(matches!(channels.selected(&history), Cow::Borrowed(_)));
+
+        channels.excluded.insert("pressure".to_string());
+        channels.excluded.insert("fetch_count".to_string());
+        let selected = channels.selected(&history);
+        let mut names: Vec<_> = selected.channel_names().collect();
+        names.sort_unstable();
+        assert_eq!(names, ["latency_ms", "pending_messages", "temperature"]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use egui::Ui;
+use gilrs::{Axis, Button, EventType};
+
+const BUTTONS: [Button; 17] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+const AXES: [Axis; 8] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::RightStickX,
+    Axis::RightStickY,
+    Axis::LeftZ,
+    Axis::RightZ,
+    Axis::DPadX,
+    Axis::DPadY,
+];
+
+/// The placeholder in the message of an axis that is replaced by its position.
+const VALUE_PLACEHOLDER: &str = "<value>";
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Sends its message when pressed
+    Button(Button),
+    /// Sends its message with the position from -1 to 1 while it moves
+    Axis(Axis),
+}
+
+impl std::fmt::Display for Control {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Control::Button(button) => write!(f, "{:?}", button),
+            Control::Axis(axis) => write!(f, "{:?}", axis),
+        }
+    }
+}
+
+/// A message sent when a control is used, e.g. `South → start\n` or `LeftStickY → throttle:<value>\n`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub control: Control,
+    /// Supports the escapes `\n`, `\r` and `\t`, axes replace `<value>` with their position
+    pub message: String,
+}
+
+impl Binding {
+    fn message(&self, value: Option<f32>) -> String {
+        let message = unescape(&self.message);
+        match value {
+            Some(value) => message.replace(VALUE_PLACEHOLDER, &format!("{:.3}", value)),
+            None => message,
+        }
+    }
+}
+
+/// Sends messages to the serial port from the controls of a gamepad, to steer the device
+/// that is plotted, e.g. a robot.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct GamepadMapping {
+    bindings: Vec<Binding>,
+    /// Messages per second an axis sends at most while it moves
+    rate: f64,
+
+    /// Disabled on start, so a gamepad does not steer a device by accident
+    #[serde(skip)]
+    enabled: bool,
+    show: bool,
+    #[serde(skip)]
+    axes: HashMap<Axis, f32>,
+    /// The positions the axes were last sent with
+    #[serde(skip)]
+    sent_axes: HashMap<Axis, f32>,
+    #[serde(skip)]
+    last_axis_send: f64,
+    #[serde(skip)]
+    pending: Vec<String>,
+    #[serde(skip)]
+    last_message: Option<String>,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        Self {
+            bindings: Vec::new(),
+            rate: 10.0,
+            enabled: false,
+            show: false,
+            axes: HashMap::new(),
+            sent_axes: HashMap::new(),
+            last_axis_send: 0.0,
+            pending: Vec::new(),
+            last_message: None,
+        }
+    }
+}
+
+impl GamepadMapping {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn handle(&mut self, event: &EventType) {
+        match *event {
+            EventType::ButtonPressed(button, _) => self.press(button),
+            EventType::AxisChanged(axis, value, _) => {
+                self.axes.insert(axis, value);
+            }
+            _ => {}
+        }
+    }
+
+    fn press(&mut self, button: Button) {
+        if !self.enabled {
+            return;
+        }
+        let messages = self
+            .bindings
+            .iter()
+            .filter(|binding| binding.control == Control::Button(button))
+            .map(|binding| binding.message(None));
+        self.pending.extend(messages);
+    }
+
+    /// The messages to send by now, axes are limited to the configured rate.
+    pub fn take_messages(&mut self, now: f64) -> Vec<String> {
+        if self.enabled && now - self.last_axis_send >= 1.0 / self.rate {
+            self.last_axis_send = now;
+            for binding in &self.bindings {
+                let Control::Axis(axis) = binding.control else {
+                    continue;
+                };
+                let value = self.axes.get(&axis).copied().unwrap_or_default();
+                if self.sent_axes.insert(axis, value) != Some(value) {
+                    self.pending.push(binding.message(Some(value)));
+                }
+            }
+        }
+        if let Some(last) = self.pending.last() {
+            self.last_message = Some(last.clone());
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) {
+        let mut show = self.show;
+        egui::Window::new("Gamepad mapping")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Send to the serial port");
+        ui.add(
+            egui::DragValue::new(&mut self.rate)
+                .clamp_range(0.1..=1000.0)
+                .prefix("axes at most ")
+                .suffix(" /s"),
+        );
+
+        let mut remove = None;
+        egui::Grid::new("gamepad_bindings")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Control");
+                ui.strong("Message");
+                ui.end_row();
+                for (index, binding) in self.bindings.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_source(("gamepad_control", index))
+                        .selected_text(binding.control.to_string())
+                        .show_ui(ui, |ui| {
+                            let controls = BUTTONS
+                                .map(Control::Button)
+                                .into_iter()
+                                .chain(AXES.map(Control::Axis));
+                            for control in controls {
+                                ui.selectable_value(
+                                    &mut binding.control,
+                                    control,
+                                    control.to_string(),
+                                );
+                            }
+                        });
+                    ui.text_edit_singleline(&mut binding.message)
+                        .on_hover_text("\\n, \\r and \\t are escapes, axes replace <value> with their position from -1 to 1");
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            self.bindings.remove(index);
+        }
+        if ui.button("add binding").clicked() {
+            self.bindings.push(Binding {
+                control: Control::Button(Button::South),
+                message: "\\n".to_string(),
+            });
+        }
+
+        if let Some(message) = &self.last_message {
+            ui.label(format!("last sent: {:?}", message));
+        }
+    }
+}
+
+fn unescape(message: &str) -> String {
+    message
+        .replace("\\n", "\n")
+        .replace("\\r", "\r")
+        .replace("\\t", "\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_send_buttons_on_press_and_moved_axes_at_the_rate() {
+        let mut mapping = GamepadMapping {
+            bindings: vec![
+                Binding {
+                    control: Control::Button(Button::South),
+                    message: "start\\n".to_string(),
+                },
+                Binding {
+                    control: Control::Axis(Axis::LeftStickY),
+                    message: "throttle:<value>\\n".to_string(),
+                },
+            ],
+            rate: 2.0,
+            enabled: true,
+            ..Default::default()
+        };
+
+        mapping.press(Button::South);
+        mapping.axes.insert(Axis::LeftStickY, 0.5);
+        assert_eq!(mapping.take_messages(1.0), ["start\n", "throttle:0.500\n"]);
+
+        mapping.axes.insert(Axis::LeftStickY, 0.25);
+        assert!(mapping.take_messages(1.1).is_empty(), "limited by the rate");
+        assert_eq!(mapping.take_messages(1.5), ["throttle:0.250\n"]);
+        assert!(
+            mapping.take_messages(2.0).is_empty(),
+            "the axis did not move"
+        );
+    }
+}
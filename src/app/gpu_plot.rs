@@ -0,0 +1,252 @@
+use std::{num::NonZeroU64, ops::Range, sync::Arc};
+
+use eframe::egui_wgpu::{self, wgpu, wgpu::util::DeviceExt};
+use egui::{
+    plot::{PlotBounds, PlotPoint, PlotUi},
+    Color32, Rect, Ui,
+};
+
+const SHADER: &str = r#"
+struct Transform {
+    scale: vec2<f32>,
+    offset: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> transform: Transform;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position * transform.scale + transform.offset, 0.0, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+/// Maps plot coordinates to the normalized device coordinates of the plot frame.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Transform {
+    scale: [f32; 2],
+    offset: [f32; 2],
+}
+
+/// The part of the screen the plot covers and the values it shows there.
+pub struct PlotView {
+    frame: Rect,
+    bounds: PlotBounds,
+}
+
+impl PlotView {
+    /// The view of the previous frame, egui fixes the bounds of the current one after the items are added.
+    pub fn of(plot_ui: &PlotUi) -> Self {
+        let bounds = plot_ui.plot_bounds();
+        let [min_x, min_y] = bounds.min();
+        let [max_x, max_y] = bounds.max();
+        let frame = Rect::from_two_pos(
+            plot_ui.screen_from_plot(PlotPoint::new(min_x, min_y)),
+            plot_ui.screen_from_plot(PlotPoint::new(max_x, max_y)),
+        );
+        Self { frame, bounds }
+    }
+
+    fn transform(&self) -> Transform {
+        let [min_x, min_y] = self.bounds.min();
+        let scale_x = 2.0 / self.bounds.width();
+        let scale_y = 2.0 / self.bounds.height();
+        Transform {
+            scale: [scale_x as f32, scale_y as f32],
+            offset: [
+                (-1.0 - min_x * scale_x) as f32,
+                (-1.0 - min_y * scale_y) as f32,
+            ],
+        }
+    }
+}
+
+/// The gpu objects drawing the series, kept in the paint callback resources of the renderer.
+struct GpuPlotResources {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    transform: wgpu::Buffer,
+    vertices: Option<wgpu::Buffer>,
+    /// The vertices of each series in `vertices`
+    ranges: Vec<Range<u32>>,
+}
+
+impl GpuPlotResources {
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        transform: Transform,
+        vertices: &[Vertex],
+        ranges: &[Range<u32>],
+    ) {
+        queue.write_buffer(&self.transform, 0, bytemuck::bytes_of(&transform));
+        let bytes: &[u8] = bytemuck::cast_slice(vertices);
+        // The buffer only grows, so a steady stream of samples does not allocate every frame
+        let too_small = match &self.vertices {
+            Some(buffer) => buffer.size() < bytes.len() as u64,
+            None => true,
+        };
+        if too_small {
+            self.vertices = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("plot_vertices"),
+                size: (bytes.len() as u64).next_power_of_two().max(64),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+        if let Some(buffer) = &self.vertices {
+            queue.write_buffer(buffer, 0, bytes);
+        }
+        self.ranges = ranges.to_vec();
+    }
+
+    fn paint<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        let Some(vertices) = &self.vertices else {
+            return;
+        };
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        for range in &self.ranges {
+            render_pass.draw(range.clone(), 0..1);
+        }
+    }
+}
+
+/// Creates the pipeline drawing the series, without it [`paint`] draws nothing.
+pub fn init(render_state: &egui_wgpu::RenderState) {
+    let device = &render_state.device;
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("plot_shader"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("plot_bind_group_layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(std::mem::size_of::<Transform>() as u64),
+            },
+            count: None,
+        }],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("plot_pipeline_layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("plot_pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4],
+            }],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(render_state.target_format.into())],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::LineStrip,
+            ..Default::default()
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+    let transform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("plot_transform"),
+        contents: bytemuck::bytes_of(&Transform {
+            scale: [1.0, 1.0],
+            offset: [0.0, 0.0],
+        }),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("plot_bind_group"),
+        layout: &bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: transform.as_entire_binding(),
+        }],
+    });
+
+    render_state
+        .renderer
+        .write()
+        .paint_callback_resources
+        .insert(GpuPlotResources {
+            pipeline,
+            bind_group,
+            transform,
+            vertices: None,
+            ranges: Vec::new(),
+        });
+}
+
+/// Draws the series as line strips over the frame of the plot, without decimating them.
+pub fn paint(ui: &Ui, view: &PlotView, series: &[(Vec<[f64; 2]>, Color32)]) {
+    let mut vertices = Vec::with_capacity(series.iter().map(|(points, _)| points.len()).sum());
+    let mut ranges = Vec::with_capacity(series.len());
+    for (points, color) in series {
+        let [r, g, b, a] = color.to_array();
+        let color = [r, g, b, a].map(|channel| channel as f32 / 255.0);
+        let start = vertices.len() as u32;
+        vertices.extend(points.iter().map(|&[x, y]| Vertex {
+            position: [x as f32, y as f32],
+            color,
+        }));
+        ranges.push(start..vertices.len() as u32);
+    }
+
+    let transform = view.transform();
+    let vertices = Arc::new(vertices);
+    let callback = egui_wgpu::CallbackFn::new()
+        .prepare(move |device, queue, _encoder, resources| {
+            if let Some(resources) = resources.get_mut::<GpuPlotResources>() {
+                resources.prepare(device, queue, transform, &vertices, &ranges);
+            }
+            Vec::new()
+        })
+        .paint(|_info, render_pass, resources| {
+            if let Some(resources) = resources.get::<GpuPlotResources>() {
+                resources.paint(render_pass);
+            }
+        });
+
+    ui.painter_at(view.frame).add(egui::PaintCallback {
+        rect: view.frame,
+        callback: Arc::new(callback),
+    });
+}
@@ -0,0 +1,194 @@
+use egui::{
+    plot::{Bar, BarChart, Plot},
+    Ui,
+};
+
+use super::value_history::{PlotSettings, ValueHistory, YRange};
+
+/// The counts of the samples falling into equally wide bins.
+#[derive(Debug, Clone, PartialEq)]
+struct Bins {
+    start: f64,
+    width: f64,
+    counts: Vec<usize>,
+    /// Samples outside of a fixed range
+    outside: usize,
+}
+
+/// Sorts `values` into `count` bins spanning `range`, or the range of the values if there is none.
+fn bins(values: &[f64], count: usize, range: Option<YRange>) -> Option<Bins> {
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let YRange { min, max, .. } = match range {
+        Some(range) => range,
+        None => finite.clone().fold(None, |range, value| {
+            let YRange { min, max, .. } = range.unwrap_or(YRange::new(value, value));
+            Some(YRange::new(min.min(value), max.max(value)))
+        })?,
+    };
+    let count = count.max(1);
+    // A constant signal still gets a bin of its own
+    let width = if max > min {
+        (max - min) / count as f64
+    } else {
+        1.0
+    };
+
+    let mut bins = Bins {
+        start: min,
+        width,
+        counts: vec![0; count],
+        outside: 0,
+    };
+    for value in finite {
+        if value < min || value > max {
+            bins.outside += 1;
+            continue;
+        }
+        // The maximum belongs to the last bin
+        let index = (((value - min) / width) as usize).min(count - 1);
+        bins.counts[index] += 1;
+    }
+    Some(bins)
+}
+
+/// A live histogram of the newest samples of a channel, e.g. to characterize the noise of an ADC.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Histogram {
+    channel: String,
+    /// The number of newest samples counted
+    samples: usize,
+    bins: usize,
+    /// The range divided into the bins, the range of the samples if there is none
+    range: Option<YRange>,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            samples: 1000,
+            bins: 32,
+            range: None,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        history: &ValueHistory,
+        plot: &PlotSettings,
+        channels: &[&str],
+    ) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("histogram_channel")
+                .selected_text(&self.channel)
+                .show_ui(ui, |ui| {
+                    for channel in channels {
+                        ui.selectable_value(&mut self.channel, channel.to_string(), *channel);
+                    }
+                });
+            ui.add(
+                egui::DragValue::new(&mut self.samples)
+                    .clamp_range(2..=1_000_000)
+                    .speed(10.0)
+                    .suffix(" samples"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.bins)
+                    .clamp_range(1..=1000)
+                    .suffix(" bins"),
+            );
+            let mut fixed = self.range.is_some();
+            ui.checkbox(&mut fixed, "Fixed range");
+            match (fixed, &self.range) {
+                (true, None) => self.range = Some(YRange::new(0.0, 1.0)),
+                (false, Some(_)) => self.range = None,
+                _ => {}
+            }
+            if let Some(YRange { min, max, .. }) = &mut self.range {
+                ui.add(egui::DragValue::new(min).speed(0.1).prefix("min: "));
+                ui.add(egui::DragValue::new(max).speed(0.1).prefix("max: "));
+                *max = max.max(*min + f64::EPSILON);
+            }
+        });
+
+        let Some(buffer) = history.samples(&self.channel) else {
+            ui.label("Select a channel that received values");
+            return;
+        };
+        let start = buffer.len().saturating_sub(self.samples);
+        let values: Vec<f64> = buffer
+            .range(start..buffer.len())
+            .map(|sample| plot.calibrated(&self.channel, sample.value))
+            .filter(|value| value.is_finite())
+            .collect();
+        let Some(bins) = bins(&values, self.bins, self.range).filter(|_| !values.is_empty()) else {
+            ui.label("No finite values");
+            return;
+        };
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / values.len() as f64;
+        let unit = plot
+            .unit(&self.channel)
+            .map_or(String::new(), |unit| format!(" {}", unit));
+        ui.horizontal(|ui| {
+            ui.label(format!("{} samples", values.len()));
+            ui.label(format!("mean {:.6}{}", mean, unit));
+            ui.label(format!("std dev {:.6}{}", variance.sqrt(), unit));
+            if bins.outside > 0 {
+                ui.label(format!("{} outside of the range", bins.outside));
+            }
+        });
+
+        let bars = bins
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                Bar::new(
+                    bins.start + bins.width * (index as f64 + 0.5),
+                    *count as f64,
+                )
+                .width(bins.width)
+            })
+            .collect();
+        Plot::new("histogram")
+            .auto_bounds_x()
+            .auto_bounds_y()
+            .show(ui, |plot_ui| {
+                plot_ui.bar_chart(BarChart::new(bars).name(&self.channel));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_values_into_bins() {
+        let values = [0.0, 0.1, 0.5, 0.9, 1.0, f64::NAN];
+        assert_eq!(
+            bins(&values, 2, None),
+            Some(Bins {
+                start: 0.0,
+                width: 0.5,
+                counts: vec![2, 3],
+                outside: 0,
+            })
+        );
+        assert_eq!(
+            bins(&values, 1, Some(YRange::new(0.2, 0.6))).map(|bins| bins.counts),
+            Some(vec![1])
+        );
+        assert_eq!(bins(&[f64::NAN], 4, None), None);
+    }
+}
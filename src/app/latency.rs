@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use egui::Ui;
+
+use super::{
+    condition::{condition_ui, crossings, Condition, Edge},
+    value_history::{Sample, ValueHistory},
+};
+use crate::value_parsing::unix_timestamp;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub enum Stimulus {
+    /// The operator marks the stimulus by pressing a button.
+    Marker,
+    Channel(Condition),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Statistics {
+    count: usize,
+    last: f64,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+impl Statistics {
+    fn add(&mut self, latency: f64) {
+        if self.count == 0 {
+            self.min = latency;
+            self.max = latency;
+        }
+        self.count += 1;
+        self.last = latency;
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+        self.sum += latency;
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Measures the delay from a stimulus until each response channel crosses its threshold.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LatencyMeasurement {
+    stimulus: Stimulus,
+    responses: Vec<Condition>,
+    /// Responses later than this many seconds after the stimulus are not attributed to it.
+    timeout: f64,
+
+    show: bool,
+    #[serde(skip)]
+    tracker: LatencyTracker,
+    /// The last processed sample of every channel involved, used to detect edges.
+    #[serde(skip)]
+    previous: HashMap<String, Sample>,
+}
+
+impl Default for LatencyMeasurement {
+    fn default() -> Self {
+        Self {
+            stimulus: Stimulus::Marker,
+            responses: Vec::new(),
+            timeout: 1.0,
+            show: false,
+            tracker: LatencyTracker::default(),
+            previous: HashMap::new(),
+        }
+    }
+}
+
+impl LatencyMeasurement {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn update(&mut self, history: &ValueHistory) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!("latency measurement");
+
+        self.tracker.timeout = self.timeout;
+        self.tracker.responses.resize(self.responses.len(), None);
+        self.tracker
+            .statistics
+            .resize(self.responses.len(), Statistics::default());
+
+        if let Stimulus::Channel(condition) = &self.stimulus {
+            for time in crossings(history, condition, &mut self.previous) {
+                self.tracker.stimulus(time);
+            }
+        }
+
+        for (index, condition) in self.responses.iter().enumerate() {
+            for time in crossings(history, condition, &mut self.previous) {
+                self.tracker.response(index, time);
+            }
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, history: &ValueHistory) {
+        let mut show = self.show;
+        egui::Window::new("Latency measurement")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, history));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, history: &ValueHistory) {
+        let mut channels: Vec<&str> = history.channel_names().collect();
+        channels.sort_unstable();
+
+        ui.heading("Stimulus");
+        ui.horizontal(|ui| {
+            let is_marker = self.stimulus == Stimulus::Marker;
+            if ui.selectable_label(is_marker, "Marker").clicked() && !is_marker {
+                self.stimulus = Stimulus::Marker;
+            }
+            if ui.selectable_label(!is_marker, "Channel edge").clicked() && is_marker {
+                self.stimulus = Stimulus::Channel(Condition {
+                    channel: channels.first().copied().unwrap_or_default().to_string(),
+                    threshold: 0.0,
+                    edge: Edge::Rising,
+                });
+            }
+        });
+        match &mut self.stimulus {
+            Stimulus::Marker => {
+                if ui.button("Mark stimulus now").clicked() {
+                    self.tracker.stimulus(unix_timestamp());
+                }
+            }
+            Stimulus::Channel(condition) => {
+                condition_ui(ui, "stimulus", condition, &channels);
+            }
+        }
+        ui.add(
+            egui::DragValue::new(&mut self.timeout)
+                .clamp_range(0.001..=3600.0)
+                .speed(0.01)
+                .prefix("timeout: ")
+                .suffix(" s"),
+        );
+
+        ui.separator();
+        ui.heading("Responses");
+        let mut removed = None;
+        for (index, condition) in self.responses.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                condition_ui(ui, &format!("response {index}"), condition, &channels);
+                if ui.button("🗑").on_hover_text("remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            self.responses.remove(index);
+            self.tracker.remove_response(index);
+        }
+        if ui.button("add response").clicked() {
+            self.responses.push(Condition {
+                channel: channels.first().copied().unwrap_or_default().to_string(),
+                threshold: 0.0,
+                edge: Edge::Rising,
+            });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.heading("Results");
+            if ui.button("reset").clicked() {
+                self.tracker.reset();
+            }
+        });
+        egui::Grid::new("latency_results")
+            .striped(true)
+            .num_columns(6)
+            .show(ui, |ui| {
+                for header in ["channel", "count", "last", "min", "mean", "max"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                for (condition, statistics) in self.responses.iter().zip(&self.tracker.statistics) {
+                    ui.label(&condition.channel);
+                    ui.label(statistics.count.to_string());
+                    if statistics.count > 0 {
+                        for seconds in [
+                            statistics.last,
+                            statistics.min,
+                            statistics.mean(),
+                            statistics.max,
+                        ] {
+                            ui.monospace(format!("{:.1} ms", seconds * 1000.0));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// Pairs stimuli with the first matching response of each response channel.
+#[derive(Debug, Default)]
+struct LatencyTracker {
+    timeout: f64,
+    /// Recent stimuli, oldest first
+    stimuli: Vec<f64>,
+    /// The stimulus each response channel has already answered
+    responses: Vec<Option<f64>>,
+    statistics: Vec<Statistics>,
+}
+
+impl LatencyTracker {
+    fn stimulus(&mut self, time: f64) {
+        self.stimuli.push(time);
+        // Stimuli older than the timeout can no longer be answered
+        let timeout = self.timeout;
+        self.stimuli.retain(|x| time - x <= timeout);
+    }
+
+    fn response(&mut self, index: usize, time: f64) {
+        let Some(stimulus) = self.stimuli.iter().rev().find(|x| **x <= time).copied() else {
+            return;
+        };
+        if time - stimulus > self.timeout || self.responses[index] == Some(stimulus) {
+            return;
+        }
+
+        self.responses[index] = Some(stimulus);
+        self.statistics[index].add(time - stimulus);
+    }
+
+    fn remove_response(&mut self, index: usize) {
+        if index < self.responses.len() {
+            self.responses.remove(index);
+            self.statistics.remove(index);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.stimuli.clear();
+        self.responses.iter_mut().for_each(|x| *x = None);
+        self.statistics
+            .iter_mut()
+            .for_each(|x| *x = Statistics::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(responses: usize) -> LatencyTracker {
+        LatencyTracker {
+            timeout: 1.0,
+            stimuli: Vec::new(),
+            responses: vec![None; responses],
+            statistics: vec![Statistics::default(); responses],
+        }
+    }
+
+    #[test]
+    fn should_measure_first_response_only() {
+        let mut tracker = tracker(2);
+
+        tracker.stimulus(10.0);
+        tracker.response(0, 10.25);
+        tracker.response(0, 10.5);
+        tracker.response(1, 10.5);
+
+        assert_eq!(tracker.statistics[0].count, 1);
+        assert_eq!(tracker.statistics[0].last, 0.25);
+        assert_eq!(tracker.statistics[1].count, 1);
+        assert_eq!(tracker.statistics[1].last, 0.5);
+    }
+
+    #[test]
+    fn should_ignore_responses_after_timeout() {
+        let mut tracker = tracker(1);
+
+        tracker.response(0, 1.0);
+        tracker.stimulus(2.0);
+        tracker.response(0, 3.5);
+
+        assert_eq!(tracker.statistics[0].count, 0);
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeSet;
+
+use egui::{Color32, Rect, Sense, Ui};
+
+/// The legend above the plot, a click hides a series, a double click shows only that series and
+/// dragging an entry changes the order the series are drawn in. Its context menu moves a series to
+/// the right y axis.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct SeriesLegend {
+    /// The series by their label, later ones are drawn on top
+    order: Vec<String>,
+    hidden: BTreeSet<String>,
+    /// Series scaled to a y axis of their own on the right, e.g. a pressure next to a temperature
+    right_axis: BTreeSet<String>,
+
+    /// The entry being dragged to another position
+    #[serde(skip)]
+    dragged: Option<String>,
+}
+
+impl SeriesLegend {
+    /// Adds the series that are not in the legend yet at its end.
+    pub fn update<'a>(&mut self, labels: impl Iterator<Item = &'a String>) {
+        let mut new: Vec<&String> = labels.filter(|label| !self.order.contains(label)).collect();
+        new.sort_unstable();
+        self.order.extend(new.into_iter().cloned());
+    }
+
+    /// The position of a series in the legend, series unknown to it come last.
+    pub fn position(&self, label: &str) -> usize {
+        self.order
+            .iter()
+            .position(|x| x == label)
+            .unwrap_or(self.order.len())
+    }
+
+    pub fn shows(&self, label: &str) -> bool {
+        !self.hidden.contains(label)
+    }
+
+    pub fn on_right_axis(&self, label: &str) -> bool {
+        self.right_axis.contains(label)
+    }
+
+    /// Hides every series of `labels` but `solo`, or shows all of them again if it is the only
+    /// one shown already.
+    fn solo(&mut self, solo: &str, labels: &[&str]) {
+        let others = labels.iter().filter(|label| **label != solo);
+        if self.shows(solo) && others.clone().all(|label| !self.shows(label)) {
+            self.hidden.clear();
+        } else {
+            self.hidden = others.map(|label| label.to_string()).collect();
+        }
+    }
+
+    /// Moves the series `label` to the position of `target`.
+    fn move_to(&mut self, label: &str, target: &str) {
+        let (Some(from), Some(to)) = (
+            self.order.iter().position(|x| x == label),
+            self.order.iter().position(|x| x == target),
+        ) else {
+            return;
+        };
+        let label = self.order.remove(from);
+        self.order.insert(to, label);
+    }
+
+    /// Draws the entries of `series`, their label and color in the order of the legend, hovering
+    /// an entry shows its sample rate.
+    pub fn ui(&mut self, ui: &mut Ui, series: &[(&str, Color32, Option<f64>)]) {
+        let labels: Vec<&str> = series.iter().map(|(label, _, _)| *label).collect();
+        let mut entries: Vec<(&str, Rect)> = Vec::with_capacity(series.len());
+        let mut toggled = None;
+        let mut moved = None;
+        let mut solo = None;
+        let mut dropped = false;
+        ui.horizontal_wrapped(|ui| {
+            for (label, color, rate) in series {
+                let shown = self.shows(label);
+                let color = if shown {
+                    *color
+                } else {
+                    ui.visuals().weak_text_color()
+                };
+                let right = self.on_right_axis(label);
+                let axis = if right { " (right)" } else { "" };
+                let text = egui::RichText::new(format!("⏺ {}{}", label, axis)).color(color);
+                let text = if shown { text } else { text.strikethrough() };
+                let response = ui
+                    .add(egui::Label::new(text).sense(Sense::click_and_drag()))
+                    .on_hover_text(format!(
+                        "{}\nClick to hide, double click to show only this series, drag to reorder",
+                        rate.map_or("no sample rate yet".to_string(), |rate| format!(
+                            "{:.1} Hz",
+                            rate
+                        ))
+                    ))
+                    .context_menu(|ui| {
+                        let mut right = right;
+                        if ui.checkbox(&mut right, "Right y axis").changed() {
+                            moved = Some((*label, right));
+                            ui.close_menu();
+                        }
+                    });
+                if response.double_clicked() {
+                    solo = Some(*label);
+                } else if response.clicked() {
+                    toggled = Some(*label);
+                }
+                if response.drag_started() {
+                    self.dragged = Some(label.to_string());
+                }
+                dropped |= response.drag_released();
+                entries.push((label, response.rect));
+            }
+        });
+        if let Some(label) = toggled {
+            if !self.hidden.remove(label) {
+                self.hidden.insert(label.to_string());
+            }
+        }
+        if let Some((label, right)) = moved {
+            if right {
+                self.right_axis.insert(label.to_string());
+            } else {
+                self.right_axis.remove(label);
+            }
+        }
+        if let Some(label) = solo {
+            self.solo(label, &labels);
+        }
+        if let Some(dragged) = &self.dragged {
+            let pointer = ui.input(|x| x.pointer.interact_pos());
+            let target = pointer.and_then(|pointer| {
+                entries
+                    .iter()
+                    .find(|(_, rect)| rect.contains(pointer))
+                    .copied()
+            });
+            if let Some((target, rect)) = target {
+                ui.painter()
+                    .rect_stroke(rect, 2.0, ui.visuals().selection.stroke);
+                if dropped {
+                    let dragged = dragged.clone();
+                    self.move_to(&dragged, target);
+                }
+            }
+        }
+        if dropped {
+            self.dragged = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_solo_and_reorder_series() {
+        let mut legend = SeriesLegend::default();
+        let labels = ["a", "b", "c"].map(str::to_string);
+        legend.update(labels.iter().rev());
+        assert_eq!(legend.order, labels);
+
+        legend.solo("b", &["a", "b", "c"]);
+        assert!(!legend.shows("a") && legend.shows("b") && !legend.shows("c"));
+        legend.solo("b", &["a", "b", "c"]);
+        assert!(legend.shows("a") && legend.shows("c"));
+
+        legend.move_to("c", "a");
+        assert_eq!(legend.order, ["c", "a", "b"]);
+        assert_eq!(legend.position("unknown"), 3);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeSet;
+
+use egui::{Color32, Rect, Sense, Ui};
+
+use super::value_history::{PlotSettings, ValueHistory, XAxis};
+
+/// The height of a lane in points.
+const LANE_HEIGHT: f32 = 18.0;
+
+/// The stretches of `series` within `range` where the level is held, and whether it is high.
+///
+/// A value is held until the next sample, any value but zero is high.
+fn levels(series: &[[f64; 2]], [min, max]: [f64; 2]) -> Vec<(f64, f64, bool)> {
+    let mut levels: Vec<(f64, f64, bool)> = Vec::new();
+    for pair in series.windows(2) {
+        let ([start, value], [end, _]) = (pair[0], pair[1]);
+        let (start, end) = (start.max(min), end.min(max));
+        if start >= end {
+            continue;
+        }
+        let high = value != 0.0;
+        match levels.last_mut() {
+            Some((_, last_end, last_high)) if *last_high == high && *last_end == start => {
+                *last_end = end;
+            }
+            _ => levels.push((start, end, high)),
+        }
+    }
+    levels
+}
+
+/// Digital channels drawn as lanes of high and low levels below the plot, like a logic analyzer,
+/// instead of squashed lines between the analog ones.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct LogicLanes {
+    /// The digital channels, by the name the samples are stored under
+    pub channels: BTreeSet<String>,
+
+    show: bool,
+}
+
+impl LogicLanes {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, history: &ValueHistory) {
+        let mut show = self.show;
+        egui::Window::new("Logic lanes")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, history));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, history: &ValueHistory) {
+        let mut channels: Vec<&str> = history.channel_names().collect();
+        channels.sort_unstable();
+        if ui
+            .button("Detect")
+            .on_hover_text("Marks the channels that only received 0 and 1 as digital")
+            .clicked()
+        {
+            let binary = channels.iter().filter(|name| history.is_binary(name));
+            self.channels.extend(binary.map(|name| name.to_string()));
+        }
+        for channel in channels {
+            let mut digital = self.channels.contains(channel);
+            if ui.checkbox(&mut digital, channel).changed() {
+                if digital {
+                    self.channels.insert(channel.to_string());
+                } else {
+                    self.channels.remove(channel);
+                }
+            }
+        }
+    }
+
+    /// Draws a lane for every digital channel, aligned with the plot above that spans `plot` and
+    /// shows the x range `shown`.
+    pub fn paint(
+        &self,
+        ui: &mut Ui,
+        history: &ValueHistory,
+        plot_settings: &PlotSettings,
+        x_axis: XAxis,
+        plot: Rect,
+        shown: [f64; 2],
+    ) {
+        let [min, max] = shown;
+        if max <= min {
+            return;
+        }
+        let high_fill = ui.visuals().selection.bg_fill;
+        let stroke = ui.visuals().widgets.noninteractive.fg_stroke;
+        let text_color = ui.visuals().text_color();
+        let max_points = (plot.width() * 2.0).max(2.0) as usize;
+        for channel in &self.channels {
+            let Some(series) = plot_settings.channel_series(history, channel, x_axis, max_points)
+            else {
+                continue;
+            };
+            let (row, _) = ui.allocate_exact_size(
+                egui::vec2(ui.available_width(), LANE_HEIGHT),
+                Sense::hover(),
+            );
+            let lane =
+                Rect::from_x_y_ranges(plot.x_range(), row.y_range()).shrink2(egui::vec2(0.0, 2.0));
+            let painter = ui.painter_at(lane);
+            let x = |value: f64| lane.left() + ((value - min) / (max - min)) as f32 * lane.width();
+            let mut previous: Option<f32> = None;
+            for (start, end, high) in levels(&series, shown) {
+                let y = if high { lane.top() } else { lane.bottom() };
+                let (start, end) = (x(start), x(end));
+                if high {
+                    painter.rect_filled(
+                        Rect::from_x_y_ranges(start..=end, lane.y_range()),
+                        0.0,
+                        high_fill,
+                    );
+                }
+                if let Some(previous) = previous.filter(|previous| *previous != y) {
+                    painter
+                        .line_segment([egui::pos2(start, previous), egui::pos2(start, y)], stroke);
+                }
+                painter.line_segment([egui::pos2(start, y), egui::pos2(end, y)], stroke);
+                previous = Some(y);
+            }
+            painter.text(
+                lane.left_center() + egui::vec2(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                channel,
+                egui::FontId::proportional(11.0),
+                text_color,
+            );
+            ui.painter()
+                .hline(lane.x_range(), row.bottom(), (0.5, Color32::GRAY));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_merge_held_levels_within_the_range() {
+        let series = [[0.0, 0.0], [1.0, 1.0], [2.0, 1.0], [3.0, 0.0], [4.0, 0.0]];
+        assert_eq!(
+            levels(&series, [0.5, 3.5]),
+            vec![(0.5, 1.0, false), (1.0, 3.0, true), (3.0, 3.5, false)]
+        );
+        assert!(levels(&series, [5.0, 6.0]).is_empty());
+    }
+}
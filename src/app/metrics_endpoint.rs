@@ -0,0 +1,68 @@
+use std::sync::{Mutex, PoisonError};
+
+use egui::Ui;
+
+use crate::{
+    metrics::MetricsSink,
+    sinks::{DataSink, Sinks},
+};
+
+/// Serves the last values and sample rates of the channels for Prometheus while plotting
+/// continues.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct MetricsEndpoint {
+    /// The address the endpoint listens on, e.g. `127.0.0.1:9898`
+    address: String,
+
+    /// The name of the running sink
+    #[serde(skip)]
+    running: Option<String>,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl Default for MetricsEndpoint {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:9898".to_string(),
+            running: None,
+            error: None,
+        }
+    }
+}
+
+impl MetricsEndpoint {
+    pub fn ui(&mut self, ui: &mut Ui, sinks: &Mutex<Sinks>) {
+        let mut enabled = self.running.is_some();
+        ui.add_enabled_ui(!enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Address");
+                ui.text_edit_singleline(&mut self.address)
+                    .on_hover_text("0.0.0.0 accepts requests from other computers");
+            });
+        });
+        if ui.checkbox(&mut enabled, "Serve metrics").changed() {
+            let mut sinks = sinks.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(name) = self.running.take() {
+                sinks.remove(&name);
+            }
+            if enabled {
+                match MetricsSink::bind(self.address.trim()) {
+                    Ok(sink) => {
+                        self.running = Some(sink.name().to_string());
+                        self.error = None;
+                        sinks.add(Box::new(sink));
+                    }
+                    Err(err) => self.error = Some(format!("{}: {}", self.address, err)),
+                }
+            }
+        }
+        if let Some(name) = &self.running {
+            ui.hyperlink(name);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
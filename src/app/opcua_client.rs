@@ -0,0 +1,178 @@
+#[cfg(feature = "opcua")]
+use std::time::Duration;
+
+use egui::Ui;
+
+use super::event_log::{EventKind, EventLog};
+#[cfg(feature = "opcua")]
+use crate::value_parsing::OpcUaSource;
+use crate::value_parsing::{DataSource, MonitoredNode, SourceSenders};
+
+/// A connection to an OPC UA server next to the serial port, to trend the values of e.g. a PLC
+/// together with those of the device.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct OpcUaClient {
+    /// The url of the server, e.g. `opc.tcp://localhost:4840`
+    endpoint: String,
+    nodes: Vec<MonitoredNode>,
+    /// How often the server sends the changed values, in milliseconds
+    publishing_interval: f64,
+
+    show: bool,
+    #[serde(skip)]
+    source: Option<Box<dyn DataSource>>,
+}
+
+impl Default for OpcUaClient {
+    fn default() -> Self {
+        Self {
+            endpoint: "opc.tcp://localhost:4840".to_string(),
+            nodes: Vec::new(),
+            publishing_interval: 100.0,
+            show: false,
+            source: None,
+        }
+    }
+}
+
+impl OpcUaClient {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Returns whether connecting was requested.
+    pub fn window(&mut self, ctx: &egui::Context, event_log: &mut EventLog) -> bool {
+        let mut show = self.show;
+        let mut connect = false;
+        egui::Window::new("OPC UA")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| connect = self.ui(ui, event_log));
+        self.show = show;
+        connect
+    }
+
+    /// Subscribes to the nodes, the values are received like those of the serial port.
+    pub fn connect(&mut self, senders: SourceSenders, event_log: &mut EventLog) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+        self.source = self.start(senders);
+        if let Some(source) = &self.source {
+            let channels: Vec<&str> = self.nodes.iter().map(MonitoredNode::channel_name).collect();
+            let message = format!("{}: {}", source.name(), channels.join(", "));
+            event_log.record(EventKind::Connected, message);
+        }
+    }
+
+    #[cfg(feature = "opcua")]
+    fn start(&self, senders: SourceSenders) -> Option<Box<dyn DataSource>> {
+        let publishing_interval = Duration::from_secs_f64(self.publishing_interval / 1000.0);
+        Some(Box::new(OpcUaSource::start(
+            self.endpoint.trim().to_string(),
+            self.nodes.clone(),
+            publishing_interval,
+            senders,
+        )))
+    }
+
+    #[cfg(not(feature = "opcua"))]
+    fn start(&self, _senders: SourceSenders) -> Option<Box<dyn DataSource>> {
+        None
+    }
+
+    /// Forgets the connection once the source stopped on its own, it reports why.
+    pub fn update(&mut self) {
+        if self
+            .source
+            .as_ref()
+            .is_some_and(|source| !source.is_running())
+        {
+            self.source = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, event_log: &mut EventLog) -> bool {
+        let connected = self.source.is_some();
+        ui.add_enabled_ui(!connected, |ui| {
+            egui::Grid::new("opcua_settings").show(ui, |ui| {
+                ui.label("Endpoint");
+                ui.text_edit_singleline(&mut self.endpoint);
+                ui.end_row();
+
+                ui.label("Publishing interval");
+                ui.add(
+                    egui::DragValue::new(&mut self.publishing_interval)
+                        .clamp_range(10.0..=60_000.0)
+                        .suffix(" ms"),
+                );
+                ui.end_row();
+            });
+
+            ui.separator();
+            let mut remove = None;
+            egui::Grid::new("opcua_nodes").striped(true).show(ui, |ui| {
+                ui.strong("Node id");
+                ui.strong("Channel");
+                ui.end_row();
+                for (index, node) in self.nodes.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut node.node_id)
+                        .on_hover_text("e.g. ns=2;s=Temperature or ns=3;i=1001");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut node.channel)
+                            .hint_text(node.node_id.as_str()),
+                    );
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = remove {
+                self.nodes.remove(index);
+            }
+            if ui.button("add node").clicked() {
+                self.nodes.push(MonitoredNode::default());
+            }
+        });
+
+        ui.separator();
+        match &mut self.source {
+            Some(source) => {
+                let disconnect = ui
+                    .horizontal(|ui| {
+                        ui.label(format!("Connected to {}", source.name()));
+                        ui.button("disconnect").clicked()
+                    })
+                    .inner;
+                if disconnect {
+                    source.stop();
+                    event_log.record(EventKind::Disconnected, source.name());
+                    self.source = None;
+                }
+                false
+            }
+            None if cfg!(feature = "opcua") => {
+                let can_connect = !self.endpoint.trim().is_empty()
+                    && !self.nodes.is_empty()
+                    && self
+                        .nodes
+                        .iter()
+                        .all(|node| !node.node_id.trim().is_empty());
+                ui.add_enabled(can_connect, egui::Button::new("connect"))
+                    .clicked()
+            }
+            None => {
+                ui.label("Built without the `opcua` feature");
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use egui::{
+    plot::{Line, Plot, PlotBounds, PlotPoints, Polygon},
+    Color32, Ui,
+};
+
+/// The range of the x axis the main plot shows, and a range the overview moves it to.
+///
+/// egui keeps the bounds of the plot itself, whether they were zoomed or moved is kept here so the
+/// plot is restored together with its Live button.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Viewport {
+    /// The range shown in the last frame
+    pub shown: Option<[f64; 2]>,
+    /// Applied to the main plot in the next frame
+    #[serde(skip)]
+    pub requested: Option<[f64; 2]>,
+    /// Moves the main plot by these fractions of its width and height in the next frame
+    #[serde(skip)]
+    pan: Option<[f64; 2]>,
+    /// The plot was zoomed or moved, it stays there while new values keep arriving
+    pub browsing: bool,
+    /// Returns the main plot to the automatic bounds in the next frame
+    #[serde(skip)]
+    follow: bool,
+}
+
+impl Viewport {
+    /// Moves the main plot to `requested` and remembers the range it shows.
+    pub fn apply(&mut self, bounds: PlotBounds) -> Option<PlotBounds> {
+        let [_, min_y] = bounds.min();
+        let [_, max_y] = bounds.max();
+        let mut requested = self
+            .requested
+            .take()
+            .map(|[min_x, max_x]| PlotBounds::from_min_max([min_x, min_y], [max_x, max_y]));
+        if let Some([x, y]) = self.pan.take() {
+            let [min_x, min_y] = requested.unwrap_or(bounds).min();
+            let [max_x, max_y] = requested.unwrap_or(bounds).max();
+            let dx = x * (max_x - min_x);
+            let dy = y * (max_y - min_y);
+            requested = Some(PlotBounds::from_min_max(
+                [min_x + dx, min_y + dy],
+                [max_x + dx, max_y + dy],
+            ));
+        }
+        let [min_x, _] = requested.unwrap_or(bounds).min();
+        let [max_x, _] = requested.unwrap_or(bounds).max();
+        self.shown = Some([min_x, max_x]);
+        self.browsing |= requested.is_some();
+        requested
+    }
+
+    /// Follows the newest values again, leaving the range the plot was zoomed or moved to.
+    pub fn follow_live(&mut self) {
+        self.browsing = false;
+        self.follow = true;
+    }
+
+    /// Whether the automatic bounds are to be restored in this frame.
+    pub fn take_follow(&mut self) -> bool {
+        std::mem::take(&mut self.follow)
+    }
+
+    /// Moves the plot by fractions of its width and height, e.g. `[0.1, 0.0]` to the right.
+    pub fn pan(&mut self, fraction: [f64; 2]) {
+        let [x, y] = self.pan.unwrap_or_default();
+        self.pan = Some([x + fraction[0], y + fraction[1]]);
+    }
+
+    /// Shows the range between `a` and `b`, in either order, unless they are too close to tell apart.
+    fn select(&mut self, a: f64, b: f64) {
+        let [min, max] = [a.min(b), a.max(b)];
+        if max - min > f64::EPSILON * max.abs().max(1.0) {
+            self.requested = Some([min, max]);
+        }
+    }
+
+    /// Centers the shown range at `x`, keeping its width.
+    fn center_at(&mut self, x: f64) {
+        if let Some([min, max]) = self.shown {
+            let half = (max - min) / 2.0;
+            self.requested = Some([x - half, x + half]);
+        }
+    }
+}
+
+/// A strip below the plot with the whole history, dragging the highlighted range moves the main
+/// plot and dragging elsewhere selects the range it shows, like the overview of an audio editor.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Overview {
+    pub enabled: bool,
+    pub viewport: Viewport,
+    /// Where the drag selecting a new range started
+    #[serde(skip)]
+    selecting: Option<f64>,
+}
+
+impl Overview {
+    /// Draws the decimated `series` and the range the main plot shows.
+    pub fn ui(&mut self, ui: &mut Ui, series: Vec<Vec<[f64; 2]>>) {
+        let highlight = ui.visuals().selection.bg_fill;
+        let viewport = &mut self.viewport;
+        let selecting = &mut self.selecting;
+        Plot::new("overview")
+            .height(60.0)
+            .auto_bounds_x()
+            .auto_bounds_y()
+            .show_y(false)
+            .show_axes([true, false])
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false)
+            .show(ui, |plot_ui| {
+                for points in series {
+                    plot_ui.line(Line::new(PlotPoints::from(points)).color(Color32::GRAY));
+                }
+                if let Some([min_x, max_x]) = viewport.shown {
+                    let bounds = plot_ui.plot_bounds();
+                    let [_, min_y] = bounds.min();
+                    let [_, max_y] = bounds.max();
+                    plot_ui.polygon(
+                        Polygon::new(PlotPoints::from(vec![
+                            [min_x, min_y],
+                            [max_x, min_y],
+                            [max_x, max_y],
+                            [min_x, max_y],
+                        ]))
+                        .color(highlight),
+                    );
+                }
+                let (pressed, dragging) = plot_ui
+                    .ctx()
+                    .input(|x| (x.pointer.primary_pressed(), x.pointer.primary_down()));
+                if !dragging {
+                    *selecting = None;
+                }
+                let Some(pointer) = plot_ui.pointer_coordinate() else {
+                    return;
+                };
+                if pressed && plot_ui.plot_hovered() {
+                    let inside = viewport
+                        .shown
+                        .is_some_and(|[min_x, max_x]| (min_x..=max_x).contains(&pointer.x));
+                    *selecting = (!inside).then_some(pointer.x);
+                }
+                match *selecting {
+                    Some(start) => viewport.select(start, pointer.x),
+                    None if dragging && plot_ui.plot_hovered() => viewport.center_at(pointer.x),
+                    None => {}
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_move_the_shown_range_to_the_requested_one() {
+        let mut viewport = Viewport::default();
+        let bounds = PlotBounds::from_min_max([0.0, -1.0], [10.0, 1.0]);
+        assert_eq!(viewport.apply(bounds), None);
+        assert_eq!(viewport.shown, Some([0.0, 10.0]));
+
+        viewport.center_at(100.0);
+        let moved = viewport.apply(bounds).unwrap();
+        assert_eq!((moved.min(), moved.max()), ([95.0, -1.0], [105.0, 1.0]));
+        assert_eq!(viewport.shown, Some([95.0, 105.0]));
+        assert_eq!(viewport.requested, None);
+
+        viewport.pan([0.5, 0.0]);
+        viewport.pan([0.0, -0.25]);
+        let panned = viewport.apply(bounds).unwrap();
+        assert_eq!((panned.min(), panned.max()), ([5.0, -1.5], [15.0, 0.5]));
+        assert_eq!(viewport.shown, Some([5.0, 15.0]));
+
+        viewport.select(30.0, 20.0);
+        assert_eq!(viewport.requested, Some([20.0, 30.0]));
+        viewport.requested = None;
+        viewport.select(20.0, 20.0);
+        assert_eq!(viewport.requested, None);
+    }
+
+    #[test]
+    fn should_restore_a_moved_plot_without_pending_moves() {
+        let mut viewport = Viewport::default();
+        viewport.pan([0.1, 0.0]);
+        viewport.apply(PlotBounds::from_min_max([0.0, -1.0], [10.0, 1.0]));
+        viewport.pan([0.1, 0.0]);
+
+        let json = serde_json::to_string(&viewport).unwrap();
+        let restored: Viewport = serde_json::from_str(&json).unwrap();
+        assert!(restored.browsing);
+        assert_eq!(restored.shown, Some([1.0, 11.0]));
+        assert_eq!(restored.pan, None);
+    }
+
+    #[test]
+    fn should_follow_live_values_until_moved() {
+        let mut viewport = Viewport::default();
+        let bounds = PlotBounds::from_min_max([0.0, -1.0], [10.0, 1.0]);
+        viewport.apply(bounds);
+        assert!(!viewport.browsing);
+
+        viewport.pan([0.1, 0.0]);
+        viewport.apply(bounds);
+        assert!(viewport.browsing);
+
+        viewport.follow_live();
+        assert!(!viewport.browsing);
+        assert!(viewport.take_follow());
+        assert!(!viewport.take_follow());
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crossbeam::channel::Receiver;
+use egui::Ui;
+
+use crate::value_parsing::ParseFailure;
+
+const RECENT_FAILURES: usize = 100;
+
+/// Collects the lines the parser had to discard, so they can be shown in the ui.
+#[derive(Default)]
+pub struct ParseErrors {
+    total: usize,
+    per_channel: BTreeMap<String, usize>,
+    recent: VecDeque<ParseFailure>,
+    show_details: bool,
+}
+
+impl ParseErrors {
+    pub fn update(&mut self, receiver: &Receiver<ParseFailure>) {
+        for failure in receiver.try_iter() {
+            self.total += 1;
+            *self.per_channel.entry(failure.channel.clone()).or_default() += 1;
+
+            self.recent.push_back(failure);
+            if self.recent.len() > RECENT_FAILURES {
+                self.recent.pop_front();
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.total = 0;
+        self.per_channel.clear();
+        self.recent.clear();
+    }
+
+    /// Shows a short summary, which opens the details window when clicked.
+    pub fn badge(&mut self, ui: &mut Ui) {
+        let Some(last) = self.recent.back() else {
+            return;
+        };
+
+        let text = egui::RichText::new(format!(
+            "{} parse errors, last: '{}'",
+            self.total, last.value
+        ))
+        .color(ui.visuals().warn_fg_color);
+        if ui
+            .add(egui::Label::new(text).sense(egui::Sense::click()))
+            .on_hover_text("Show recent parse errors")
+            .clicked()
+        {
+            self.show_details = true;
+        }
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_details;
+        let mut reset = false;
+        egui::Window::new("Parse errors")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} lines discarded", self.total));
+                    reset = ui.button("reset").clicked();
+                });
+
+                ui.collapsing("Errors per channel", |ui| {
+                    egui::Grid::new("parse_errors_per_channel")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (channel, count) in &self.per_channel {
+                                ui.label(channel);
+                                ui.label(count.to_string());
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                ui.separator();
+                ui.label("Recent failures (newest first)");
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("recent_parse_errors")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.strong("channel");
+                            ui.strong("value");
+                            ui.strong("line");
+                            ui.end_row();
+                            for failure in self.recent.iter().rev() {
+                                ui.label(&failure.channel);
+                                ui.monospace(format!("{:?}", failure.value));
+                                ui.monospace(format!("{:?}", failure.line));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        if reset {
+            self.reset();
+        }
+        self.show_details = open;
+    }
+}
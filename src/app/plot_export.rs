@@ -0,0 +1,407 @@
+use std::{fmt::Display, path::Path, sync::Once};
+
+use egui::Ui;
+use plotters::{
+    coord::Shift,
+    element::DashedPathElement,
+    prelude::*,
+    style::{register_font, FontStyle},
+};
+
+use super::{
+    event_log::{file_stamp, format_utc},
+    export_channels::ExportChannels,
+    value_history::{decimate, ValueHistory},
+};
+use crate::value_parsing::unix_timestamp;
+
+const FONT: &str = "sans-serif";
+
+/// Pixels between the markers of a series in a monochrome image.
+const MARKER_SPACING: i32 = 60;
+/// The dash and gap lengths of the lines in a monochrome image, solid lines have none.
+const DASHES: [Option<(i32, i32)>; 3] = [None, Some((12, 6)), Some((3, 4))];
+/// The number of marker shapes, circle, triangle, cross and square.
+const MARKERS: usize = 4;
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// The colors of the series in the image.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageStyle {
+    #[default]
+    Color,
+    /// Black on white with distinct dash patterns and markers, for grayscale printing
+    Monochrome,
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    /// Drawing or writing the image failed
+    Draw(String),
+    /// The chart needs at least one sample
+    NoData,
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Draw(err) => write!(f, "{}", err),
+            ExportError::NoData => write!(f, "there are no values to plot"),
+        }
+    }
+}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for ExportError {
+    fn from(value: DrawingAreaErrorKind<E>) -> Self {
+        Self::Draw(value.to_string())
+    }
+}
+
+/// Saves the plotted values as an image with legend, axes and a caption, e.g. for lab reports.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PlotExport {
+    path: String,
+    format: ImageFormat,
+    style: ImageStyle,
+    width: u32,
+    height: u32,
+    /// Screenshots taken with the shortcut are saved here without asking for a file
+    screenshot_directory: String,
+
+    show: bool,
+    /// Numbers the screenshots of the session, as several may be taken within a second
+    #[serde(skip)]
+    screenshot_count: usize,
+}
+
+impl Default for PlotExport {
+    fn default() -> Self {
+        Self {
+            path: "plot.png".to_string(),
+            format: ImageFormat::Png,
+            style: ImageStyle::Color,
+            width: 1280,
+            height: 720,
+            screenshot_directory: "screenshots".to_string(),
+            show: false,
+            screenshot_count: 0,
+        }
+    }
+}
+
+impl PlotExport {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// `source` names the connection the values came from in the caption of the image.
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        history: &ValueHistory,
+        source: &str,
+        channels: &mut ExportChannels,
+    ) {
+        let mut open = self.show;
+        let mut save = false;
+        egui::Window::new("Save plot image")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.settings_ui(ui);
+                channels.button(ui);
+                save = ui.button("Save").clicked();
+            });
+        self.show = open && !save;
+
+        if save {
+            let path = Path::new(&self.path).with_extension(self.format.extension());
+            self.save(&channels.selected(history), source, &path);
+        }
+    }
+
+    /// Saves the selected `channels` of `history` to a new file in the screenshot directory.
+    pub fn screenshot(&mut self, history: &ValueHistory, source: &str, channels: &ExportChannels) {
+        let directory = Path::new(&self.screenshot_directory);
+        if let Err(err) = std::fs::create_dir_all(directory) {
+            tracing::error!("Failed to create the screenshot directory: {}", err);
+            return;
+        }
+        self.screenshot_count += 1;
+        let name = format!(
+            "plot_{}_{:03}.{}",
+            file_stamp(unix_timestamp()),
+            self.screenshot_count,
+            self.format.extension()
+        );
+        self.save(&channels.selected(history), source, &directory.join(name));
+    }
+
+    fn save(&self, history: &ValueHistory, source: &str, path: &Path) {
+        let caption = format!("{}, {}", source, format_utc(unix_timestamp()));
+        match export(
+            history,
+            &caption,
+            self.format,
+            self.style,
+            path,
+            (self.width, self.height),
+        ) {
+            Ok(()) => tracing::info!("Saved plot image to {}", path.display()),
+            Err(err) => tracing::error!("Failed to save plot image: {}", err),
+        }
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.text_edit_singleline(&mut self.path);
+        });
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.format, ImageFormat::Png, "PNG");
+            ui.radio_value(&mut self.format, ImageFormat::Svg, "SVG");
+        });
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.style, ImageStyle::Color, "Color");
+            ui.radio_value(
+                &mut self.style,
+                ImageStyle::Monochrome,
+                "Print (monochrome)",
+            )
+            .on_hover_text("Black on white, the series differ in dash pattern and marker");
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.width)
+                    .clamp_range(320..=7680)
+                    .suffix(" px"),
+            );
+            ui.label("×");
+            ui.add(
+                egui::DragValue::new(&mut self.height)
+                    .clamp_range(240..=4320)
+                    .suffix(" px"),
+            );
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Screenshots to");
+            ui.text_edit_singleline(&mut self.screenshot_directory);
+        })
+        .response
+        .on_hover_text(
+            "The screenshot shortcut saves the plot with the settings above without asking for a file",
+        );
+    }
+}
+
+/// Renders all channels of `history` the way the plot shows them to an image file.
+pub fn export(
+    history: &ValueHistory,
+    caption: &str,
+    format: ImageFormat,
+    style: ImageStyle,
+    path: &Path,
+    size: (u32, u32),
+) -> Result<(), ExportError> {
+    register_egui_font();
+    match format {
+        ImageFormat::Png => draw(
+            BitMapBackend::new(path, size).into_drawing_area(),
+            history,
+            caption,
+            style,
+        ),
+        ImageFormat::Svg => draw(
+            SVGBackend::new(path, size).into_drawing_area(),
+            history,
+            caption,
+            style,
+        ),
+    }
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    history: &ValueHistory,
+    caption: &str,
+    style: ImageStyle,
+) -> Result<(), ExportError>
+where
+    DB::ErrorType: 'static,
+{
+    let max_points = root.dim_in_pixel().0 as usize * 2;
+    let mut names: Vec<&str> = history.channel_names().collect();
+    names.sort_unstable();
+    let series: Vec<(&str, Vec<[f64; 2]>)> = names
+        .into_iter()
+        .filter_map(|name| Some((name, decimate(history.samples(name)?, max_points))))
+        .collect();
+
+    let points = series.iter().flat_map(|(_, points)| points);
+    let (x_max, y_min, y_max) = points.fold(
+        (0.0f64, f64::INFINITY, f64::NEG_INFINITY),
+        |(x_max, y_min, y_max), [x, y]| (x_max.max(*x), y_min.min(*y), y_max.max(*y)),
+    );
+    if y_min > y_max {
+        return Err(ExportError::NoData);
+    }
+    // A constant series would collapse the axis
+    let padding = ((y_max - y_min) * 0.05).max(0.5);
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(caption, (FONT, 20))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..x_max.max(1.0), (y_min - padding)..(y_max + padding))?;
+    chart
+        .configure_mesh()
+        .x_desc("sample")
+        .label_style((FONT, 14))
+        .draw()?;
+
+    for (index, (name, points)) in series.into_iter().enumerate() {
+        let points = points.into_iter().map(|[x, y]| (x, y));
+        match style {
+            ImageStyle::Color => {
+                let color = Palette99::pick(index).to_rgba();
+                chart
+                    .draw_series(LineSeries::new(points, color.stroke_width(2)))?
+                    .label(name)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+            }
+            ImageStyle::Monochrome => {
+                let line = BLACK.stroke_width(2);
+                let dashes = DASHES[index % DASHES.len()];
+                match dashes {
+                    Some((dash, gap)) => {
+                        chart.draw_series(DashedLineSeries::new(points.clone(), dash, gap, line))?
+                    }
+                    None => chart.draw_series(LineSeries::new(points.clone(), line))?,
+                };
+                // Without a gap the dash covers the whole line of the legend
+                let (dash, gap) = dashes.unwrap_or((20, 0));
+                let legend = move |(x, y)| {
+                    EmptyElement::at((x, y))
+                        + DashedPathElement::new(vec![(0, 0), (20, 0)], dash, gap, line)
+                };
+                let (shift, spacing) = (MARKER_SPACING / 2, MARKER_SPACING);
+                let center = (10, 0);
+                // The markers tell apart the series with the same dash pattern
+                match (index / DASHES.len()) % MARKERS {
+                    0 => {
+                        let marker = |c: (i32, i32)| Circle::new(c, 4, BLACK.filled());
+                        chart
+                            .draw_series(DottedLineSeries::new(points, shift, spacing, marker))?
+                            .label(name)
+                            .legend(move |c| legend(c) + marker(center));
+                    }
+                    1 => {
+                        let marker =
+                            |c: (i32, i32)| TriangleMarker::new(c, 5, BLACK.stroke_width(2));
+                        chart
+                            .draw_series(DottedLineSeries::new(points, shift, spacing, marker))?
+                            .label(name)
+                            .legend(move |c| legend(c) + marker(center));
+                    }
+                    2 => {
+                        let marker = |c: (i32, i32)| Cross::new(c, 4, BLACK.stroke_width(2));
+                        chart
+                            .draw_series(DottedLineSeries::new(points, shift, spacing, marker))?
+                            .label(name)
+                            .legend(move |c| legend(c) + marker(center));
+                    }
+                    _ => {
+                        let marker = |(x, y): (i32, i32)| {
+                            Rectangle::new([(x - 4, y - 4), (x + 4, y + 4)], BLACK.stroke_width(2))
+                        };
+                        chart
+                            .draw_series(DottedLineSeries::new(points, shift, spacing, marker))?
+                            .label(name)
+                            .legend(move |c| legend(c) + marker(center));
+                    }
+                }
+            }
+        }
+    }
+    chart
+        .configure_series_labels()
+        .label_font((FONT, 14))
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Plotters has no fonts of its own, the images use the font egui renders the ui with.
+fn register_egui_font() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        let fonts = egui::FontDefinitions::default();
+        let font = fonts.families[&egui::FontFamily::Proportional]
+            .first()
+            .and_then(|name| fonts.font_data.get(name));
+        if let Some(egui::FontData {
+            font: std::borrow::Cow::Borrowed(data),
+            ..
+        }) = font
+        {
+            if register_font(FONT, FontStyle::Normal, data).is_err() {
+                tracing::warn!("Failed to register the font for plot images");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_parsing::DataValue;
+
+    #[test]
+    fn should_export_png_and_svg() {
+        let (sender, mut receiver) = crossbeam::channel::unbounded();
+        for index in 0..100 {
+            let value = DataValue {
+                name: "sine".to_string(),
+                value: (index as f64 / 10.0).sin(),
+                timestamp: index as f64,
+                line: 0,
+            };
+            sender.send(vec![value]).unwrap();
+        }
+        let mut history = ValueHistory::with_capacity(1000);
+        history.update(&mut receiver, 1000, None);
+
+        let directory = std::env::temp_dir();
+        for (format, style) in [
+            (ImageFormat::Png, ImageStyle::Color),
+            (ImageFormat::Svg, ImageStyle::Monochrome),
+        ] {
+            let path = directory.join(format!("serialplotter_export_test.{}", format.extension()));
+            export(&history, "test", format, style, &path, (640, 480)).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,677 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use egui::{RichText, Ui};
+use serialport::{
+    available_ports, ErrorKind, SerialPort, SerialPortInfo, SerialPortType, UsbPortInfo,
+};
+
+use crate::value_parsing::{unix_timestamp, Commands, DataSource, ReadTiming};
+
+/// Seconds a lost port has to be present again before it is reopened, an IDE flashing the board
+/// may still hold it right after it reappeared.
+const REOPEN_DELAY: f64 = 1.0;
+/// Seconds between two enumerations of the ports while watching for new devices.
+const POLL_INTERVAL: f64 = 1.0;
+/// Seconds a new device is announced.
+const ARRIVAL_NOTICE: f64 = 10.0;
+
+/// Notices the ports that appeared between two enumerations.
+#[derive(Debug, Default)]
+struct DeviceWatch {
+    /// The ports of the last enumeration, none before the first one
+    known: Option<BTreeSet<String>>,
+    /// Seconds since the unix epoch of the last enumeration
+    last_poll: f64,
+    /// The new ports and when they appeared, while they are announced
+    arrived: Vec<(String, f64)>,
+}
+
+impl DeviceWatch {
+    fn update(&mut self, present: BTreeSet<String>, now: f64) {
+        // The ports present at the start are not new
+        if let Some(known) = &self.known {
+            let new = present.difference(known).map(|name| (name.clone(), now));
+            self.arrived.extend(new);
+        }
+        self.arrived
+            .retain(|(name, since)| present.contains(name) && now - since < ARRIVAL_NOTICE);
+        self.known = Some(present);
+    }
+}
+
+/// Identifies a USB device independent of the name the operating system gave its port.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct UsbDevice {
+    pub vid: u16,
+    pub pid: u16,
+    pub serial_number: Option<String>,
+}
+
+impl From<&UsbPortInfo> for UsbDevice {
+    fn from(info: &UsbPortInfo) -> Self {
+        Self {
+            vid: info.vid,
+            pid: info.pid,
+            serial_number: info.serial_number.clone(),
+        }
+    }
+}
+
+/// Chooses the serial port, showing what is connected to each one.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PortSelection {
+    /// The device that was opened last, it is highlighted even if its port got a new name
+    last_device: Option<UsbDevice>,
+    /// The state of the Data Terminal Ready line, many boards only send while it is asserted
+    dtr: bool,
+    /// The state of the Request To Send line
+    rts: bool,
+    /// Reopens the port once it reappears after it vanished, e.g. while an IDE uploads a sketch
+    reopen: bool,
+    /// Keeps other programs from opening the port while it is open, only supported on unix
+    exclusive: bool,
+    /// Lists the legacy and virtual ports as well, see `clutter`
+    show_all: bool,
+    /// Milliseconds a read waits for the first byte, the reader only stops between reads
+    read_timeout: f64,
+    /// Milliseconds the reader pauses after every read
+    poll_interval: f64,
+    /// The most bytes read at once
+    read_size: usize,
+    /// Milliseconds an FTDI adapter collects bytes before sending them, only set on Linux
+    latency_timer: Option<u8>,
+
+    /// The port that vanished while it was open, until it is reopened
+    #[serde(skip)]
+    lost: Option<String>,
+    /// Seconds since the unix epoch the lost port is present again since
+    #[serde(skip)]
+    present_since: Option<f64>,
+    /// Why the port could not be opened, until it is retried or dismissed
+    #[serde(skip)]
+    failure: Option<String>,
+    #[serde(skip)]
+    watch: DeviceWatch,
+}
+
+impl Default for PortSelection {
+    fn default() -> Self {
+        Self {
+            last_device: None,
+            dtr: true,
+            rts: true,
+            reopen: true,
+            exclusive: true,
+            show_all: false,
+            read_timeout: 0.0,
+            poll_interval: 0.0,
+            read_size: 1024,
+            latency_timer: None,
+            lost: None,
+            present_since: None,
+            failure: None,
+            watch: DeviceWatch::default(),
+        }
+    }
+}
+
+impl PortSelection {
+    /// Remembers the device behind `port_name` after it was opened.
+    pub fn opened(&mut self, port_name: &str) {
+        let ports = available_ports().unwrap_or_default();
+        if let Some(port) = ports.iter().find(|port| port.port_name == port_name) {
+            self.last_device = usb_device(port);
+        }
+        self.lost = None;
+        self.present_since = None;
+        self.failure = None;
+    }
+
+    /// Opens the port, a failure describes why it failed, e.g. which program uses the port.
+    pub fn open(&self, port_name: &str, baud_rate: u32) -> Result<Box<dyn SerialPort>, String> {
+        #[cfg(target_os = "linux")]
+        if let Some(latency_timer) = self.latency_timer {
+            if let Err(err) = set_latency_timer(port_name, latency_timer) {
+                tracing::warn!("Failed to set the latency timer of {}: {}", port_name, err);
+            }
+        }
+        let timeout = Duration::from_secs_f64(self.read_timeout.max(0.0) / 1000.0);
+        let builder = serialport::new(port_name, baud_rate).timeout(timeout);
+        #[cfg(unix)]
+        let port = builder.open_native().and_then(|mut port| {
+            // Opened exclusively by default
+            if !self.exclusive {
+                port.set_exclusive(false)?;
+            }
+            Ok(Box::new(port) as Box<dyn SerialPort>)
+        });
+        #[cfg(not(unix))]
+        let port = builder.open();
+        port.map_err(|err| describe_failure(port_name, &err, &holders(port_name)))
+    }
+
+    pub fn read_timing(&self) -> ReadTiming {
+        ReadTiming {
+            poll_interval: Duration::from_secs_f64(self.poll_interval.max(0.0) / 1000.0),
+            read_size: self.read_size,
+        }
+    }
+
+    /// Whether a lost port is waited for, its failed attempts to reopen are expected.
+    pub fn waiting(&self) -> bool {
+        self.lost.is_some()
+    }
+
+    /// Offers to retry or pick another port after opening the port failed.
+    pub fn failed(&mut self, message: String) {
+        self.failure = Some(message);
+    }
+
+    /// Returns whether opening the port again was requested, with the port picked instead of it.
+    pub fn failure_window(
+        &mut self,
+        ctx: &egui::Context,
+        serial_port_name: &mut Option<String>,
+    ) -> bool {
+        let Some(failure) = &self.failure else {
+            return false;
+        };
+        let mut open = true;
+        let mut retry = false;
+        egui::Window::new("Cannot open the port")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, failure);
+                ui.label("Close the other program and retry, or pick another port.");
+                let others: Vec<SerialPortInfo> = available_ports()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|port| Some(&port.port_name) != serial_port_name.as_ref())
+                    .filter(|port| self.show_all || !clutter(port))
+                    .collect();
+                for port in others {
+                    if ui
+                        .button(describe(&port))
+                        .on_hover_text(details(&port))
+                        .clicked()
+                    {
+                        *serial_port_name = Some(port.port_name);
+                        retry = true;
+                    }
+                }
+                ui.separator();
+                retry |= ui.button("Retry").clicked();
+            });
+        if retry || !open {
+            self.failure = None;
+        }
+        retry
+    }
+
+    /// Announces devices that were plugged in, returns the port of one to connect to.
+    ///
+    /// The ports are enumerated every second, a lost port that reappears is reopened instead.
+    pub fn arrivals_ui(&mut self, ctx: &egui::Context, connected: bool) -> Option<String> {
+        let now = unix_timestamp();
+        if now - self.watch.last_poll >= POLL_INTERVAL {
+            self.watch.last_poll = now;
+            let present = available_ports()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|port| self.show_all || !clutter(port))
+                .map(|port| port.port_name)
+                .collect();
+            self.watch.update(present, now);
+            if self.waiting() {
+                self.watch.arrived.clear();
+            }
+        }
+        ctx.request_repaint_after(Duration::from_secs_f64(POLL_INTERVAL));
+        if self.watch.arrived.is_empty() {
+            return None;
+        }
+
+        let mut connect = None;
+        let mut dismissed = None;
+        egui::Area::new("device_arrivals")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    for (name, _) in &self.watch.arrived {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("New device {} detected", name));
+                            if ui
+                                .add_enabled(!connected, egui::Button::new("connect"))
+                                .on_disabled_hover_text("Close the open port first")
+                                .clicked()
+                            {
+                                connect = Some(name.clone());
+                            }
+                            if ui.small_button("✖").clicked() {
+                                dismissed = Some(name.clone());
+                            }
+                        });
+                    }
+                });
+            });
+        if let Some(name) = dismissed.as_ref().or(connect.as_ref()) {
+            self.watch.arrived.retain(|(x, _)| x != name);
+        }
+        connect
+    }
+
+    /// Waits for the port to reappear after it stopped on its own.
+    pub fn lost(&mut self, port_name: &str) {
+        if self.reopen {
+            self.lost = Some(port_name.to_string());
+        }
+    }
+
+    /// Whether the lost port is back long enough to be opened again.
+    ///
+    /// A device that reappears under a new name is followed, e.g. a board that got another
+    /// `/dev/ttyACM` number after flashing. Selecting another port stops waiting.
+    pub fn reopen_due(&mut self, serial_port_name: &mut Option<String>) -> bool {
+        let Some(lost) = &self.lost else {
+            return false;
+        };
+        if serial_port_name.as_ref() != Some(lost) {
+            self.lost = None;
+            return false;
+        }
+        let ports = available_ports().unwrap_or_default();
+        let present = ports
+            .iter()
+            .find(|port| &port.port_name == lost)
+            .or_else(|| {
+                let device = self.last_device.as_ref()?;
+                ports
+                    .iter()
+                    .find(|port| usb_device(port).as_ref() == Some(device))
+            });
+        if let Some(port) = present {
+            if &port.port_name != lost {
+                *serial_port_name = Some(port.port_name.clone());
+                self.lost = Some(port.port_name.clone());
+            }
+        }
+        self.due(unix_timestamp(), present.is_some())
+    }
+
+    /// Another attempt follows after the delay if opening fails.
+    fn due(&mut self, now: f64, present: bool) -> bool {
+        if !present {
+            self.present_since = None;
+            return false;
+        }
+        let since = *self.present_since.get_or_insert(now);
+        if now - since < REOPEN_DELAY {
+            return false;
+        }
+        self.present_since = Some(now);
+        true
+    }
+
+    /// Sets the control lines of a newly opened port.
+    pub fn apply_signals(&self, source: &mut dyn DataSource) {
+        source.command(Commands::SetDtr(self.dtr));
+        source.command(Commands::SetRts(self.rts));
+    }
+
+    /// The control lines of the open port.
+    pub fn signals_ui(&mut self, ui: &mut Ui, source: &mut dyn DataSource) {
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.dtr, "DTR").changed() {
+                source.command(Commands::SetDtr(self.dtr));
+            }
+            if ui.checkbox(&mut self.rts, "RTS").changed() {
+                source.command(Commands::SetRts(self.rts));
+            }
+            if ui
+                .button("Reset board")
+                .on_hover_text("Pulses DTR, which reboots most Arduino-compatible boards")
+                .clicked()
+            {
+                source.command(Commands::ResetBoard);
+            }
+            if ui
+                .button("Send break")
+                .on_hover_text("Holds the transmit line low, e.g. for LIN or to enter a bootloader")
+                .clicked()
+            {
+                source.command(Commands::SendBreak);
+            }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, serial_port_name: &mut Option<String>) {
+        // The list is enumerated again every frame, so unplugged devices simply disappear from it
+        let mut ports = available_ports().unwrap_or_default();
+        let hidden = ports.iter().filter(|port| clutter(port)).count();
+        if !self.show_all {
+            // The selected port stays in the list, it was chosen deliberately
+            ports.retain(|port| {
+                !clutter(port) || Some(&port.port_name) == serial_port_name.as_ref()
+            });
+        }
+        let find = |name: &String| ports.iter().find(|port| &port.port_name == name);
+        if let [port] = ports.as_slice() {
+            if serial_port_name.as_ref().and_then(find).is_none() {
+                *serial_port_name = Some(port.port_name.clone());
+            }
+        }
+        let selected = serial_port_name.as_ref().and_then(find);
+
+        let selected_text = match (serial_port_name.as_ref(), selected) {
+            (None, _) => "none".to_string(),
+            (Some(name), None) => format!("{} (not present)", name),
+            (Some(_), Some(port)) => describe(port),
+        };
+        egui::ComboBox::from_label("Serial port")
+            .selected_text(selected_text)
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                for port in &ports {
+                    let mut text = RichText::new(describe(port));
+                    if self.last_device.is_some() && usb_device(port) == self.last_device {
+                        text = text.strong();
+                    }
+                    ui.selectable_value(serial_port_name, Some(port.port_name.clone()), text)
+                        .on_hover_text(details(port));
+                }
+            });
+        if hidden > 0 {
+            ui.checkbox(
+                &mut self.show_all,
+                format!("Show {} legacy and virtual ports", hidden),
+            )
+            .on_hover_text("Lists the serial ports of the mainboard and virtual ports as well");
+        }
+        ui.add_enabled(
+            cfg!(unix),
+            egui::Checkbox::new(&mut self.exclusive, "Exclusive"),
+        )
+        .on_hover_text("Keeps other programs from opening the port while it is open")
+        .on_disabled_hover_text("Windows always opens ports exclusively");
+        ui.collapsing("Read timing", |ui| self.timing_ui(ui));
+        ui.checkbox(&mut self.reopen, "Reopen after uploads")
+            .on_hover_text("Reopens the port once it reappears after it vanished, e.g. while an IDE flashes the board");
+        if let Some(lost) = &self.lost {
+            let cancel = ui
+                .horizontal(|ui| {
+                    ui.label(format!("Waiting for {} to reappear", lost));
+                    ui.button("cancel").clicked()
+                })
+                .inner;
+            if cancel {
+                self.lost = None;
+            }
+        }
+    }
+
+    /// Trades the latency of the values against the load of reading, applied when the port opens.
+    fn timing_ui(&mut self, ui: &mut Ui) {
+        ui.add(
+            egui::DragValue::new(&mut self.read_timeout)
+                .clamp_range(0.0..=1000.0)
+                .prefix("Read timeout: ")
+                .suffix(" ms"),
+        )
+        .on_hover_text("How long a read waits for the first byte");
+        ui.add(
+            egui::DragValue::new(&mut self.poll_interval)
+                .clamp_range(0.0..=1000.0)
+                .prefix("Poll interval: ")
+                .suffix(" ms"),
+        )
+        .on_hover_text("Pauses after every read, collecting more bytes per read");
+        ui.add(
+            egui::DragValue::new(&mut self.read_size)
+                .clamp_range(64..=65536)
+                .prefix("Read size: ")
+                .suffix(" bytes"),
+        )
+        .on_hover_text("The most bytes read at once");
+        ui.horizontal(|ui| {
+            let mut enabled = self.latency_timer.is_some();
+            ui.add_enabled(
+                cfg!(target_os = "linux"),
+                egui::Checkbox::new(&mut enabled, "Latency timer"),
+            )
+            .on_hover_text(
+                "How long FTDI adapters collect bytes before sending them, 16 ms by default",
+            )
+            .on_disabled_hover_text("Only set on Linux");
+            self.latency_timer = enabled.then(|| self.latency_timer.unwrap_or(1));
+            if let Some(latency_timer) = &mut self.latency_timer {
+                ui.add(
+                    egui::DragValue::new(latency_timer)
+                        .clamp_range(1..=255)
+                        .suffix(" ms"),
+                );
+            }
+        });
+    }
+}
+
+/// Sets how long an FTDI adapter collects bytes before it sends them to the host, 16 ms by default.
+#[cfg(target_os = "linux")]
+fn set_latency_timer(port_name: &str, milliseconds: u8) -> std::io::Result<()> {
+    let name = std::path::Path::new(port_name)
+        .file_name()
+        .unwrap_or_default();
+    let path = std::path::Path::new("/sys/class/tty")
+        .join(name)
+        .join("device/latency_timer");
+    std::fs::write(path, milliseconds.to_string())
+}
+
+/// Why opening the port failed, naming the programs that have it open.
+fn describe_failure(port_name: &str, err: &serialport::Error, holders: &[(u32, String)]) -> String {
+    // Linux reports a port locked by another program as busy, Windows denies the access
+    let busy = match err.kind() {
+        ErrorKind::NoDevice => err.description.contains("busy"),
+        ErrorKind::Io(std::io::ErrorKind::PermissionDenied) => cfg!(windows),
+        _ => false,
+    };
+    if !busy && holders.is_empty() {
+        return format!("Failed to open {}: {}", port_name, err);
+    }
+    let used_by = holders
+        .iter()
+        .map(|(pid, name)| format!("{} (pid {})", name, pid))
+        .collect::<Vec<_>>()
+        .join(", ");
+    match used_by.as_str() {
+        "" => format!("{} is busy, another program uses it", port_name),
+        used_by => format!("{} is busy, used by {}", port_name, used_by),
+    }
+}
+
+/// The id and name of the processes that have the port open, as far as they can be seen.
+#[cfg(target_os = "linux")]
+fn holders(port_name: &str) -> Vec<(u32, String)> {
+    let Ok(port) = std::fs::canonicalize(port_name) else {
+        return Vec::new();
+    };
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let own = std::process::id();
+    processes
+        .flatten()
+        .filter_map(|process| {
+            let pid: u32 = process.file_name().to_str()?.parse().ok()?;
+            // Descriptors of processes of other users can not be read
+            let mut descriptors = std::fs::read_dir(process.path().join("fd")).ok()?;
+            let holds = descriptors.any(|fd| {
+                fd.is_ok_and(|fd| std::fs::read_link(fd.path()).is_ok_and(|x| x == port))
+            });
+            if !holds || pid == own {
+                return None;
+            }
+            let name = std::fs::read_to_string(process.path().join("comm")).unwrap_or_default();
+            Some((pid, name.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn holders(_port_name: &str) -> Vec<(u32, String)> {
+    Vec::new()
+}
+
+/// Ports that are rarely the ones to plot, like the legacy serial ports Linux lists whether a
+/// connector exists or not, and the virtual ports of macOS.
+fn clutter(port: &SerialPortInfo) -> bool {
+    let name = port.port_name.as_str();
+    if let SerialPortType::UsbPort(_) | SerialPortType::BluetoothPort = port.port_type {
+        return false;
+    }
+    let legacy = name
+        .strip_prefix("/dev/ttyS")
+        .is_some_and(|number| number.parse::<u32>().is_ok());
+    // macOS lists every port as `tty.` for incoming and `cu.` for outgoing connections
+    let incoming = name.starts_with("/dev/tty.");
+    let virtual_port = ["Bluetooth-Incoming-Port", "debug-console", "/dev/ttyprintk"]
+        .iter()
+        .any(|x| name.ends_with(x));
+    legacy || incoming || virtual_port
+}
+
+fn usb_device(port: &SerialPortInfo) -> Option<UsbDevice> {
+    match &port.port_type {
+        SerialPortType::UsbPort(info) => Some(info.into()),
+        _ => None,
+    }
+}
+
+/// A one line description of a port, e.g. `/dev/ttyUSB0 - FT232R USB UART (0403:6001)`.
+fn describe(port: &SerialPortInfo) -> String {
+    match &port.port_type {
+        SerialPortType::UsbPort(info) => {
+            let name = info.product.as_deref().or(info.manufacturer.as_deref());
+            match name {
+                Some(name) => format!(
+                    "{} - {} ({:04x}:{:04x})",
+                    port.port_name, name, info.vid, info.pid
+                ),
+                None => format!("{} ({:04x}:{:04x})", port.port_name, info.vid, info.pid),
+            }
+        }
+        SerialPortType::BluetoothPort => format!("{} - Bluetooth", port.port_name),
+        SerialPortType::PciPort | SerialPortType::Unknown => port.port_name.clone(),
+    }
+}
+
+fn details(port: &SerialPortInfo) -> String {
+    match &port.port_type {
+        SerialPortType::UsbPort(info) => format!(
+            "USB device {:04x}:{:04x}\nmanufacturer: {}\nproduct: {}\nserial number: {}",
+            info.vid,
+            info.pid,
+            info.manufacturer.as_deref().unwrap_or("-"),
+            info.product.as_deref().unwrap_or("-"),
+            info.serial_number.as_deref().unwrap_or("-"),
+        ),
+        SerialPortType::PciPort => "PCI port".to_string(),
+        SerialPortType::BluetoothPort => "Bluetooth port".to_string(),
+        SerialPortType::Unknown => "unknown port type".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_announce_ports_that_appeared_since_the_start() {
+        let ports = |names: &[&str]| names.iter().map(|x| x.to_string()).collect();
+        let mut watch = DeviceWatch::default();
+        watch.update(ports(&["/dev/ttyS0"]), 0.0);
+        assert!(watch.arrived.is_empty());
+
+        watch.update(ports(&["/dev/ttyS0", "/dev/ttyACM0"]), 1.0);
+        assert_eq!(watch.arrived, [("/dev/ttyACM0".to_string(), 1.0)]);
+        watch.update(ports(&["/dev/ttyS0", "/dev/ttyACM0"]), 2.0);
+        assert_eq!(watch.arrived.len(), 1);
+        watch.update(ports(&["/dev/ttyS0", "/dev/ttyACM0"]), 1.0 + ARRIVAL_NOTICE);
+        assert!(watch.arrived.is_empty());
+
+        watch.update(ports(&["/dev/ttyS0", "/dev/ttyUSB0"]), 20.0);
+        watch.update(ports(&["/dev/ttyS0"]), 21.0);
+        assert!(watch.arrived.is_empty());
+    }
+
+    fn usb_port(product: Option<&str>) -> SerialPortInfo {
+        SerialPortInfo {
+            port_name: "/dev/ttyUSB0".to_string(),
+            port_type: SerialPortType::UsbPort(UsbPortInfo {
+                vid: 0x0403,
+                pid: 0x6001,
+                serial_number: Some("A50285BI".to_string()),
+                manufacturer: Some("FTDI".to_string()),
+                product: product.map(str::to_string),
+            }),
+        }
+    }
+
+    #[test]
+    fn should_reopen_once_the_port_is_back_for_the_delay() {
+        let mut selection = PortSelection::default();
+        assert!(!selection.due(10.0, false));
+        assert!(!selection.due(10.5, true));
+        assert!(!selection.due(11.0, false));
+        assert!(!selection.due(11.2, true));
+        assert!(selection.due(12.2, true));
+        // Opening failed, the next attempt waits for the delay again
+        assert!(!selection.due(12.5, true));
+        assert!(selection.due(13.2, true));
+    }
+
+    #[test]
+    fn should_name_the_programs_using_a_busy_port() {
+        let busy = serialport::Error::new(ErrorKind::NoDevice, "Device or resource busy");
+        assert_eq!(
+            describe_failure("/dev/ttyACM0", &busy, &[(1234, "arduino-ide".to_string())]),
+            "/dev/ttyACM0 is busy, used by arduino-ide (pid 1234)"
+        );
+        assert_eq!(
+            describe_failure("/dev/ttyACM0", &busy, &[]),
+            "/dev/ttyACM0 is busy, another program uses it"
+        );
+        let missing = serialport::Error::new(ErrorKind::NoDevice, "No such device");
+        assert_eq!(
+            describe_failure("/dev/ttyACM0", &missing, &[]),
+            "Failed to open /dev/ttyACM0: No such device"
+        );
+    }
+
+    #[test]
+    fn should_hide_legacy_and_virtual_ports() {
+        let port = |name: &str| SerialPortInfo {
+            port_name: name.to_string(),
+            port_type: SerialPortType::Unknown,
+        };
+        assert!(clutter(&port("/dev/ttyS4")));
+        assert!(clutter(&port("/dev/tty.usbserial-1420")));
+        assert!(clutter(&port("/dev/cu.Bluetooth-Incoming-Port")));
+        assert!(!clutter(&port("/dev/ttySC0")));
+        assert!(!clutter(&port("/dev/cu.usbserial-1420")));
+        assert!(!clutter(&port("COM3")));
+        assert!(!clutter(&usb_port(None)));
+    }
+
+    #[test]
+    fn should_describe_usb_ports() {
+        assert_eq!(
+            describe(&usb_port(Some("FT232R USB UART"))),
+            "/dev/ttyUSB0 - FT232R USB UART (0403:6001)"
+        );
+        assert_eq!(describe(&usb_port(None)), "/dev/ttyUSB0 - FTDI (0403:6001)");
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::VecDeque;
+
+use crossbeam::channel::Receiver;
+use egui::{
+    text::{LayoutJob, TextFormat},
+    Color32, TextStyle, Ui,
+};
+
+use crate::value_parsing::{unix_timestamp, EscapeScanner, Scanned};
+
+const BYTES_PER_HEX_LINE: usize = 16;
+
+/// The 16 colors of a terminal, as xterm shows them.
+const PALETTE: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 0, 0),
+    Color32::from_rgb(0, 205, 0),
+    Color32::from_rgb(205, 205, 0),
+    Color32::from_rgb(0, 0, 238),
+    Color32::from_rgb(205, 0, 205),
+    Color32::from_rgb(0, 205, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(255, 0, 0),
+    Color32::from_rgb(0, 255, 0),
+    Color32::from_rgb(255, 255, 0),
+    Color32::from_rgb(92, 92, 255),
+    Color32::from_rgb(255, 0, 255),
+    Color32::from_rgb(0, 255, 255),
+    Color32::from_rgb(255, 255, 255),
+];
+
+/// The attributes of a piece of terminal output, the colors by their index in [`PALETTE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Style {
+    foreground: Option<u8>,
+    background: Option<u8>,
+    bold: bool,
+}
+
+impl Style {
+    /// Applies the parameters of a `ESC[...m` sequence, extended colors are skipped.
+    fn select_graphic_rendition(&mut self, params: &str) {
+        let mut codes = params
+            .split(';')
+            .map(|code| code.parse::<u8>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.foreground = Some(code - 30),
+                90..=97 => self.foreground = Some(code - 90 + 8),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(code - 40),
+                100..=107 => self.background = Some(code - 100 + 8),
+                49 => self.background = None,
+                // `38;5;n` or `38;2;r;g;b`
+                38 | 48 => match codes.next() {
+                    Some(5) => {
+                        codes.next();
+                    }
+                    Some(2) => {
+                        codes.nth(2);
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn format(self, ui: &Ui) -> TextFormat {
+        let color = match (self.foreground, self.bold) {
+            // Bold brightens the first 8 colors, like most terminals do
+            (Some(color), true) if color < 8 => PALETTE[usize::from(color) + 8],
+            (Some(color), _) => PALETTE[usize::from(color)],
+            (None, true) => ui.visuals().strong_text_color(),
+            (None, false) => ui.visuals().text_color(),
+        };
+        TextFormat {
+            font_id: TextStyle::Monospace.resolve(ui.style()),
+            color,
+            background: self
+                .background
+                .map_or(Color32::TRANSPARENT, |color| PALETTE[usize::from(color)]),
+            ..Default::default()
+        }
+    }
+}
+
+/// A line of terminal output as runs of bytes of the same style.
+type TerminalLine = Vec<(Style, Vec<u8>)>;
+
+/// Renders the received bytes like a terminal: colors are applied, a carriage return moves back
+/// to the start of the line so the next text overwrites it, e.g. a progress line, and every
+/// other escape sequence is hidden.
+fn terminal_lines(bytes: &[u8]) -> Vec<TerminalLine> {
+    let mut scanner = EscapeScanner::default();
+    let mut style = Style::default();
+    let mut lines = Vec::new();
+    // The bytes of the current line with their style and the column written next
+    let mut line: Vec<(Style, u8)> = Vec::new();
+    let mut column: usize = 0;
+    for &byte in bytes {
+        match scanner.scan(byte) {
+            Scanned::Text(b'\n') => {
+                lines.push(std::mem::take(&mut line));
+                column = 0;
+            }
+            Scanned::Text(b'\r') => column = 0,
+            Scanned::Text(0x08) => column = column.saturating_sub(1),
+            Scanned::Text(byte) => {
+                match line.get_mut(column) {
+                    Some(cell) => *cell = (style, byte),
+                    None => line.push((style, byte)),
+                }
+                column += 1;
+            }
+            Scanned::Csi { params, command } => match command {
+                b'm' => style.select_graphic_rendition(&params),
+                // Erases the rest of the line, or all of it
+                b'K' => match params.as_str() {
+                    "" | "0" => line.truncate(column),
+                    "2" => line.clear(),
+                    _ => {}
+                },
+                _ => {}
+            },
+            Scanned::Pending | Scanned::Other => {}
+        }
+    }
+    lines.push(line);
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut runs: TerminalLine = Vec::new();
+            for (style, byte) in line {
+                match runs.last_mut() {
+                    Some((last, run)) if *last == style => run.push(byte),
+                    _ => runs.push((style, vec![byte])),
+                }
+            }
+            runs
+        })
+        .collect()
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawView {
+    Text,
+    Hex,
+}
+
+/// Keeps the most recent raw bytes received from the serial port,
+/// so the incoming stream can be inspected when parsing fails.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct RawMonitor {
+    capacity: usize,
+    view: RawView,
+    /// Shows the text with the colors and carriage returns of a terminal
+    ansi: bool,
+
+    #[serde(skip)]
+    paused: bool,
+
+    #[serde(skip)]
+    buffer: VecDeque<u8>,
+    /// The bytes received with the last update, paused or not
+    #[serde(skip)]
+    received: Vec<u8>,
+    /// The bytes received per second, over the last full second
+    #[serde(skip)]
+    byte_rate: f64,
+    /// The bytes received since `counting_since`
+    #[serde(skip)]
+    counted: usize,
+    #[serde(skip)]
+    counting_since: f64,
+}
+
+impl Default for RawMonitor {
+    fn default() -> Self {
+        Self {
+            capacity: 16 * 1024,
+            view: RawView::Text,
+            ansi: true,
+            paused: false,
+            buffer: VecDeque::new(),
+            received: Vec::new(),
+            byte_rate: 0.0,
+            counted: 0,
+            counting_since: 0.0,
+        }
+    }
+}
+
+impl RawMonitor {
+    pub fn update(&mut self, receiver: &Receiver<Vec<u8>>) {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!("update raw monitor");
+
+        // Always drain the channel, so the serial thread never waits on a paused monitor.
+        self.received.clear();
+        for chunk in receiver.try_iter() {
+            self.counted += chunk.len();
+            self.received.extend_from_slice(&chunk);
+            if !self.paused {
+                self.buffer.extend(chunk);
+            }
+        }
+
+        while self.buffer.len() > self.capacity {
+            self.buffer.pop_front();
+        }
+
+        let now = unix_timestamp();
+        let elapsed = now - self.counting_since;
+        if elapsed >= 1.0 {
+            // The first second starts with the first update
+            if self.counting_since > 0.0 {
+                self.byte_rate = self.counted as f64 / elapsed;
+            }
+            self.counted = 0;
+            self.counting_since = now;
+        }
+    }
+
+    /// The bytes per second the port received, whether the monitor is paused or not.
+    pub fn byte_rate(&self) -> f64 {
+        self.byte_rate
+    }
+
+    /// The bytes received with the last update.
+    pub fn received(&self) -> &[u8] {
+        &self.received
+    }
+
+    /// The last `count` bytes received, e.g. to preview how they are parsed.
+    pub fn recent(&self, count: usize) -> Vec<u8> {
+        let skip = self.buffer.len().saturating_sub(count);
+        self.buffer.iter().skip(skip).copied().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.view, RawView::Text, "Text");
+            ui.selectable_value(&mut self.view, RawView::Hex, "Hex");
+            if self.view == RawView::Text {
+                ui.checkbox(&mut self.ansi, "ANSI")
+                    .on_hover_text("Interprets colors and carriage returns like a terminal");
+            }
+            ui.separator();
+
+            let pause_label = if self.paused { "resume" } else { "pause" };
+            if ui.button(pause_label).clicked() {
+                self.paused = !self.paused;
+            }
+            if ui.button("clear").clicked() {
+                self.clear();
+            }
+            ui.separator();
+
+            ui.add(
+                egui::DragValue::new(&mut self.capacity)
+                    .clamp_range(256..=1024 * 1024)
+                    .speed(256)
+                    .suffix(" bytes"),
+            )
+            .on_hover_text("Size of the ring buffer holding the raw bytes");
+            ui.label(format!("{} bytes buffered", self.buffer.len()));
+            ui.label(format!("{:.0} bytes/s", self.byte_rate));
+        });
+
+        let bytes: &[u8] = self.buffer.make_contiguous();
+
+        match self.view {
+            RawView::Text if self.ansi => {
+                let mut job = LayoutJob::default();
+                for (index, line) in terminal_lines(bytes).into_iter().enumerate() {
+                    if index > 0 {
+                        job.append("\n", 0.0, Style::default().format(ui));
+                    }
+                    for (style, run) in line {
+                        job.append(&String::from_utf8_lossy(&run), 0.0, style.format(ui));
+                    }
+                }
+                job.wrap.max_width = ui.available_width();
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| ui.label(job));
+            }
+            RawView::Text => {
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(String::from_utf8_lossy(bytes)).monospace(),
+                            )
+                            .wrap(true),
+                        );
+                    });
+            }
+            RawView::Hex => {
+                let row_height = ui.text_style_height(&TextStyle::Monospace);
+                let rows = bytes.len().div_ceil(BYTES_PER_HEX_LINE);
+                egui::ScrollArea::vertical()
+                    .stick_to_bottom(true)
+                    .auto_shrink([false, false])
+                    .show_rows(ui, row_height, rows, |ui, row_range| {
+                        for row in row_range {
+                            let start = row * BYTES_PER_HEX_LINE;
+                            let end = (start + BYTES_PER_HEX_LINE).min(bytes.len());
+                            ui.monospace(hex_dump_line(start, &bytes[start..end]));
+                        }
+                    });
+            }
+        }
+    }
+}
+
+/// Formats up to [`BYTES_PER_HEX_LINE`] bytes like `hexdump -C` does.
+fn hex_dump_line(offset: usize, bytes: &[u8]) -> String {
+    let mut line = format!("{:08x}  ", offset);
+    for index in 0..BYTES_PER_HEX_LINE {
+        match bytes.get(index) {
+            Some(byte) => line.push_str(&format!("{:02x} ", byte)),
+            None => line.push_str("   "),
+        }
+        if index == BYTES_PER_HEX_LINE / 2 - 1 {
+            line.push(' ');
+        }
+    }
+    line.push_str(" |");
+    line.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+    line.push('|');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_overwrite_progress_lines_and_color_the_text() {
+        let lines =
+            terminal_lines(b"load 10%\rload 100%\n\x1b[1;31merror\x1b[0m ok\r\nab\rx\x1b[K");
+        let red = Style {
+            foreground: Some(1),
+            background: None,
+            bold: true,
+        };
+
+        assert_eq!(
+            lines,
+            vec![
+                vec![(Style::default(), b"load 100%".to_vec())],
+                vec![
+                    (red, b"error".to_vec()),
+                    (Style::default(), b" ok".to_vec())
+                ],
+                vec![(Style::default(), b"x".to_vec())],
+            ]
+        );
+    }
+}
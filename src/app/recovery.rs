@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+};
+
+use egui::Ui;
+use tracing::{info, warn};
+
+use super::value_history::{Sample, ValueHistory};
+use crate::value_parsing::unix_timestamp;
+
+/// The samples of all channels at the time of an autosave.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Default, PartialEq)]
+pub struct RecoverySnapshot {
+    /// Seconds since the unix epoch
+    saved_at: f64,
+    /// The time and value of the samples, by the name the samples are stored under
+    channels: BTreeMap<String, Vec<[f64; 2]>>,
+}
+
+impl RecoverySnapshot {
+    fn of(history: &ValueHistory) -> Self {
+        let channels = history
+            .channel_names()
+            .filter_map(|name| {
+                let samples = history.samples(name)?;
+                let samples = samples.iter().map(|x| [x.time, x.value]).collect();
+                Some((name.to_string(), samples))
+            })
+            .collect();
+        Self {
+            saved_at: unix_timestamp(),
+            channels,
+        }
+    }
+
+    fn sample_count(&self) -> usize {
+        self.channels.values().map(Vec::len).sum()
+    }
+
+    /// Adds the samples to `history`, which keeps its limits.
+    pub fn restore(self, history: &mut ValueHistory) {
+        history.restore(self.channels.into_iter().map(|(name, samples)| {
+            let samples = samples
+                .into_iter()
+                .map(|[time, value]| Sample { time, value });
+            (name, samples.collect())
+        }));
+    }
+}
+
+/// Saves the samples periodically, so they can be restored after the application crashed.
+///
+/// The file is removed when the window is closed, finding it at the start means the last run ended
+/// uncleanly.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Recovery {
+    enabled: bool,
+    /// Seconds between two saves
+    interval: f64,
+
+    #[serde(skip)]
+    last_save: f64,
+    /// Writes the last save, the samples are serialized without blocking the ui
+    #[serde(skip)]
+    writer: Option<JoinHandle<()>>,
+    /// The samples of the last run, until they are restored or discarded
+    #[serde(skip)]
+    found: Option<RecoverySnapshot>,
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: 30.0,
+            last_save: unix_timestamp(),
+            writer: None,
+            found: None,
+        }
+    }
+}
+
+impl Recovery {
+    fn path() -> PathBuf {
+        std::env::temp_dir().join("serialplotter-recovery.json")
+    }
+
+    /// Looks for the samples of a run that ended without closing the window.
+    pub fn check(&mut self) {
+        let path = Self::path();
+        let found = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str::<RecoverySnapshot>(&content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return,
+            Err(err) => {
+                warn!("Failed to read {}: {}", path.display(), err);
+                return;
+            }
+        };
+        match found {
+            Ok(found) if found.sample_count() > 0 => {
+                info!("Found the samples of an unclean exit in {}", path.display());
+                self.found = Some(found);
+            }
+            Ok(_) => {}
+            Err(err) => warn!("Invalid recovery file {}: {}", path.display(), err),
+        }
+    }
+
+    /// Saves the samples once the interval passed, unless the ones of the last run are still
+    /// waiting for a decision.
+    pub fn update(&mut self, history: &ValueHistory) {
+        let now = unix_timestamp();
+        if !self.enabled || self.found.is_some() || now - self.last_save < self.interval {
+            return;
+        }
+        if self.writer.as_ref().is_some_and(|x| !x.is_finished()) {
+            return;
+        }
+        self.last_save = now;
+        let snapshot = RecoverySnapshot::of(history);
+        self.writer = Some(thread::spawn(move || {
+            if let Err(err) = write(&snapshot) {
+                warn!("Failed to save the samples for recovery: {}", err);
+            }
+        }));
+    }
+
+    /// Removes the file when the application exits cleanly.
+    pub fn exit(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        if self.found.is_some() {
+            // Kept for the next start, nothing was decided
+            return;
+        }
+        if let Err(err) = fs::remove_file(Self::path()) {
+            if err.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to remove the recovery file: {}", err);
+            }
+        }
+    }
+
+    /// Asks whether to restore the samples of the last run, returns them once restoring was chosen.
+    pub fn window(&mut self, ctx: &egui::Context) -> Option<RecoverySnapshot> {
+        let found = self.found.as_ref()?;
+        let mut restore = None;
+        egui::Window::new("Restore the last run")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The last run ended without closing the window.");
+                ui.label(format!(
+                    "{} samples of {} channels were saved {:.0} minutes ago.",
+                    found.sample_count(),
+                    found.channels.len(),
+                    (unix_timestamp() - found.saved_at) / 60.0
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        restore = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        restore = Some(false);
+                    }
+                });
+            });
+        match restore? {
+            true => self.found.take(),
+            false => {
+                self.found = None;
+                None
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Autosave every")
+                .on_hover_text(
+                    "Saves the samples to a temporary file, to restore them after a crash",
+                );
+            ui.add_enabled(
+                self.enabled,
+                egui::DragValue::new(&mut self.interval)
+                    .clamp_range(5.0..=3600.0)
+                    .suffix(" s"),
+            );
+        });
+    }
+}
+
+/// Writes to a file next to the recovery file first, so a crash while writing keeps the last save.
+fn write(snapshot: &RecoverySnapshot) -> io::Result<()> {
+    let path = Recovery::path();
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, serde_json::to_vec(snapshot)?)?;
+    fs::rename(partial, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_restore_the_saved_samples() {
+        let mut history = ValueHistory::with_capacity(100);
+        history.restore([(
+            "temperature".to_string(),
+            [1.0, 2.0].map(|time| Sample { time, value: 20.0 }).to_vec(),
+        )]);
+        let snapshot = RecoverySnapshot::of(&history);
+        assert_eq!(snapshot.sample_count(), 2);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let mut restored = ValueHistory::with_capacity(100);
+        serde_json::from_str::<RecoverySnapshot>(&json)
+            .unwrap()
+            .restore(&mut restored);
+        let samples: Vec<_> = restored.samples("temperature").unwrap().iter().collect();
+        assert_eq!(
+            samples,
+            vec![
+                Sample {
+                    time: 1.0,
+                    value: 20.0
+                },
+                Sample {
+                    time: 2.0,
+                    value: 20.0
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use egui::Ui;
+
+use super::value_history::ValueHistory;
+
+/// Plays an imported recording back as if it was received again, with the samples up to the
+/// position of a scrub bar shown.
+pub struct Replay {
+    name: String,
+    recording: ValueHistory,
+    /// The times of the first and the last sample of the recording
+    range: [f64; 2],
+    /// The time of the recording up to which the samples are shown
+    position: f64,
+    playing: bool,
+    /// Seconds of the recording played per second
+    speed: f64,
+    /// The ui time of the last frame while playing
+    clock: Option<f64>,
+    /// The samples of the recording up to `position`
+    shown: ValueHistory,
+}
+
+impl Replay {
+    /// Starts at the end of the recording, so all of it is shown; `None` without samples.
+    pub fn new(name: String, recording: ValueHistory) -> Option<Self> {
+        let range = recording.time_range()?;
+        Some(Self {
+            name,
+            shown: recording.clone(),
+            recording,
+            range,
+            position: range[1],
+            playing: false,
+            speed: 1.0,
+            clock: None,
+        })
+    }
+
+    /// The samples up to the position of the replay.
+    pub fn shown(&self) -> &ValueHistory {
+        &self.shown
+    }
+
+    fn seek(&mut self, position: f64) {
+        let [start, end] = self.range;
+        let position = position.clamp(start, end);
+        if position != self.position {
+            self.position = position;
+            self.shown = self.recording.snapshot(position);
+        }
+    }
+
+    /// Moves on by the time since the last frame times the speed, stops at the end.
+    fn advance(&mut self, now: f64) {
+        if !self.playing {
+            self.clock = None;
+            return;
+        }
+        let elapsed = self.clock.map_or(0.0, |clock| now - clock);
+        self.clock = Some(now);
+        self.seek(self.position + elapsed * self.speed);
+        if self.position >= self.range[1] {
+            self.playing = false;
+        }
+    }
+
+    /// The transport controls above the plot, returns whether the replay was closed.
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        self.advance(ui.input(|x| x.time));
+        if self.playing {
+            ui.ctx().request_repaint();
+        }
+
+        let [start, end] = self.range;
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.colored_label(ui.visuals().warn_fg_color, format!("Replay: {}", self.name));
+            if ui
+                .button("⏮")
+                .on_hover_text("Back to the previous sample")
+                .clicked()
+            {
+                self.playing = false;
+                let previous = self.recording.previous_sample(self.position);
+                self.seek(previous.unwrap_or(start));
+            }
+            let play = if self.playing { "⏸" } else { "▶" };
+            if ui.button(play).clicked() {
+                if !self.playing && self.position >= end {
+                    self.seek(start);
+                }
+                self.playing = !self.playing;
+            }
+            if ui
+                .button("⏭")
+                .on_hover_text("On to the next sample")
+                .clicked()
+            {
+                self.playing = false;
+                let next = self.recording.next_sample(self.position);
+                self.seek(next.unwrap_or(end));
+            }
+            egui::ComboBox::from_id_source("replay_speed")
+                .selected_text(format!("{}×", self.speed))
+                .width(60.0)
+                .show_ui(ui, |ui| {
+                    for speed in [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 100.0] {
+                        ui.selectable_value(&mut self.speed, speed, format!("{}×", speed));
+                    }
+                });
+            close = ui
+                .button("Close")
+                .on_hover_text("Returns to the live values")
+                .clicked();
+        });
+
+        let mut offset = self.position - start;
+        let response = ui.add(
+            egui::Slider::new(&mut offset, 0.0..=(end - start))
+                .suffix(" s")
+                .show_value(true),
+        );
+        if response.changed() {
+            self.seek(start + offset);
+        }
+        close
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_step_through_the_samples_of_the_recording() {
+        let recording = ValueHistory::imported([
+            ("a".to_string(), vec![[10.0, 1.0], [12.0, 2.0], [14.0, 3.0]]),
+            ("b".to_string(), vec![[11.0, 5.0]]),
+        ]);
+        let mut replay = Replay::new("test.csv".to_string(), recording).unwrap();
+        let shown = |replay: &Replay, name| replay.shown().samples(name).map_or(0, |x| x.len());
+        assert_eq!((shown(&replay, "a"), shown(&replay, "b")), (3, 1));
+
+        replay.seek(10.0);
+        assert_eq!((shown(&replay, "a"), shown(&replay, "b")), (1, 0));
+        replay.seek(replay.recording.next_sample(10.0).unwrap());
+        assert_eq!((shown(&replay, "a"), shown(&replay, "b")), (1, 1));
+        assert_eq!(replay.recording.previous_sample(11.0), Some(10.0));
+
+        replay.playing = true;
+        replay.speed = 2.0;
+        replay.advance(100.0);
+        replay.advance(100.5);
+        assert_eq!(replay.position, 12.0);
+        replay.advance(102.0);
+        assert_eq!(replay.position, 14.0);
+        assert!(!replay.playing, "the replay should stop at the end");
+    }
+}
@@ -0,0 +1,336 @@
+use egui::Ui;
+
+use super::{
+    event_log::format_utc,
+    value_history::{PlotSettings, ValueHistory},
+};
+use crate::value_parsing::{unix_timestamp, DataSource};
+
+/// What a channel received during a run.
+#[derive(Debug, Clone, PartialEq)]
+struct ChannelSummary {
+    name: String,
+    samples: usize,
+    min: f64,
+    max: f64,
+    unit: Option<String>,
+}
+
+/// What happened between opening and closing a source.
+#[derive(Debug, Clone, PartialEq)]
+struct RunSummary {
+    source: String,
+    start: f64,
+    end: f64,
+    channels: Vec<ChannelSummary>,
+    alarms: usize,
+    /// Values dropped because the ui fell behind
+    dropped: u64,
+}
+
+impl RunSummary {
+    /// Summarizes the samples of `history` received from the start of `run` to `end`, as they are plotted.
+    fn of(history: &ValueHistory, plot: &PlotSettings, run: &Run, end: f64, dropped: u64) -> Self {
+        let mut channels: Vec<ChannelSummary> = history
+            .channel_names()
+            .filter_map(|name| {
+                let samples = history.samples(name)?;
+                let values = samples
+                    .iter()
+                    .filter(|sample| (run.start..=end).contains(&sample.time))
+                    .map(|sample| plot.calibrated(name, sample.value));
+                let (samples, min, max) = values.fold(
+                    (0, f64::INFINITY, f64::NEG_INFINITY),
+                    |(samples, min, max), value| (samples + 1, min.min(value), max.max(value)),
+                );
+                (samples > 0).then(|| ChannelSummary {
+                    name: name.to_string(),
+                    samples,
+                    min,
+                    max,
+                    unit: plot.unit(name).map(str::to_string),
+                })
+            })
+            .collect();
+        channels.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            source: run.source.clone(),
+            start: run.start,
+            end,
+            channels,
+            alarms: run.alarms,
+            dropped: dropped.saturating_sub(run.dropped),
+        }
+    }
+}
+
+/// A source that is currently open.
+struct Run {
+    source: String,
+    start: f64,
+    alarms: usize,
+    /// The dropped values counted before the run
+    dropped: u64,
+}
+
+/// What was chosen on the summary of a run.
+pub enum RunAction {
+    /// Saves the samples of the run
+    #[cfg(not(target_arch = "wasm32"))]
+    Export,
+    /// Drops the samples received since the run started
+    Discard(f64),
+}
+
+/// Shows a summary of each measurement run once its source closes, to export or discard it.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct RunSummaries {
+    enabled: bool,
+    /// Exported runs are saved here
+    directory: String,
+
+    #[serde(skip)]
+    run: Option<Run>,
+    #[serde(skip)]
+    summary: Option<RunSummary>,
+    /// The result of the last export
+    #[serde(skip)]
+    status: Option<Result<String, String>>,
+}
+
+impl Default for RunSummaries {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: "runs".to_string(),
+            run: None,
+            summary: None,
+            status: None,
+        }
+    }
+}
+
+impl RunSummaries {
+    /// Starts a run when a source opened and summarizes it once the source is gone.
+    pub fn update(
+        &mut self,
+        source: Option<&dyn DataSource>,
+        history: &ValueHistory,
+        plot: &PlotSettings,
+        dropped: u64,
+    ) {
+        match (source, &self.run) {
+            (Some(source), None) => {
+                self.run = Some(Run {
+                    source: source.name().to_string(),
+                    start: unix_timestamp(),
+                    alarms: 0,
+                    dropped,
+                });
+            }
+            (None, Some(_)) => {
+                let run = self.run.take();
+                if let Some(run) = run.filter(|_| self.enabled) {
+                    self.summary = Some(RunSummary::of(
+                        history,
+                        plot,
+                        &run,
+                        unix_timestamp(),
+                        dropped,
+                    ));
+                    self.status = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts an alarm fired during the current run.
+    pub fn alarm(&mut self) {
+        if let Some(run) = &mut self.run {
+            run.alarms += 1;
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, "Summarize each run")
+            .on_hover_text("Shows the statistics of the values received once the port closes");
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Exports to");
+                ui.text_edit_singleline(&mut self.directory);
+            });
+        });
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) -> Option<RunAction> {
+        let summary = self.summary.as_ref()?;
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new("Run summary")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.label(&summary.source);
+                ui.label(format!(
+                    "{} for {}",
+                    format_utc(summary.start),
+                    format_duration(summary.end - summary.start)
+                ));
+                ui.label(format!(
+                    "{} alarms fired, {} values dropped",
+                    summary.alarms, summary.dropped
+                ));
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("run_summary").striped(true).show(ui, |ui| {
+                            ui.strong("Channel");
+                            ui.strong("Samples");
+                            ui.strong("Min");
+                            ui.strong("Max");
+                            ui.strong("Unit");
+                            ui.end_row();
+                            for channel in &summary.channels {
+                                ui.label(&channel.name);
+                                ui.label(channel.samples.to_string());
+                                ui.label(format!("{:.4}", channel.min));
+                                ui.label(format!("{:.4}", channel.max));
+                                ui.label(channel.unit.as_deref().unwrap_or_default());
+                                ui.end_row();
+                            }
+                        });
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui.button("Export CSV").clicked() {
+                        action = Some(RunAction::Export);
+                    }
+                    if ui
+                        .button("Discard")
+                        .on_hover_text("Drop the samples of the run from the plot")
+                        .clicked()
+                    {
+                        action = Some(RunAction::Discard(summary.start));
+                    }
+                });
+                match &self.status {
+                    Some(Ok(status)) => {
+                        ui.label(status);
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    None => {}
+                }
+            });
+        if !open || matches!(action, Some(RunAction::Discard(_))) {
+            self.summary = None;
+        }
+        action
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::export_channels::ExportChannels;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{calibration::Calibrations, csv_format::CsvFormat};
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RunSummaries {
+    /// Saves the samples of the summarized run like the data of an alarm capture.
+    pub fn export(
+        &mut self,
+        history: &ValueHistory,
+        format: &CsvFormat,
+        calibrations: &Calibrations,
+        channels: &ExportChannels,
+    ) {
+        let Some(summary) = &self.summary else {
+            return;
+        };
+        let directory = std::path::Path::new(&self.directory);
+        let path = directory.join(format!(
+            "run_{}.csv",
+            super::event_log::file_stamp(summary.start)
+        ));
+        let result = std::fs::create_dir_all(directory).and_then(|()| {
+            let range = summary.start..=summary.end;
+            super::alarms::write_snippet(history, range, &path, format, calibrations, channels)
+        });
+        self.status = Some(match result {
+            Ok(()) => Ok(format!("Exported to {}", path.display())),
+            Err(err) => Err(format!("Failed to export the run: {}", err)),
+        });
+    }
+}
+
+/// `seconds` as hours, minutes and seconds.
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calibration::{Calibrations, Pipeline, Step};
+    use crate::value_parsing::DataValue;
+
+    #[test]
+    fn should_summarize_the_samples_of_the_run() {
+        let (sender, mut receiver) = crossbeam::channel::unbounded();
+        for (timestamp, value) in [(1.0, 5.0), (2.0, -1.0), (3.0, 4.0), (4.0, 9.0)] {
+            let value = DataValue {
+                name: "x".to_string(),
+                value,
+                timestamp,
+                line: 0,
+            };
+            sender.send(vec![value]).unwrap();
+        }
+        let mut history = ValueHistory::with_capacity(100);
+        while history.try_receive(&mut receiver) {}
+        let mut calibrations = Calibrations::default();
+        calibrations.plot_calibrated = true;
+        calibrations.pipelines.insert(
+            "x".to_string(),
+            Pipeline {
+                steps: vec![Step::Scale(2.0)],
+            },
+        );
+        calibrations.units.insert("x".to_string(), "V".to_string());
+        let mut plot = PlotSettings::default();
+        plot.set_calibrations(&calibrations);
+
+        let run = Run {
+            source: "COM1".to_string(),
+            start: 2.0,
+            alarms: 1,
+            dropped: 3,
+        };
+        let summary = RunSummary::of(&history, &plot, &run, 3.5, 10);
+        assert_eq!(
+            summary.channels,
+            vec![ChannelSummary {
+                name: "x".to_string(),
+                samples: 2,
+                min: -2.0,
+                max: 8.0,
+                unit: Some("V".to_string()),
+            }]
+        );
+        assert_eq!(summary.dropped, 7);
+        assert_eq!(format_duration(3723.0), "1:02:03");
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::{BTreeSet, HashMap};
+#[cfg(feature = "scripting")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+use egui::Ui;
+#[cfg(feature = "scripting")]
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, INT};
+
+use super::value_history::ValueHistory;
+
+/// The most operations a hook may run, so a script stuck in a loop can not freeze the ui.
+#[cfg(feature = "scripting")]
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+const EXAMPLE: &str = r#"// Called with every received value
+fn on_sample(name, value) {
+    if name == "temperature" && value > 80.0 {
+        alert("too hot: " + value);
+    }
+}
+
+// Called with every line received from the port
+fn on_line(line) {
+}
+
+// Called once per second, `this` is a map kept between the calls
+fn on_tick() {
+    // send("status\n");
+}
+"#;
+
+/// What a script asked for, carried out by the app after the hooks ran.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+pub enum ScriptAction {
+    /// A value of a channel the script derives, `emit(name, value)`
+    Emit { name: String, value: f64 },
+    /// A message to the device, `send(text)`
+    Send(String),
+    /// An entry in the alerts panel, `alert(message)`
+    Alert(String),
+}
+
+/// Splits the received bytes into lines, keeping an incomplete line until its end arrives.
+#[derive(Default)]
+struct LineSplitter {
+    pending: Vec<u8>,
+}
+
+impl LineSplitter {
+    fn lines(&mut self, received: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for &byte in received {
+            if byte == b'\n' {
+                let line = String::from_utf8_lossy(&self.pending);
+                lines.push(line.trim_end_matches('\r').to_string());
+                self.pending.clear();
+            } else {
+                self.pending.push(byte);
+            }
+        }
+        lines
+    }
+}
+
+/// A compiled script and the functions it can call.
+#[cfg(feature = "scripting")]
+struct Runtime {
+    engine: Engine,
+    ast: AST,
+    /// The `this` of every hook
+    state: Dynamic,
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+}
+
+#[cfg(feature = "scripting")]
+impl Runtime {
+    fn compile(script: &str) -> Result<Self, String> {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print(|text| tracing::info!("script: {}", text));
+        let push = |actions: &Arc<Mutex<Vec<ScriptAction>>>| {
+            let actions = actions.clone();
+            move |action| {
+                actions
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(action)
+            }
+        };
+        let emit = push(&actions);
+        engine.register_fn("emit", move |name: &str, value: f64| {
+            emit(ScriptAction::Emit {
+                name: name.to_string(),
+                value,
+            })
+        });
+        let emit = push(&actions);
+        engine.register_fn("emit", move |name: &str, value: INT| {
+            emit(ScriptAction::Emit {
+                name: name.to_string(),
+                value: value as f64,
+            })
+        });
+        let send = push(&actions);
+        engine.register_fn("send", move |text: &str| {
+            send(ScriptAction::Send(text.to_string()))
+        });
+        let alert = push(&actions);
+        engine.register_fn("alert", move |message: &str| {
+            alert(ScriptAction::Alert(message.to_string()))
+        });
+
+        let ast = engine.compile(script).map_err(|err| err.to_string())?;
+        // Runs the statements outside of the hooks once, e.g. to print a greeting
+        engine
+            .run_ast_with_scope(&mut Scope::new(), &ast)
+            .map_err(|err| err.to_string())?;
+        Ok(Self {
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            actions,
+        })
+    }
+
+    fn has_hook(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == arity)
+    }
+
+    fn call(&mut self, hook: &str, args: impl rhai::FuncArgs) -> Result<(), String> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, hook, args)
+            .map(|_| ())
+            .map_err(|err| format!("{}: {}", hook, err))
+    }
+
+    fn take_actions(&self) -> Vec<ScriptAction> {
+        std::mem::take(&mut self.actions.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Runs a Rhai script with hooks for the received values and lines and a tick every second.
+///
+/// The script can derive channels, send messages to the device and raise alerts, e.g. for
+/// custom protocols or test automation. A failing script is stopped.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Scripting {
+    script: String,
+
+    show: bool,
+    #[cfg(feature = "scripting")]
+    #[serde(skip)]
+    runtime: Option<Runtime>,
+    #[serde(skip)]
+    error: Option<String>,
+    /// The time of the last sample passed to the script, by channel
+    #[serde(skip)]
+    last: HashMap<String, f64>,
+    /// Samples up to this time were received before the script started
+    #[serde(skip)]
+    started: f64,
+    #[serde(skip)]
+    last_tick: f64,
+    /// The channels the script emits, they are not passed back to it
+    #[serde(skip)]
+    emitted: BTreeSet<String>,
+    #[serde(skip)]
+    lines: LineSplitter,
+}
+
+impl Default for Scripting {
+    fn default() -> Self {
+        Self {
+            script: EXAMPLE.to_string(),
+            show: false,
+            #[cfg(feature = "scripting")]
+            runtime: None,
+            error: None,
+            last: HashMap::new(),
+            started: 0.0,
+            last_tick: 0.0,
+            emitted: BTreeSet::new(),
+            lines: LineSplitter::default(),
+        }
+    }
+}
+
+impl Scripting {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) {
+        let mut show = self.show;
+        egui::Window::new("Script")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+        self.show = show;
+    }
+
+    #[cfg(feature = "scripting")]
+    fn is_running(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn is_running(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "scripting")]
+    fn start(&mut self, now: f64) {
+        match Runtime::compile(&self.script) {
+            Ok(runtime) => {
+                self.runtime = Some(runtime);
+                self.error = None;
+                self.last.clear();
+                self.emitted.clear();
+                self.lines = LineSplitter::default();
+                self.started = now;
+                self.last_tick = now;
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn start(&mut self, _now: f64) {}
+
+    fn stop(&mut self) {
+        #[cfg(feature = "scripting")]
+        {
+            self.runtime = None;
+        }
+    }
+
+    /// Passes the samples and `received` bytes since the last call to the hooks of the script.
+    pub fn update(
+        &mut self,
+        history: &ValueHistory,
+        received: &[u8],
+        now: f64,
+    ) -> Vec<ScriptAction> {
+        if !self.is_running() {
+            return Vec::new();
+        }
+        let lines = self.lines.lines(received);
+        let mut samples = Vec::new();
+        let channels = history
+            .channel_names()
+            .filter(|name| !self.emitted.contains(*name));
+        for name in channels {
+            let Some(buffer) = history.samples(name) else {
+                continue;
+            };
+            let last = self.last.entry(name.to_string()).or_insert(self.started);
+            let new = buffer.iter().rev().take_while(|x| x.time > *last).count();
+            for sample in buffer.iter().skip(buffer.len() - new) {
+                samples.push((name.to_string(), sample.value));
+                *last = sample.time;
+            }
+        }
+        let tick = now - self.last_tick >= 1.0;
+        if tick {
+            self.last_tick = now;
+        }
+
+        let (actions, result) = self.run_hooks(lines, samples, tick);
+        for action in &actions {
+            if let ScriptAction::Emit { name, .. } = action {
+                self.emitted.insert(name.clone());
+            }
+        }
+        if let Err(err) = result {
+            tracing::warn!("Stopped the script: {}", err);
+            self.error = Some(err);
+            self.stop();
+        }
+        actions
+    }
+
+    #[cfg(feature = "scripting")]
+    fn run_hooks(
+        &mut self,
+        lines: Vec<String>,
+        samples: Vec<(String, f64)>,
+        tick: bool,
+    ) -> (Vec<ScriptAction>, Result<(), String>) {
+        let Some(runtime) = &mut self.runtime else {
+            return (Vec::new(), Ok(()));
+        };
+        let run = || -> Result<(), String> {
+            if runtime.has_hook("on_line", 1) {
+                for line in lines {
+                    runtime.call("on_line", (line,))?;
+                }
+            }
+            if runtime.has_hook("on_sample", 2) {
+                for sample in samples {
+                    runtime.call("on_sample", sample)?;
+                }
+            }
+            if tick && runtime.has_hook("on_tick", 0) {
+                runtime.call("on_tick", ())?;
+            }
+            Ok(())
+        };
+        let result = run();
+        (runtime.take_actions(), result)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn run_hooks(
+        &mut self,
+        _lines: Vec<String>,
+        _samples: Vec<(String, f64)>,
+        _tick: bool,
+    ) -> (Vec<ScriptAction>, Result<(), String>) {
+        (Vec::new(), Ok(()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        if !cfg!(feature = "scripting") {
+            ui.label("Built without the `scripting` feature");
+            return;
+        }
+
+        let running = self.is_running();
+        ui.horizontal(|ui| {
+            if running {
+                if ui.button("stop").clicked() {
+                    self.stop();
+                }
+                ui.label("running");
+            } else if ui.button("run").clicked() {
+                self.start(crate::value_parsing::unix_timestamp());
+            }
+        })
+        .response
+        .on_hover_text(
+            "Hooks: on_sample(name, value), on_line(line), on_tick()\n\
+             Functions: emit(name, value), send(text), alert(message), print(text)",
+        );
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.add_enabled_ui(!running, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.script)
+                        .code_editor()
+                        .desired_rows(16)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_an_incomplete_line_until_its_end_arrives() {
+        let mut splitter = LineSplitter::default();
+        assert_eq!(splitter.lines(b"a,1\r\nb,"), vec!["a,1"]);
+        assert!(splitter.lines(b"2").is_empty());
+        assert_eq!(splitter.lines(b"\n\n"), vec!["b,2", ""]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use egui::Ui;
+
+/// How the samples of a channel are drawn.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeriesStyle {
+    #[default]
+    Line,
+    /// Holds every value until the next one, for states and digital pins
+    Step,
+    /// Only the samples, without connecting them
+    Points,
+    LinePoints,
+}
+
+impl SeriesStyle {
+    pub const ALL: [SeriesStyle; 4] = [
+        SeriesStyle::Line,
+        SeriesStyle::Step,
+        SeriesStyle::Points,
+        SeriesStyle::LinePoints,
+    ];
+
+    pub fn has_line(self) -> bool {
+        self != SeriesStyle::Points
+    }
+
+    pub fn has_points(self) -> bool {
+        matches!(self, SeriesStyle::Points | SeriesStyle::LinePoints)
+    }
+}
+
+impl std::fmt::Display for SeriesStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            SeriesStyle::Line => "line",
+            SeriesStyle::Step => "step",
+            SeriesStyle::Points => "points",
+            SeriesStyle::LinePoints => "line and points",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The points of a staircase through `series`, every value is held until the next sample.
+pub fn steps(series: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut stairs = Vec::with_capacity(series.len() * 2);
+    let mut previous = None;
+    for &[x, y] in series {
+        if let Some(previous) = previous.replace(y) {
+            stairs.push([x, previous]);
+        }
+        stairs.push([x, y]);
+    }
+    stairs
+}
+
+/// The style of the channels drawn other than as a line.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct SeriesStyles {
+    /// The style of each channel, by the name the samples are stored under
+    pub styles: BTreeMap<String, SeriesStyle>,
+
+    show: bool,
+}
+
+impl SeriesStyles {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Series styles")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        egui::Grid::new("series_styles")
+            .striped(true)
+            .show(ui, |ui| {
+                for channel in channels {
+                    let mut style = self.styles.get(*channel).copied().unwrap_or_default();
+                    ui.label(*channel);
+                    egui::ComboBox::from_id_source(("series_style", channel))
+                        .selected_text(style.to_string())
+                        .show_ui(ui, |ui| {
+                            for option in SeriesStyle::ALL {
+                                ui.selectable_value(&mut style, option, option.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    if style == SeriesStyle::default() {
+                        self.styles.remove(*channel);
+                    } else {
+                        self.styles.insert(channel.to_string(), style);
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_hold_every_value_until_the_next_sample() {
+        assert_eq!(
+            steps(&[[0.0, 0.0], [1.0, 1.0], [3.0, 0.0]]),
+            vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [3.0, 1.0], [3.0, 0.0]]
+        );
+        assert!(steps(&[]).is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{ffi::OsStr, fmt::Display, fs, io, path::PathBuf};
+
+use egui::Ui;
+use serde::{de::DeserializeOwned, Serialize};
+
+const EXTENSION: &str = "json";
+
+#[derive(Debug)]
+pub enum SessionError {
+    NoSessionDirectory,
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::NoSessionDirectory => write!(f, "no directory to store sessions in"),
+            SessionError::Io(err) => write!(f, "{}", err),
+            SessionError::Format(err) => write!(f, "invalid session file: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for SessionError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+/// The directory in which named sessions are stored.
+pub fn session_directory() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "serialplotter")
+        .map(|dirs| dirs.data_dir().join("sessions"))
+}
+
+/// The names of all stored sessions, sorted alphabetically.
+pub fn list_sessions() -> Vec<String> {
+    let Some(directory) = session_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension() == Some(OsStr::new(EXTENSION)))
+        .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+fn session_path(name: &str) -> Result<PathBuf, SessionError> {
+    let directory = session_directory().ok_or(SessionError::NoSessionDirectory)?;
+    Ok(directory.join(format!("{}.{}", name, EXTENSION)))
+}
+
+pub fn save<T: Serialize>(name: &str, session: &T) -> Result<PathBuf, SessionError> {
+    let path = session_path(name)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(session)?)?;
+    Ok(path)
+}
+
+pub fn load<T: DeserializeOwned>(name: &str) -> Result<T, SessionError> {
+    let content = fs::read_to_string(session_path(name)?)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub enum SessionAction {
+    Save(String),
+    Load(String),
+}
+
+/// The entries of the File menu for storing and restoring named sessions.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct SessionMenu {
+    /// The name of the last saved or loaded session
+    name: String,
+
+    #[serde(skip)]
+    show_save: bool,
+}
+
+impl SessionMenu {
+    pub fn menu_ui(&mut self, ui: &mut Ui) -> Option<SessionAction> {
+        if ui.button("Save session…").clicked() {
+            self.show_save = true;
+            ui.close_menu();
+        }
+
+        let mut action = None;
+        ui.menu_button("Load session", |ui| {
+            let sessions = list_sessions();
+            if sessions.is_empty() {
+                ui.label("no saved sessions");
+            }
+            for name in sessions {
+                if ui.button(&name).clicked() {
+                    self.name = name.clone();
+                    action = Some(SessionAction::Load(name));
+                    ui.close_menu();
+                }
+            }
+        })
+        .response
+        .on_hover_text("Closes the port and clears the plot before applying the session");
+        action
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) -> Option<SessionAction> {
+        let mut action = None;
+        let mut open = self.show_save;
+        egui::Window::new("Save session")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    ui.text_edit_singleline(&mut self.name);
+                });
+                let valid = is_valid_name(&self.name);
+                if ui
+                    .add_enabled(valid, egui::Button::new("Save"))
+                    .on_disabled_hover_text("The name must not be empty or contain path separators")
+                    .clicked()
+                {
+                    action = Some(SessionAction::Save(self.name.clone()));
+                }
+            });
+        self.show_save = open && action.is_none();
+        action
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.contains(['/', '\\', ':']) && name != "." && name != ".."
+}
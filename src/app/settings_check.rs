@@ -0,0 +1,162 @@
+use std::ops::RangeInclusive;
+
+use super::value_history::HistoryLimits;
+
+/// Sane baud rates, anything outside is most likely a corrupted or hand edited setting.
+const BAUD_RATES: RangeInclusive<u32> = 50..=12_000_000;
+/// The range the slider of the displayed values allows.
+const DISPLAYED_VALUES: RangeInclusive<usize> = 100..=100_000;
+/// The range the slider of the time window allows, in seconds.
+const TIME_WINDOW: RangeInclusive<f64> = 0.1..=3600.0;
+/// The largest capacity of a channel the settings allow.
+const MAX_CHANNEL_CAPACITY: usize = 10_000_000;
+/// The range the memory limit allows, in megabytes.
+const MEMORY_BUDGET: RangeInclusive<usize> = 1..=16_000;
+
+/// Validates the settings of the last run when they are loaded.
+///
+/// Missing settings are filled with their defaults, but values that were valid once, like a port
+/// that is gone, would only fail later. The adjustments are noted and shown without blocking the
+/// start.
+#[derive(Default)]
+pub struct SettingsCheck {
+    adjustments: Vec<String>,
+}
+
+impl SettingsCheck {
+    pub fn baud_rate(&mut self, baud_rate: &mut u32, default: u32) {
+        if !BAUD_RATES.contains(baud_rate) {
+            self.adjustments.push(format!(
+                "The baud rate {} was reset to {}",
+                baud_rate, default
+            ));
+            *baud_rate = default;
+        }
+    }
+
+    pub fn displayed_values(&mut self, displayed_values: &mut usize) {
+        let clamped = (*displayed_values).clamp(*DISPLAYED_VALUES.start(), *DISPLAYED_VALUES.end());
+        if clamped != *displayed_values {
+            self.adjustments.push(format!(
+                "The displayed values were limited from {} to {}",
+                displayed_values, clamped
+            ));
+            *displayed_values = clamped;
+        }
+    }
+
+    /// Resets a duration that is out of its range, e.g. not a number, to its default.
+    fn duration(
+        &mut self,
+        name: &str,
+        value: &mut f64,
+        range: RangeInclusive<f64>,
+        default: f64,
+        unit: &str,
+    ) {
+        if !range.contains(value) {
+            self.adjustments.push(format!(
+                "The {} of {} {} was reset to {} {}",
+                name, value, unit, default, unit
+            ));
+            *value = default;
+        }
+    }
+
+    pub fn time_window(&mut self, time_window: &mut f64, default: f64) {
+        self.duration("time window", time_window, TIME_WINDOW, default, "s");
+    }
+
+    /// Limits the capacity of the channels to what their samples may use of the memory limit.
+    pub fn history_limits(&mut self, limits: &mut HistoryLimits) {
+        if let Some(budget) = &mut limits.memory_budget {
+            if !MEMORY_BUDGET.contains(budget) {
+                let clamped = (*budget).clamp(*MEMORY_BUDGET.start(), *MEMORY_BUDGET.end());
+                self.adjustments.push(format!(
+                    "The memory limit was changed from {} MB to {} MB",
+                    budget, clamped
+                ));
+                *budget = clamped;
+            }
+        }
+        let max_capacity = match limits.memory_budget {
+            Some(budget) => budget * 1_000_000 / limits.precision.sample_size(),
+            None => MAX_CHANNEL_CAPACITY,
+        }
+        .min(MAX_CHANNEL_CAPACITY);
+        for (name, capacity) in &mut limits.channel_capacities {
+            if *capacity > max_capacity || *capacity < 2 {
+                let clamped = (*capacity).clamp(2, max_capacity);
+                self.adjustments.push(format!(
+                    "The capacity of {} was limited from {} to {} samples",
+                    name, capacity, clamped
+                ));
+                *capacity = clamped;
+            }
+        }
+    }
+
+    /// Notes that the port of the last run is gone, it is kept in case it is plugged in again.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub fn serial_port(&mut self, port: Option<&str>, present: &[String]) {
+        if let Some(port) = port.filter(|port| !present.iter().any(|x| x == port)) {
+            self.adjustments.push(format!(
+                "The port {} of the last run is not connected",
+                port
+            ));
+        }
+    }
+
+    /// Shows the adjustments until they are dismissed, the application is usable meanwhile.
+    pub fn window(&mut self, ctx: &egui::Context) {
+        if self.adjustments.is_empty() {
+            return;
+        }
+        let mut dismissed = false;
+        egui::Window::new("Settings adjusted")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_TOP, [-8.0, 8.0])
+            .show(ctx, |ui| {
+                ui.label("Some settings of the last run were not valid anymore:");
+                for adjustment in &self.adjustments {
+                    ui.label(format!("• {}", adjustment));
+                }
+                dismissed = ui.button("OK").clicked();
+            });
+        if dismissed {
+            self.adjustments.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_adjust_stale_settings_only() {
+        let mut check = SettingsCheck::default();
+        let mut baud_rate = 115_200;
+        check.baud_rate(&mut baud_rate, 9600);
+        let mut time_window = 10.0;
+        check.time_window(&mut time_window, 10.0);
+        check.serial_port(Some("/dev/ttyACM0"), &["/dev/ttyACM0".to_string()]);
+        assert!(check.adjustments.is_empty());
+
+        baud_rate = 0;
+        check.baud_rate(&mut baud_rate, 9600);
+        time_window = f64::NAN;
+        check.time_window(&mut time_window, 10.0);
+        let mut limits = HistoryLimits {
+            memory_budget: Some(1),
+            channel_capacities: [("a".to_string(), 1_000_000)].into(),
+            ..HistoryLimits::default()
+        };
+        check.history_limits(&mut limits);
+        check.serial_port(Some("COM3"), &[]);
+        assert_eq!((baud_rate, time_window), (9600, 10.0));
+        assert_eq!(limits.channel_capacities["a"], 62_500);
+        assert_eq!(check.adjustments.len(), 4);
+    }
+}
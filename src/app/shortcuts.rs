@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use egui::{Key, Modifiers, Ui};
+
+/// The keys offered for the shortcuts.
+const KEYS: [Key; 43] = [
+    Key::Space,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::ArrowDown,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+];
+
+/// Everything that can be done with a shortcut or from the command palette.
+#[derive(
+    serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Action {
+    /// Freezes the plot, or resumes it
+    TogglePause,
+    /// Drops the samples of all channels
+    Clear,
+    /// Saves the plot to the screenshot directory
+    Screenshot,
+    /// Opens the selected port, or closes the open one
+    TogglePort,
+    /// Starts the stopwatch, or stops it
+    ToggleStopwatch,
+    /// Records a lap of the stopwatch
+    StopwatchLap,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::TogglePause,
+        Action::Clear,
+        Action::Screenshot,
+        Action::TogglePort,
+        Action::ToggleStopwatch,
+        Action::StopwatchLap,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::PanUp,
+        Action::PanDown,
+    ];
+
+    fn default_key(self) -> Key {
+        match self {
+            Action::TogglePause => Key::Space,
+            Action::Clear => Key::C,
+            Action::Screenshot => Key::S,
+            Action::TogglePort => Key::O,
+            Action::ToggleStopwatch => Key::T,
+            Action::StopwatchLap => Key::L,
+            Action::PanLeft => Key::ArrowLeft,
+            Action::PanRight => Key::ArrowRight,
+            Action::PanUp => Key::ArrowUp,
+            Action::PanDown => Key::ArrowDown,
+        }
+    }
+
+    /// The fractions of the width and height of the plot a pan moves it by.
+    pub fn pan(self) -> Option<[f64; 2]> {
+        const STEP: f64 = 0.1;
+        match self {
+            Action::PanLeft => Some([-STEP, 0.0]),
+            Action::PanRight => Some([STEP, 0.0]),
+            Action::PanUp => Some([0.0, STEP]),
+            Action::PanDown => Some([0.0, -STEP]),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Action::TogglePause => "Pause / resume the plot",
+            Action::Clear => "Clear all channels",
+            Action::Screenshot => "Save a screenshot of the plot",
+            Action::TogglePort => "Open / close the port",
+            Action::ToggleStopwatch => "Start / stop the stopwatch",
+            Action::StopwatchLap => "Lap of the stopwatch",
+            Action::PanLeft => "Pan left",
+            Action::PanRight => "Pan right",
+            Action::PanUp => "Pan up",
+            Action::PanDown => "Pan down",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The actions in the order of the palette that contain every word of `filter`.
+fn matching(filter: &str) -> impl Iterator<Item = Action> + '_ {
+    let filter = filter.to_lowercase();
+    Action::ALL.into_iter().filter(move |action| {
+        let description = action.to_string().to_lowercase();
+        filter
+            .split_whitespace()
+            .all(|word| description.contains(word))
+    })
+}
+
+/// Single key shortcuts for the frequent actions, and a command palette listing all of them.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    /// The key of each action, actions without one are only in the palette
+    keys: BTreeMap<Action, Key>,
+
+    show: bool,
+    /// The filter typed into the palette while it is open
+    #[serde(skip)]
+    palette: Option<String>,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+            show: false,
+            palette: None,
+        }
+    }
+}
+
+impl Shortcuts {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// The actions whose keys were pressed or which were chosen in the palette.
+    ///
+    /// The keys are ignored while a text field has the focus, so typing never triggers them.
+    pub fn update(&mut self, ctx: &egui::Context) -> Vec<Action> {
+        if ctx.input_mut(|x| x.consume_key(Modifiers::COMMAND, Key::P)) {
+            self.palette = match self.palette {
+                Some(_) => None,
+                None => Some(String::new()),
+            };
+        }
+
+        let mut actions = Vec::new();
+        if self.palette.is_none() && !ctx.wants_keyboard_input() {
+            for (action, key) in &self.keys {
+                if ctx.input_mut(|x| x.consume_key(Modifiers::NONE, *key)) {
+                    actions.push(*action);
+                }
+            }
+        }
+        actions.extend(self.palette_ui(ctx));
+        actions
+    }
+
+    fn palette_ui(&mut self, ctx: &egui::Context) -> Option<Action> {
+        let filter = self.palette.as_mut()?;
+        let mut chosen = None;
+        let mut close = ctx.input_mut(|x| x.consume_key(Modifiers::NONE, Key::Escape));
+        egui::Window::new("Commands")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(filter)
+                        .hint_text("Type to search, Enter runs the first command"),
+                );
+                response.request_focus();
+                let enter = ui.input(|x| x.key_pressed(Key::Enter));
+                let mut first = true;
+                for action in matching(filter) {
+                    let label = match self.keys.get(&action) {
+                        Some(key) => format!("{}    {:?}", action, key),
+                        None => action.to_string(),
+                    };
+                    if ui.selectable_label(first, label).clicked() || (first && enter) {
+                        chosen = Some(action);
+                    }
+                    first = false;
+                }
+            });
+        close |= chosen.is_some();
+        if close {
+            self.palette = None;
+        }
+        chosen
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context) {
+        let mut show = self.show;
+        egui::Window::new("Keyboard shortcuts")
+            .open(&mut show)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.label("Ctrl+P opens the command palette with all actions");
+        egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+            for action in Action::ALL {
+                let mut key = self.keys.get(&action).copied();
+                ui.label(action.to_string());
+                egui::ComboBox::from_id_source(("shortcut", action))
+                    .selected_text(key.map_or("none".to_string(), |key| format!("{:?}", key)))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut key, None, "none");
+                        for option in KEYS {
+                            ui.selectable_value(&mut key, Some(option), format!("{:?}", option));
+                        }
+                    });
+                ui.end_row();
+
+                match key {
+                    Some(key) => self.keys.insert(action, key),
+                    None => self.keys.remove(&action),
+                };
+            }
+        });
+        if ui.button("Restore the defaults").clicked() {
+            self.keys = Shortcuts::default().keys;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_actions_containing_all_words() {
+        assert_eq!(
+            matching("pan").collect::<Vec<_>>(),
+            vec![
+                Action::PanLeft,
+                Action::PanRight,
+                Action::PanUp,
+                Action::PanDown
+            ]
+        );
+        assert_eq!(
+            matching("PORT close").collect::<Vec<_>>(),
+            vec![Action::TogglePort]
+        );
+        assert_eq!(matching("").count(), Action::ALL.len());
+    }
+}
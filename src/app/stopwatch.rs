@@ -0,0 +1,147 @@
+use egui::{Align2, Rect, Ui};
+
+use super::event_log::{EventKind, EventLog};
+use crate::value_parsing::unix_timestamp;
+
+/// Times manually marked phases of an experiment with the clock of the samples.
+///
+/// Starting, stopping and every lap are recorded as markers in the event log, so the phases can
+/// be found in the exported data.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Stopwatch {
+    /// Shows the elapsed time over the plot
+    overlay: bool,
+
+    /// Seconds since the unix epoch the stopwatch was started at
+    #[serde(skip)]
+    started: Option<f64>,
+    /// Seconds since the unix epoch of the last lap, or the start
+    #[serde(skip)]
+    last_lap: f64,
+    /// The duration of every lap of the current run
+    #[serde(skip)]
+    laps: Vec<f64>,
+    /// The elapsed time of the last run, kept on the overlay once stopped
+    #[serde(skip)]
+    stopped: Option<f64>,
+}
+
+impl Stopwatch {
+    /// Starts the stopwatch, or stops the running one.
+    pub fn toggle(&mut self, event_log: &mut EventLog) {
+        let note = self.toggle_at(unix_timestamp());
+        event_log.record(EventKind::Marker, note);
+    }
+
+    /// Records a lap of the running stopwatch, a stopped one is started.
+    pub fn lap(&mut self, event_log: &mut EventLog) {
+        let note = match self.started {
+            Some(_) => self.lap_at(unix_timestamp()),
+            None => self.toggle_at(unix_timestamp()),
+        };
+        event_log.record(EventKind::Marker, note);
+    }
+
+    /// Returns the note of the marker.
+    fn toggle_at(&mut self, now: f64) -> String {
+        match self.started.take() {
+            Some(started) => {
+                self.stopped = Some(now - started);
+                format!("stopwatch stopped at {}", format_elapsed(now - started))
+            }
+            None => {
+                self.started = Some(now);
+                self.last_lap = now;
+                self.laps.clear();
+                self.stopped = None;
+                "stopwatch started".to_string()
+            }
+        }
+    }
+
+    /// Returns the note of the marker, with the time since the previous lap and the total.
+    fn lap_at(&mut self, now: f64) -> String {
+        let started = self.started.unwrap_or(now);
+        let lap = now - self.last_lap;
+        self.last_lap = now;
+        self.laps.push(lap);
+        format!(
+            "lap {}: {} ({})",
+            self.laps.len(),
+            format_elapsed(lap),
+            format_elapsed(now - started)
+        )
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, event_log: &mut EventLog) {
+        ui.horizontal(|ui| {
+            let text = match self.started {
+                Some(_) => "stop",
+                None => "start",
+            };
+            if ui.button(text).clicked() {
+                self.toggle(event_log);
+            }
+            if ui.button("lap").clicked() {
+                self.lap(event_log);
+            }
+            ui.checkbox(&mut self.overlay, "Show on plot");
+        });
+        if let Some(started) = self.started {
+            ui.label(format_elapsed(unix_timestamp() - started));
+        }
+    }
+
+    /// Draws the elapsed time and the last laps into the corner of the plot.
+    pub fn paint(&self, ui: &Ui, plot: Rect) {
+        if !self.overlay {
+            return;
+        }
+        let mut text = match (self.started, self.stopped) {
+            (Some(started), _) => {
+                // Keeps the elapsed time counting without new samples
+                ui.ctx()
+                    .request_repaint_after(std::time::Duration::from_millis(100));
+                format_elapsed(unix_timestamp() - started)
+            }
+            (None, Some(elapsed)) => format!("{} (stopped)", format_elapsed(elapsed)),
+            (None, None) => return,
+        };
+        for (index, lap) in self.laps.iter().enumerate().rev().take(3) {
+            text.push_str(&format!("\nlap {}: {}", index + 1, format_elapsed(*lap)));
+        }
+        ui.painter().text(
+            plot.right_top() + egui::vec2(-8.0, 8.0),
+            Align2::RIGHT_TOP,
+            text,
+            egui::FontId::monospace(16.0),
+            ui.visuals().strong_text_color(),
+        );
+    }
+}
+
+/// `seconds` as minutes, seconds and milliseconds.
+fn format_elapsed(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_note_the_split_and_total_of_each_lap() {
+        let mut stopwatch = Stopwatch::default();
+        assert_eq!(stopwatch.toggle_at(100.0), "stopwatch started");
+        assert_eq!(stopwatch.lap_at(101.5), "lap 1: 00:01.500 (00:01.500)");
+        assert_eq!(stopwatch.lap_at(164.0), "lap 2: 01:02.500 (01:04.000)");
+        assert_eq!(stopwatch.toggle_at(165.0), "stopwatch stopped at 01:05.000");
+    }
+}
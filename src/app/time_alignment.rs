@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use egui::Ui;
+
+use super::value_history::{Sample, SampleBuffer};
+
+/// How the value of a channel between two of its samples is found.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resampling {
+    /// The value of the closest sample
+    Nearest,
+    /// Interpolated between the samples before and after
+    #[default]
+    Linear,
+}
+
+/// Shifts channels in time to compensate for known delays of their sensors, so cause and effect line up.
+///
+/// Channels from independent clocks can be resampled onto the timeline of a reference channel,
+/// so all channels have values at the same times.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct TimeAlignment {
+    /// Seconds added to the time of each channel, by the name shown in the legend
+    pub offsets: BTreeMap<String, f64>,
+    /// The channel whose sample times the other channels are resampled onto
+    pub reference: Option<String>,
+    pub resampling: Resampling,
+
+    show: bool,
+}
+
+impl TimeAlignment {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Time alignment")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        ui.horizontal(|ui| {
+            ui.label("Reference clock");
+            egui::ComboBox::from_id_source("reference_channel")
+                .selected_text(self.reference.as_deref().unwrap_or("none"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.reference, None, "none");
+                    for channel in channels {
+                        ui.selectable_value(
+                            &mut self.reference,
+                            Some(channel.to_string()),
+                            *channel,
+                        );
+                    }
+                });
+            ui.add_enabled_ui(self.reference.is_some(), |ui| {
+                ui.radio_value(&mut self.resampling, Resampling::Nearest, "nearest");
+                ui.radio_value(&mut self.resampling, Resampling::Linear, "linear");
+            });
+        })
+        .response
+        .on_hover_text("The plot shows the other channels at the sample times of the reference");
+
+        ui.label("The offsets apply to the time axis of the plot and to exported snippets.");
+        egui::Grid::new("time_offsets")
+            .striped(true)
+            .show(ui, |ui| {
+                for channel in channels {
+                    let offset = self.offsets.get(*channel).copied().unwrap_or_default();
+                    let mut milliseconds = offset * 1000.0;
+                    ui.label(*channel);
+                    ui.add(
+                        egui::DragValue::new(&mut milliseconds)
+                            .speed(1.0)
+                            .suffix(" ms"),
+                    );
+                    if milliseconds == 0.0 {
+                        self.offsets.remove(*channel);
+                    } else {
+                        self.offsets
+                            .insert(channel.to_string(), milliseconds / 1000.0);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+/// The values of `samples` at the ascending `times` on the same clock.
+///
+/// Times before the first or after the last sample are skipped instead of extrapolated.
+pub fn resample(
+    samples: &SampleBuffer,
+    times: impl Iterator<Item = f64>,
+    resampling: Resampling,
+) -> SampleBuffer {
+    let (Some(first), Some(last)) = (samples.front(), samples.back()) else {
+        return SampleBuffer::default();
+    };
+    let mut index = 0;
+    times
+        .filter(|time| (first.time..=last.time).contains(time))
+        .map(|time| {
+            while index + 1 < samples.len() && samples.sample(index + 1).time <= time {
+                index += 1;
+            }
+            let before = samples.sample(index);
+            let value = match samples.get(index + 1) {
+                Some(after) if after.time > before.time => match resampling {
+                    Resampling::Nearest if time - before.time <= after.time - time => before.value,
+                    Resampling::Nearest => after.value,
+                    Resampling::Linear => {
+                        let fraction = (time - before.time) / (after.time - before.time);
+                        before.value + (after.value - before.value) * fraction
+                    }
+                },
+                _ => before.value,
+            };
+            Sample { time, value }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_resample_onto_the_reference_times() {
+        let samples: SampleBuffer = [(1.0, 10.0), (2.0, 20.0), (4.0, 0.0)]
+            .into_iter()
+            .map(|(time, value)| Sample { time, value })
+            .collect();
+        let times = [0.5, 1.5, 3.5, 4.0, 5.0];
+
+        let values = |resampling| -> Vec<[f64; 2]> {
+            resample(&samples, times.into_iter(), resampling)
+                .iter()
+                .map(|sample| [sample.time, sample.value])
+                .collect()
+        };
+
+        assert_eq!(
+            values(Resampling::Linear),
+            [[1.5, 15.0], [3.5, 5.0], [4.0, 0.0]]
+        );
+        assert_eq!(
+            values(Resampling::Nearest),
+            [[1.5, 10.0], [3.5, 0.0], [4.0, 0.0]]
+        );
+    }
+}
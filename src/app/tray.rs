@@ -0,0 +1,238 @@
+use egui::Ui;
+
+/// What was chosen in the menu of the tray icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayCommand {
+    Show,
+    StopLogging,
+    Quit,
+}
+
+/// The menu of the tray icon, by the id of the item.
+#[cfg_attr(not(feature = "tray"), allow(dead_code))]
+const MENU: [(TrayCommand, &str, &str); 3] = [
+    (TrayCommand::Show, "show", "Show window"),
+    (TrayCommand::StopLogging, "stop_logging", "Stop logging"),
+    (TrayCommand::Quit, "quit", "Quit"),
+];
+
+/// The icon in the system tray and the commands of its menu.
+#[cfg(feature = "tray")]
+struct Icon {
+    commands: crossbeam::channel::Receiver<TrayCommand>,
+    /// Linux keeps the icon on the thread running the gtk loop
+    #[cfg(not(target_os = "linux"))]
+    _icon: tray_icon::TrayIcon,
+}
+
+#[cfg(feature = "tray")]
+impl Icon {
+    /// Adds the icon to the tray, its menu repaints `ctx` so the commands are applied right away.
+    fn start(ctx: &egui::Context) -> Result<Self, String> {
+        use tray_icon::menu::MenuEvent;
+
+        let (sender, commands) = crossbeam::channel::unbounded();
+        let ctx = ctx.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let command = MENU.iter().find(|(_, id, _)| event.id.0 == *id);
+            if let Some((command, _, _)) = command {
+                let _ = sender.send(*command);
+                ctx.request_repaint();
+            }
+        }));
+
+        // The tray of gtk only works on a thread running its loop
+        #[cfg(target_os = "linux")]
+        {
+            let (started, result) = crossbeam::channel::bounded(1);
+            std::thread::Builder::new()
+                .name("Tray icon".to_string())
+                .spawn(move || {
+                    if let Err(err) = gtk::init() {
+                        let _ = started.send(Err(err.to_string()));
+                        return;
+                    }
+                    match build() {
+                        Ok(_icon) => {
+                            let _ = started.send(Ok(()));
+                            gtk::main();
+                        }
+                        Err(err) => {
+                            let _ = started.send(Err(err));
+                        }
+                    }
+                })
+                .map_err(|err| err.to_string())?;
+            result.recv().map_err(|err| err.to_string())??;
+            Ok(Self { commands })
+        }
+        #[cfg(not(target_os = "linux"))]
+        Ok(Self {
+            commands,
+            _icon: build()?,
+        })
+    }
+}
+
+#[cfg(feature = "tray")]
+fn build() -> Result<tray_icon::TrayIcon, String> {
+    use tray_icon::{
+        menu::{Menu, MenuItem},
+        TrayIconBuilder,
+    };
+
+    let menu = Menu::new();
+    for (_, id, text) in MENU {
+        menu.append(&MenuItem::with_id(id, text, true, None))
+            .map_err(|err| err.to_string())?;
+    }
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip("serialplotter")
+        .with_icon(picture().map_err(|err| err.to_string())?)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// A sine on a blue square.
+#[cfg(feature = "tray")]
+fn picture() -> Result<tray_icon::Icon, tray_icon::BadIcon> {
+    const SIZE: u32 = 32;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let phase = x as f32 / SIZE as f32 * std::f32::consts::TAU;
+            let sine = SIZE as f32 / 2.0 - phase.sin() * SIZE as f32 / 3.0;
+            let pixel = match (y as f32 - sine).abs() < 2.0 {
+                true => [255, 255, 255, 255],
+                false => [30, 110, 200, 255],
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+    tray_icon::Icon::from_rgba(rgba, SIZE, SIZE)
+}
+
+/// Hides the window in the system tray while the sources and the data logger keep running, e.g.
+/// for captures overnight. The menu of the tray icon shows the window again, stops logging or
+/// quits.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct TrayMode {
+    /// Closing the window hides it in the tray instead of quitting
+    close_to_tray: bool,
+
+    #[cfg(feature = "tray")]
+    #[serde(skip)]
+    icon: Option<Icon>,
+    /// Hides the window with the next frame
+    #[serde(skip)]
+    hide: bool,
+    #[serde(skip)]
+    quitting: bool,
+    #[serde(skip)]
+    error: Option<String>,
+}
+
+impl TrayMode {
+    #[cfg(feature = "tray")]
+    fn has_icon(&self) -> bool {
+        self.icon.is_some()
+    }
+
+    #[cfg(not(feature = "tray"))]
+    fn has_icon(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "tray")]
+    fn show_icon(&mut self, ctx: &egui::Context) {
+        if self.icon.is_none() {
+            match Icon::start(ctx) {
+                Ok(icon) => self.icon = Some(icon),
+                Err(err) => self.error = Some(format!("Failed to add the tray icon: {}", err)),
+            }
+        }
+    }
+
+    #[cfg(not(feature = "tray"))]
+    fn show_icon(&mut self, _ctx: &egui::Context) {}
+
+    /// Lets the next close request quit instead of hiding the window.
+    pub fn quit(&mut self) {
+        self.quitting = true;
+    }
+
+    /// Returns whether the window may close, with close to tray it is hidden instead.
+    pub fn on_close(&mut self) -> bool {
+        if self.close_to_tray && self.has_icon() && !self.quitting {
+            self.hide = true;
+            return false;
+        }
+        true
+    }
+
+    /// Hides and shows the window as requested, returns the commands of the menu left to the app.
+    pub fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) -> Vec<TrayCommand> {
+        // Also adds the icon for the setting restored at startup
+        if self.close_to_tray && self.error.is_none() {
+            self.show_icon(ctx);
+        }
+        if std::mem::take(&mut self.hide) {
+            frame.set_visible(false);
+        }
+        #[cfg(feature = "tray")]
+        if let Some(icon) = &self.icon {
+            let mut commands = Vec::new();
+            for command in icon.commands.try_iter() {
+                match command {
+                    TrayCommand::Show => frame.set_visible(true),
+                    TrayCommand::Quit => {
+                        self.quitting = true;
+                        frame.close();
+                    }
+                    TrayCommand::StopLogging => commands.push(command),
+                }
+            }
+            return commands;
+        }
+        Vec::new()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        if !cfg!(feature = "tray") {
+            ui.label("Built without the `tray` feature");
+            return;
+        }
+
+        ui.checkbox(&mut self.close_to_tray, "Close to tray")
+            .on_hover_text("Closing the window keeps reading and logging in the background");
+        if ui
+            .button("Hide to tray")
+            .on_hover_text("The menu of the tray icon shows the window again")
+            .clicked()
+        {
+            self.show_icon(ui.ctx());
+            self.hide = self.has_icon();
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_hide_the_window_with_an_icon_to_show_it_again() {
+        let mut tray_mode = TrayMode {
+            close_to_tray: true,
+            ..Default::default()
+        };
+
+        assert!(tray_mode.on_close());
+        assert!(!tray_mode.hide);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use egui::Ui;
+
+/// The time a frame may take, ingestion uses what the rest of the frame leaves of it.
+const TARGET_FRAME_TIME: f64 = 1.0 / 60.0;
+/// The least time an ingestion gets, so a slow ui still catches up eventually.
+const MIN_BUDGET: f64 = 0.001;
+/// The most time an ingestion gets while the backlog grows, the frame rate drops meanwhile.
+const MAX_BUDGET: f64 = 0.05;
+/// The share of the grown budget kept per frame while the backlog shrinks.
+const BUDGET_DECAY: f64 = 0.75;
+
+/// How often new samples are integrated into the history and how often the window is repainted.
+///
+/// Lowering either rate saves power, the samples wait in the data channel in the meantime.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct UpdateCadence {
+    /// Upper bound for repaints per second, `None` repaints as fast as vsync allows.
+    max_fps: Option<f64>,
+    /// Minimal time in seconds between two integrations of new samples, zero integrates every frame.
+    ingest_interval: f64,
+
+    #[serde(skip)]
+    last_ingest: f64,
+    /// The seconds the next ingestion may take
+    #[serde(skip)]
+    budget: f64,
+    /// The seconds the recent ingestions took, smoothed
+    #[serde(skip)]
+    ingest_time: f64,
+    /// The reads that were waiting before the last ingestion
+    #[serde(skip)]
+    waiting: usize,
+}
+
+impl Default for UpdateCadence {
+    fn default() -> Self {
+        Self {
+            max_fps: None,
+            ingest_interval: 0.0,
+            last_ingest: f64::NEG_INFINITY,
+            budget: MIN_BUDGET,
+            ingest_time: 0.0,
+            waiting: 0,
+        }
+    }
+}
+
+impl UpdateCadence {
+    /// Whether new samples should be integrated in the frame starting at `now`.
+    pub fn ingest_due(&mut self, now: f64) -> bool {
+        if now - self.last_ingest >= self.ingest_interval {
+            self.last_ingest = now;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The time an ingestion may take, `None` if it must not be limited.
+    ///
+    /// The ingestion gets what the rest of a frame of `frame_time` seconds leaves until 60 fps.
+    /// While the `waiting` reads pile up from frame to frame, e.g. after a burst of a device that
+    /// stalled, the budget grows on until they shrink, even if the frame rate drops meanwhile.
+    /// While some are left, it decays by [`BUDGET_DECAY`] per frame instead of dropping at once.
+    /// A throttled ingestion has to catch up on everything that arrived since the last one.
+    pub fn fetch_budget(&mut self, frame_time: f64, waiting: usize) -> Option<Duration> {
+        if self.ingest_interval > 0.0 {
+            return None;
+        }
+        let headroom = TARGET_FRAME_TIME - (frame_time - self.ingest_time).max(0.0);
+        let piling_up = waiting > 0 && waiting >= self.waiting;
+        self.budget = if piling_up {
+            (self.budget * 1.5).max(headroom)
+        } else if waiting > 0 {
+            (self.budget * BUDGET_DECAY).max(headroom)
+        } else {
+            headroom
+        }
+        .clamp(MIN_BUDGET, MAX_BUDGET);
+        self.waiting = waiting;
+        Some(Duration::from_secs_f64(self.budget))
+    }
+
+    /// Notes the seconds an ingestion took, the rest of the frame time is left to the ui.
+    pub fn ingested(&mut self, seconds: f64) {
+        self.ingest_time = 0.9 * self.ingest_time + 0.1 * seconds;
+    }
+
+    /// The seconds the next ingestion may take without a limiting ingest interval.
+    pub fn budget(&self) -> f64 {
+        self.budget
+    }
+
+    pub fn request_repaint(&self, ctx: &egui::Context) {
+        match self.max_fps {
+            None => ctx.request_repaint(),
+            Some(fps) => ctx.request_repaint_after(Duration::from_secs_f64(1.0 / fps)),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut limited = self.max_fps.is_some();
+        ui.checkbox(&mut limited, "Limit repaint rate");
+        match (limited, &mut self.max_fps) {
+            (true, None) => self.max_fps = Some(30.0),
+            (false, Some(_)) => self.max_fps = None,
+            _ => {}
+        }
+        if let Some(fps) = &mut self.max_fps {
+            ui.add(
+                egui::Slider::new(fps, 1.0..=60.0)
+                    .integer()
+                    .suffix(" fps")
+                    .text("max repaint rate"),
+            );
+        }
+
+        ui.add(
+            egui::Slider::new(&mut self.ingest_interval, 0.0..=2.0)
+                .suffix(" s")
+                .text("ingest interval"),
+        )
+        .on_hover_text("Minimal time between two integrations of new samples into the plot");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_grow_the_budget_while_the_reads_pile_up() {
+        let mut cadence = UpdateCadence::default();
+        // A light frame leaves most of the frame time
+        let budget = cadence.fetch_budget(0.004, 0).unwrap().as_secs_f64();
+        assert!((budget - (TARGET_FRAME_TIME - 0.004)).abs() < 1e-9);
+
+        // A burst of a heavy frame still grows until the backlog shrinks, then decays while it drains
+        let mut budgets = Vec::new();
+        for waiting in [100, 200, 300, 400, 400, 400, 400, 400, 350, 200, 50, 0] {
+            budgets.push(cadence.fetch_budget(0.030, waiting).unwrap().as_secs_f64());
+        }
+        assert!(budgets.windows(2).take(7).all(|x| x[1] >= x[0]));
+        assert_eq!(budgets[7], MAX_BUDGET);
+        assert!((budgets[8] - MAX_BUDGET * BUDGET_DECAY).abs() < 1e-9);
+        assert!((budgets[9] - MAX_BUDGET * BUDGET_DECAY * BUDGET_DECAY).abs() < 1e-9);
+        assert!(budgets[10] < budgets[9] && budgets[10] > MIN_BUDGET);
+        assert_eq!(budgets[11], MIN_BUDGET);
+    }
+}
@@ -1,102 +1,1030 @@
+pub use serialplotter_core::{
+    decimate, decimate_with, interpolate, HistoryLimits, Precision, Sample, SampleBuffer,
+    ValueHistory,
+};
+
 use std::{
     borrow::Cow,
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet},
 };
 
-use crossbeam::channel::{Receiver, TryRecvError};
 use egui::{
-    plot::{Legend, Line, Plot, PlotPoints},
-    Ui,
+    epaint::Hsva,
+    plot::{Line, MarkerShape, Plot, PlotBounds, PlotPoints, Points, VLine},
+    Color32, PointerButton, Ui,
 };
 use tracing::info;
 
-use crate::value_parsing::DataValue;
+use super::cursors::Cursors;
+use super::derived::Derivation;
+#[cfg(feature = "gpu_plot")]
+use super::gpu_plot;
+use super::legend::SeriesLegend;
+use super::overview::Viewport;
+use super::series_styles::{steps, SeriesStyle};
+use super::time_alignment::{resample, Resampling};
+use crate::calibration::{Calibrations, Pipeline};
+use crate::dsp::{DisplayFilter, Filter};
 
-pub struct ValueHistory {
-    buffers: HashMap<String, VecDeque<f64>>,
-    cap: usize,
+/// A fixed range of the y axis of the plot.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct YRange {
+    pub min: f64,
+    pub max: f64,
+    /// Keeps the range centered at zero, `min` follows `max`
+    #[serde(default)]
+    pub symmetric: bool,
 }
 
-impl ValueHistory {
-    pub fn try_receive(&mut self, rx: &mut Receiver<DataValue>) -> bool {
-        #[cfg(feature = "profiling")]
-        puffin::profile_scope!("receive data");
-
-        match rx.try_recv() {
-            Err(TryRecvError::Disconnected) => false,
-            Err(TryRecvError::Empty) => false, // Great we are faster at consuming than producing (Blocking is not available as this thread must render the ui)
-            Ok(DataValue { value, name, .. }) => {
-                self.store_value(value, Cow::Owned(name));
-                true
+impl YRange {
+    /// A range from `min` to `max`.
+    pub fn new(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            symmetric: false,
+        }
+    }
+
+    /// The checkbox to lock the y axis and the bounds of the locked range.
+    pub fn ui(range: &mut Option<YRange>, ui: &mut Ui) {
+        let mut locked = range.is_some();
+        ui.checkbox(&mut locked, "Lock y axis")
+            .on_hover_text("Keeps the noise of flat signals from filling the plot");
+        match (locked, &range) {
+            (true, None) => *range = Some(YRange::new(0.0, 1.0)),
+            (false, Some(_)) => *range = None,
+            _ => {}
+        }
+        if let Some(range) = range {
+            ui.horizontal(|ui| {
+                if range.symmetric {
+                    ui.add(
+                        egui::DragValue::new(&mut range.max)
+                            .speed(0.1)
+                            .clamp_range(0.0..=f64::MAX)
+                            .prefix("± "),
+                    );
+                } else {
+                    ui.add(
+                        egui::DragValue::new(&mut range.min)
+                            .speed(0.1)
+                            .prefix("min: "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut range.max)
+                            .speed(0.1)
+                            .prefix("max: "),
+                    );
+                }
+            });
+            if ui
+                .checkbox(&mut range.symmetric, "Symmetric around zero")
+                .changed()
+            {
+                // Still covers the range locked before
+                range.max = range.max.abs().max(range.min.abs());
+            }
+            range.normalize();
+        }
+    }
+
+    /// Keeps `max` above `min`, and `min` at `-max` if the range is symmetric.
+    fn normalize(&mut self) {
+        if self.symmetric {
+            self.max = self.max.abs().max(f64::EPSILON);
+            self.min = -self.max;
+        }
+        // Relative to `min`, an absolute epsilon vanishes in the rounding of larger values
+        self.max = self
+            .max
+            .max(self.min + f64::EPSILON * self.min.abs().max(1.0));
+    }
+}
+
+/// What the horizontal axis of the plot shows.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XAxis {
+    /// The index of the sample, every channel spans the whole width
+    Samples,
+    /// Seconds relative to the newest sample, including the time offset of the channel
+    Time,
+    /// The index of the line the sample arrived in, values sent together stay aligned even if
+    /// some lines omit channels
+    Frames,
+    /// Like `Time`, but only the last seconds of the time window are shown
+    Window,
+}
+
+/// The limits of the history, returns the channel whose samples were asked to be cleared from its context menu.
+pub fn limits_ui(
+    limits: &mut HistoryLimits,
+    ui: &mut Ui,
+    history: &ValueHistory,
+) -> Option<String> {
+    let mut clear = None;
+    let mut limited = limits.memory_budget.is_some();
+    ui.checkbox(&mut limited, "Limit memory");
+    match (limited, &mut limits.memory_budget) {
+        (true, None) => limits.memory_budget = Some(200),
+        (false, Some(_)) => limits.memory_budget = None,
+        _ => {}
+    }
+    if let Some(budget) = &mut limits.memory_budget {
+        ui.add(
+            egui::DragValue::new(budget)
+                .clamp_range(1..=16_000)
+                .prefix("max ")
+                .suffix(" MB"),
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.label("Precision");
+        ui.radio_value(&mut limits.precision, Precision::Double, "f64");
+        ui.radio_value(&mut limits.precision, Precision::Single, "f32");
+    })
+    .response
+    .on_hover_text("f32 halves the memory of long captures, times stay below a millisecond while a channel keeps up to 4 hours of samples");
+
+    let mut names: Vec<&str> = history.channel_names().collect();
+    names.sort_unstable();
+    egui::Grid::new("channel_capacities").show(ui, |ui| {
+        for name in names {
+            let mut overridden = limits.channel_capacities.contains_key(name);
+            ui.checkbox(&mut overridden, name)
+                .on_hover_text("Keep a different number of samples for this channel")
+                .context_menu(|ui| {
+                    if ui.button("Clear samples").clicked() {
+                        clear = Some(name.to_string());
+                        ui.close_menu();
+                    }
+                });
+            if overridden {
+                let capacity = limits
+                    .channel_capacities
+                    .entry(name.to_string())
+                    .or_insert(history.capacity());
+                ui.add(
+                    egui::DragValue::new(capacity)
+                        .clamp_range(2..=10_000_000)
+                        .speed(100.0),
+                );
+            } else {
+                limits.channel_capacities.remove(name);
+                ui.label("");
             }
+            let mut compressed = limits.compressed_channels.contains(name);
+            ui.checkbox(&mut compressed, "compress").on_hover_text(
+                "Store a stretch of equal values once, for channels like status flags",
+            );
+            if compressed {
+                limits.compressed_channels.insert(name.to_string());
+            } else {
+                limits.compressed_channels.remove(name);
+            }
+            ui.end_row();
+        }
+    });
+    clear
+}
+
+/// The memory used by the samples of the history.
+pub fn memory_ui(history: &ValueHistory, ui: &mut Ui) {
+    ui.label(format!(
+        "history memory: {:.1} MB",
+        history.memory_usage() as f64 / 1e6
+    ))
+    .on_hover_text(format!("{} samples", history.sample_count()));
+}
+
+/// How the samples of the history are plotted, the history itself only stores them.
+#[derive(Clone)]
+pub struct PlotSettings {
+    /// The channel whose sample times the other channels are plotted at
+    resampling: Option<(String, Resampling)>,
+    /// Smoothing of the plotted values, by the name the samples are stored under
+    display_filters: BTreeMap<String, DisplayFilter>,
+    /// Values subtracted from the plotted values, by the name the samples are stored under
+    baselines: BTreeMap<String, f64>,
+    /// Conversions of the values to engineering units, by the name the samples are stored under
+    pipelines: BTreeMap<String, Pipeline>,
+    /// The unit of the calibrated values, by the name the samples are stored under
+    units: BTreeMap<String, String>,
+    /// Plots the values converted by their pipeline
+    plot_calibrated: bool,
+    /// Series computed from the channels, by the name the samples are stored under
+    derived: BTreeSet<(String, Derivation)>,
+    /// How the channels are drawn, by the name the samples are stored under
+    series_styles: BTreeMap<String, SeriesStyle>,
+    /// Digital channels drawn in lanes below the plot instead of in it
+    logic_channels: BTreeSet<String>,
+    /// Draws the series without decimating them through the gpu
+    #[cfg(feature = "gpu_plot")]
+    gpu_rendering: bool,
+    /// Seconds before the newest sample shown with `XAxis::Window`
+    time_window: f64,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            resampling: None,
+            display_filters: BTreeMap::new(),
+            baselines: BTreeMap::new(),
+            pipelines: BTreeMap::new(),
+            units: BTreeMap::new(),
+            plot_calibrated: false,
+            derived: BTreeSet::new(),
+            series_styles: BTreeMap::new(),
+            logic_channels: BTreeSet::new(),
+            #[cfg(feature = "gpu_plot")]
+            gpu_rendering: false,
+            time_window: 10.0,
         }
     }
+}
 
-    pub fn render_plot(&self, ui: &mut Ui) {
+impl PlotSettings {
+    /// Draws all channels, `y_range` locks the y axis and marks the samples outside of it at its edges.
+    /// The lines of the `flashing` channels blink red, together with their legend entries.
+    /// The `legend` above the plot hides series and orders them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_plot(
+        &self,
+        history: &ValueHistory,
+        ui: &mut Ui,
+        y_range: Option<YRange>,
+        x_axis: XAxis,
+        flashing: &[&str],
+        viewport: &mut Viewport,
+        cursors: &mut Cursors,
+        legend: &mut SeriesLegend,
+    ) -> egui::Response {
         #[cfg(feature = "profiling")]
         puffin::profile_scope!("plot_rendering");
 
-        let lines = self.buffers.iter().map(|(name, buffer)| {
-            let series: Vec<f64> = buffer.iter().copied().collect();
-            info!("Dataseries {} with {} points", &name, series.len());
-            Line::new(PlotPoints::from_ys_f64(&series)).name(name)
+        let flash_on = (ui.input(|x| x.time) * 2.0) as i64 % 2 == 0;
+
+        // Two points per pixel are enough to draw the envelope of a series
+        #[allow(unused_mut)]
+        let mut max_points = (ui.available_width() * 2.0).max(2.0) as usize;
+        #[cfg(feature = "gpu_plot")]
+        let mut gpu_series = Vec::new();
+        #[cfg(feature = "gpu_plot")]
+        if self.gpu_rendering {
+            max_points = usize::MAX;
+        }
+        let newest = history.newest();
+        let mut clipped = Vec::new();
+        let analog = history
+            .channels()
+            .filter(|(name, _)| !self.logic_channels.contains(*name));
+        let traces = analog.flat_map(|(name, buffer)| {
+            let buffer = self.calibrated_samples(name, self.resampled(history, name, buffer));
+            let (label, buffer) = match self.baselines.get(name) {
+                Some(&baseline) => (
+                    format!("{} − baseline", name),
+                    Cow::Owned(map_values(&buffer, |value| value - baseline)),
+                ),
+                None => (name.clone(), buffer),
+            };
+            let unit = self.unit(name);
+            let label = match unit {
+                Some(unit) => format!("{} [{}]", label, unit),
+                None => label,
+            };
+            let display = self.display_filters.get(name).copied().unwrap_or_default();
+            if display.filter == Filter::None {
+                return vec![(name, label, buffer, unit)];
+            }
+            let filtered = Cow::Owned(filtered(&buffer, display.filter));
+            let mut traces = Vec::new();
+            if display.show_raw {
+                traces.push((name, format!("{} (raw)", label), buffer, unit));
+            }
+            traces.push((
+                name,
+                format!("{} ({})", label, display.filter),
+                filtered,
+                unit,
+            ));
+            traces
         });
-        Plot::new("my_plot")
+        // Derived series have units of their own, e.g. per second
+        let derived = self.derived.iter().filter_map(|(name, derivation)| {
+            let buffer = history.samples(name)?;
+            let buffer = self.calibrated_samples(name, self.resampled(history, name, buffer));
+            let derived = Cow::Owned(derivation.apply(&buffer));
+            Some((name, derivation.label(name), derived, None))
+        });
+        let mut traces: Vec<_> = traces.chain(derived).collect();
+        legend.update(traces.iter().map(|(_, label, _, _)| label));
+        traces.sort_by_key(|(_, label, _, _)| legend.position(label));
+        // Hidden series keep their color, so it does not change when they are shown again
+        let colors: Vec<Color32> = traces
+            .iter()
+            .enumerate()
+            .map(
+                |(index, (name, _, _, _))| match flash_on && flashing.contains(&name.as_str()) {
+                    true => Color32::RED,
+                    false => series_color(index),
+                },
+            )
+            .collect();
+        let entries: Vec<_> = traces
+            .iter()
+            .zip(&colors)
+            .map(|((name, label, _, _), color)| (label.as_str(), *color, history.sample_rate(name)))
+            .collect();
+        legend.ui(ui, &entries);
+        // Cut after filtering and deriving, so they see the samples before the window as well
+        let (traces, colors): (Vec<_>, Vec<_>) = traces
+            .into_iter()
+            .zip(colors)
+            .filter(|((_, label, _, _), _)| legend.shows(label))
+            .map(|((name, label, buffer, unit), color)| {
+                let buffer = match x_axis {
+                    XAxis::Window => self.windowed(history, name, buffer, newest),
+                    _ => buffer,
+                };
+                ((name, label, buffer, unit), color)
+            })
+            .unzip();
+        // The values of every series at `x`, for the hover tooltip and the cursors
+        let values_at = |x: f64| -> Vec<Option<f64>> {
+            traces
+                .iter()
+                .map(|(name, _, buffer, _)| {
+                    interpolate(buffer, x, self.x_of(history, name, x_axis, newest))
+                })
+                .collect()
+        };
+
+        let units: Vec<Option<&str>> = traces.iter().map(|(_, _, _, unit)| *unit).collect();
+        let series: Vec<(bool, Vec<[f64; 2]>)> = traces
+            .iter()
+            .zip(unit_axes(&units))
+            .map(|((name, label, buffer, _), second_unit)| {
+                let series = self.series(history, name, buffer, x_axis, max_points, newest);
+                (second_unit || legend.on_right_axis(label), series)
+            })
+            .collect();
+        let axis_unit = |right: bool| {
+            let units = series
+                .iter()
+                .zip(&units)
+                .filter(|((on_right, _), _)| *on_right == right)
+                .map(|(_, unit)| *unit);
+            shared_unit(units).map(str::to_string)
+        };
+        let (left_unit, right_unit) = (axis_unit(false), axis_unit(true));
+        let right_axis = RightAxis::fit(&series, y_range);
+        let (lines, markers): (Vec<Option<Line>>, Vec<Option<Points>>) = traces
+            .iter()
+            .zip(&colors)
+            .zip(series)
+            .map(|(((name, label, _, _), &color), (right, mut series))| {
+                info!("Dataseries {} with {} points", &name, series.len());
+                match (right, right_axis) {
+                    (true, Some(axis)) => {
+                        series.iter_mut().for_each(|[_, y]| *y = axis.to_left(*y))
+                    }
+                    // The channels of the right axis are scaled to their own range, so nothing is clipped
+                    (true, None) => {}
+                    (false, _) => {
+                        if let Some(range) = y_range {
+                            clipped.extend(clipping_indicators(&series, range));
+                        }
+                    }
+                }
+                let style = self.series_styles.get(*name).copied().unwrap_or_default();
+                let markers = style
+                    .has_points()
+                    .then(|| Points::new(series.clone()).radius(2.0).color(color));
+                if style == SeriesStyle::Step {
+                    series = steps(&series);
+                }
+                let flash = flash_on && flashing.contains(&name.as_str());
+                #[cfg(feature = "gpu_plot")]
+                if self.gpu_rendering {
+                    gpu_series.push((series, color));
+                    // The gpu draws the series
+                    return (None, markers);
+                }
+                let line = Line::new(PlotPoints::from(series)).name(label).color(color);
+                let line = if flash { line.width(3.0) } else { line };
+                (style.has_line().then_some(line), markers)
+            })
+            .unzip();
+
+        if viewport.browsing {
+            ui.horizontal(|ui| {
+                ui.label("Zoomed, new values keep arriving");
+                if ui
+                    .button("Live")
+                    .on_hover_text("Follows the newest values again")
+                    .clicked()
+                {
+                    viewport.follow_live();
+                }
+            });
+        }
+
+        let mut plot = Plot::new("my_plot")
             .view_aspect(2.0)
             .auto_bounds_x()
-            .auto_bounds_y()
-            .legend(Legend::default())
-            .show(ui, |plot_ui| {
-                lines.for_each(|line| plot_ui.line(line));
+            .allow_drag(!cursors.grabbed())
+            // The tooltip with the values of all series replaces the label of the nearest point
+            .show_x(false)
+            .show_y(false);
+        if x_axis == XAxis::Window {
+            plot = plot.include_x(-self.time_window).include_x(0.0);
+        }
+        if let Some(unit) = left_unit {
+            plot = plot.y_axis_formatter(move |y, _| {
+                format!("{} {}", egui::emath::round_to_decimals(y, 5), unit)
+            });
+        }
+        plot = match y_range {
+            // Without automatic bounds the y axis stays at the included range
+            Some(range) => plot.include_y(range.min).include_y(range.max),
+            None => plot.auto_bounds_y(),
+        };
+        // The automatic bounds only know the lines, which are empty for the series drawn by the gpu
+        #[cfg(feature = "gpu_plot")]
+        {
+            let mut min = [f64::INFINITY; 2];
+            let mut max = [f64::NEG_INFINITY; 2];
+            for [x, y] in gpu_series.iter().flat_map(|(series, _)| series) {
+                min = [min[0].min(*x), min[1].min(*y)];
+                max = [max[0].max(*x), max[1].max(*y)];
+            }
+            if min[0] <= max[0] {
+                plot = plot.include_x(min[0]).include_x(max[0]);
+                if y_range.is_none() {
+                    plot = plot.include_y(min[1]).include_y(max[1]);
+                }
+            }
+        }
+        if viewport.take_follow() {
+            plot = plot.reset();
+        }
+        #[cfg(feature = "gpu_plot")]
+        let mut view = None;
+        let mut shown = None;
+        let response = plot.show(ui, |plot_ui| {
+            shown = Some(plot_ui.plot_bounds());
+            if let Some(bounds) = viewport.apply(plot_ui.plot_bounds()) {
+                plot_ui.set_plot_bounds(bounds);
+            }
+            #[cfg(feature = "gpu_plot")]
+            {
+                view = Some(gpu_plot::PlotView::of(plot_ui));
+            }
+            lines
+                .into_iter()
+                .flatten()
+                .for_each(|line| plot_ui.line(line));
+            markers
+                .into_iter()
+                .flatten()
+                .for_each(|points| plot_ui.points(points));
+            cursors.update(plot_ui);
+            for (shape, points) in clipped {
+                plot_ui.points(
+                    Points::new(points)
+                        .shape(shape)
+                        .radius(5.0)
+                        .filled(true)
+                        .color(Color32::RED),
+                );
+            }
+
+            let pointer = plot_ui
+                .pointer_coordinate()
+                .filter(|_| plot_ui.plot_hovered() && !cursors.grabbed())?;
+            plot_ui.vline(VLine::new(pointer.x).color(Color32::GRAY));
+            let values = values_at(pointer.x);
+            for (value, color) in values.iter().zip(&colors) {
+                if let Some(value) = value {
+                    plot_ui.points(
+                        Points::new([pointer.x, *value])
+                            .radius(3.0)
+                            .filled(true)
+                            .color(*color),
+                    );
+                }
+            }
+            Some((pointer.x, values))
+        });
+        #[cfg(feature = "gpu_plot")]
+        if let Some(view) = view {
+            gpu_plot::paint(ui, &view, &gpu_series);
+        }
+
+        if let (Some(axis), Some(shown)) = (right_axis, shown) {
+            axis.paint(ui, response.response.rect, shown, right_unit.as_deref());
+        }
+
+        // Dragging, scrolling and zooming leave the automatic bounds, double clicking returns to them
+        let plot_response = &response.response;
+        let (scroll, zoom) = ui.input(|x| (x.scroll_delta, x.zoom_delta()));
+        if plot_response.double_clicked() {
+            viewport.browsing = false;
+        } else if (plot_response.dragged_by(PointerButton::Primary) && !cursors.grabbed())
+            || plot_response.drag_released_by(PointerButton::Secondary)
+            || (plot_response.hovered() && (scroll != egui::Vec2::ZERO || zoom != 1.0))
+        {
+            viewport.browsing = true;
+        }
+
+        if let Some((x, values)) = &response.inner {
+            let id = egui::Id::new("plot_hover_values");
+            egui::show_tooltip_at_pointer(ui.ctx(), id, |ui| {
+                ui.label(match x_axis {
+                    XAxis::Samples => format!("sample {:.1}", x),
+                    XAxis::Time | XAxis::Window => format!("{:.3} s", x),
+                    XAxis::Frames => format!("frame {:.1}", x),
+                });
+                egui::Grid::new(id).show(ui, |ui| {
+                    for (((_, label, _, _), value), color) in traces.iter().zip(values).zip(&colors)
+                    {
+                        let (swatch, _) =
+                            ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                        ui.painter().rect_filled(swatch, 2.0, *color);
+                        ui.label(label);
+                        ui.label(value.map_or("-".to_string(), |value| format!("{:.6}", value)));
+                        ui.end_row();
+                    }
+                });
             });
+        }
+
+        if let Some(positions) = cursors.positions() {
+            let [first, second] = positions.map(values_at);
+            let mut readout: Vec<_> = traces
+                .iter()
+                .zip(first.into_iter().zip(second))
+                .map(|((_, label, _, _), (first, second))| (label.clone(), [first, second]))
+                .collect();
+            readout.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            cursors.readout_ui(ui, x_axis, &readout);
+        }
+        response.response
+    }
+
+    /// The decimated points of a channel in the coordinates of the plot, `newest` is the time at the right edge.
+    fn series(
+        &self,
+        history: &ValueHistory,
+        name: &str,
+        buffer: &SampleBuffer,
+        x_axis: XAxis,
+        max_points: usize,
+        newest: f64,
+    ) -> Vec<[f64; 2]> {
+        decimate_with(buffer, max_points, self.x_of(history, name, x_axis, newest))
     }
 
-    pub fn with_capacity(capacity: usize) -> Self {
-        ValueHistory {
-            buffers: HashMap::new(),
-            cap: capacity,
+    /// The x coordinate of the samples of a channel in the plot from their index and the sample.
+    fn x_of<'a>(
+        &self,
+        history: &'a ValueHistory,
+        name: &'a str,
+        x_axis: XAxis,
+        newest: f64,
+    ) -> impl Fn(usize, &Sample) -> f64 + 'a {
+        let offset = history.time_offset(name);
+        let first = history.first_frame().unwrap_or_default();
+        move |index, sample| match x_axis {
+            XAxis::Samples => index as f64,
+            XAxis::Time | XAxis::Window => sample.time + offset - newest,
+            XAxis::Frames => history
+                .frame(name, index, sample.time)
+                .map_or(0.0, |frame| frame.saturating_sub(first) as f64),
         }
     }
 
-    pub fn set_capacity(&mut self, capacity: usize) {
-        self.cap = capacity;
-        for (_name, buffer) in self.buffers.iter_mut() {
-            while buffer.len() >= self.cap {
-                buffer.pop_front();
-            }
+    /// The whole history of a channel in the coordinates of the plot, decimated to `max_points`.
+    pub fn channel_series(
+        &self,
+        history: &ValueHistory,
+        name: &str,
+        x_axis: XAxis,
+        max_points: usize,
+    ) -> Option<Vec<[f64; 2]>> {
+        let buffer = history.samples(name)?;
+        Some(self.series(history, name, buffer, x_axis, max_points, history.newest()))
+    }
+
+    /// The whole history of every channel for the overview below the plot, decimated to `max_points` each.
+    pub fn overview_series(
+        &self,
+        history: &ValueHistory,
+        x_axis: XAxis,
+        max_points: usize,
+    ) -> Vec<Vec<[f64; 2]>> {
+        let newest = history.newest();
+        history
+            .channels()
+            .map(|(name, buffer)| self.series(history, name, buffer, x_axis, max_points, newest))
+            .collect()
+    }
+
+    /// Draws the series through the gpu instead of decimated lines, requires the wgpu renderer.
+    #[cfg(feature = "gpu_plot")]
+    pub fn set_gpu_rendering(&mut self, enabled: bool) {
+        self.gpu_rendering = enabled;
+    }
+
+    /// Resamples the other channels onto the sample times of `reference` in the plot.
+    pub fn set_resampling(&mut self, reference: Option<&str>, resampling: Resampling) {
+        let current = self
+            .resampling
+            .as_ref()
+            .map(|(reference, resampling)| (reference.as_str(), *resampling));
+        if current != reference.map(|reference| (reference, resampling)) {
+            self.resampling = reference.map(|reference| (reference.to_string(), resampling));
         }
     }
 
-    pub fn update(
-        &mut self,
-        receiver: &mut Receiver<DataValue>,
-        displayed_values: usize,
-        max_fetch_count: usize,
-    ) {
-        #[cfg(feature = "profiling")]
-        puffin::profile_scope!("update serial values");
+    /// The samples of the channel at the times of the reference channel, taking the time offsets of both into account.
+    fn resampled<'a>(
+        &self,
+        history: &ValueHistory,
+        name: &str,
+        buffer: &'a SampleBuffer,
+    ) -> Cow<'a, SampleBuffer> {
+        let Some((reference, resampling)) = &self.resampling else {
+            return Cow::Borrowed(buffer);
+        };
+        let Some(timeline) = history.samples(reference).filter(|_| name != reference) else {
+            return Cow::Borrowed(buffer);
+        };
+        let shift = history.time_offset(reference) - history.time_offset(name);
+        let times = timeline.iter().map(|sample| sample.time + shift);
+        Cow::Owned(resample(buffer, times, *resampling))
+    }
+
+    /// Smooths the plotted values of the channels, the stored samples stay as they were received.
+    pub fn set_display_filters(&mut self, filters: &BTreeMap<String, DisplayFilter>) {
+        if self.display_filters != *filters {
+            self.display_filters = filters.clone();
+        }
+    }
+
+    /// Plots the values of the channels minus their baseline, e.g. to tare a load cell.
+    pub fn set_baselines(&mut self, baselines: &BTreeMap<String, f64>) {
+        if self.baselines != *baselines {
+            self.baselines = baselines.clone();
+        }
+    }
+
+    /// Plots the values converted by the pipelines of their channel, labeled with their unit.
+    pub fn set_calibrations(&mut self, calibrations: &Calibrations) {
+        if self.pipelines != calibrations.pipelines {
+            self.pipelines = calibrations.pipelines.clone();
+        }
+        if self.units != calibrations.units {
+            self.units = calibrations.units.clone();
+        }
+        self.plot_calibrated = calibrations.plot_calibrated;
+    }
+
+    /// The pipeline the plotted values of a channel are converted by.
+    fn pipeline(&self, name: &str) -> Option<&Pipeline> {
+        self.pipelines
+            .get(name)
+            .filter(|pipeline| self.plot_calibrated && !pipeline.steps.is_empty())
+    }
+
+    /// The unit of the plotted values of a channel, raw values only have one without a conversion.
+    pub fn unit(&self, name: &str) -> Option<&str> {
+        let converted = self
+            .pipelines
+            .get(name)
+            .is_some_and(|pipeline| !pipeline.steps.is_empty());
+        self.units
+            .get(name)
+            .map(String::as_str)
+            .filter(|unit| !unit.is_empty() && (self.plot_calibrated || !converted))
+    }
+
+    /// A value of a channel as it is plotted, converted by its pipeline but without a baseline.
+    pub fn calibrated(&self, name: &str, value: f64) -> f64 {
+        self.pipeline(name)
+            .map_or(value, |pipeline| pipeline.apply(value))
+    }
+
+    /// The samples of a channel converted by its pipeline.
+    fn calibrated_samples<'a>(
+        &self,
+        name: &str,
+        buffer: Cow<'a, SampleBuffer>,
+    ) -> Cow<'a, SampleBuffer> {
+        match self.pipeline(name) {
+            Some(pipeline) => Cow::Owned(map_values(&buffer, |value| pipeline.apply(value))),
+            None => buffer,
+        }
+    }
+
+    /// Draws the channels as steps or points instead of lines.
+    pub fn set_series_styles(&mut self, styles: &BTreeMap<String, SeriesStyle>) {
+        if self.series_styles != *styles {
+            self.series_styles = styles.clone();
+        }
+    }
+
+    /// Leaves the digital channels out of the plot, they are drawn in lanes of their own.
+    pub fn set_logic_channels(&mut self, channels: &BTreeSet<String>) {
+        if self.logic_channels != *channels {
+            self.logic_channels = channels.clone();
+        }
+    }
+
+    /// Plots the derived series next to the channels they are computed from.
+    pub fn set_derived(&mut self, derived: &BTreeSet<(String, Derivation)>) {
+        if self.derived != *derived {
+            self.derived = derived.clone();
+        }
+    }
 
-        self.set_capacity(displayed_values);
-        let mut count = max_fetch_count;
-        while self.try_receive(receiver) && count > 0 {
-            count -= 1;
+    pub fn set_time_window(&mut self, seconds: f64) {
+        self.time_window = seconds;
+    }
+
+    /// The samples of a channel within the time window before `newest`, keeping the one before
+    /// the window so the line reaches its edge.
+    fn windowed<'a>(
+        &self,
+        history: &ValueHistory,
+        name: &str,
+        buffer: Cow<'a, SampleBuffer>,
+        newest: f64,
+    ) -> Cow<'a, SampleBuffer> {
+        let start = newest - self.time_window - history.time_offset(name);
+        let first = buffer
+            .partition_point(|_, sample| sample.time < start)
+            .saturating_sub(1);
+        if first == 0 {
+            return buffer;
         }
+        Cow::Owned(buffer.range(first..buffer.len()).collect())
+    }
+}
+
+/// The color of the series with this index, like the ones egui picks for the lines.
+///
+/// Kept explicit so the hover tooltip and the series drawn by the gpu match the legend.
+fn series_color(index: usize) -> Color32 {
+    let golden_ratio = 0.618_034;
+    Hsva::new((index as f32 * golden_ratio) % 1.0, 0.85, 0.5, 1.0).into()
+}
+
+/// The samples of the buffer with their values passed through `filter`.
+fn filtered(buffer: &SampleBuffer, filter: Filter) -> SampleBuffer {
+    let values = filter.apply(buffer.iter().map(|sample| sample.value));
+    buffer
+        .iter()
+        .zip(values)
+        .map(|(sample, value)| Sample {
+            time: sample.time,
+            value,
+        })
+        .collect()
+}
+
+/// The samples with `f` applied to their values.
+fn map_values(buffer: &SampleBuffer, f: impl Fn(f64) -> f64) -> SampleBuffer {
+    buffer
+        .iter()
+        .map(|sample| Sample {
+            time: sample.time,
+            value: f(sample.value),
+        })
+        .collect()
+}
+
+/// Which series go to the right axis because of their unit: the series of the second unit among
+/// `units`, the first unit keeps the left axis.
+fn unit_axes(units: &[Option<&str>]) -> Vec<bool> {
+    let mut distinct = units.iter().flatten();
+    let first = distinct.next();
+    let second = distinct.find(|unit| Some(*unit) != first);
+    units
+        .iter()
+        .map(|unit| unit.is_some() && unit.as_ref() == second)
+        .collect()
+}
+
+/// The unit of all `units`, if they have the same.
+fn shared_unit<'a>(mut units: impl Iterator<Item = Option<&'a str>>) -> Option<&'a str> {
+    let first = units.next()??;
+    units.all(|unit| unit == Some(first)).then_some(first)
+}
+
+/// The range of the values of the series on the right y axis and the range of the left one they
+/// are drawn in, egui plots only have a single y axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RightAxis {
+    left: [f64; 2],
+    right: [f64; 2],
+}
 
-        let count = max_fetch_count - count;
-        self.store_value(count as f64, Cow::Borrowed("fetch_count"));
+impl RightAxis {
+    /// Fits the series marked for the right axis into the range of the others, there is nothing to
+    /// scale if either axis has no values.
+    fn fit(series: &[(bool, Vec<[f64; 2]>)], y_range: Option<YRange>) -> Option<Self> {
+        let range = |right: bool| {
+            let values = series
+                .iter()
+                .filter(|(on_right, _)| *on_right == right)
+                .flat_map(|(_, series)| series.iter().map(|[_, y]| *y))
+                .filter(|y| y.is_finite());
+            let [min, max] = values.fold(None, |range: Option<[f64; 2]>, y| {
+                let [min, max] = range.unwrap_or([y, y]);
+                Some([min.min(y), max.max(y)])
+            })?;
+            // A flat series stays in the middle of the axis
+            Some(match max > min {
+                true => [min, max],
+                false => [min - 0.5, max + 0.5],
+            })
+        };
+        let right = range(true)?;
+        let left = match y_range {
+            Some(YRange { min, max, .. }) => [min, max],
+            None => range(false)?,
+        };
+        Some(Self { left, right })
+    }
 
-        self.store_value(receiver.len() as f64, Cow::Borrowed("pending_messages"));
+    fn to_left(self, y: f64) -> f64 {
+        let [left_min, left_max] = self.left;
+        let [right_min, right_max] = self.right;
+        left_min + (y - right_min) * (left_max - left_min) / (right_max - right_min)
     }
 
-    fn store_value(&mut self, value: f64, key: Cow<'_, str>) {
-        let buffer = self
-            .buffers
-            .entry(key.into_owned())
-            .or_insert_with(|| VecDeque::with_capacity(self.cap));
+    /// Evenly spaced values of the right axis at steps of 1, 2 or 5 times a power of ten, with
+    /// the decimals to show them with.
+    fn ticks(&self) -> (Vec<f64>, usize) {
+        let [min, max] = self.right;
+        let rough = (max - min) / 5.0;
+        let magnitude = 10f64.powf(rough.log10().floor());
+        let step = [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|factor| factor * magnitude)
+            .find(|step| *step >= rough)
+            .unwrap_or(rough);
+        let decimals = (-step.log10().floor()).max(0.0) as usize;
+        let first = (min / step).ceil() as i64;
+        let last = (max / step).floor() as i64;
+        let ticks = (first..=last).map(|index| index as f64 * step).collect();
+        (ticks, decimals)
+    }
 
-        buffer.push_back(value);
-        if buffer.len() >= self.cap {
-            buffer.pop_front();
+    /// Labels the ticks of the right axis at the right edge of the plot, which shows `shown`.
+    fn paint(&self, ui: &Ui, rect: egui::Rect, shown: PlotBounds, unit: Option<&str>) {
+        let [_, min_y] = shown.min();
+        let [_, max_y] = shown.max();
+        let painter = ui.painter_at(rect);
+        let (ticks, decimals) = self.ticks();
+        for tick in ticks {
+            let fraction = (self.to_left(tick) - min_y) / (max_y - min_y);
+            if !(0.0..=1.0).contains(&fraction) {
+                continue;
+            }
+            let y = rect.bottom() - fraction as f32 * rect.height();
+            painter.text(
+                egui::pos2(rect.right() - 4.0, y),
+                egui::Align2::RIGHT_CENTER,
+                match unit {
+                    Some(unit) => format!("{:.*} {}", decimals, tick, unit),
+                    None => format!("{:.*}", decimals, tick),
+                },
+                egui::FontId::monospace(12.0),
+                ui.visuals().text_color(),
+            );
         }
     }
 }
+
+/// Markers at the edges of `range` where the series leaves it, one per excursion.
+///
+/// Like the off-screen arrows of a scope, so excursions outside of a locked axis aren't silently missed.
+fn clipping_indicators(series: &[[f64; 2]], range: YRange) -> Vec<(MarkerShape, Vec<[f64; 2]>)> {
+    let mut above = Vec::new();
+    let mut below = Vec::new();
+    let mut previous = None;
+    for &[x, y] in series {
+        let current = if y > range.max {
+            Some(MarkerShape::Up)
+        } else if y < range.min {
+            Some(MarkerShape::Down)
+        } else {
+            None
+        };
+        if current != previous {
+            match current {
+                Some(MarkerShape::Up) => above.push([x, range.max]),
+                Some(_) => below.push([x, range.min]),
+                None => {}
+            }
+        }
+        previous = current;
+    }
+
+    [(MarkerShape::Up, above), (MarkerShape::Down, below)]
+        .into_iter()
+        .filter(|(_, points)| !points.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_mark_each_excursion_once() {
+        let series = [
+            [0.0, 0.5],
+            [1.0, 2.0],
+            [2.0, 3.0],
+            [3.0, 0.5],
+            [4.0, -1.0],
+            [5.0, 2.0],
+        ];
+
+        let markers = clipping_indicators(&series, YRange::new(0.0, 1.0));
+
+        assert_eq!(
+            markers,
+            vec![
+                (MarkerShape::Up, vec![[1.0, 1.0], [5.0, 1.0]]),
+                (MarkerShape::Down, vec![[4.0, 0.0]]),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_scale_the_right_axis_into_the_left_one() {
+        let series = vec![
+            (false, vec![[0.0, 0.0], [1.0, 100.0]]),
+            (true, vec![[0.0, 0.0], [1.0, 100_000.0]]),
+        ];
+        let axis = RightAxis::fit(&series, None).unwrap();
+        assert_eq!(axis.to_left(50_000.0), 50.0);
+        let (ticks, decimals) = axis.ticks();
+        assert_eq!(
+            ticks,
+            [0.0, 20_000.0, 40_000.0, 60_000.0, 80_000.0, 100_000.0]
+        );
+        assert_eq!(decimals, 0);
+
+        let locked = RightAxis::fit(&series, Some(YRange::new(-1.0, 1.0))).unwrap();
+        assert_eq!(locked.to_left(100_000.0), 1.0);
+        assert_eq!(RightAxis::fit(&series[..1], None), None);
+    }
+
+    #[test]
+    fn should_group_the_channels_of_a_second_unit_on_the_right_axis() {
+        let units = [Some("V"), None, Some("A"), Some("V"), Some("A"), Some("Pa")];
+
+        assert_eq!(
+            unit_axes(&units),
+            vec![false, false, true, false, true, false]
+        );
+        assert_eq!(unit_axes(&[Some("V"), Some("V")]), vec![false, false]);
+        assert_eq!(shared_unit([Some("A"), Some("A")].into_iter()), Some("A"));
+        assert_eq!(shared_unit([Some("V"), None].into_iter()), None);
+    }
+
+    #[test]
+    fn should_keep_a_symmetric_range_centered_at_zero() {
+        let mut range = YRange {
+            symmetric: true,
+            ..YRange::new(-1.0, 3.0)
+        };
+        range.normalize();
+        assert_eq!((range.min, range.max), (-3.0, 3.0));
+
+        let mut range = YRange::new(2.0, 1.0);
+        range.normalize();
+        assert!(range.max > range.min);
+    }
+
+    #[test]
+    fn should_keep_the_samples_of_the_time_window() {
+        let history = ValueHistory::imported([(
+            "a".to_string(),
+            vec![[1.0, 0.0], [2.0, 0.0], [3.0, 0.0], [4.0, 0.0], [5.0, 0.0]],
+        )]);
+        let mut plot = PlotSettings::default();
+        plot.set_time_window(2.5);
+
+        let buffer = Cow::Borrowed(history.samples("a").unwrap());
+        let windowed = plot.windowed(&history, "a", buffer, 5.0);
+        let times: Vec<_> = windowed.iter().map(|sample| sample.time).collect();
+        assert_eq!(times, [2.0, 3.0, 4.0, 5.0]);
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::{Mutex, PoisonError};
+
+use egui::Ui;
+
+use super::event_log::{EventKind, EventLog};
+use crate::sinks::Sinks;
+#[cfg(feature = "websocket")]
+use crate::sinks::{BroadcastClients, WebSocketBroadcast};
+#[cfg(feature = "websocket")]
+use crate::value_parsing::WebSocketSource;
+use crate::value_parsing::{DataSource, SourceSenders, ValueParser};
+
+/// The clients of a broadcast, which can not be started without the `websocket` feature.
+#[cfg(not(feature = "websocket"))]
+enum BroadcastClients {}
+
+#[cfg(not(feature = "websocket"))]
+impl BroadcastClients {
+    fn count(&self) -> usize {
+        match *self {}
+    }
+}
+
+/// A WebSocket connection next to the serial port, to receive the values of a device on the
+/// network, and a server that passes the values on to other tools, e.g. a dashboard in the
+/// browser.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WebSocketLink {
+    /// The server to receive from, e.g. `ws://device/stream`
+    url: String,
+    /// The address the broadcast listens on, e.g. `127.0.0.1:8765`
+    address: String,
+
+    show: bool,
+    #[serde(skip)]
+    source: Option<Box<dyn DataSource>>,
+    /// The name of the running broadcast sink and its clients
+    #[serde(skip)]
+    broadcast: Option<(String, BroadcastClients)>,
+    #[serde(skip)]
+    broadcast_error: Option<String>,
+}
+
+impl Default for WebSocketLink {
+    fn default() -> Self {
+        Self {
+            url: "ws://localhost:8080/stream".to_string(),
+            address: "127.0.0.1:8765".to_string(),
+            show: false,
+            source: None,
+            broadcast: None,
+            broadcast_error: None,
+        }
+    }
+}
+
+impl WebSocketLink {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// Returns whether connecting was requested.
+    pub fn window(
+        &mut self,
+        ctx: &egui::Context,
+        event_log: &mut EventLog,
+        sinks: &Mutex<Sinks>,
+    ) -> bool {
+        let mut show = self.show;
+        let mut connect = false;
+        egui::Window::new("WebSocket")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| connect = self.ui(ui, event_log, sinks));
+        self.show = show;
+        connect
+    }
+
+    /// Receives the messages of the server, they are parsed like the bytes of the serial port.
+    pub fn connect(
+        &mut self,
+        parser: Box<dyn ValueParser>,
+        senders: SourceSenders,
+        event_log: &mut EventLog,
+    ) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+        self.source = self.start(parser, senders);
+        if let Some(source) = &self.source {
+            event_log.record(EventKind::Connected, source.name());
+        }
+    }
+
+    #[cfg(feature = "websocket")]
+    fn start(
+        &self,
+        parser: Box<dyn ValueParser>,
+        senders: SourceSenders,
+    ) -> Option<Box<dyn DataSource>> {
+        Some(Box::new(WebSocketSource::start(
+            self.url.trim().to_string(),
+            parser,
+            senders,
+        )))
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn start(
+        &self,
+        _parser: Box<dyn ValueParser>,
+        _senders: SourceSenders,
+    ) -> Option<Box<dyn DataSource>> {
+        None
+    }
+
+    #[cfg(feature = "websocket")]
+    fn start_broadcast(&mut self, sinks: &Mutex<Sinks>) {
+        match WebSocketBroadcast::bind(self.address.trim()) {
+            Ok(broadcast) => {
+                let name = crate::sinks::DataSink::name(&broadcast).to_string();
+                self.broadcast = Some((name, broadcast.clients()));
+                self.broadcast_error = None;
+                sinks
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .add(Box::new(broadcast));
+            }
+            Err(err) => self.broadcast_error = Some(format!("{}: {}", self.address, err)),
+        }
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn start_broadcast(&mut self, _sinks: &Mutex<Sinks>) {}
+
+    /// Forgets the connection once the source stopped on its own, it reports why.
+    pub fn update(&mut self) {
+        if self
+            .source
+            .as_ref()
+            .is_some_and(|source| !source.is_running())
+        {
+            self.source = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(source) = &mut self.source {
+            source.stop();
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, event_log: &mut EventLog, sinks: &Mutex<Sinks>) -> bool {
+        if !cfg!(feature = "websocket") {
+            ui.label("Built without the `websocket` feature");
+            return false;
+        }
+
+        ui.strong("Receive");
+        let mut connect = false;
+        ui.horizontal(|ui| match &mut self.source {
+            Some(source) => {
+                ui.label(format!("Connected to {}", source.name()));
+                if ui.button("disconnect").clicked() {
+                    source.stop();
+                    event_log.record(EventKind::Disconnected, source.name());
+                    self.source = None;
+                }
+            }
+            None => {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.url)
+                        .hint_text("ws://device/stream")
+                        .desired_width(200.0),
+                )
+                .on_hover_text(
+                    "Text messages are parsed as lines of the selected format, \
+                     binary messages as they are",
+                );
+                connect = ui
+                    .add_enabled(
+                        self.url.trim().starts_with("ws://"),
+                        egui::Button::new("connect"),
+                    )
+                    .clicked();
+            }
+        });
+
+        ui.separator();
+        ui.strong("Broadcast");
+        ui.horizontal(|ui| match &self.broadcast {
+            Some((name, clients)) => {
+                ui.label(format!("Serving {} to {} clients", name, clients.count()))
+                    .on_hover_text("Every value is sent as a json message");
+                if ui.button("stop").clicked() {
+                    sinks
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .remove(name);
+                    self.broadcast = None;
+                }
+            }
+            None => {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.address)
+                        .hint_text("127.0.0.1:8765")
+                        .desired_width(200.0),
+                )
+                .on_hover_text("0.0.0.0 accepts clients from other computers");
+                if ui.button("start").clicked() {
+                    self.start_broadcast(sinks);
+                }
+            }
+        });
+        if let Some(err) = &self.broadcast_error {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        connect
+    }
+}
@@ -0,0 +1,130 @@
+use egui::{Response, Ui, Widget};
+
+use super::cursors::Cursors;
+use super::legend::SeriesLegend;
+use super::overview::Viewport;
+use super::value_history::{PlotSettings, ValueHistory, XAxis, YRange};
+use crate::value_parsing::{unix_timestamp, DataValue};
+
+/// The live plot of the serial plotter, to embed it into other egui applications.
+///
+/// Values are fed by the application instead of a serial port, the plot keeps the last
+/// `capacity` values of every channel and offers the legend, cursors and hover values of the
+/// plotter.
+///
+/// ```no_run
+/// # fn show(ui: &mut egui::Ui, plot: &mut serialplotter::SerialPlotWidget) {
+/// plot.feed("temperature", 21.5);
+/// ui.add(plot);
+/// # }
+/// ```
+pub struct SerialPlotWidget {
+    history: ValueHistory,
+    plot: PlotSettings,
+    /// The number of the next value fed
+    lines: u64,
+    x_axis: XAxis,
+    y_range: Option<YRange>,
+    viewport: Viewport,
+    cursors: Cursors,
+    legend: SeriesLegend,
+}
+
+impl SerialPlotWidget {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: ValueHistory::with_capacity(capacity),
+            plot: PlotSettings::default(),
+            lines: 0,
+            x_axis: XAxis::Time,
+            y_range: None,
+            viewport: Viewport::default(),
+            cursors: Cursors::default(),
+            legend: SeriesLegend::default(),
+        }
+    }
+
+    /// What the horizontal axis shows, the time relative to the newest value by default.
+    pub fn x_axis(mut self, x_axis: XAxis) -> Self {
+        self.x_axis = x_axis;
+        self
+    }
+
+    /// Locks the y axis to `y_range`, values outside of it are marked at its edges.
+    pub fn y_range(mut self, y_range: Option<YRange>) -> Self {
+        self.y_range = y_range;
+        self
+    }
+
+    /// Adds a value of a channel received now.
+    pub fn feed(&mut self, name: impl Into<String>, value: f64) {
+        self.feed_at(name, value, unix_timestamp());
+    }
+
+    /// Adds a value of a channel received at `timestamp`, in seconds since the unix epoch.
+    ///
+    /// Every value is a line of its own, like a device sending a single value per line.
+    pub fn feed_at(&mut self, name: impl Into<String>, value: f64, timestamp: f64) {
+        self.history.push(DataValue {
+            name: name.into(),
+            value,
+            timestamp,
+            line: self.lines,
+        });
+        self.lines += 1;
+    }
+
+    /// Drops the values of all channels.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// The values of the plot, e.g. to read them back or to limit their memory.
+    pub fn history(&self) -> &ValueHistory {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut ValueHistory {
+        &mut self.history
+    }
+}
+
+impl Widget for &mut SerialPlotWidget {
+    fn ui(self, ui: &mut Ui) -> Response {
+        self.plot.render_plot(
+            &self.history,
+            ui,
+            self.y_range,
+            self.x_axis,
+            &[],
+            &mut self.viewport,
+            &mut self.cursors,
+            &mut self.legend,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_feed_and_clear_values() {
+        let mut widget = SerialPlotWidget::new(10);
+        widget.feed_at("a", 1.0, 100.0);
+        widget.feed_at("b", 2.0, 100.0);
+        widget.feed("a", 3.0);
+        let samples: Vec<f64> = widget
+            .history()
+            .samples("a")
+            .unwrap()
+            .iter()
+            .map(|x| x.value)
+            .collect();
+        assert_eq!(samples, [1.0, 3.0]);
+        assert_eq!(widget.history().channel_names().count(), 2);
+
+        widget.clear();
+        assert_eq!(widget.history().channel_names().count(), 0);
+    }
+}
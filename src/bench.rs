@@ -0,0 +1,235 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    app::value_history::{decimate, ValueHistory},
+    value_parsing::{
+        parsing_state_machine::{Parser, ParsingResult},
+        DataValue,
+    },
+};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts the allocations of the process, so the benchmark can report them.
+///
+/// The counters only move if the binary registers it as `#[global_allocator]`, which it does with
+/// the `bench` feature.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// The channels of the synthetic input, every line contains one value of each.
+const CHANNELS: [&str; 4] = ["temperature", "pressure", "voltage", "flag"];
+/// Number of values kept per channel, as configured by the "displayed values" slider.
+const HISTORY_CAPACITY: usize = 100_000;
+/// Number of points a plot of 1000 pixels width is decimated to.
+const PLOT_POINTS: usize = 2000;
+/// Number of frames rendered while decimating.
+const FRAMES: usize = 100;
+/// Number of values the reads of the port hold, 16 lines.
+const READ_VALUES: usize = 16 * CHANNELS.len();
+
+pub struct StageResult {
+    pub name: &'static str,
+    pub samples: u64,
+    pub elapsed: Duration,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+impl StageResult {
+    fn measure<R>(name: &'static str, stage: impl FnOnce() -> (u64, R)) -> (Self, R) {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let (samples, result) = stage();
+        let elapsed = start.elapsed();
+        let stage = Self {
+            name,
+            samples,
+            elapsed,
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes,
+        };
+        (stage, result)
+    }
+
+    pub fn samples_per_second(&self) -> f64 {
+        self.samples as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+pub struct BenchReport {
+    pub lines: usize,
+    pub input_bytes: usize,
+    pub stages: Vec<StageResult>,
+}
+
+impl std::fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} lines, {:.1} MB of synthetic input",
+            self.lines,
+            self.input_bytes as f64 / 1e6
+        )?;
+        writeln!(
+            f,
+            "{:<12} {:>12} {:>10} {:>15} {:>14} {:>16}",
+            "stage", "samples", "time", "samples/s", "allocations", "allocs/sample"
+        )?;
+        for stage in &self.stages {
+            // Without the counting allocator there is nothing to report
+            let (allocations, per_sample) = match cfg!(feature = "bench") {
+                true => (
+                    stage.allocations.to_string(),
+                    format!(
+                        "{:.3}",
+                        stage.allocations as f64 / stage.samples.max(1) as f64
+                    ),
+                ),
+                false => ("-".to_string(), "-".to_string()),
+            };
+            writeln!(
+                f,
+                "{:<12} {:>12} {:>8.1}ms {:>15.0} {:>14} {:>16}",
+                stage.name,
+                stage.samples,
+                stage.elapsed.as_secs_f64() * 1000.0,
+                stage.samples_per_second(),
+                allocations,
+                per_sample
+            )?;
+        }
+        if !cfg!(feature = "bench") {
+            writeln!(f, "Build with the `bench` feature to count the allocations")?;
+        }
+        if let Some(parser) = self.stages.iter().find(|stage| stage.name == "parser") {
+            let bytes_per_second = self.input_bytes as f64 / parser.elapsed.as_secs_f64();
+            writeln!(
+                f,
+                "The parser reads {:.1} MB/s, input at 1 MB/s takes {:.1}% of a core",
+                bytes_per_second / 1e6,
+                100.0 * 1e6 / bytes_per_second
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Pushes `lines` lines of synthetic data through parser, history and decimation as fast as possible.
+pub fn run_bench(lines: usize) -> BenchReport {
+    let input = synthetic_input(lines);
+    let mut stages = Vec::new();
+
+    let (stage, values) = StageResult::measure("parser", || {
+        let mut parser = Parser::new();
+        let mut values = Vec::with_capacity(lines * CHANNELS.len());
+        for byte in &input {
+            if let ParsingResult::Ok(line) = parser.parse(*byte) {
+                values.extend(line);
+            }
+        }
+        (values.len() as u64, values)
+    });
+    stages.push(stage);
+
+    let (stage, history) = StageResult::measure("history", || {
+        let samples = values.len() as u64;
+        let mut history = ValueHistory::with_capacity(HISTORY_CAPACITY);
+        let (tx, mut rx) = crossbeam::channel::bounded::<Vec<DataValue>>(1000);
+        let mut values = values.into_iter().peekable();
+        while values.peek().is_some() {
+            // Reads of a busy port hold a few lines
+            for _ in 0..1000 {
+                let read: Vec<_> = values.by_ref().take(READ_VALUES).collect();
+                if read.is_empty() {
+                    break;
+                }
+                tx.send(read).expect("the receiver is still alive");
+            }
+            history.update(&mut rx, HISTORY_CAPACITY, None);
+        }
+        (samples, history)
+    });
+    stages.push(stage);
+
+    let (stage, ()) = StageResult::measure("decimation", || {
+        let mut samples = 0;
+        for _ in 0..FRAMES {
+            for name in CHANNELS {
+                if let Some(series) = history.samples(name) {
+                    samples += series.len() as u64;
+                    std::hint::black_box(decimate(series, PLOT_POINTS));
+                }
+            }
+        }
+        (samples, ())
+    });
+    stages.push(stage);
+
+    BenchReport {
+        lines,
+        input_bytes: input.len(),
+        stages,
+    }
+}
+
+/// Lines like `temperature:21.5,pressure:1013.2,voltage:3.3,flag:1` with slowly varying values.
+fn synthetic_input(lines: usize) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut input = Vec::with_capacity(lines * 64);
+    for line in 0..lines {
+        let phase = line as f64 * 0.01;
+        writeln!(
+            input,
+            "{}:{:.2},{}:{:.1},{}:{:.3},{}:{}",
+            CHANNELS[0],
+            21.5 + phase.sin() * 3.0,
+            CHANNELS[1],
+            1013.2 + phase.cos() * 10.0,
+            CHANNELS[2],
+            3.3 + (phase * 7.0).sin() * 0.1,
+            CHANNELS[3],
+            line / 500 % 2
+        )
+        .expect("writing to a Vec does not fail");
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_process_all_samples() {
+        let report = run_bench(1000);
+
+        assert_eq!(report.stages[0].samples, 4000);
+        assert_eq!(report.stages[1].samples, 4000);
+        assert_eq!(report.stages[2].samples, (FRAMES * 4000) as u64);
+    }
+}
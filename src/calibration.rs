@@ -0,0 +1,370 @@
+use std::{collections::BTreeMap, fmt::Display, io, path::Path};
+
+use clap::ValueEnum;
+use egui::Ui;
+
+/// A single operation of a conversion pipeline.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Scale(f64),
+    Offset(f64),
+}
+
+impl Step {
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Step::Scale(factor) => value * factor,
+            Step::Offset(offset) => value + offset,
+        }
+    }
+}
+
+/// Converts the raw values of a channel to engineering units, e.g. ADC counts to volts.
+///
+/// The steps are applied in order, so `°F → °C` is `Offset(-32)` followed by `Scale(5 / 9)`.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Pipeline {
+    pub steps: Vec<Step>,
+}
+
+impl Pipeline {
+    pub fn apply(&self, value: f64) -> f64 {
+        self.steps
+            .iter()
+            .fold(value, |value, step| step.apply(value))
+    }
+}
+
+/// How the pipeline of a channel was found with the two-point calibration.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct CalibrationRecord {
+    /// Seconds since the unix epoch
+    pub date: f64,
+    pub notes: String,
+    /// The raw reading and the reference value of both points
+    pub points: [[f64; 2]; 2],
+}
+
+/// Which values the exports contain.
+#[derive(
+    serde::Deserialize, serde::Serialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default,
+)]
+pub enum ExportValues {
+    /// The values as the device sent them
+    Raw,
+    /// The values converted by the pipeline of their channel
+    #[default]
+    Calibrated,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(io::Error),
+    Format(serde_json::Error),
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::Io(err) => write!(f, "{}", err),
+            CalibrationError::Format(err) => write!(f, "invalid calibration file: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for CalibrationError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for CalibrationError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Format(value)
+    }
+}
+
+/// The pipelines and units saved to a calibration file.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+struct ChannelCalibrations {
+    pipelines: BTreeMap<String, Pipeline>,
+    #[serde(default)]
+    units: BTreeMap<String, String>,
+}
+
+/// A calibration file, the files of earlier versions only contain the pipelines.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum CalibrationFile {
+    Units(ChannelCalibrations),
+    Pipelines(BTreeMap<String, Pipeline>),
+}
+
+/// The conversion pipelines and units of all channels, applied when values are exported and,
+/// if enabled, to the plotted values.
+///
+/// The stored samples stay raw, so the same capture can be exported both ways.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Calibrations {
+    /// The pipeline of each channel, by the name shown in the legend
+    pub pipelines: BTreeMap<String, Pipeline>,
+    /// The unit of the calibrated values of each channel, e.g. `°C`
+    pub units: BTreeMap<String, String>,
+    pub export: ExportValues,
+    /// Plots the calibrated values instead of the raw ones
+    pub plot_calibrated: bool,
+    /// The two-point calibrations the pipelines were computed from, kept with the session
+    pub records: BTreeMap<String, CalibrationRecord>,
+    /// The file the pipelines are saved to and loaded from
+    pub file: String,
+
+    show: bool,
+    #[serde(skip)]
+    new_channel: String,
+}
+
+impl Default for Calibrations {
+    fn default() -> Self {
+        Self {
+            pipelines: BTreeMap::new(),
+            units: BTreeMap::new(),
+            export: ExportValues::Calibrated,
+            plot_calibrated: false,
+            records: BTreeMap::new(),
+            file: "calibration.json".to_string(),
+            show: false,
+            new_channel: String::new(),
+        }
+    }
+}
+
+impl Calibrations {
+    /// Loads the pipelines and units saved by the calibration window.
+    pub fn load(path: &Path, export: ExportValues) -> Result<Self, CalibrationError> {
+        let content = std::fs::read_to_string(path)?;
+        let ChannelCalibrations { pipelines, units } = match serde_json::from_str(&content)? {
+            CalibrationFile::Units(calibrations) => calibrations,
+            CalibrationFile::Pipelines(pipelines) => ChannelCalibrations {
+                pipelines,
+                units: BTreeMap::new(),
+            },
+        };
+        Ok(Self {
+            pipelines,
+            units,
+            export,
+            file: path.display().to_string(),
+            ..Default::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CalibrationError> {
+        let file = ChannelCalibrations {
+            pipelines: self.pipelines.clone(),
+            units: self.units.clone(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    /// The value of `channel` as it is written to exports.
+    pub fn export_value(&self, channel: &str, value: f64) -> f64 {
+        match (self.export, self.pipelines.get(channel)) {
+            (ExportValues::Calibrated, Some(pipeline)) => pipeline.apply(value),
+            _ => value,
+        }
+    }
+
+    /// The unit of `channel` in exports, values exported raw only have one without a conversion.
+    pub fn export_unit(&self, channel: &str) -> Option<&str> {
+        let converted = self
+            .pipelines
+            .get(channel)
+            .is_some_and(|pipeline| !pipeline.steps.is_empty());
+        match (self.export, converted) {
+            (ExportValues::Raw, true) => None,
+            _ => self.units.get(channel).map(String::as_str),
+        }
+        .filter(|unit| !unit.is_empty())
+    }
+
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    /// `channels` are offered as channels to add a pipeline for.
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Calibration")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        ui.horizontal(|ui| {
+            ui.label("Export");
+            ui.radio_value(&mut self.export, ExportValues::Raw, "raw");
+            ui.radio_value(&mut self.export, ExportValues::Calibrated, "calibrated");
+        })
+        .response
+        .on_hover_text("The values written to csv files");
+        ui.checkbox(&mut self.plot_calibrated, "Plot calibrated values")
+            .on_hover_text(
+                "Channels with the same unit share an axis, a second unit gets the right axis",
+            );
+        ui.separator();
+
+        let mut remove = None;
+        egui::Grid::new("calibrations")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Channel");
+                ui.strong("Unit");
+                ui.strong("Conversion");
+                ui.end_row();
+                for (channel, pipeline) in self.pipelines.iter_mut() {
+                    ui.label(channel);
+                    let unit = self.units.entry(channel.clone()).or_default();
+                    ui.add(
+                        egui::TextEdit::singleline(unit)
+                            .hint_text("unit")
+                            .desired_width(50.0),
+                    );
+                    ui.horizontal(|ui| pipeline_ui(ui, pipeline));
+                    if ui.button("🗑").on_hover_text("remove").clicked() {
+                        remove = Some(channel.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(channel) = remove {
+            self.pipelines.remove(&channel);
+            self.units.remove(&channel);
+            self.records.remove(&channel);
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("new_calibration_channel")
+                .selected_text(&self.new_channel)
+                .show_ui(ui, |ui| {
+                    for channel in channels
+                        .iter()
+                        .filter(|channel| !self.pipelines.contains_key(**channel))
+                    {
+                        ui.selectable_value(&mut self.new_channel, channel.to_string(), *channel);
+                    }
+                });
+            if ui
+                .add_enabled(!self.new_channel.is_empty(), egui::Button::new("add"))
+                .clicked()
+            {
+                let channel = std::mem::take(&mut self.new_channel);
+                self.pipelines.insert(channel, Pipeline::default());
+            }
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.file)
+                    .on_hover_text("Can be used with --calibration in headless mode");
+                if ui.button("Save").clicked() {
+                    match self.save(Path::new(&self.file)) {
+                        Ok(()) => tracing::info!("Saved calibration to {}", self.file),
+                        Err(err) => tracing::error!("Failed to save calibration: {}", err),
+                    }
+                }
+                if ui.button("Load").clicked() {
+                    match Self::load(Path::new(&self.file), self.export) {
+                        Ok(loaded) => {
+                            self.pipelines = loaded.pipelines;
+                            self.units = loaded.units;
+                        }
+                        Err(err) => tracing::error!("Failed to load calibration: {}", err),
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn pipeline_ui(ui: &mut Ui, pipeline: &mut Pipeline) {
+    let mut remove = None;
+    for (index, step) in pipeline.steps.iter_mut().enumerate() {
+        let (prefix, value) = match step {
+            Step::Scale(factor) => ("× ", factor),
+            Step::Offset(offset) => ("+ ", offset),
+        };
+        let response = ui.add(egui::DragValue::new(value).speed(0.01).prefix(prefix));
+        if response.secondary_clicked() {
+            remove = Some(index);
+        }
+        response.on_hover_text("right click to remove");
+    }
+    if let Some(index) = remove {
+        pipeline.steps.remove(index);
+    }
+    if ui.small_button("× …").on_hover_text("add scale").clicked() {
+        pipeline.steps.push(Step::Scale(1.0));
+    }
+    if ui.small_button("+ …").on_hover_text("add offset").clicked() {
+        pipeline.steps.push(Step::Offset(0.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_apply_steps_in_order() {
+        let fahrenheit_to_celsius = Pipeline {
+            steps: vec![Step::Offset(-32.0), Step::Scale(5.0 / 9.0)],
+        };
+        let mut calibrations = Calibrations::default();
+        calibrations
+            .pipelines
+            .insert("temp".to_string(), fahrenheit_to_celsius);
+
+        assert_eq!(calibrations.export_value("temp", 212.0), 100.0);
+        assert_eq!(calibrations.export_value("other", 212.0), 212.0);
+
+        calibrations.export = ExportValues::Raw;
+        assert_eq!(calibrations.export_value("temp", 212.0), 212.0);
+    }
+
+    #[test]
+    fn should_read_saved_pipelines() {
+        let pipelines: BTreeMap<String, Pipeline> =
+            serde_json::from_str(r#"{"a0": [{"Scale": 0.5}, {"Offset": 1.0}]}"#).unwrap();
+
+        assert_eq!(pipelines["a0"].apply(4.0), 3.0);
+    }
+
+    #[test]
+    fn should_only_give_converted_values_a_unit_if_they_are_exported_calibrated() {
+        let file: CalibrationFile =
+            serde_json::from_str(r#"{"pipelines": {"a0": [{"Scale": 0.5}], "a1": []}, "units": {"a0": "V", "a1": "mA"}}"#)
+                .unwrap();
+        let CalibrationFile::Units(ChannelCalibrations { pipelines, units }) = file else {
+            panic!("read the units as pipelines");
+        };
+        let mut calibrations = Calibrations {
+            pipelines,
+            units,
+            ..Default::default()
+        };
+
+        assert_eq!(calibrations.export_unit("a0"), Some("V"));
+        assert_eq!(calibrations.export_unit("a1"), Some("mA"));
+        calibrations.export = ExportValues::Raw;
+        assert_eq!(calibrations.export_unit("a0"), None);
+        assert_eq!(calibrations.export_unit("a1"), Some("mA"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use crate::calibration::ExportValues;
+use crate::csv_format::{CsvFormat, DecimalSeparator, Delimiter};
+use crate::value_parsing::DataFormat;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use headless::run_headless;
+
+/// Baud rate used if neither the arguments nor the persisted settings name one.
+pub const DEFAULT_BAUD_RATE: u32 = 9600;
+
+/// Plots values received from a serial port.
+#[derive(Parser, Debug, Clone)]
+#[command(author, version, about)]
+pub struct Args {
+    /// Run without a window and write the parsed values to stdout or the output file
+    #[arg(long, requires = "port")]
+    pub headless: bool,
+
+    /// Push synthetic data through parser, history and decimation and report the throughput
+    #[arg(long, conflicts_with = "headless")]
+    pub bench: bool,
+
+    /// Number of lines of synthetic data the benchmark processes
+    #[arg(long, default_value_t = 1_000_000)]
+    pub bench_lines: usize,
+
+    /// The serial port to read from
+    #[arg(long)]
+    pub port: Option<String>,
+
+    /// The baud rate of the serial port [default: 9600, or the last one used in the window]
+    #[arg(long)]
+    pub baud: Option<u32>,
+
+    /// The format in which the device sends its values
+    #[arg(long, value_enum)]
+    pub format: Option<DataFormat>,
+
+    /// The WebAssembly module that parses the values of `--format plugin`
+    #[arg(long)]
+    pub plugin: Option<String>,
+
+    /// Open the port right after the window is shown
+    #[arg(long, requires = "port")]
+    pub connect: bool,
+
+    /// The format in which headless mode writes the values
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    pub output_format: OutputFormat,
+
+    /// Write the values of headless mode to this file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// The delimiter between the fields of csv output
+    #[arg(long, value_enum, default_value_t = Delimiter::Comma)]
+    pub csv_delimiter: Delimiter,
+
+    /// The decimal separator of numbers in csv output
+    #[arg(long, value_enum, default_value_t = DecimalSeparator::Point)]
+    pub decimal_separator: DecimalSeparator,
+
+    /// Convert the values of headless mode with the pipelines saved by the calibration window
+    #[arg(long)]
+    pub calibration: Option<PathBuf>,
+
+    /// Whether headless mode writes the raw or the calibrated values
+    #[arg(long, value_enum, default_value_t = ExportValues::Calibrated)]
+    pub export_values: ExportValues,
+
+    /// Serve the last values of headless mode for Prometheus on this address, e.g. 127.0.0.1:9898
+    #[cfg(feature = "metrics")]
+    #[arg(long, requires = "headless")]
+    pub metrics: Option<String>,
+
+    /// The format of the log messages
+    #[arg(long, value_enum, default_value_t = LogFormat::Pretty)]
+    pub log_format: LogFormat,
+
+    /// Write the log messages to this file instead of stderr
+    #[arg(long)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn csv_format(&self) -> CsvFormat {
+        CsvFormat {
+            delimiter: self.csv_delimiter,
+            decimal_separator: self.decimal_separator,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable, multi line messages
+    Pretty,
+    /// One json object per message, for ingestion by log pipelines
+    Json,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One `timestamp,channel,value` row per value
+    Csv,
+    /// One json object per value
+    Jsonl,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
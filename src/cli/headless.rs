@@ -0,0 +1,101 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    sync::Arc,
+    time::Duration,
+};
+
+use crossbeam::channel::RecvTimeoutError;
+use tracing::info;
+
+use super::Args;
+use crate::calibration::Calibrations;
+use crate::sinks::{RecordSink, Sinks, FLUSH_INTERVAL};
+use crate::value_parsing::{
+    unix_timestamp, Backpressure, DataFormat, OverflowPolicy, ParserSettings, ReadTiming,
+    SerialSource, SourceSenders,
+};
+
+/// Reads from the serial port given in `args` and writes every parsed value to the output,
+/// until the port is closed.
+pub fn run_headless(args: &Args) -> io::Result<()> {
+    let port_name = args
+        .port
+        .clone()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no port given"))?;
+    let port = serialport::new(port_name, args.baud.unwrap_or(super::DEFAULT_BAUD_RATE))
+        .timeout(Duration::from_millis(100))
+        .open()?;
+
+    let calibrations = match &args.calibration {
+        Some(path) => Calibrations::load(path, args.export_values)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?,
+        None => Calibrations::default(),
+    };
+
+    let (name, output): (String, Box<dyn Write + Send>) = match &args.output {
+        Some(path) => (
+            path.display().to_string(),
+            Box::new(BufWriter::new(File::create(path)?)),
+        ),
+        None => ("stdout".to_string(), Box::new(BufWriter::new(io::stdout()))),
+    };
+    let mut sinks = Sinks::default();
+    sinks.add(Box::new(RecordSink::new(
+        name,
+        output,
+        args.output_format,
+        args.csv_format(),
+        calibrations,
+    )?));
+    #[cfg(feature = "metrics")]
+    if let Some(address) = &args.metrics {
+        sinks.add(Box::new(crate::metrics::MetricsSink::bind(address)?));
+    }
+
+    let (data_tx, data_rx) = crossbeam::channel::bounded(1000);
+    // Headless mode has no use for the raw bytes and parse errors, the source drops them once the receivers are gone.
+    let (raw_tx, _) = crossbeam::channel::bounded(1);
+    let (parse_error_tx, _) = crossbeam::channel::bounded(1);
+    let (event_tx, _) = crossbeam::channel::bounded(1);
+    let parser_settings = ParserSettings {
+        format: args.format.unwrap_or(DataFormat::Csv),
+        plugin: args.plugin.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+    let _source = SerialSource::start(
+        port,
+        parser_settings.create_parser(),
+        ReadTiming::default(),
+        parser_settings.format.has_lines(),
+        SourceSenders {
+            data: data_tx,
+            queued: data_rx.clone(),
+            // A recording has to be complete, the file is written by the reading thread anyway
+            backpressure: Arc::new(Backpressure::new(OverflowPolicy::Block)),
+            raw: raw_tx,
+            parse_errors: parse_error_tx,
+            events: event_tx,
+            sinks: Default::default(),
+            lines: Default::default(),
+        },
+    );
+
+    // The channel disconnects once the reading thread stops.
+    loop {
+        match data_rx.recv_timeout(Duration::from_secs_f64(FLUSH_INTERVAL)) {
+            Ok(values) => values.iter().for_each(|value| sinks.write(value)),
+            // Writes out the last values while the device is quiet
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        sinks.flush_due(unix_timestamp());
+        if sinks.is_empty() {
+            let errors = sinks.take_errors().join(", ");
+            return Err(io::Error::other(errors));
+        }
+    }
+    sinks.flush();
+    info!("Serial port closed, stopping headless mode");
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use clap::ValueEnum;
+use egui::Ui;
+
+#[derive(
+    serde::Deserialize, serde::Serialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default,
+)]
+pub enum Delimiter {
+    #[default]
+    Comma,
+    /// Expected by spreadsheets in locales that use the decimal comma
+    Semicolon,
+}
+
+#[derive(
+    serde::Deserialize, serde::Serialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default,
+)]
+pub enum DecimalSeparator {
+    #[default]
+    Point,
+    Comma,
+}
+
+/// How numbers and fields are written to csv files, so they open correctly in the spreadsheet of every locale.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct CsvFormat {
+    pub delimiter: Delimiter,
+    pub decimal_separator: DecimalSeparator,
+}
+
+impl CsvFormat {
+    fn delimiter_char(&self) -> char {
+        match self.delimiter {
+            Delimiter::Comma => ',',
+            Delimiter::Semicolon => ';',
+        }
+    }
+
+    pub fn number(&self, value: f64) -> String {
+        self.localize(value.to_string())
+    }
+
+    /// Seconds since the unix epoch with microsecond resolution.
+    pub fn timestamp(&self, seconds: f64) -> String {
+        self.localize(format!("{:.6}", seconds))
+    }
+
+    fn localize(&self, number: String) -> String {
+        match self.decimal_separator {
+            DecimalSeparator::Point => number,
+            DecimalSeparator::Comma => number.replace('.', ","),
+        }
+    }
+
+    /// Quotes a field if it contains characters with a special meaning.
+    pub fn field<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        if field.contains([self.delimiter_char(), '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\"")).into()
+        } else {
+            field.into()
+        }
+    }
+
+    pub fn write_row(&self, output: &mut dyn Write, fields: &[&str]) -> io::Result<()> {
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                write!(output, "{}", self.delimiter_char())?;
+            }
+            write!(output, "{}", self.field(field))?;
+        }
+        writeln!(output)
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Delimiter");
+            ui.radio_value(&mut self.delimiter, Delimiter::Comma, ",");
+            ui.radio_value(&mut self.delimiter, Delimiter::Semicolon, ";");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Decimal separator");
+            ui.radio_value(&mut self.decimal_separator, DecimalSeparator::Point, ".");
+            ui.radio_value(&mut self.decimal_separator, DecimalSeparator::Comma, ",");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(format: CsvFormat, fields: &[&str]) -> String {
+        let mut output = Vec::new();
+        format.write_row(&mut output, fields).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn should_quote_fields_containing_the_delimiter() {
+        let format = CsvFormat::default();
+
+        assert_eq!(
+            row(format, &["a,b", "c;d", "say \"hi\""]),
+            "\"a,b\",c;d,\"say \"\"hi\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn should_write_decimal_comma() {
+        let format = CsvFormat {
+            delimiter: Delimiter::Semicolon,
+            decimal_separator: DecimalSeparator::Comma,
+        };
+
+        let value = format.number(1.5);
+        assert_eq!(row(format, &[&value, "a,b"]), "1,5;a,b\n");
+
+        // With a comma as delimiter the number has to be quoted
+        let format = CsvFormat {
+            delimiter: Delimiter::Comma,
+            ..format
+        };
+        assert_eq!(row(format, &[&value]), "\"1,5\"\n");
+    }
+}
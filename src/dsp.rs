@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use egui::Ui;
+
+/// Smoothing applied to the values of a channel before they are plotted.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Filter {
+    #[default]
+    None,
+    /// The mean of the latest `window` values
+    MovingAverage { window: usize },
+    /// Exponential smoothing, `alpha` is the weight of the newest value
+    Exponential { alpha: f64 },
+}
+
+impl Filter {
+    /// The filtered values, one for every value.
+    pub fn apply(self, values: impl IntoIterator<Item = f64>) -> Vec<f64> {
+        match self {
+            Filter::None => values.into_iter().collect(),
+            Filter::MovingAverage { window } => moving_average(values, window),
+            Filter::Exponential { alpha } => exponential(values, alpha),
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::None => write!(f, "none"),
+            Filter::MovingAverage { window } => write!(f, "moving average of {}", window),
+            Filter::Exponential { alpha } => write!(f, "exponential α={}", alpha),
+        }
+    }
+}
+
+/// The mean of every value and the values before it, up to `window` values in total.
+///
+/// The first values are averaged over the values received so far.
+pub fn moving_average(values: impl IntoIterator<Item = f64>, window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut latest = VecDeque::with_capacity(window);
+    let mut sum = 0.0;
+    values
+        .into_iter()
+        .map(|value| {
+            latest.push_back(value);
+            sum += value;
+            if latest.len() > window {
+                sum -= latest.pop_front().unwrap_or_default();
+            }
+            sum / latest.len() as f64
+        })
+        .collect()
+}
+
+/// Exponential smoothing starting at the first value, `alpha` is clamped to `0..=1`.
+pub fn exponential(values: impl IntoIterator<Item = f64>, alpha: f64) -> Vec<f64> {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let mut smoothed: Option<f64> = None;
+    values
+        .into_iter()
+        .map(|value| {
+            let next = match smoothed {
+                Some(previous) => previous + alpha * (value - previous),
+                None => value,
+            };
+            smoothed = Some(next);
+            next
+        })
+        .collect()
+}
+
+/// The rate of change between consecutive `[time, value]` points, at the time of the later point.
+///
+/// Points sharing their time with the previous one have no defined slope and are skipped.
+pub fn derivative(points: impl IntoIterator<Item = [f64; 2]>) -> Vec<[f64; 2]> {
+    let mut previous: Option<[f64; 2]> = None;
+    points
+        .into_iter()
+        .filter_map(|[time, value]| {
+            let [previous_time, previous_value] = previous.replace([time, value])?;
+            (time > previous_time)
+                .then(|| [time, (value - previous_value) / (time - previous_time)])
+        })
+        .collect()
+}
+
+/// The area under the `[time, value]` points by the trapezoidal rule, starting at zero with the first point.
+pub fn integral(points: impl IntoIterator<Item = [f64; 2]>) -> Vec<[f64; 2]> {
+    let mut previous: Option<[f64; 2]> = None;
+    let mut area = 0.0;
+    points
+        .into_iter()
+        .map(|[time, value]| {
+            if let Some([previous_time, previous_value]) = previous {
+                area += (time - previous_time) * (value + previous_value) / 2.0;
+            }
+            previous = Some([time, value]);
+            [time, area]
+        })
+        .collect()
+}
+
+/// The filter of a channel and whether the raw values are drawn beneath the filtered ones.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct DisplayFilter {
+    pub filter: Filter,
+    pub show_raw: bool,
+}
+
+/// The display filters of the channels, they only change the plot and leave the stored values alone.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct DisplayFilters {
+    /// The filter of each channel, by the name shown in the legend
+    pub filters: BTreeMap<String, DisplayFilter>,
+
+    show: bool,
+}
+
+impl DisplayFilters {
+    pub fn open(&mut self) {
+        self.show = true;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, channels: &[&str]) {
+        let mut show = self.show;
+        egui::Window::new("Display filters")
+            .open(&mut show)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui, channels));
+        self.show = show;
+    }
+
+    fn ui(&mut self, ui: &mut Ui, channels: &[&str]) {
+        egui::Grid::new("display_filters")
+            .striped(true)
+            .show(ui, |ui| {
+                for channel in channels {
+                    let mut display = self.filters.get(*channel).copied().unwrap_or_default();
+                    ui.label(*channel);
+                    filter_ui(ui, channel, &mut display.filter);
+                    ui.add_enabled(
+                        display.filter != Filter::None,
+                        egui::Checkbox::new(&mut display.show_raw, "raw"),
+                    )
+                    .on_hover_text("Draw the raw values beneath the filtered ones");
+                    ui.end_row();
+
+                    if display == DisplayFilter::default() {
+                        self.filters.remove(*channel);
+                    } else {
+                        self.filters.insert(channel.to_string(), display);
+                    }
+                }
+            });
+    }
+}
+
+fn filter_ui(ui: &mut Ui, channel: &str, filter: &mut Filter) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(("display_filter", channel))
+            .selected_text(match filter {
+                Filter::None => "none",
+                Filter::MovingAverage { .. } => "moving average",
+                Filter::Exponential { .. } => "exponential",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(filter, Filter::None, "none");
+                if !matches!(filter, Filter::MovingAverage { .. }) {
+                    ui.selectable_value(
+                        filter,
+                        Filter::MovingAverage { window: 10 },
+                        "moving average",
+                    );
+                }
+                if !matches!(filter, Filter::Exponential { .. }) {
+                    ui.selectable_value(filter, Filter::Exponential { alpha: 0.1 }, "exponential");
+                }
+            });
+        match filter {
+            Filter::None => {}
+            Filter::MovingAverage { window } => {
+                ui.add(
+                    egui::DragValue::new(window)
+                        .clamp_range(1..=100_000)
+                        .suffix(" values"),
+                );
+            }
+            Filter::Exponential { alpha } => {
+                ui.add(
+                    egui::DragValue::new(alpha)
+                        .clamp_range(0.001..=1.0)
+                        .speed(0.001)
+                        .prefix("α "),
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_smooth_values() {
+        assert_eq!(
+            moving_average([1.0, 3.0, 5.0, 7.0], 2),
+            vec![1.0, 2.0, 4.0, 6.0]
+        );
+        assert_eq!(exponential([0.0, 4.0, 4.0], 0.5), vec![0.0, 2.0, 3.0]);
+        assert_eq!(Filter::None.apply([1.0, 2.0]), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn should_differentiate_and_integrate_over_time() {
+        let points = [[0.0, 0.0], [1.0, 2.0], [1.0, 2.0], [3.0, 2.0]];
+        assert_eq!(derivative(points), vec![[1.0, 2.0], [3.0, 0.0]]);
+        assert_eq!(
+            integral(points),
+            vec![[0.0, 0.0], [1.0, 1.0], [1.0, 1.0], [3.0, 5.0]]
+        );
+    }
+}
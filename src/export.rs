@@ -0,0 +1,292 @@
+//! Reads and writes captures as files, to look at them again without the device.
+//!
+//! Csv files are read in the `timestamp,channel,value` layout of the data logger and the exports,
+//! or with a column per channel next to the timestamp. Parquet files have a column per channel, they
+//! require the `parquet` feature.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+/// The `[time, value]` samples of every channel by its name, oldest first.
+pub type Channels = BTreeMap<String, Vec<[f64; 2]>>;
+
+/// Reads a capture, the format is chosen by the extension of the file.
+pub fn read(path: &Path) -> io::Result<Channels> {
+    let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+    if extension.eq_ignore_ascii_case("parquet") {
+        #[cfg(feature = "parquet")]
+        return parquet::read(path);
+        #[cfg(not(feature = "parquet"))]
+        return Err(io::Error::other(
+            "reading parquet files requires the parquet feature",
+        ));
+    }
+    read_csv(BufReader::new(File::open(path)?))
+}
+
+/// Reads the rows of a csv file in either layout, the delimiter and decimal separator are
+/// detected from the header.
+pub fn read_csv(input: impl BufRead) -> io::Result<Channels> {
+    let mut lines = input.lines();
+    let header = lines
+        .next()
+        .ok_or_else(|| invalid("the file is empty".to_string()))??;
+    let Some(delimiter) = header
+        .strip_prefix("timestamp")
+        .and_then(|x| x.chars().next())
+    else {
+        return Err(invalid(
+            "the first column has to be the timestamp".to_string(),
+        ));
+    };
+    let header = split(&header, delimiter);
+    let long = header.get(1).map(String::as_str) == Some("channel");
+
+    let mut channels = Channels::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split(&line, delimiter);
+        let number = |field: &str| {
+            let field = field.trim();
+            let parsed = match delimiter {
+                // The decimal comma is only written with semicolons as delimiter
+                ';' => field.replace(',', ".").parse(),
+                _ => field.parse(),
+            };
+            parsed.map_err(|_| invalid(format!("invalid number {:?} in row {}", field, index + 2)))
+        };
+        let time = number(&fields[0])?;
+        if long {
+            let (Some(channel), Some(value)) = (fields.get(1), fields.get(2)) else {
+                return Err(invalid(format!("missing value in row {}", index + 2)));
+            };
+            let value = number(value)?;
+            channels
+                .entry(channel.clone())
+                .or_default()
+                .push([time, value]);
+        } else {
+            for (channel, field) in header.iter().zip(&fields).skip(1) {
+                // Channels without a value at this time leave their field empty
+                if !field.trim().is_empty() {
+                    let value = number(field)?;
+                    channels
+                        .entry(channel.clone())
+                        .or_default()
+                        .push([time, value]);
+                }
+            }
+        }
+    }
+    for samples in channels.values_mut() {
+        samples.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    }
+    Ok(channels)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The fields of a csv row, quoted fields may contain the delimiter and doubled quotes.
+fn split(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(char) = chars.next() {
+        let field = fields.last_mut().expect("starts with a field");
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            char if char == delimiter && !quoted => fields.push(String::new()),
+            char => field.push(char),
+        }
+    }
+    fields
+}
+
+/// The samples of all channels as rows of a table with a column per channel, ordered by time.
+///
+/// Samples of different channels at the same time share a row, the other channels have no value
+/// in it.
+#[cfg(any(feature = "parquet", test))]
+fn rows(channels: &Channels) -> Vec<(f64, Vec<Option<f64>>)> {
+    let mut samples: Vec<(f64, usize, f64)> = channels
+        .values()
+        .enumerate()
+        .flat_map(|(column, samples)| {
+            samples
+                .iter()
+                .map(move |&[time, value]| (time, column, value))
+        })
+        .collect();
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut rows: Vec<(f64, Vec<Option<f64>>)> = Vec::new();
+    for (time, column, value) in samples {
+        match rows.last_mut() {
+            Some((row_time, values)) if *row_time == time && values[column].is_none() => {
+                values[column] = Some(value);
+            }
+            _ => {
+                let mut values = vec![None; channels.len()];
+                values[column] = Some(value);
+                rows.push((time, values));
+            }
+        }
+    }
+    rows
+}
+
+#[cfg(feature = "parquet")]
+pub mod parquet {
+    use std::{fs::File, io, path::Path, sync::Arc};
+
+    use parquet::{
+        basic::{Compression, Repetition, Type as PhysicalType},
+        data_type::DoubleType,
+        errors::ParquetError,
+        file::{
+            properties::WriterProperties,
+            reader::{FileReader, SerializedFileReader},
+            writer::SerializedFileWriter,
+        },
+        record::Field,
+        schema::types::Type,
+    };
+
+    use super::{rows, Channels};
+
+    /// Rows written at once, bounds the memory used while writing long captures.
+    const ROW_GROUP: usize = 100_000;
+
+    fn to_io(err: ParquetError) -> io::Error {
+        io::Error::other(err)
+    }
+
+    /// Writes a `timestamp` column and a column per channel, which is empty in the rows of
+    /// other channels.
+    pub fn write(path: &Path, channels: &Channels) -> io::Result<()> {
+        let column = |name: &str, repetition| {
+            Type::primitive_type_builder(name, PhysicalType::DOUBLE)
+                .with_repetition(repetition)
+                .build()
+                .map(Arc::new)
+        };
+        let mut fields = vec![column("timestamp", Repetition::REQUIRED).map_err(to_io)?];
+        for name in channels.keys() {
+            fields.push(column(name, Repetition::OPTIONAL).map_err(to_io)?);
+        }
+        let schema = Type::group_type_builder("capture")
+            .with_fields(fields)
+            .build()
+            .map_err(to_io)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let file = File::create(path)?;
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+            .map_err(to_io)?;
+
+        for group in rows(channels).chunks(ROW_GROUP) {
+            let mut row_group = writer.next_row_group().map_err(to_io)?;
+            let times: Vec<f64> = group.iter().map(|(time, _)| *time).collect();
+            let mut column = row_group.next_column().map_err(to_io)?.expect("timestamp");
+            column
+                .typed::<DoubleType>()
+                .write_batch(&times, None, None)
+                .map_err(to_io)?;
+            column.close().map_err(to_io)?;
+            for index in 0..channels.len() {
+                let values: Vec<f64> = group.iter().filter_map(|(_, x)| x[index]).collect();
+                let levels: Vec<i16> = group
+                    .iter()
+                    .map(|(_, x)| i16::from(x[index].is_some()))
+                    .collect();
+                let mut column = row_group.next_column().map_err(to_io)?.expect("channel");
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&values, Some(&levels), None)
+                    .map_err(to_io)?;
+                column.close().map_err(to_io)?;
+            }
+            row_group.close().map_err(to_io)?;
+        }
+        writer.close().map_err(to_io)?;
+        Ok(())
+    }
+
+    /// Reads a file with a `timestamp` column, every other numeric column is a channel.
+    pub fn read(path: &Path) -> io::Result<Channels> {
+        let reader = SerializedFileReader::new(File::open(path)?).map_err(to_io)?;
+        let mut channels = Channels::new();
+        for row in reader.get_row_iter(None).map_err(to_io)? {
+            let row = row.map_err(to_io)?;
+            let mut time = None;
+            let mut values = Vec::new();
+            for (name, field) in row.get_column_iter() {
+                let value = match field {
+                    Field::Double(x) => *x,
+                    Field::Float(x) => f64::from(*x),
+                    Field::Int(x) => f64::from(*x),
+                    Field::Long(x) => *x as f64,
+                    _ => continue,
+                };
+                match name.as_str() {
+                    "timestamp" => time = Some(value),
+                    _ => values.push((name, value)),
+                }
+            }
+            let Some(time) = time else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the file has no timestamp column",
+                ));
+            };
+            for (name, value) in values {
+                channels
+                    .entry(name.clone())
+                    .or_default()
+                    .push([time, value]);
+            }
+        }
+        Ok(channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_read_both_csv_layouts_into_the_same_channels() {
+        let long = "timestamp;channel;value\n1,5;\"a;b\";2,0\n1,5;c;3\n2,5;c;4\n";
+        let wide = "timestamp,\"a;b\",c\n1.5,2,3\n2.5,,4\n";
+
+        let expected: Channels = [
+            ("a;b".to_string(), vec![[1.5, 2.0]]),
+            ("c".to_string(), vec![[1.5, 3.0], [2.5, 4.0]]),
+        ]
+        .into();
+        assert_eq!(read_csv(long.as_bytes()).unwrap(), expected);
+        assert_eq!(read_csv(wide.as_bytes()).unwrap(), expected);
+
+        assert_eq!(
+            rows(&expected),
+            vec![
+                (1.5, vec![Some(2.0), Some(3.0)]),
+                (2.5, vec![None, Some(4.0)])
+            ]
+        );
+    }
+}
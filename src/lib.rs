@@ -1,6 +1,19 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub mod bench;
+pub mod calibration;
+pub mod cli;
+pub mod csv_format;
+pub mod dsp;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
 mod frame_history;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+mod sinks;
 mod value_parsing;
-pub use app::TemplateApp;
+pub use app::value_history::{ValueHistory, XAxis, YRange};
+pub use app::{SerialPlotWidget, TemplateApp};
+pub use value_parsing::{DataValue, ParserPlugin, PluginStep, PLUGIN_ABI_VERSION};
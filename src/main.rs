@@ -1,30 +1,86 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
+// Counts allocations for the `--bench` report
+#[cfg(all(feature = "bench", not(target_arch = "wasm32")))]
+#[global_allocator]
+static ALLOCATOR: serialplotter::bench::CountingAllocator = serialplotter::bench::CountingAllocator;
+
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
-    // Log to stdout (if you run with `RUST_LOG=debug`).
+    // Log to stderr or the log file (if you run with `RUST_LOG=debug`), stdout may carry the values in headless mode.
+
+    use clap::Parser;
+    use serialplotter::cli::LogFormat;
+    use tracing_subscriber::{fmt::writer::BoxMakeWriter, prelude::*};
 
-    use tracing_subscriber::prelude::*;
+    let args = serialplotter::cli::Args::parse();
 
     #[cfg(feature = "profiling")]
     start_puffin_server();
 
+    let writer = match &args.log_file {
+        None => BoxMakeWriter::new(std::io::stderr),
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => BoxMakeWriter::new(std::sync::Mutex::new(file)),
+            Err(err) => {
+                eprintln!("Failed to create log file {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        },
+    };
+    let (pretty, json) = match args.log_format {
+        LogFormat::Pretty => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .pretty()
+                    .with_ansi(args.log_file.is_none())
+                    .with_writer(writer),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            Some(tracing_subscriber::fmt::layer().json().with_writer(writer)),
+        ),
+    };
+
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer().pretty())
+        .with(pretty)
+        .with(json)
         .with(tracing_memory::layer())
         .init();
 
+    if args.bench {
+        print!("{}", serialplotter::bench::run_bench(args.bench_lines));
+        return Ok(());
+    }
+
+    if args.headless {
+        if let Err(err) = serialplotter::cli::run_headless(&args) {
+            tracing::error!("Headless mode failed: {}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let mut native_options = eframe::NativeOptions {
         vsync: true,
+        // The gpu plot draws through wgpu, glow stays the renderer otherwise
+        #[cfg(feature = "gpu_plot")]
+        renderer: eframe::Renderer::Wgpu,
         ..Default::default()
     };
     native_options.vsync = true;
     eframe::run_native(
         "serialplotter",
         native_options,
-        Box::new(|cc| Box::new(serialplotter::TemplateApp::new(cc))),
+        Box::new(move |cc| {
+            let mut app = serialplotter::TemplateApp::new(cc);
+            app.apply_args(&args);
+            Box::new(app)
+        }),
     )
 }
 
@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use tracing::{info, warn};
+
+use crate::{
+    sinks::{DataSink, SinkError},
+    value_parsing::DataValue,
+};
+
+/// How often the server looks for new requests.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a client may take to send its request or to take the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+/// The number of recent values the sample rate of a channel is measured over.
+const RATE_SAMPLES: usize = 100;
+
+/// What the endpoint knows about a channel.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ChannelMetrics {
+    value: f64,
+    /// The times of the recent values, in seconds since the unix epoch
+    recent: VecDeque<f64>,
+    samples: u64,
+}
+
+impl ChannelMetrics {
+    fn record(&mut self, value: &DataValue) {
+        self.value = value.value;
+        self.samples += 1;
+        if self.recent.len() == RATE_SAMPLES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(value.timestamp);
+    }
+
+    /// The values per second over the recent values, zero until there are two of them.
+    fn sample_rate(&self) -> f64 {
+        match (self.recent.front(), self.recent.back()) {
+            (Some(first), Some(last)) if last > first => {
+                (self.recent.len() - 1) as f64 / (last - first)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+type Channels = Arc<Mutex<BTreeMap<String, ChannelMetrics>>>;
+
+/// Serves the last value and the sample rate of every channel in the text format of Prometheus
+/// on `/metrics`, so a long running setup can be scraped by existing monitoring.
+///
+/// The server stops as the sink is removed.
+pub struct MetricsSink {
+    name: String,
+    channels: Channels,
+    running: Arc<AtomicBool>,
+}
+
+impl MetricsSink {
+    /// Listens on `address`, e.g. `127.0.0.1:9898`, and answers requests on a separate thread.
+    pub fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let name = format!("http://{}/metrics", listener.local_addr()?);
+        info!("Serve the metrics on {}", name);
+        let channels = Channels::default();
+        let running = Arc::new(AtomicBool::new(true));
+        let (thread_channels, thread_running) = (channels.clone(), running.clone());
+        thread::Builder::new()
+            .name(format!("Metrics {}", name))
+            .spawn(move || {
+                while thread_running.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, peer)) => {
+                            if let Err(err) = respond(stream, &thread_channels) {
+                                warn!("Failed to answer the metrics request of {}: {}", peer, err);
+                            }
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(ACCEPT_INTERVAL)
+                        }
+                        Err(err) => {
+                            warn!("Failed to accept a metrics request: {}", err);
+                            thread::sleep(ACCEPT_INTERVAL);
+                        }
+                    }
+                }
+            })?;
+        Ok(Self {
+            name,
+            channels,
+            running,
+        })
+    }
+}
+
+impl DataSink for MetricsSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write(&mut self, value: &DataValue) -> Result<(), SinkError> {
+        self.channels
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(value.name.clone())
+            .or_default()
+            .record(value);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+impl Drop for MetricsSink {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Answers a single request, the connection is closed afterwards.
+fn respond(stream: TcpStream, channels: &Channels) -> io::Result<()> {
+    // Accepted streams inherit the non-blocking mode of the listener on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers are of no interest, but have to be read before the response is sent
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut stream = &stream;
+    match request.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", "/metrics", _] => {
+            let body = render(&channels.lock().unwrap_or_else(PoisonError::into_inner));
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )?;
+        }
+        [_, "/metrics", _] => {
+            write!(stream, "HTTP/1.1 405 Method Not Allowed\r\nAllow: GET\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+        }
+        _ => {
+            write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )?;
+        }
+    }
+    stream.flush()
+}
+
+/// A metric of every channel: its name, type, description and how it is taken from the channel.
+type Family = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&ChannelMetrics) -> f64,
+);
+
+const FAMILIES: [Family; 3] = [
+    (
+        "serialplotter_value",
+        "gauge",
+        "The last value received on the channel.",
+        |channel| channel.value,
+    ),
+    (
+        "serialplotter_sample_rate_hertz",
+        "gauge",
+        "The values received on the channel per second, over the recent values.",
+        ChannelMetrics::sample_rate,
+    ),
+    (
+        "serialplotter_samples_total",
+        "counter",
+        "The values received on the channel since the endpoint was started.",
+        |channel| channel.samples as f64,
+    ),
+];
+
+/// The metrics of all channels in the text exposition format of Prometheus.
+fn render(channels: &BTreeMap<String, ChannelMetrics>) -> String {
+    let mut text = String::new();
+    for (metric, kind, help, value) in FAMILIES {
+        let _ = writeln!(text, "# HELP {} {}", metric, help);
+        let _ = writeln!(text, "# TYPE {} {}", metric, kind);
+        for (name, channel) in channels {
+            let _ = writeln!(
+                text,
+                "{}{{channel=\"{}\"}} {}",
+                metric,
+                escape_label(name),
+                number(value(channel))
+            );
+        }
+    }
+    text
+}
+
+/// Escapes a label value, Prometheus only escapes backslashes, quotes and line feeds.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Formats a sample value, Prometheus spells the special values differently than Rust.
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_the_value_and_the_rate_of_every_channel() {
+        let mut channels = BTreeMap::<String, ChannelMetrics>::new();
+        for (name, value, timestamp) in [
+            ("a\"b", 1.0, 10.0),
+            ("a\"b", 2.5, 10.5),
+            ("c", f64::NAN, 10.0),
+        ] {
+            let value = DataValue {
+                name: name.to_string(),
+                value,
+                timestamp,
+                line: 0,
+            };
+            channels
+                .entry(value.name.clone())
+                .or_default()
+                .record(&value);
+        }
+
+        assert_eq!(
+            render(&channels),
+            "# HELP serialplotter_value The last value received on the channel.\n\
+             # TYPE serialplotter_value gauge\n\
+             serialplotter_value{channel=\"a\\\"b\"} 2.5\n\
+             serialplotter_value{channel=\"c\"} NaN\n\
+             # HELP serialplotter_sample_rate_hertz The values received on the channel per second, over the recent values.\n\
+             # TYPE serialplotter_sample_rate_hertz gauge\n\
+             serialplotter_sample_rate_hertz{channel=\"a\\\"b\"} 2\n\
+             serialplotter_sample_rate_hertz{channel=\"c\"} 0\n\
+             # HELP serialplotter_samples_total The values received on the channel since the endpoint was started.\n\
+             # TYPE serialplotter_samples_total counter\n\
+             serialplotter_samples_total{channel=\"a\\\"b\"} 2\n\
+             serialplotter_samples_total{channel=\"c\"} 1\n"
+        );
+    }
+}
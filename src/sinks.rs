@@ -0,0 +1,340 @@
+use std::io::{self, Write};
+#[cfg(feature = "websocket")]
+use std::{
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "websocket")]
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender, TrySendError};
+#[cfg(feature = "websocket")]
+use tracing::{info, warn};
+#[cfg(feature = "websocket")]
+use tungstenite::{Message, WebSocket};
+
+pub use serialplotter_core::{DataSink, SinkError, Sinks, FLUSH_INTERVAL};
+
+use crate::{
+    calibration::Calibrations, cli::OutputFormat, csv_format::CsvFormat, value_parsing::DataValue,
+};
+
+/// Records the values as csv rows or json lines, e.g. to a file or stdout.
+pub struct RecordSink {
+    name: String,
+    output: Box<dyn Write + Send>,
+    format: OutputFormat,
+    csv_format: CsvFormat,
+    calibrations: Calibrations,
+}
+
+impl RecordSink {
+    /// Writes the header right away, so even a recording without values can be read.
+    pub fn new(
+        name: String,
+        mut output: Box<dyn Write + Send>,
+        format: OutputFormat,
+        csv_format: CsvFormat,
+        calibrations: Calibrations,
+    ) -> io::Result<Self> {
+        if format == OutputFormat::Csv {
+            csv_format.write_row(&mut output, &["timestamp", "channel", "value"])?;
+        }
+        Ok(Self {
+            name,
+            output,
+            format,
+            csv_format,
+            calibrations,
+        })
+    }
+}
+
+impl DataSink for RecordSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write(&mut self, value: &DataValue) -> Result<(), SinkError> {
+        let value = DataValue {
+            value: self.calibrations.export_value(&value.name, value.value),
+            ..value.clone()
+        };
+        write_value(&mut self.output, self.format, &self.csv_format, &value)
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        Ok(self.output.flush()?)
+    }
+}
+
+/// How often the server of a [`WebSocketBroadcast`] looks for new clients.
+#[cfg(feature = "websocket")]
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a client may take for the handshake or to take a message before it is disconnected.
+#[cfg(feature = "websocket")]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+/// The values queued for the broadcast thread and the messages queued for each client, newer ones
+/// are dropped while a queue is full.
+#[cfg(feature = "websocket")]
+const QUEUED_MESSAGES: usize = 1000;
+
+/// The queues of the clients connected to a [`WebSocketBroadcast`], each one is sent by a thread
+/// of its own.
+#[cfg(feature = "websocket")]
+#[derive(Clone, Default)]
+pub struct BroadcastClients(Arc<Mutex<Vec<Sender<Arc<str>>>>>);
+
+#[cfg(feature = "websocket")]
+impl BroadcastClients {
+    pub fn count(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Sender<Arc<str>>>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues the message for every client, a client that is behind misses it.
+    ///
+    /// The queues of disconnected clients are removed.
+    fn send(&self, message: Arc<str>) {
+        self.lock()
+            .retain(|client| match client.try_send(message.clone()) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+/// A WebSocket server that sends every value as a json text message to all its clients, e.g. to
+/// a dashboard in the browser.
+///
+/// The values are handed to a thread of the server, so a slow client never holds up the thread
+/// reading the source. Clients receive the values from the time they connect, a client that
+/// falls behind misses values, and a failing client is disconnected without disabling the sink.
+/// The server stops as the sink is removed.
+#[cfg(feature = "websocket")]
+pub struct WebSocketBroadcast {
+    name: String,
+    values: Sender<DataValue>,
+    clients: BroadcastClients,
+}
+
+#[cfg(feature = "websocket")]
+impl WebSocketBroadcast {
+    /// Listens on `address`, e.g. `127.0.0.1:8765`, and accepts clients on a separate thread.
+    pub fn bind(address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let name = format!("ws://{}", listener.local_addr()?);
+        info!("Broadcast the values on {}", name);
+        let clients = BroadcastClients::default();
+        let (values, receiver) = crossbeam::channel::bounded(QUEUED_MESSAGES);
+        let thread_clients = clients.clone();
+        thread::Builder::new()
+            .name(format!("Broadcast {}", name))
+            .spawn(move || broadcast(listener, receiver, thread_clients))?;
+        Ok(Self {
+            name,
+            values,
+            clients,
+        })
+    }
+
+    pub fn clients(&self) -> BroadcastClients {
+        self.clients.clone()
+    }
+}
+
+/// Accepts the clients and sends them the values until the sink is removed.
+#[cfg(feature = "websocket")]
+fn broadcast(listener: TcpListener, values: Receiver<DataValue>, clients: BroadcastClients) {
+    loop {
+        loop {
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let (queue, messages) = crossbeam::channel::bounded(QUEUED_MESSAGES);
+                    let spawned = thread::Builder::new()
+                        .name(format!("WebSocket client {}", peer))
+                        .spawn(move || serve_client(stream, peer, messages));
+                    match spawned {
+                        Ok(_) => clients.lock().push(queue),
+                        Err(err) => warn!("WebSocket client {} failed: {}", peer, err),
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("Failed to accept a WebSocket client: {}", err);
+                    break;
+                }
+            }
+        }
+        match values.recv_timeout(ACCEPT_INTERVAL) {
+            Ok(value) => match serde_json::to_string(&value) {
+                Ok(json) => clients.send(json.into()),
+                Err(err) => warn!("Failed to serialize {}: {}", value.name, err),
+            },
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    // The clients close their connections as their queues disconnect
+    clients.lock().clear();
+}
+
+/// Sends the queued messages to a client, until it fails or the server stops.
+#[cfg(feature = "websocket")]
+fn serve_client(stream: TcpStream, peer: SocketAddr, messages: Receiver<Arc<str>>) {
+    let mut client = match accept_client(stream) {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("WebSocket client {} failed: {}", peer, err);
+            return;
+        }
+    };
+    info!("WebSocket client {} connected", peer);
+    let sent = messages.iter().try_for_each(|message| {
+        client.write(Message::Text(message.to_string()))?;
+        // Writes the messages queued meanwhile at once
+        if messages.is_empty() {
+            client.flush()?;
+        }
+        Ok::<_, tungstenite::Error>(())
+    });
+    match sent {
+        Ok(()) => {
+            let _ = client.close(None); // Err: the client is gone already
+        }
+        Err(err) => info!("Disconnect WebSocket client {}: {}", peer, err),
+    }
+}
+
+#[cfg(feature = "websocket")]
+fn accept_client(stream: TcpStream) -> Result<WebSocket<TcpStream>, String> {
+    // Accepted streams inherit the non-blocking mode of the listener on some platforms
+    stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(CLIENT_TIMEOUT)))
+        .and_then(|_| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+        .map_err(|err| err.to_string())?;
+    tungstenite::accept(stream).map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "websocket")]
+impl DataSink for WebSocketBroadcast {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Queues the value for the broadcast thread, it is dropped while the queue is full.
+    fn write(&mut self, value: &DataValue) -> Result<(), SinkError> {
+        let _ = self.values.try_send(value.clone()); // Err: the clients are behind, they miss the value
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+fn write_value(
+    output: &mut dyn Write,
+    format: OutputFormat,
+    csv_format: &CsvFormat,
+    value: &DataValue,
+) -> Result<(), SinkError> {
+    match format {
+        OutputFormat::Csv => csv_format.write_row(
+            output,
+            &[
+                &csv_format.timestamp(value.timestamp),
+                &value.name,
+                &csv_format.number(value.value),
+            ],
+        )?,
+        OutputFormat::Jsonl => {
+            serde_json::to_writer(&mut *output, value)?;
+            writeln!(output)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_csv_rows() {
+        let mut output = Vec::new();
+        let value = DataValue {
+            name: "a,b".to_string(),
+            value: 1.5,
+            timestamp: 2.0,
+            line: 0,
+        };
+
+        write_value(
+            &mut output,
+            OutputFormat::Csv,
+            &CsvFormat::default(),
+            &value,
+        )
+        .unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "2.000000,\"a,b\",1.5\n");
+    }
+
+    #[test]
+    fn should_write_json_lines() {
+        let mut output = Vec::new();
+        let value = DataValue {
+            name: "X".to_string(),
+            value: 1.5,
+            timestamp: 2.0,
+            line: 0,
+        };
+
+        write_value(
+            &mut output,
+            OutputFormat::Jsonl,
+            &CsvFormat::default(),
+            &value,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"name\":\"X\",\"value\":1.5,\"timestamp\":2.0}\n"
+        );
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn should_drop_messages_for_slow_clients() {
+        let clients = BroadcastClients::default();
+        let (fast, fast_messages) = crossbeam::channel::unbounded();
+        let (slow, slow_messages) = crossbeam::channel::bounded(1);
+        let (gone, _) = crossbeam::channel::bounded(1);
+        clients.lock().extend([fast, slow, gone]);
+
+        clients.send("1".into());
+        clients.send("2".into());
+
+        assert_eq!(
+            clients.count(),
+            2,
+            "the disconnected client should be removed"
+        );
+        let received = |messages: &Receiver<Arc<str>>| {
+            messages
+                .try_iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(received(&fast_messages), ["1", "2"]);
+        assert_eq!(received(&slow_messages), ["1"]);
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Device, InputCallbackInfo, SampleFormat, StreamError,
+};
+use crossbeam::channel::{Receiver, Sender};
+use tracing::{info, warn};
+
+use super::{
+    unix_timestamp, AudioSettings, Commands, DataSource, DataValue, SourceEvent, SourceSenders,
+};
+
+/// The names of the input devices of the default host, e.g. microphones and line inputs.
+pub fn input_devices() -> Vec<String> {
+    let host = cpal::default_host();
+    match host.input_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(err) => {
+            warn!("Failed to list the audio inputs: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Streams the samples of an audio input as channels, one per channel of the input.
+///
+/// The stream is driven by the audio driver, it has to stay on the thread that built it.
+pub struct AudioSource {
+    name: String,
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl AudioSource {
+    pub fn start(settings: AudioSettings, senders: SourceSenders) -> Self {
+        let name = match settings.device.as_str() {
+            "" => "default audio input".to_string(),
+            device => device.to_string(),
+        };
+        info!("Start recording from {}", name);
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread_commands = commands.clone();
+        let _thread = thread::Builder::new()
+            .name(format!("Audio {}", name))
+            .spawn(move || {
+                let result = record(&settings, &senders, &command_receiver, thread_commands);
+                if let Err(err) = result {
+                    warn!("Audio input failed: {}", err);
+                    senders.report(SourceEvent::Disconnected(format!("Audio input: {}", err)));
+                }
+                thread_running.store(false, Ordering::Relaxed);
+            });
+        Self {
+            name,
+            commands,
+            running,
+        }
+    }
+}
+
+impl DataSource for AudioSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+}
+
+fn find_device(name: &str) -> Result<Device, String> {
+    let host = cpal::default_host();
+    if name.is_empty() {
+        return host
+            .default_input_device()
+            .ok_or_else(|| "there is no audio input".to_string());
+    }
+    let mut devices = host.input_devices().map_err(|err| err.to_string())?;
+    devices
+        .find(|device| device.name().is_ok_and(|x| x == name))
+        .ok_or_else(|| format!("the audio input {} is not present", name))
+}
+
+/// Records until the source is stopped or the device fails.
+fn record(
+    settings: &AudioSettings,
+    senders: &SourceSenders,
+    commands: &Receiver<Commands>,
+    stop: Sender<Commands>,
+) -> Result<(), String> {
+    let device = find_device(&settings.device)?;
+    let config = device
+        .default_input_config()
+        .map_err(|err| err.to_string())?;
+    let format = config.sample_format();
+    let config: cpal::StreamConfig = config.into();
+    info!(
+        "Audio input with {} channels at {} Hz, {:?}",
+        config.channels, config.sample_rate.0, format
+    );
+
+    let mut blocks = Blocks::new(
+        settings,
+        config.channels.into(),
+        config.sample_rate.0.into(),
+    );
+    let callback_senders = senders.clone();
+    let mut received = move |samples: &mut dyn Iterator<Item = f64>| {
+        let values = blocks.values(samples, unix_timestamp(), || callback_senders.next_line());
+        callback_senders.record(&values);
+        // Err: the ui closed, the thread is stopped with the next command
+        let _ = callback_senders.send_values(values);
+    };
+    let error_senders = senders.clone();
+    let on_error = move |err: StreamError| match err {
+        StreamError::DeviceNotAvailable => {
+            error_senders.report(SourceEvent::Disconnected(format!("Audio input: {}", err)));
+            let _ = stop.send(Commands::Stop);
+        }
+        StreamError::BackendSpecific { .. } => {
+            error_senders.report(SourceEvent::Error(format!("Audio input: {}", err)));
+        }
+    };
+    let stream = match format {
+        SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &InputCallbackInfo| {
+                received(&mut data.iter().map(|x| f64::from(*x)))
+            },
+            on_error,
+            None,
+        ),
+        SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &InputCallbackInfo| {
+                received(&mut data.iter().map(|x| f64::from(*x) / -f64::from(i16::MIN)))
+            },
+            on_error,
+            None,
+        ),
+        SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _: &InputCallbackInfo| {
+                received(&mut data.iter().map(|x| f64::from(*x) / 32768.0 - 1.0))
+            },
+            on_error,
+            None,
+        ),
+        format => return Err(format!("the sample format {} is not supported", format)),
+    }
+    .map_err(|err| err.to_string())?;
+    stream.play().map_err(|err| err.to_string())?;
+
+    for command in commands {
+        if let Commands::Stop = command {
+            break;
+        }
+    }
+    info!("Stop recording audio");
+    Ok(())
+}
+
+/// Averages blocks of `downsample` frames into a value per channel of the input.
+struct Blocks {
+    names: Vec<String>,
+    downsample: usize,
+    /// Seconds between two frames
+    period: f64,
+    sums: Vec<f64>,
+    frames: usize,
+    /// The channel of the next sample within its frame
+    channel: usize,
+}
+
+impl Blocks {
+    fn new(settings: &AudioSettings, channels: usize, sample_rate: f64) -> Self {
+        let names = (1..=channels)
+            .map(|index| match channels {
+                1 => settings.channel.clone(),
+                _ => format!("{} {}", settings.channel, index),
+            })
+            .collect();
+        Self {
+            names,
+            downsample: settings.downsample.max(1) as usize,
+            period: 1.0 / sample_rate,
+            sums: vec![0.0; channels],
+            frames: 0,
+            channel: 0,
+        }
+    }
+
+    /// The values of the interleaved `samples`, the last of them arrived at `received_at`.
+    ///
+    /// The channels of a block form a line, numbered by `next_line`.
+    fn values(
+        &mut self,
+        samples: &mut dyn Iterator<Item = f64>,
+        received_at: f64,
+        next_line: impl Fn() -> u64,
+    ) -> Vec<DataValue> {
+        let mut averages = Vec::new();
+        for sample in samples {
+            self.sums[self.channel] += sample;
+            self.channel += 1;
+            if self.channel < self.sums.len() {
+                continue;
+            }
+            self.channel = 0;
+            self.frames += 1;
+            if self.frames == self.downsample {
+                averages.push(
+                    self.sums
+                        .iter()
+                        .map(|sum| sum / self.downsample as f64)
+                        .collect::<Vec<_>>(),
+                );
+                self.sums.fill(0.0);
+                self.frames = 0;
+            }
+        }
+        // The blocks are spread over the time the samples took, ending when they arrived
+        let block = self.period * self.downsample as f64;
+        let start = received_at - block * averages.len() as f64;
+        averages
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, values)| {
+                let timestamp = start + block * (index + 1) as f64;
+                let line = next_line();
+                self.names
+                    .iter()
+                    .zip(values)
+                    .map(move |(name, value)| DataValue {
+                        name: name.clone(),
+                        value,
+                        timestamp,
+                        line,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_average_blocks_of_frames_per_channel() {
+        let settings = AudioSettings {
+            downsample: 2,
+            ..AudioSettings::default()
+        };
+        let mut blocks = Blocks::new(&settings, 2, 10.0);
+        let samples = [1.0, 10.0, 3.0, 30.0, 5.0];
+        let values = blocks.values(&mut samples.into_iter(), 100.0, || 0);
+        let values: Vec<(&str, f64, f64)> = values
+            .iter()
+            .map(|x| (x.name.as_str(), x.value, x.timestamp))
+            .collect();
+        assert_eq!(values, [("audio 1", 2.0, 100.0), ("audio 2", 20.0, 100.0)]);
+
+        // The frame started by the last call is completed by the next one
+        let values = blocks.values(&mut [50.0, 7.0, 70.0].into_iter(), 101.0, || 1);
+        assert_eq!(values[0].value, 6.0);
+        assert_eq!(values[1].value, 60.0);
+    }
+}
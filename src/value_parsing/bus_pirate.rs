@@ -0,0 +1,323 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam::channel::{Receiver, Sender, TryRecvError};
+use serialport::{ClearBuffer, SerialPort};
+use tracing::{info, warn};
+
+use super::{
+    unix_timestamp, Commands, DataSource, DataValue, NumberType, SourceEvent, SourceSenders,
+};
+
+/// The baud rate of the terminal and the binary modes of the Bus Pirate.
+const BAUD_RATE: u32 = 115_200;
+/// How long a reply of the Bus Pirate may take before the port is considered lost.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long the Bus Pirate gets to answer a zero while entering the bitbang mode.
+const BITBANG_REPLY: Duration = Duration::from_millis(10);
+
+/// The bus the sensors are connected to.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    I2c,
+    Spi,
+}
+
+/// A register of a sensor that is read with every poll.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SensorRegister {
+    /// The name of the channel, `device:register` if it is empty
+    pub name: String,
+    /// The 7 bit address of the I2C device, on SPI the chip select picks the device
+    pub device: u8,
+    pub register: u8,
+    pub number_type: NumberType,
+    pub little_endian: bool,
+    /// Multiplies the raw value, e.g. 0.0625 for the 12 bit temperature of a TMP102
+    pub scale: f64,
+}
+
+impl Default for SensorRegister {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            device: 0x48,
+            register: 0,
+            number_type: NumberType::I16,
+            little_endian: false,
+            scale: 1.0,
+        }
+    }
+}
+
+impl SensorRegister {
+    pub fn channel_name(&self) -> String {
+        if self.name.trim().is_empty() {
+            format!("0x{:02x}:0x{:02x}", self.device, self.register)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// What the Bus Pirate polls and how often.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BusPirateSettings {
+    pub bus: Bus,
+    pub registers: Vec<SensorRegister>,
+    /// Milliseconds from the start of one poll of all registers to the next
+    pub interval: f64,
+    /// Combined with the register on SPI, most sensors expect the highest bit set for a read
+    pub spi_read_flag: u8,
+}
+
+impl Default for BusPirateSettings {
+    fn default() -> Self {
+        Self {
+            bus: Bus::I2c,
+            registers: Vec::new(),
+            interval: 100.0,
+            spi_read_flag: 0x80,
+        }
+    }
+}
+
+impl BusPirateSettings {
+    /// The write-then-read command of the binary mode reading the value of `register`.
+    fn read_request(&self, register: &SensorRegister) -> Vec<u8> {
+        let (command, written) = match self.bus {
+            // The Bus Pirate repeats the address with the read bit after a restart
+            Bus::I2c => (0x08, vec![register.device << 1, register.register]),
+            Bus::Spi => (0x04, vec![register.register | self.spi_read_flag]),
+        };
+        let read = register.number_type.size() as u16;
+        let mut request = vec![command];
+        request.extend((written.len() as u16).to_be_bytes());
+        request.extend(read.to_be_bytes());
+        request.extend(written);
+        request
+    }
+}
+
+/// Polls sensor registers over I2C or SPI with a Bus Pirate on a separate thread.
+///
+/// The Bus Pirate is switched to its binary mode and back to the terminal when the source stops.
+pub struct BusPirateSource {
+    name: String,
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl BusPirateSource {
+    /// Opens the port of the Bus Pirate and starts polling.
+    pub fn open(
+        port_name: &str,
+        settings: BusPirateSettings,
+        senders: SourceSenders,
+    ) -> serialport::Result<Self> {
+        let port = serialport::new(port_name, BAUD_RATE)
+            .timeout(REPLY_TIMEOUT)
+            .open()?;
+        Ok(Self::start(port, settings, senders))
+    }
+
+    fn start(
+        port: Box<dyn SerialPort>,
+        settings: BusPirateSettings,
+        senders: SourceSenders,
+    ) -> Self {
+        let name = format!(
+            "Bus Pirate {:?} on {}",
+            settings.bus,
+            port.name().unwrap_or_default()
+        );
+        info!("Start polling with the {}", name);
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread_name = name.clone();
+        let _thread = thread::Builder::new().name(name.clone()).spawn(move || {
+            if let Err(err) = poll(port, &settings, &senders, &command_receiver) {
+                warn!("Polling with the {} failed: {}", thread_name, err);
+                senders.report(SourceEvent::Disconnected(format!(
+                    "{}: {}",
+                    thread_name, err
+                )));
+            }
+            thread_running.store(false, Ordering::Relaxed);
+        });
+        Self {
+            name,
+            commands,
+            running,
+        }
+    }
+}
+
+impl DataSource for BusPirateSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+}
+
+fn poll(
+    mut port: Box<dyn SerialPort>,
+    settings: &BusPirateSettings,
+    senders: &SourceSenders,
+    commands: &Receiver<Commands>,
+) -> io::Result<()> {
+    setup(port.as_mut(), settings.bus)?;
+    let interval = Duration::from_secs_f64(settings.interval.max(1.0) / 1000.0);
+    // Reported once when a device stops acknowledging, not with every poll
+    let mut failing = vec![false; settings.registers.len()];
+    'poll: loop {
+        let started = Instant::now();
+        match commands.try_recv() {
+            Ok(Commands::Stop) | Err(TryRecvError::Disconnected) => break,
+            // The other commands control serial ports
+            Ok(_) | Err(TryRecvError::Empty) => {}
+        }
+
+        // The registers of a poll form a line
+        let line = senders.next_line();
+        let mut values = Vec::with_capacity(settings.registers.len());
+        for (register, failing) in settings.registers.iter().zip(failing.iter_mut()) {
+            let value = read_register(port.as_mut(), settings, register)?;
+            if value.is_none() && !*failing {
+                senders.report(SourceEvent::Error(format!(
+                    "no acknowledge reading {}",
+                    register.channel_name()
+                )));
+            }
+            *failing = value.is_none();
+            values.extend(value.map(|value| DataValue {
+                name: register.channel_name(),
+                value,
+                timestamp: unix_timestamp(),
+                line,
+            }));
+        }
+        senders.record(&values);
+        if senders.send_values(values).is_err() {
+            break 'poll;
+        }
+        thread::sleep(interval.saturating_sub(started.elapsed()));
+    }
+
+    // Leaves the binary mode for the terminal
+    port.write_all(&[0x00, 0x0F])?;
+    info!("Stop polling with the Bus Pirate");
+    Ok(())
+}
+
+/// Switches to the binary mode of `bus`, powers the sensors and enables the pull-ups.
+fn setup(port: &mut dyn SerialPort, bus: Bus) -> io::Result<()> {
+    enter_bitbang(port)?;
+    match bus {
+        Bus::I2c => {
+            expect(port, &[0x02], b"I2C1")?;
+            // Power and pull-ups on
+            expect(port, &[0x4C], &[0x01])?;
+            // 100 kHz
+            expect(port, &[0x62], &[0x01])?;
+        }
+        Bus::Spi => {
+            expect(port, &[0x01], b"SPI1")?;
+            // Power and pull-ups on, chip select idles high
+            expect(port, &[0x4D], &[0x01])?;
+            // 1 MHz
+            expect(port, &[0x63], &[0x01])?;
+            // SPI mode 0 with 3.3 V outputs
+            expect(port, &[0x8A], &[0x01])?;
+        }
+    }
+    Ok(())
+}
+
+/// Up to 20 zeros leave the terminal, the bitbang mode answers `BBIO1`.
+fn enter_bitbang(port: &mut dyn SerialPort) -> io::Result<()> {
+    port.clear(ClearBuffer::Input)?;
+    for _ in 0..20 {
+        port.write_all(&[0x00])?;
+        thread::sleep(BITBANG_REPLY);
+        let mut received = vec![0; port.bytes_to_read()? as usize];
+        port.read_exact(&mut received)?;
+        if received.ends_with(b"BBIO1") {
+            return Ok(());
+        }
+    }
+    Err(io::Error::other("no Bus Pirate answered the bitbang mode"))
+}
+
+fn expect(port: &mut dyn SerialPort, request: &[u8], reply: &[u8]) -> io::Result<()> {
+    port.write_all(request)?;
+    let mut received = vec![0; reply.len()];
+    port.read_exact(&mut received)?;
+    if received != reply {
+        return Err(io::Error::other(format!(
+            "unexpected reply {:02X?} to {:02X?}",
+            received, request
+        )));
+    }
+    Ok(())
+}
+
+/// The scaled value of the register, `None` if the device did not acknowledge.
+fn read_register(
+    port: &mut dyn SerialPort,
+    settings: &BusPirateSettings,
+    register: &SensorRegister,
+) -> io::Result<Option<f64>> {
+    port.write_all(&settings.read_request(register))?;
+    let mut status = [0u8];
+    port.read_exact(&mut status)?;
+    if status[0] != 0x01 {
+        return Ok(None);
+    }
+    let mut bytes = vec![0; register.number_type.size()];
+    port.read_exact(&mut bytes)?;
+    let raw = register.number_type.decode(&bytes, register.little_endian);
+    Ok(Some(raw * register.scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_the_write_then_read_commands() {
+        let register = SensorRegister {
+            device: 0x48,
+            register: 0x01,
+            ..Default::default()
+        };
+        let mut settings = BusPirateSettings::default();
+        assert_eq!(
+            settings.read_request(&register),
+            vec![0x08, 0x00, 0x02, 0x00, 0x02, 0x90, 0x01]
+        );
+
+        settings.bus = Bus::Spi;
+        assert_eq!(
+            settings.read_request(&register),
+            vec![0x04, 0x00, 0x01, 0x00, 0x02, 0x81]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    f64::consts::TAU,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{Receiver, Sender, TryRecvError};
+use tracing::info;
+
+use super::{unix_timestamp, Commands, DataSource, DataValue, SourceSenders};
+
+/// The longest the generator sleeps, so it stops promptly even for slow signals.
+const MAX_SLEEP: Duration = Duration::from_millis(100);
+/// The lowest rate of a signal in samples per second, slower ones are sped up to it.
+const MIN_RATE: f64 = 0.1;
+
+/// The shape of a generated signal.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    /// Uniformly distributed values within the amplitude
+    Noise,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 4] = [
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Noise,
+    ];
+}
+
+/// A channel of the demo source.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DemoSignal {
+    pub name: String,
+    pub waveform: Waveform,
+    /// Periods per second, unused by noise
+    pub frequency: f64,
+    pub amplitude: f64,
+    pub offset: f64,
+    /// Samples per second
+    pub rate: f64,
+}
+
+impl Default for DemoSignal {
+    fn default() -> Self {
+        Self {
+            name: "sine".to_string(),
+            waveform: Waveform::Sine,
+            frequency: 0.5,
+            amplitude: 1.0,
+            offset: 0.0,
+            rate: 100.0,
+        }
+    }
+}
+
+impl DemoSignal {
+    /// The value `time` seconds after the start, `noise` supplies the random values.
+    fn value_at(&self, time: f64, noise: &mut Noise) -> f64 {
+        let phase = (time * self.frequency).rem_euclid(1.0);
+        let unit = match self.waveform {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square if phase < 0.5 => 1.0,
+            Waveform::Square => -1.0,
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Noise => noise.next() * 2.0 - 1.0,
+        };
+        self.offset + self.amplitude * unit
+    }
+}
+
+/// The signals the demo source generates.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DemoSettings {
+    pub signals: Vec<DemoSignal>,
+}
+
+impl Default for DemoSettings {
+    fn default() -> Self {
+        Self {
+            signals: vec![
+                DemoSignal::default(),
+                DemoSignal {
+                    name: "square".to_string(),
+                    waveform: Waveform::Square,
+                    frequency: 0.2,
+                    amplitude: 0.5,
+                    offset: 2.0,
+                    rate: 20.0,
+                },
+                DemoSignal {
+                    name: "noise".to_string(),
+                    waveform: Waveform::Noise,
+                    frequency: 0.0,
+                    amplitude: 0.2,
+                    offset: -2.0,
+                    rate: 50.0,
+                },
+            ],
+        }
+    }
+}
+
+/// A xorshift generator, random enough for noise on a plot.
+struct Noise(u64);
+
+impl Noise {
+    /// A value in `0.0..1.0`.
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Generates signals on a separate thread, to try the plotter without a device.
+pub struct DemoSource {
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl DemoSource {
+    pub fn start(settings: DemoSettings, senders: SourceSenders) -> Self {
+        info!("Start generating {} demo signals", settings.signals.len());
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let _thread = thread::Builder::new()
+            .name("Demo signals".to_string())
+            .spawn(move || {
+                generate(&settings, &senders, &command_receiver);
+                thread_running.store(false, Ordering::Relaxed);
+            });
+        Self { commands, running }
+    }
+}
+
+impl DataSource for DemoSource {
+    fn name(&self) -> &str {
+        "Demo signals"
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+}
+
+fn generate(settings: &DemoSettings, senders: &SourceSenders, commands: &Receiver<Commands>) {
+    let start = unix_timestamp();
+    let mut noise = Noise(start.to_bits() | 1);
+    // The index of the next sample of every signal
+    let mut next = vec![0u64; settings.signals.len()];
+    'generate: loop {
+        match commands.try_recv() {
+            Ok(Commands::Stop) | Err(TryRecvError::Disconnected) => break,
+            // The other commands control serial ports
+            Ok(_) | Err(TryRecvError::Empty) => {}
+        }
+
+        // Every sample due since the last round, so high rates do not depend on the sleep
+        let elapsed = unix_timestamp() - start;
+        let mut values = Vec::new();
+        let mut sleep = MAX_SLEEP.as_secs_f64();
+        for (signal, next) in settings.signals.iter().zip(next.iter_mut()) {
+            let period = 1.0 / signal.rate.max(MIN_RATE);
+            while *next as f64 * period <= elapsed {
+                let time = *next as f64 * period;
+                values.push(DataValue {
+                    name: signal.name.clone(),
+                    value: signal.value_at(time, &mut noise),
+                    timestamp: start + time,
+                    line: 0,
+                });
+                *next += 1;
+            }
+            sleep = sleep.min(*next as f64 * period - elapsed);
+        }
+        // The samples of a line arrive in order of their time
+        values.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        // The signals sampled at the same time form a line, like the channels of a device
+        let mut previous = None;
+        for value in values.iter_mut() {
+            match previous {
+                Some((timestamp, line)) if timestamp == value.timestamp => value.line = line,
+                _ => {
+                    value.line = senders.next_line();
+                    previous = Some((value.timestamp, value.line));
+                }
+            }
+        }
+        senders.record(&values);
+        if senders.send_values(values).is_err() {
+            break 'generate;
+        }
+        thread::sleep(Duration::from_secs_f64(sleep.max(0.001)));
+    }
+    info!("Stop generating demo signals");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_generate_the_waveforms() {
+        let mut noise = Noise(1);
+        let signal = |waveform| DemoSignal {
+            waveform,
+            frequency: 1.0,
+            amplitude: 2.0,
+            offset: 1.0,
+            ..DemoSignal::default()
+        };
+        let at = |waveform, time, noise: &mut Noise| signal(waveform).value_at(time, noise);
+
+        assert!((at(Waveform::Sine, 0.25, &mut noise) - 3.0).abs() < 1e-9);
+        assert_eq!(at(Waveform::Square, 0.25, &mut noise), 3.0);
+        assert_eq!(at(Waveform::Square, 1.75, &mut noise), -1.0);
+        assert_eq!(at(Waveform::Triangle, 0.5, &mut noise), 3.0);
+        assert_eq!(at(Waveform::Triangle, 0.0, &mut noise), -1.0);
+        for _ in 0..1000 {
+            let value = at(Waveform::Noise, 0.0, &mut noise);
+            assert!((-1.0..3.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender};
+use opcua::client::prelude::{
+    ClientBuilder, DataChangeCallback, IdentityToken, MessageSecurityMode, MonitoredItem,
+    MonitoredItemCreateRequest, MonitoredItemService, NodeId, SecurityPolicy, Session,
+    SessionCommand, SubscriptionService, TimestampsToReturn, UserTokenPolicy, Variant,
+};
+use tracing::{info, warn};
+
+use super::{
+    unix_timestamp, Commands, DataSource, DataValue, MonitoredNode, SourceEvent, SourceSenders,
+};
+
+/// How often the thread looks for commands and checks the connection.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Subscribes to the value changes of nodes of an OPC UA server on a separate thread.
+///
+/// Only anonymous connections without security are supported.
+pub struct OpcUaSource {
+    name: String,
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl OpcUaSource {
+    /// Connects to `endpoint`, e.g. `opc.tcp://localhost:4840`, the server sends the changed
+    /// values of `nodes` every `publishing_interval`.
+    pub fn start(
+        endpoint: String,
+        nodes: Vec<MonitoredNode>,
+        publishing_interval: Duration,
+        senders: SourceSenders,
+    ) -> Self {
+        info!("Subscribe to {} nodes of {}", nodes.len(), endpoint);
+        let name = endpoint.clone();
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let _thread = thread::Builder::new()
+            .name(format!("OPC UA {}", endpoint))
+            .spawn(move || {
+                let result = subscribe(
+                    &endpoint,
+                    &nodes,
+                    publishing_interval,
+                    &senders,
+                    &command_receiver,
+                );
+                if let Err(err) = result {
+                    warn!("OPC UA connection to {} failed: {}", endpoint, err);
+                    senders.report(SourceEvent::Disconnected(format!(
+                        "OPC UA {}: {}",
+                        endpoint, err
+                    )));
+                }
+                thread_running.store(false, Ordering::Relaxed);
+            });
+        Self {
+            name,
+            commands,
+            running,
+        }
+    }
+}
+
+impl DataSource for OpcUaSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+}
+
+/// Runs the subscription until it is stopped or the connection is lost.
+fn subscribe(
+    endpoint: &str,
+    nodes: &[MonitoredNode],
+    publishing_interval: Duration,
+    senders: &SourceSenders,
+    commands: &Receiver<Commands>,
+) -> Result<(), String> {
+    let mut channels = HashMap::new();
+    for node in nodes {
+        let node_id = NodeId::from_str(node.node_id.trim())
+            .map_err(|_| format!("invalid node id {:?}", node.node_id))?;
+        channels.insert(node_id, node.channel_name().to_string());
+    }
+
+    let mut client = ClientBuilder::new()
+        .application_name("serialplotter")
+        .application_uri("urn:serialplotter")
+        .create_sample_keypair(true)
+        .trust_server_certs(true)
+        // A lost connection stops the source like an unplugged serial port
+        .session_retry_limit(0)
+        .client()
+        .ok_or("invalid client configuration")?;
+    let session = client
+        .connect_to_endpoint(
+            (
+                endpoint,
+                SecurityPolicy::None.to_str(),
+                MessageSecurityMode::None,
+                UserTokenPolicy::anonymous(),
+            ),
+            IdentityToken::Anonymous,
+        )
+        .map_err(|status| format!("failed to connect: {}", status))?;
+
+    {
+        let session = session.read();
+        let callback_channels = channels.clone();
+        let callback_senders = senders.clone();
+        let subscription = session
+            .create_subscription(
+                publishing_interval.as_secs_f64() * 1000.0,
+                10,
+                30,
+                0,
+                0,
+                true,
+                DataChangeCallback::new(move |items| {
+                    received(&items, &callback_channels, &callback_senders)
+                }),
+            )
+            .map_err(|status| format!("failed to subscribe: {}", status))?;
+        let requests: Vec<MonitoredItemCreateRequest> =
+            channels.into_keys().map(|node_id| node_id.into()).collect();
+        let results = session
+            .create_monitored_items(subscription, TimestampsToReturn::Both, &requests)
+            .map_err(|status| format!("failed to monitor the nodes: {}", status))?;
+        for (request, result) in requests.iter().zip(results) {
+            if result.status_code.is_bad() {
+                senders.report(SourceEvent::Error(format!(
+                    "OPC UA {}: can not monitor {}: {}",
+                    endpoint, request.item_to_monitor.node_id, result.status_code
+                )));
+            }
+        }
+    }
+
+    let session_commands = Session::run_async(session.clone());
+    let result = loop {
+        match commands.recv_timeout(POLL_INTERVAL) {
+            // Dropping the source stops it as well
+            Ok(Commands::Stop) | Err(RecvTimeoutError::Disconnected) => break Ok(()),
+            // The other commands control serial ports
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+        }
+        if !session.read().is_connected() {
+            break Err("connection lost".to_string());
+        }
+    };
+    let _ = session_commands.send(SessionCommand::Stop);
+    session.read().disconnect();
+    info!("Stop subscription to {}", endpoint);
+    result
+}
+
+/// Hands the changed values of the monitored nodes over to the sinks and the ui, the values of
+/// a notification form a line.
+fn received(items: &[&MonitoredItem], channels: &HashMap<NodeId, String>, senders: &SourceSenders) {
+    let line = senders.next_line();
+    let values: Vec<DataValue> = items
+        .iter()
+        .filter_map(|item| {
+            let name = channels.get(&item.item_to_monitor().node_id)?;
+            let value = item.last_value();
+            Some(DataValue {
+                name: name.clone(),
+                value: number(value.value.as_ref()?)?,
+                timestamp: value
+                    .source_timestamp
+                    .as_ref()
+                    .map_or_else(unix_timestamp, |time| {
+                        time.as_chrono().timestamp_millis() as f64 / 1000.0
+                    }),
+                line,
+            })
+        })
+        .collect();
+    if values.is_empty() {
+        return;
+    }
+    senders.record(&values);
+    // Err: the ui is gone, the thread stops as the source is dropped with it
+    let _ = senders.send_values(values);
+}
+
+/// The value of a numeric or boolean variant, other types can not be plotted.
+fn number(variant: &Variant) -> Option<f64> {
+    Some(match *variant {
+        Variant::Boolean(value) => f64::from(u8::from(value)),
+        Variant::SByte(value) => value.into(),
+        Variant::Byte(value) => value.into(),
+        Variant::Int16(value) => value.into(),
+        Variant::UInt16(value) => value.into(),
+        Variant::Int32(value) => value.into(),
+        Variant::UInt32(value) => value.into(),
+        Variant::Int64(value) => value as f64,
+        Variant::UInt64(value) => value as f64,
+        Variant::Float(value) => value.into(),
+        Variant::Double(value) => value,
+        _ => return None,
+    })
+}
@@ -0,0 +1,275 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{Receiver, Sender};
+use serialport::SerialPort;
+use tracing::{info, warn};
+
+use super::{
+    process_chunk, unix_timestamp, Commands, DataSource, ParseError, SourceEvent, SourceSenders,
+    ValueParser,
+};
+
+/// How long DTR is released to reset a board, long enough for the reset capacitor of an Arduino.
+const RESET_PULSE: Duration = Duration::from_millis(100);
+/// How long a break holds the line low, longer than a byte even at 300 baud.
+const BREAK_DURATION: Duration = Duration::from_millis(50);
+
+/// How the port is read, trading the delay of the values for the load of the reading thread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadTiming {
+    /// A pause after every read, more bytes are read at once at the cost of their delay
+    pub poll_interval: Duration,
+    /// The most bytes read at once
+    pub read_size: usize,
+}
+
+impl Default for ReadTiming {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::ZERO,
+            read_size: 1024,
+        }
+    }
+}
+
+/// Reads a serial port on a separate thread.
+pub struct SerialSource {
+    name: String,
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl SerialSource {
+    /// Starts the thread reading the port, `text` tells whether the parser expects a text format.
+    pub fn start(
+        port: Box<dyn SerialPort>,
+        parser: Box<dyn ValueParser>,
+        timing: ReadTiming,
+        text: bool,
+        senders: SourceSenders,
+    ) -> Self {
+        let name = port.name().unwrap_or_default();
+        info!("Start reading from {:?}", name);
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let _thread = thread::Builder::new()
+            .name(format!("Read serial {}", name))
+            .spawn(move || {
+                process_serial_data(port, parser, timing, text, senders, command_receiver);
+                thread_running.store(false, Ordering::Relaxed);
+            });
+        Self {
+            name,
+            commands,
+            running,
+        }
+    }
+}
+
+impl DataSource for SerialSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+
+    fn command(&mut self, command: Commands) -> bool {
+        self.commands.send(command).is_ok()
+    }
+}
+
+fn process_serial_data(
+    mut port: Box<dyn SerialPort>,
+    mut parser: Box<dyn ValueParser>,
+    timing: ReadTiming,
+    text: bool,
+    senders: SourceSenders,
+    command_receiver: Receiver<Commands>,
+) {
+    #[cfg(feature = "profiling")]
+    {
+        puffin::set_scopes_on(true);
+        puffin::profile_scope!("processing serial data");
+    }
+
+    let span = tracing::span!(tracing::Level::DEBUG, "Processing Serialport");
+    let _scope = span.enter();
+    let name = port.name();
+    info!(
+        "Start reading from {:?} with timeout {:?}",
+        &name,
+        port.timeout()
+    );
+    let mut buffer = vec![0u8; timing.read_size.max(1)];
+    'read_loop: loop {
+        if let Ok(command) = command_receiver.try_recv() {
+            match command {
+                Commands::Stop => break 'read_loop,
+                Commands::SendMessage(message) => {
+                    if let Err(err) = port.write_all(message.as_bytes()) {
+                        warn!("Failed to write to the port: {}", err);
+                        senders.report(SourceEvent::Error(format!("failed to write: {}", err)));
+                    }
+                }
+                Commands::SetDtr(level) => {
+                    if let Err(err) = port.write_data_terminal_ready(level) {
+                        warn!("Failed to set DTR: {}", err);
+                        senders.report(SourceEvent::Error(format!("failed to set DTR: {}", err)));
+                    }
+                }
+                Commands::SetRts(level) => {
+                    if let Err(err) = port.write_request_to_send(level) {
+                        warn!("Failed to set RTS: {}", err);
+                        senders.report(SourceEvent::Error(format!("failed to set RTS: {}", err)));
+                    }
+                }
+                Commands::ResetBoard => {
+                    let pulse = port.write_data_terminal_ready(false).and_then(|_| {
+                        thread::sleep(RESET_PULSE);
+                        port.write_data_terminal_ready(true)
+                    });
+                    if let Err(err) = pulse {
+                        warn!("Failed to reset the board: {}", err);
+                        senders.report(SourceEvent::Error(format!(
+                            "failed to reset the board: {}",
+                            err
+                        )));
+                    }
+                }
+                Commands::SendBreak => {
+                    let pulse = port.set_break().and_then(|_| {
+                        thread::sleep(BREAK_DURATION);
+                        port.clear_break()
+                    });
+                    senders.report(match pulse {
+                        Ok(()) => SourceEvent::Condition(format!(
+                            "sent break of {} ms",
+                            BREAK_DURATION.as_millis()
+                        )),
+                        Err(err) => SourceEvent::Error(format!("failed to send break: {}", err)),
+                    });
+                }
+            };
+        }
+        // Fails once the device is gone, e.g. unplugged
+        let available = match port.bytes_to_read() {
+            Ok(available) => available as usize,
+            Err(err) => {
+                warn!("Failed to query the port: {}", err);
+                senders.report(SourceEvent::Disconnected(format!(
+                    "failed to query {:?}: {}",
+                    name, err
+                )));
+                break;
+            }
+        };
+        let size = available.clamp(1, buffer.len());
+        let result = port.read(&mut buffer[..size]);
+        {
+            #[cfg(feature = "profiling")]
+            puffin::profile_scope!("processing received data");
+            let result = match result {
+                Ok(amount) if text => {
+                    report_line_errors(&buffer[..amount], &senders);
+                    process_chunk(
+                        parser.as_mut(),
+                        &buffer[..amount],
+                        unix_timestamp(),
+                        &senders,
+                    )
+                }
+                Ok(amount) => process_chunk(
+                    parser.as_mut(),
+                    &buffer[..amount],
+                    unix_timestamp(),
+                    &senders,
+                ),
+                Err(err) => match err.kind() {
+                    io::ErrorKind::Interrupted => Ok(()),
+                    io::ErrorKind::WouldBlock => Ok(()),
+                    io::ErrorKind::TimedOut => Ok(()), // No data arrived within the timeout of the port
+                    _ => {
+                        warn!("Error reading from buffer: {}", err);
+                        senders.report(SourceEvent::Disconnected(format!(
+                            "error reading from {:?}: {}",
+                            name, err
+                        )));
+                        Err(ParseError::ChannelClosed)
+                    }
+                },
+            };
+            match result {
+                Ok(_) | Err(ParseError::InvalidFormat) => {}
+                Err(ParseError::ChannelClosed) => break,
+            }
+        }
+        if !timing.poll_interval.is_zero() {
+            thread::sleep(timing.poll_interval);
+        }
+    }
+    info!("Stop reading from {:?}", &name);
+}
+
+/// Reports the breaks and the bytes with a framing or a parity error received in a chunk of text.
+///
+/// In raw mode Linux and macOS deliver each of them as a NUL byte, which no line of text contains,
+/// the bytes of binary formats may be 0 though. Windows reports the errors through
+/// `ClearCommError`, which the `serialport` crate does not expose, so they go unnoticed there.
+fn report_line_errors(chunk: &[u8], senders: &SourceSenders) {
+    let errors = chunk.iter().filter(|byte| **byte == 0).count();
+    if errors > 0 {
+        warn!("Received {} breaks or framing errors", errors);
+        senders.report(SourceEvent::Error(format!(
+            "received {} breaks or framing errors",
+            errors
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value_parsing::{Backpressure, OverflowPolicy};
+
+    #[test]
+    fn should_report_the_nul_bytes_of_breaks() {
+        let (events, received) = crossbeam::channel::unbounded();
+        let (data, queued) = crossbeam::channel::unbounded();
+        let senders = SourceSenders {
+            data,
+            queued,
+            backpressure: Arc::new(Backpressure::new(OverflowPolicy::Block)),
+            raw: crossbeam::channel::unbounded().0,
+            parse_errors: crossbeam::channel::unbounded().0,
+            events,
+            lines: Default::default(),
+            sinks: Default::default(),
+        };
+
+        report_line_errors(b"a:1\n", &senders);
+        report_line_errors(b"a:\x002\n\x00", &senders);
+
+        let events: Vec<_> = received.try_iter().collect();
+        assert_eq!(
+            events,
+            [SourceEvent::Error(
+                "received 2 breaks or framing errors".to_string()
+            )]
+        );
+    }
+}
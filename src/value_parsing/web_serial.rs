@@ -0,0 +1,103 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Reflect, Uint8Array};
+use tracing::{info, warn};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ReadableStreamDefaultReader, SerialOptions, SerialPort};
+
+use super::{
+    process_chunk, unix_timestamp, DataSource, ParseError, SourceEvent, SourceSenders, ValueParser,
+};
+
+/// The progress of a [`WebSerialSource`], shared with the task reading the port.
+#[derive(Default)]
+struct State {
+    stopped: bool,
+    /// The reader of the opened port, cancelling it wakes a pending read.
+    reader: Option<ReadableStreamDefaultReader>,
+}
+
+/// Reads a serial port through the Web Serial API of the browser.
+///
+/// The port is chosen by the user in the dialog of the browser, which requires the source
+/// to be started from a user interaction like a button click.
+pub struct WebSerialSource {
+    name: String,
+    state: Rc<RefCell<State>>,
+}
+
+impl WebSerialSource {
+    pub fn start(baud_rate: u32, parser: Box<dyn ValueParser>, senders: SourceSenders) -> Self {
+        let state = Rc::new(RefCell::new(State::default()));
+        let task_state = state.clone();
+        let events = senders.events.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(err) = read_port(baud_rate, parser, senders, &task_state).await {
+                warn!("Web serial port failed: {:?}", err);
+                let event = SourceEvent::Disconnected(format!("web serial port failed: {:?}", err));
+                let _ = events.try_send(event);
+            }
+            task_state.borrow_mut().stopped = true;
+        });
+        Self {
+            name: "Web Serial port".to_string(),
+            state,
+        }
+    }
+}
+
+impl DataSource for WebSerialSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        !self.state.borrow().stopped
+    }
+
+    fn stop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.stopped = true;
+        if let Some(reader) = &state.reader {
+            let _ = reader.cancel();
+        }
+    }
+}
+
+async fn read_port(
+    baud_rate: u32,
+    mut parser: Box<dyn ValueParser>,
+    senders: SourceSenders,
+    state: &RefCell<State>,
+) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let serial = window.navigator().serial();
+    if serial.is_undefined() {
+        return Err("this browser does not support the Web Serial API".into());
+    }
+    let port: SerialPort = JsFuture::from(serial.request_port()).await?.dyn_into()?;
+    JsFuture::from(port.open(&SerialOptions::new(baud_rate))).await?;
+    info!("Start reading from web serial port");
+
+    let reader: ReadableStreamDefaultReader = port.readable().get_reader().dyn_into()?;
+    state.borrow_mut().reader = Some(reader.clone());
+
+    while !state.borrow().stopped {
+        let chunk = JsFuture::from(reader.read()).await?;
+        if Reflect::get(&chunk, &"done".into())?.is_truthy() {
+            break;
+        }
+        let bytes = Uint8Array::new(&Reflect::get(&chunk, &"value".into())?).to_vec();
+        match process_chunk(parser.as_mut(), &bytes, unix_timestamp(), &senders) {
+            Ok(_) | Err(ParseError::InvalidFormat) => {}
+            Err(ParseError::ChannelClosed) => break,
+        }
+    }
+
+    state.borrow_mut().reader = None;
+    reader.release_lock();
+    JsFuture::from(port.close()).await?;
+    info!("Stop reading from web serial port");
+    Ok(())
+}
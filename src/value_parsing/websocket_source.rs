@@ -0,0 +1,137 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{Receiver, Sender};
+use tracing::{info, warn};
+use tungstenite::{stream::MaybeTlsStream, Message};
+
+use super::{
+    process_chunk, unix_timestamp, Commands, DataSource, ParseError, SourceEvent, SourceSenders,
+    ValueParser,
+};
+
+/// How long a read waits for a message before the thread looks for commands.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Receives the messages of a WebSocket server on a separate thread, e.g. `ws://device/stream`.
+///
+/// The messages are parsed like the bytes of a serial port. A text message is a line, a binary
+/// message is passed on as it is. Only unencrypted `ws://` connections are supported.
+pub struct WebSocketSource {
+    name: String,
+    commands: Sender<Commands>,
+    running: Arc<AtomicBool>,
+}
+
+impl WebSocketSource {
+    pub fn start(url: String, parser: Box<dyn ValueParser>, senders: SourceSenders) -> Self {
+        info!("Connect to {}", url);
+        let name = url.clone();
+        let (commands, command_receiver) = crossbeam::channel::bounded(10);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let _thread = thread::Builder::new()
+            .name(format!("WebSocket {}", url))
+            .spawn(move || {
+                if let Err(err) = receive(&url, parser, &senders, &command_receiver) {
+                    warn!("WebSocket connection to {} failed: {}", url, err);
+                    senders.report(SourceEvent::Disconnected(format!(
+                        "WebSocket {}: {}",
+                        url, err
+                    )));
+                }
+                thread_running.store(false, Ordering::Relaxed);
+            });
+        Self {
+            name,
+            commands,
+            running,
+        }
+    }
+}
+
+impl DataSource for WebSocketSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    fn stop(&mut self) {
+        let _ = self.commands.send(Commands::Stop); // Err: the thread has already stopped, so there is nothing to stop.
+    }
+
+    fn command(&mut self, command: Commands) -> bool {
+        self.commands.send(command).is_ok()
+    }
+}
+
+/// Runs the connection until it is stopped or closed by the server.
+fn receive(
+    url: &str,
+    mut parser: Box<dyn ValueParser>,
+    senders: &SourceSenders,
+    commands: &Receiver<Commands>,
+) -> Result<(), String> {
+    let (mut socket, _response) =
+        tungstenite::connect(url).map_err(|err| format!("failed to connect: {}", err))?;
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| err.to_string())?;
+    }
+    loop {
+        match commands.try_recv() {
+            Ok(Commands::Stop) | Err(crossbeam::channel::TryRecvError::Disconnected) => {
+                let _ = socket.close(None); // Err: the connection is dropped anyway
+                info!("Stop receiving from {}", url);
+                return Ok(());
+            }
+            Ok(Commands::SendMessage(message)) => {
+                if let Err(err) = socket.send(Message::Text(message)) {
+                    warn!("Failed to send to {}: {}", url, err);
+                    senders.report(SourceEvent::Error(format!("failed to send: {}", err)));
+                }
+            }
+            // The other commands control the lines of serial ports
+            Ok(_) | Err(crossbeam::channel::TryRecvError::Empty) => {}
+        }
+
+        let chunk = match socket.read() {
+            Ok(Message::Text(mut text)) => {
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.into_bytes()
+            }
+            Ok(Message::Binary(bytes)) => bytes,
+            // Pings are answered on the next read or send
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(tungstenite::Error::ConnectionClosed) => {
+                return Err("closed by the server".to_string())
+            }
+            Err(err) => return Err(err.to_string()),
+        };
+        match process_chunk(parser.as_mut(), &chunk, unix_timestamp(), senders) {
+            Ok(()) | Err(ParseError::InvalidFormat) => {}
+            Err(ParseError::ChannelClosed) => return Ok(()),
+        }
+    }
+}